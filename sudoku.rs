@@ -0,0 +1,835 @@
+// sudoku grids, a backtracking solver and a puzzle generator. Rows and
+// columns count from 0 at the top left, and blank cells hold 0
+
+use std::error;
+use std::fmt;
+use std::str;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const SIZE: usize = 9;
+// the width of a 3x3 box
+pub const BOX: usize = 3;
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Grid {
+    cells: [[u8; SIZE]; SIZE],
+}
+
+impl Grid {
+    // an empty grid
+    pub fn new() -> Grid {
+        Grid::default()
+    }
+
+    pub fn from_rows(rows: [[u8; SIZE]; SIZE]) -> Grid {
+        Grid { cells: rows }
+    }
+
+    pub fn rows(&self) -> [[u8; SIZE]; SIZE] {
+        self.cells
+    }
+
+    // None for a blank; panics off the grid, like indexing
+    pub fn get(&self, row: usize, col: usize) -> Option<u8> {
+        match self.cells[row][col] {
+            0 => None,
+            digit => Some(digit),
+        }
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, digit: Option<u8>) {
+        self.cells[row][col] = digit.unwrap_or(0);
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.empty_cell().is_none()
+    }
+
+    fn empty_cell(&self) -> Option<(usize, usize)> {
+        (0..SIZE)
+            .flat_map(|row| (0..SIZE).map(move |col| (row, col)))
+            .find(|&(row, col)| self.cells[row][col] == 0)
+    }
+
+    // the blank with the fewest digits that fit, the first in reading order
+    // on a tie; narrowing the search there prunes it the most
+    fn most_constrained(&self) -> Option<(usize, usize)> {
+        (0..SIZE)
+            .flat_map(|row| (0..SIZE).map(move |col| (row, col)))
+            .filter(|&(row, col)| self.cells[row][col] == 0)
+            .min_by_key(|&(row, col)| {
+                (1..=SIZE as u8)
+                    .filter(|&digit| self.is_safe(row, col, digit))
+                    .count()
+            })
+    }
+
+    // whether `digit` can go at (row, col) without repeating in its row,
+    // column or box
+    pub fn is_safe(&self, row: usize, col: usize, digit: u8) -> bool {
+        let (top, left) = (row - row % BOX, col - col % BOX);
+        let in_row = self.cells[row].contains(&digit);
+        let in_col = (0..SIZE).any(|r| self.cells[r][col] == digit);
+        let in_box = (top..top + BOX)
+            .flat_map(|r| (left..left + BOX).map(move |c| (r, c)))
+            .any(|(r, c)| self.cells[r][c] == digit);
+        !in_row && !in_col && !in_box
+    }
+
+    // checks the clues break no rule, which the solver takes for granted;
+    // out-of-range values are reported before repeats
+    pub fn validate(&self) -> Result<(), InvalidGridError> {
+        for (row, cells) in self.cells.iter().enumerate() {
+            for (col, &value) in cells.iter().enumerate() {
+                if value > SIZE as u8 {
+                    return Err(InvalidGridError::OutOfRange { row, col, value });
+                }
+            }
+        }
+        for unit in Unit::all() {
+            for digit in 1..=SIZE as u8 {
+                let cells: Vec<(usize, usize)> = unit
+                    .cells()
+                    .filter(|&(row, col)| self.cells[row][col] == digit)
+                    .collect();
+                if cells.len() > 1 {
+                    return Err(InvalidGridError::Repeated { digit, unit, cells });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // the grid with its blanks filled in; the first solution found, if
+    // there's more than one
+    pub fn solve(&self) -> Result<Grid, SolveError> {
+        self.validate()?;
+        self.solutions().next().ok_or(SolveError::NoSolution)
+    }
+
+    // the number of solutions, counting no further than `limit`; a limit of
+    // 2 is enough to tell a proper puzzle, with exactly one, from the rest.
+    // An invalid grid has none
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        self.solutions().take(limit).count()
+    }
+
+    // every solution, found one at a time as the iterator's read; the first
+    // is the one `solve` returns
+    pub fn solutions(&self) -> Solutions {
+        Solutions {
+            grid: *self,
+            filled: Vec::new(),
+            started: false,
+            done: self.validate().is_err(),
+        }
+    }
+}
+
+// the search behind `Grid::solutions`, kept between calls to `next`
+pub struct Solutions {
+    grid: Grid,
+    // the blanks filled so far, most recent last
+    filled: Vec<(usize, usize)>,
+    started: bool,
+    done: bool,
+}
+
+impl Iterator for Solutions {
+    type Item = Grid;
+
+    fn next(&mut self) -> Option<Grid> {
+        if self.done {
+            return None;
+        }
+        // after a solution, carry on from its last blank
+        let mut backtrack = self.started;
+        self.started = true;
+        loop {
+            if !backtrack {
+                match self.grid.most_constrained() {
+                    Some(cell) => self.filled.push(cell),
+                    None => return Some(self.grid),
+                }
+            }
+            let Some(&(row, col)) = self.filled.last() else {
+                self.done = true;
+                return None;
+            };
+            // the next digit up that fits, if any
+            let tried = self.grid.cells[row][col];
+            self.grid.cells[row][col] = 0;
+            match (tried + 1..=SIZE as u8).find(|&digit| self.grid.is_safe(row, col, digit)) {
+                Some(digit) => {
+                    self.grid.cells[row][col] = digit;
+                    backtrack = false;
+                }
+                None => {
+                    self.filled.pop();
+                    backtrack = true;
+                }
+            }
+        }
+    }
+}
+
+// a row, column or box, each numbered from 0; boxes go along the top row of
+// boxes first
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Unit {
+    Row(usize),
+    Column(usize),
+    Box(usize),
+}
+
+impl Unit {
+    fn all() -> impl Iterator<Item = Unit> {
+        (0..SIZE)
+            .map(Unit::Row)
+            .chain((0..SIZE).map(Unit::Column))
+            .chain((0..SIZE).map(Unit::Box))
+    }
+
+    // its cells as (row, col), in reading order
+    pub fn cells(self) -> impl Iterator<Item = (usize, usize)> {
+        (0..SIZE).map(move |i| match self {
+            Unit::Row(row) => (row, i),
+            Unit::Column(col) => (i, col),
+            Unit::Box(n) => (n / BOX * BOX + i / BOX, n % BOX * BOX + i % BOX),
+        })
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Unit::Row(n) => write!(f, "row {}", n),
+            Unit::Column(n) => write!(f, "column {}", n),
+            Unit::Box(n) => write!(f, "box {}", n),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum InvalidGridError {
+    // only 0-9 fit in a cell; `Grid::from_rows` and `set` don't check
+    OutOfRange {
+        row: usize,
+        col: usize,
+        value: u8,
+    },
+    // `cells` all hold `digit`, as (row, col)
+    Repeated {
+        digit: u8,
+        unit: Unit,
+        cells: Vec<(usize, usize)>,
+    },
+}
+
+impl fmt::Display for InvalidGridError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InvalidGridError::OutOfRange { row, col, value } => {
+                write!(f, "{} at ({}, {}) isn't a digit", value, row, col)
+            }
+            InvalidGridError::Repeated { digit, unit, cells } => {
+                let cells: Vec<String> = cells
+                    .iter()
+                    .map(|(row, col)| format!("({}, {})", row, col))
+                    .collect();
+                write!(f, "{} repeats in {} at {}", digit, unit, cells.join(", "))
+            }
+        }
+    }
+}
+
+impl error::Error for InvalidGridError {}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SolveError {
+    // the clues already break a rule
+    Invalid(InvalidGridError),
+    // every way of filling the blanks breaks a rule
+    NoSolution,
+}
+
+impl From<InvalidGridError> for SolveError {
+    fn from(err: InvalidGridError) -> SolveError {
+        SolveError::Invalid(err)
+    }
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SolveError::Invalid(err) => write!(f, "invalid puzzle: {}", err),
+            SolveError::NoSolution => write!(f, "the puzzle has no solution"),
+        }
+    }
+}
+
+impl error::Error for SolveError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            SolveError::Invalid(err) => Some(err),
+            SolveError::NoSolution => None,
+        }
+    }
+}
+
+// `{}` draws the grid with a border round each box and dots for blanks:
+//
+//     +-------+-------+-------+
+//     | . 4 3 | . . . | . . 9 |
+//     ...
+//
+// `{:#}` writes the 81-character line that `FromStr` reads
+impl fmt::Display for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digit = |digit: u8| match digit {
+            0 => '.',
+            digit => char::from(b'0' + digit),
+        };
+        if f.alternate() {
+            return self
+                .cells
+                .iter()
+                .flatten()
+                .try_for_each(|&d| write!(f, "{}", digit(d)));
+        }
+
+        let border = ["-------"; SIZE / BOX].join("+");
+        for (row, cells) in self.cells.iter().enumerate() {
+            if row % BOX == 0 {
+                writeln!(f, "+{}+", border)?;
+            }
+            for (col, &cell) in cells.iter().enumerate() {
+                if col % BOX == 0 {
+                    write!(f, "| ")?;
+                }
+                write!(f, "{} ", digit(cell))?;
+            }
+            writeln!(f, "|")?;
+        }
+        write!(f, "+{}+", border)
+    }
+}
+
+// the one-line format most puzzle sites and datasets use: 81 digits, row
+// by row, with 0 or . for blanks. Whitespace anywhere is skipped, so a grid
+// laid out over nine lines parses too
+impl str::FromStr for Grid {
+    type Err = ParseGridError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut grid = Grid::new();
+        let mut cells = 0;
+        for (line, text) in s.lines().enumerate() {
+            for (column, ch) in text.chars().enumerate() {
+                let digit = match ch {
+                    '.' | '0' => 0,
+                    '1'..='9' => ch as u8 - b'0',
+                    _ if ch.is_whitespace() => continue,
+                    _ => {
+                        return Err(ParseGridError::Unexpected {
+                            ch,
+                            line: line + 1,
+                            column: column + 1,
+                        })
+                    }
+                };
+                if cells == SIZE * SIZE {
+                    return Err(ParseGridError::TooManyCells {
+                        line: line + 1,
+                        column: column + 1,
+                    });
+                }
+                grid.cells[cells / SIZE][cells % SIZE] = digit;
+                cells += 1;
+            }
+        }
+        if cells < SIZE * SIZE {
+            return Err(ParseGridError::TooFewCells { cells });
+        }
+        Ok(grid)
+    }
+}
+
+// lines and columns count from 1, as in an editor
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ParseGridError {
+    Unexpected {
+        ch: char,
+        line: usize,
+        column: usize,
+    },
+    // where the 82nd cell starts
+    TooManyCells {
+        line: usize,
+        column: usize,
+    },
+    TooFewCells {
+        cells: usize,
+    },
+}
+
+impl fmt::Display for ParseGridError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseGridError::Unexpected { ch, line, column } => write!(
+                f,
+                "unexpected {:?} at line {}, column {}, expected 1-9, 0 or .",
+                ch, line, column
+            ),
+            ParseGridError::TooManyCells { line, column } => write!(
+                f,
+                "more than {} cells, from line {}, column {}",
+                SIZE * SIZE,
+                line,
+                column
+            ),
+            ParseGridError::TooFewCells { cells } => {
+                write!(f, "only {} of {} cells", cells, SIZE * SIZE)
+            }
+        }
+    }
+}
+
+impl error::Error for ParseGridError {}
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Medium,
+    Hard,
+    Expert,
+}
+
+impl Difficulty {
+    // the clues the generator stops at; `Expert` takes away every clue it
+    // can, which usually leaves around 24
+    fn clues(self) -> usize {
+        match self {
+            Difficulty::Easy => 40,
+            Difficulty::Medium => 32,
+            Difficulty::Hard => 27,
+            Difficulty::Expert => 0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Puzzle {
+    pub grid: Grid,
+    // the only one `grid` has
+    pub solution: Grid,
+}
+
+// a new puzzle with a single solution, different each time
+pub fn generate(difficulty: Difficulty) -> Puzzle {
+    let seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+    generate_with_seed(seed, difficulty)
+}
+
+// the same puzzle for the same seed and difficulty, e.g. for everyone in a
+// tournament round. It fills a grid at random, then blanks its cells in a
+// random order, keeping each clue whose loss would let in a second solution
+pub fn generate_with_seed(seed: u64, difficulty: Difficulty) -> Puzzle {
+    let mut rng = Rng::new(seed);
+    let mut solution = Grid::new();
+    solution.fill_at_random(&mut rng);
+
+    let mut cells: Vec<(usize, usize)> = (0..SIZE)
+        .flat_map(|row| (0..SIZE).map(move |col| (row, col)))
+        .collect();
+    rng.shuffle(&mut cells);
+    let mut grid = solution;
+    let mut clues = SIZE * SIZE;
+    for (row, col) in cells {
+        if clues <= difficulty.clues() {
+            break;
+        }
+        grid.set(row, col, None);
+        if grid.count_solutions(2) == 1 {
+            clues -= 1;
+        } else {
+            grid.cells[row][col] = solution.cells[row][col];
+        }
+    }
+    Puzzle { grid, solution }
+}
+
+// how hard a puzzle is to solve by hand, judged by how the solver gets on
+// with it: one it can fill in a cell at a time without guessing, with plenty
+// of clues, is easy; one where it has to guess and back out of dead ends
+// often is for experts. The generator only aims at a clue count, so its
+// puzzles can rate a tier either side of what was asked for
+pub fn rate(puzzle: &Puzzle) -> Difficulty {
+    let clues = puzzle
+        .grid
+        .rows()
+        .iter()
+        .flatten()
+        .filter(|&&digit| digit != 0)
+        .count();
+    let mut grid = puzzle.grid;
+    let mut search = Search::default();
+    search.run(&mut grid);
+    // a dead end costs the guess that led to it and the backing out
+    match search.guesses + 2 * search.dead_ends {
+        0 if clues >= 36 => Difficulty::Easy,
+        0..=4 => Difficulty::Medium,
+        5..=30 => Difficulty::Hard,
+        _ => Difficulty::Expert,
+    }
+}
+
+// a tally of the solver's work on its way to the first solution
+#[derive(Default)]
+struct Search {
+    // blanks where more than one digit fit, so it had to pick one
+    guesses: usize,
+    // blanks where no digit fit, so a guess was wrong
+    dead_ends: usize,
+}
+
+impl Search {
+    // the same search as `Grid::solutions`, written recursively to count
+    fn run(&mut self, grid: &mut Grid) -> bool {
+        let Some((row, col)) = grid.most_constrained() else {
+            return true;
+        };
+        let digits: Vec<u8> = (1..=SIZE as u8)
+            .filter(|&digit| grid.is_safe(row, col, digit))
+            .collect();
+        match digits.len() {
+            0 => self.dead_ends += 1,
+            1 => {}
+            _ => self.guesses += 1,
+        }
+        for digit in digits {
+            grid.cells[row][col] = digit;
+            if self.run(grid) {
+                return true;
+            }
+        }
+        grid.cells[row][col] = 0;
+        false
+    }
+}
+
+impl Grid {
+    // like the solver, but trying the digits in a random order
+    fn fill_at_random(&mut self, rng: &mut Rng) -> bool {
+        let Some((row, col)) = self.empty_cell() else {
+            return true;
+        };
+        let mut digits: Vec<u8> = (1..=SIZE as u8).collect();
+        rng.shuffle(&mut digits);
+        for digit in digits {
+            if self.is_safe(row, col, digit) {
+                self.cells[row][col] = digit;
+                if self.fill_at_random(rng) {
+                    return true;
+                }
+            }
+        }
+        self.cells[row][col] = 0;
+        false
+    }
+}
+
+// splitmix64, seedable so the same seed makes the same puzzles
+struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // uniform in 0..n; n must not be zero
+    fn below(&mut self, n: usize) -> usize {
+        ((u128::from(self.next_u64()) * n as u128) >> 64) as usize
+    }
+
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUZZLE: [[u8; SIZE]; SIZE] = [
+        [0, 4, 3, 0, 0, 0, 0, 0, 9],
+        [0, 0, 0, 6, 0, 0, 0, 0, 5],
+        [0, 0, 0, 0, 0, 4, 1, 0, 0],
+        [9, 0, 1, 0, 5, 0, 0, 0, 0],
+        [0, 0, 0, 7, 2, 6, 0, 0, 0],
+        [0, 0, 8, 0, 1, 0, 0, 0, 0],
+        [0, 1, 0, 0, 0, 0, 7, 2, 0],
+        [7, 0, 0, 0, 0, 0, 0, 0, 0],
+        [2, 0, 0, 0, 0, 5, 0, 6, 0],
+    ];
+
+    // every row, column and box holds 1-9 once
+    fn is_valid_solution(grid: &Grid) -> bool {
+        let ok = |cells: Vec<u8>| {
+            let mut cells = cells;
+            cells.sort();
+            cells == (1..=9).collect::<Vec<u8>>()
+        };
+        let rows = grid.rows();
+        (0..SIZE).all(|i| {
+            let box_cells = (0..SIZE)
+                .map(|j| rows[i / BOX * BOX + j / BOX][i % BOX * BOX + j % BOX])
+                .collect();
+            ok(rows[i].to_vec()) && ok((0..SIZE).map(|r| rows[r][i]).collect()) && ok(box_cells)
+        })
+    }
+
+    #[test]
+    fn accessors() {
+        let mut grid = Grid::from_rows(PUZZLE);
+        assert_eq!(None, grid.get(0, 0));
+        assert_eq!(Some(4), grid.get(0, 1));
+        grid.set(0, 0, Some(8));
+        assert_eq!(Some(8), grid.get(0, 0));
+        assert!(!grid.is_safe(0, 2, 8));
+        assert!(!grid.is_safe(8, 0, 7));
+        assert!(!grid.is_safe(1, 1, 3));
+        assert!(grid.is_safe(1, 1, 2));
+        assert!(!grid.is_complete());
+        assert_eq!(Grid::new(), Grid::from_rows([[0; SIZE]; SIZE]));
+    }
+
+    #[test]
+    fn parse() {
+        let line =
+            "043000009000600005000004100901050000000726000008010000010000720700000000200005060";
+        assert_eq!(Ok(Grid::from_rows(PUZZLE)), line.parse());
+        let dotted = line.replace('0', ".");
+        assert_eq!(Ok(Grid::from_rows(PUZZLE)), dotted.parse());
+        let laid_out: String = line
+            .as_bytes()
+            .chunks(SIZE)
+            .map(|row| format!("  {}\n", std::str::from_utf8(row).unwrap()))
+            .collect();
+        assert_eq!(Ok(Grid::from_rows(PUZZLE)), laid_out.parse());
+
+        assert_eq!(
+            Err(ParseGridError::Unexpected {
+                ch: 'x',
+                line: 2,
+                column: 3
+            }),
+            "123456789\n12x".parse::<Grid>()
+        );
+        assert_eq!(
+            Err(ParseGridError::TooFewCells { cells: 80 }),
+            line[1..].parse::<Grid>()
+        );
+        assert_eq!(
+            Err(ParseGridError::TooManyCells {
+                line: 1,
+                column: 83
+            }),
+            format!("{} 1", line).parse::<Grid>()
+        );
+    }
+
+    #[test]
+    fn display() {
+        let grid = Grid::from_rows(PUZZLE);
+        let drawn = grid.to_string();
+        let lines: Vec<&str> = drawn.lines().collect();
+        assert_eq!(13, lines.len());
+        assert_eq!("+-------+-------+-------+", lines[0]);
+        assert_eq!("| . 4 3 | . . . | . . 9 |", lines[1]);
+        assert_eq!(lines[0], lines[4]);
+        assert_eq!("| 2 . . | . . 5 | . 6 . |", lines[11]);
+        assert_eq!(lines[0], lines[12]);
+
+        let line = format!("{:#}", grid);
+        assert_eq!(SIZE * SIZE, line.len());
+        assert!(line.starts_with(".43.....9"));
+        assert_eq!(Ok(grid), line.parse());
+    }
+
+    #[test]
+    fn solve() {
+        let puzzle = Grid::from_rows(PUZZLE);
+        let grid = puzzle.solve().unwrap();
+        assert_eq!(Grid::from_rows(PUZZLE), puzzle);
+        assert!(grid.is_complete());
+        assert!(is_valid_solution(&grid));
+        // the clues are kept
+        for (row, cols) in PUZZLE.iter().enumerate() {
+            for (col, &digit) in cols.iter().enumerate() {
+                if digit != 0 {
+                    assert_eq!(Some(digit), grid.get(row, col));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn validate() {
+        assert_eq!(Ok(()), Grid::from_rows(PUZZLE).validate());
+
+        let mut grid = Grid::from_rows(PUZZLE);
+        grid.set(8, 8, Some(4));
+        grid.set(7, 7, Some(4));
+        assert_eq!(
+            Err(InvalidGridError::Repeated {
+                digit: 4,
+                unit: Unit::Box(8),
+                cells: vec![(7, 7), (8, 8)]
+            }),
+            grid.validate()
+        );
+
+        grid.set(7, 7, None);
+        grid.set(4, 8, Some(4));
+        let err = grid.validate().unwrap_err();
+        assert_eq!("4 repeats in column 8 at (4, 8), (8, 8)", err.to_string());
+        assert_eq!(Err(SolveError::Invalid(err)), grid.solve());
+
+        grid.set(3, 1, Some(12));
+        assert_eq!(
+            Err(InvalidGridError::OutOfRange {
+                row: 3,
+                col: 1,
+                value: 12
+            }),
+            grid.validate()
+        );
+    }
+
+    #[test]
+    fn count_solutions() {
+        let puzzle = Grid::from_rows(PUZZLE);
+        assert_eq!(1, puzzle.count_solutions(2));
+        assert_eq!(0, puzzle.count_solutions(0));
+        assert_eq!(2, Grid::new().count_solutions(2));
+        assert_eq!(5, Grid::new().count_solutions(5));
+
+        // the 6s and 9s at these corners can swap rows, so blanking all
+        // four leaves two ways to fill them
+        let mut solution = puzzle.solve().unwrap();
+        assert_eq!(1, solution.count_solutions(2));
+        for (row, col) in [(7, 2), (7, 7), (8, 2), (8, 7)] {
+            solution.set(row, col, None);
+        }
+        assert_eq!(2, solution.count_solutions(10));
+
+        let mut invalid = puzzle;
+        invalid.set(0, 0, Some(4));
+        assert_eq!(0, invalid.count_solutions(2));
+    }
+
+    #[test]
+    fn solutions() {
+        let puzzle = Grid::from_rows(PUZZLE);
+        let solution = puzzle.solve().unwrap();
+        assert_eq!(vec![solution], puzzle.solutions().collect::<Vec<_>>());
+        assert_eq!(vec![solution], solution.solutions().collect::<Vec<_>>());
+
+        let mut ambiguous = solution;
+        for (row, col) in [(7, 2), (7, 7), (8, 2), (8, 7)] {
+            ambiguous.set(row, col, None);
+        }
+        let found: Vec<Grid> = ambiguous.solutions().collect();
+        assert_eq!(2, found.len());
+        assert_eq!(solution, found[0]);
+        assert_eq!(Some(9), found[1].get(7, 2));
+        assert_eq!(Some(6), found[1].get(8, 2));
+        assert!(found.iter().all(is_valid_solution));
+
+        // lazily: an empty grid has far too many to list
+        let mut solutions = Grid::new().solutions();
+        let first = solutions.next().unwrap();
+        assert_ne!(Some(first), solutions.next());
+        assert!(is_valid_solution(&first));
+    }
+
+    #[test]
+    fn generate() {
+        for difficulty in [Difficulty::Easy, Difficulty::Hard] {
+            let puzzle = super::generate(difficulty);
+            assert!(is_valid_solution(&puzzle.solution));
+            assert_eq!(1, puzzle.grid.count_solutions(2));
+            assert_eq!(Ok(puzzle.solution), puzzle.grid.solve());
+            let clues = puzzle
+                .grid
+                .rows()
+                .iter()
+                .flatten()
+                .filter(|&&d| d != 0)
+                .count();
+            assert!(clues >= difficulty.clues() && clues < 81);
+        }
+    }
+
+    #[test]
+    fn seeded() {
+        let puzzle = generate_with_seed(7, Difficulty::Medium);
+        assert_eq!(puzzle, generate_with_seed(7, Difficulty::Medium));
+        assert_ne!(puzzle, generate_with_seed(8, Difficulty::Medium));
+        // the same solution, with fewer clues left in
+        let easy = generate_with_seed(7, Difficulty::Easy);
+        assert_eq!(puzzle.solution, easy.solution);
+        assert_ne!(puzzle.grid, easy.grid);
+    }
+
+    #[test]
+    fn rate() {
+        let rated = |grid: Grid| {
+            super::rate(&Puzzle {
+                grid,
+                solution: grid.solve().unwrap(),
+            })
+        };
+        let puzzle = Grid::from_rows(PUZZLE);
+        assert_eq!(Difficulty::Expert, rated(puzzle));
+
+        // with the top rows filled in it needs fewer guesses
+        let solution = puzzle.solve().unwrap();
+        let mut easier = puzzle;
+        for row in 0..4 {
+            easier.cells[row] = solution.cells[row];
+        }
+        assert!(rated(easier) < Difficulty::Expert);
+
+        // forced all the way, but only plenty of clues make it easy
+        let medium = generate_with_seed(1, Difficulty::Medium);
+        assert_eq!(Difficulty::Medium, super::rate(&medium));
+        let easy = generate_with_seed(1, Difficulty::Easy);
+        assert_eq!(Difficulty::Easy, super::rate(&easy));
+    }
+
+    #[test]
+    fn unsolvable() {
+        // 1 can't go anywhere in the top row
+        let mut rows = [[0; SIZE]; SIZE];
+        rows[0] = [0, 2, 3, 4, 5, 6, 7, 8, 9];
+        rows[1][0] = 1;
+        assert_eq!(Err(SolveError::NoSolution), Grid::from_rows(rows).solve());
+    }
+}
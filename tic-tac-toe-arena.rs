@@ -0,0 +1,35 @@
+// plays two bot programs against each other and prints each game, e.g.
+//
+//     tic-tac-toe-arena ./my-bot ./tic-tac-toe-engine 10
+
+use std::env;
+use std::process;
+
+use tic_tac_toe::arena::{Arena, BotCommand};
+
+fn fatal(msg: String) -> ! {
+    eprintln!("fatal: {}", msg);
+    process::exit(1);
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (first, second, games) = match args.as_slice() {
+        [first, second] => (first, second, 1),
+        [first, second, games] => match games.parse() {
+            Ok(games) => (first, second, games),
+            Err(_) => fatal(format!("invalid game count {}", games)),
+        },
+        _ => fatal("usage: tic-tac-toe-arena <bot> <bot> [games]".to_string()),
+    };
+
+    let arena = Arena::new(BotCommand::new(first), BotCommand::new(second));
+    let games = match arena.play_match(games) {
+        Ok(games) => games,
+        Err(err) => fatal(format!("could not start bot: {}", err)),
+    };
+    for game in games {
+        let result = game.result().unwrap();
+        println!("{}  {}", game.transcript(), game.config().describe(result));
+    }
+}
@@ -0,0 +1,174 @@
+// the `tic-tac-toe` binary: plays a game in the terminal, two people taking
+// turns or one against the engine, e.g.
+//
+//     tic-tac-toe --ai O --difficulty easy
+//
+// moves are cell numbers 1-9 or algebraic like b2, and `hint`, `resign` and
+// `quit` do what they say. Built with the `stats` feature,
+//
+//     tic-tac-toe leaderboard [--db stats.db] [--limit 10]
+//
+// prints the best players recorded in a `stats` database instead
+
+use std::env;
+use std::io::{self, BufRead, Write};
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tic_tac_toe::ai::{Difficulty, DifficultyAi};
+use tic_tac_toe::{Game, GameConfig, Player, PlayerKind, Pos, Seat};
+
+const USAGE: &str = "usage: tic-tac-toe [--ai X|O] [--difficulty easy|medium|hard]";
+#[cfg(feature = "stats")]
+const LEADERBOARD_USAGE: &str = "usage: tic-tac-toe leaderboard [--db <path>] [--limit <n>]";
+
+fn fatal(msg: String) -> ! {
+    eprintln!("fatal: {}", msg);
+    process::exit(1);
+}
+
+// which side the engine plays, if any, and how well
+struct Options {
+    ai: Option<Player>,
+    difficulty: Difficulty,
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        ai: None,
+        difficulty: Difficulty::default(),
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(USAGE.to_string());
+        match arg.as_str() {
+            "--ai" => {
+                let side = value()?;
+                options.ai = match side.to_uppercase().parse::<Player>() {
+                    Ok(player) if player.number() < 2 => Some(player),
+                    Ok(_) => return Err(format!("{}: the engine plays X or O", side)),
+                    Err(err) => return Err(format!("{}: {}", side, err)),
+                };
+            }
+            "--difficulty" => {
+                let difficulty = value()?;
+                options.difficulty = difficulty
+                    .parse()
+                    .map_err(|err| format!("{}: {}", difficulty, err))?;
+            }
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+    Ok(options)
+}
+
+#[cfg(feature = "stats")]
+fn leaderboard(args: &[String]) -> Result<(), String> {
+    use tic_tac_toe::stats::Stats;
+
+    let mut path = "stats.db".to_string();
+    let mut limit = 10;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(LEADERBOARD_USAGE.to_string());
+        match arg.as_str() {
+            "--db" => path = value()?.clone(),
+            "--limit" => {
+                let n = value()?;
+                limit = n.parse().map_err(|_| format!("invalid limit {}", n))?;
+            }
+            _ => return Err(LEADERBOARD_USAGE.to_string()),
+        }
+    }
+
+    let stats = Stats::open(&path).map_err(|err| format!("{}: {}", path, err))?;
+    let players = stats.leaderboard(limit).map_err(|err| err.to_string())?;
+    if players.is_empty() {
+        println!("no games recorded in {}", path);
+    }
+    for (rank, player) in players.iter().enumerate() {
+        println!("{:>3}. {}", rank + 1, player);
+    }
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    #[cfg(feature = "stats")]
+    if args.first().map(String::as_str) == Some("leaderboard") {
+        leaderboard(&args[1..]).unwrap_or_else(|msg| fatal(msg));
+        return;
+    }
+    let options = parse_args(&args).unwrap_or_else(|msg| fatal(msg));
+
+    let mut config = GameConfig::default();
+    if let Some(side) = options.ai {
+        let seat = Seat {
+            name: "Computer".to_string(),
+            kind: PlayerKind::Ai,
+        };
+        *config.seat_mut(side) = seat;
+    }
+    let mut game = Game::with_config(config);
+    // a different game each time
+    let seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+    let mut ai = DifficultyAi::new(options.difficulty, seed);
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut redraw = true;
+    while game.result().is_none() {
+        if redraw {
+            println!("{:#}", game.board());
+            redraw = false;
+        }
+        let player = game.to_move();
+        if options.ai == Some(player) {
+            match game.play_ai(&mut ai) {
+                Ok(pos) => println!("{} plays {}", game.config().seat(player).name, pos),
+                Err(err) => fatal(err.to_string()),
+            }
+            redraw = true;
+            continue;
+        }
+
+        print!("{} ({}) to move: ", game.config().seat(player).name, player);
+        io::stdout()
+            .flush()
+            .unwrap_or_else(|err| fatal(err.to_string()));
+        let line = match lines.next() {
+            Some(Ok(line)) => line,
+            Some(Err(err)) => fatal(err.to_string()),
+            // end of input, as good as quitting
+            None => {
+                println!();
+                return;
+            }
+        };
+        match line.trim() {
+            "" => {}
+            "quit" => return,
+            "hint" => {
+                if let Some(hint) = game.hint() {
+                    println!("hint: {}", hint);
+                }
+            }
+            "resign" => {
+                game.resign(player)
+                    .unwrap_or_else(|err| fatal(err.to_string()));
+            }
+            input => match input.parse::<Pos>() {
+                Ok(pos) => match game.play(pos) {
+                    Ok(()) => redraw = true,
+                    Err(err) => println!("{}", err),
+                },
+                Err(err) => println!("{}", err),
+            },
+        }
+    }
+
+    println!("{:#}", game.board());
+    println!("{}", game.config().describe(game.result().unwrap()));
+}
@@ -0,0 +1,14 @@
+// runs the tic-tac-toe engine over stdin and stdout, see `protocol` for the
+// commands it understands
+
+use std::io;
+use std::process;
+
+fn main() {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    if let Err(err) = tic_tac_toe::protocol::run(stdin.lock(), stdout.lock()) {
+        eprintln!("fatal: {}", err);
+        process::exit(1);
+    }
+}
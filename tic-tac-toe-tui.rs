@@ -0,0 +1,57 @@
+// the full-screen version of the `tic-tac-toe` binary, see `tui`; takes the
+// same options, e.g.
+//
+//     tic-tac-toe-tui --ai O --difficulty easy
+
+use std::env;
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tic_tac_toe::ai::Difficulty;
+use tic_tac_toe::tui::{self, App};
+use tic_tac_toe::{GameConfig, Player, PlayerKind, Seat};
+
+const USAGE: &str = "usage: tic-tac-toe-tui [--ai X|O] [--difficulty easy|medium|hard]";
+
+fn fatal(msg: String) -> ! {
+    eprintln!("fatal: {}", msg);
+    process::exit(1);
+}
+
+fn main() {
+    let mut ai = None;
+    let mut difficulty = Difficulty::default();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args.next().unwrap_or_else(|| fatal(USAGE.to_string()));
+        match arg.as_str() {
+            "--ai" => match value.to_uppercase().parse::<Player>() {
+                Ok(player) if player.number() < 2 => ai = Some(player),
+                Ok(_) => fatal(format!("{}: the engine plays X or O", value)),
+                Err(err) => fatal(format!("{}: {}", value, err)),
+            },
+            "--difficulty" => {
+                difficulty = value
+                    .parse()
+                    .unwrap_or_else(|err| fatal(format!("{}: {}", value, err)));
+            }
+            _ => fatal(USAGE.to_string()),
+        }
+    }
+
+    let mut config = GameConfig::default();
+    if let Some(side) = ai {
+        let seat = Seat {
+            name: "Computer".to_string(),
+            kind: PlayerKind::Ai,
+        };
+        *config.seat_mut(side) = seat;
+    }
+    let seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+    let mut app = App::new(config, ai, difficulty, seed);
+    if let Err(err) = tui::run(&mut app) {
+        fatal(err.to_string());
+    }
+}
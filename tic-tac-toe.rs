@@ -1,32 +1,175 @@
-use std::fmt;
-use std::hash::Hash;
-use std::iter;
-use std::str;
-use std::usize;
+// the board and game logic only need `alloc`; everything that talks to the
+// outside world lives behind the default `std` feature
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
-use itertools::Itertools;
+extern crate alloc;
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
-pub enum Player {
-    Nought,
-    Cross,
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::array;
+use core::error;
+use core::fmt;
+use core::hash::Hash;
+use core::iter;
+use core::mem;
+use core::str;
+use core::time::Duration;
+
+#[path = "tic-tac-toe/ai/mod.rs"]
+pub mod ai;
+#[path = "tic-tac-toe/analysis.rs"]
+pub mod analysis;
+#[cfg(feature = "std")]
+#[path = "tic-tac-toe/archive.rs"]
+pub mod archive;
+#[cfg(feature = "std")]
+#[path = "tic-tac-toe/arena.rs"]
+pub mod arena;
+#[path = "tic-tac-toe/chat.rs"]
+pub mod chat;
+#[cfg(feature = "ffi")]
+#[path = "tic-tac-toe/ffi.rs"]
+pub mod ffi;
+#[path = "tic-tac-toe/floating.rs"]
+pub mod floating;
+#[cfg(feature = "grpc")]
+#[path = "tic-tac-toe/grpc.rs"]
+pub mod grpc;
+#[cfg(any(feature = "http", feature = "grpc"))]
+#[path = "tic-tac-toe/hosting.rs"]
+mod hosting;
+#[cfg(feature = "http")]
+#[path = "tic-tac-toe/http.rs"]
+pub mod http;
+#[cfg(feature = "std")]
+#[path = "tic-tac-toe/lobby.rs"]
+pub mod lobby;
+#[path = "tic-tac-toe/morris.rs"]
+pub mod morris;
+#[path = "tic-tac-toe/multiplayer.rs"]
+pub mod multiplayer;
+#[cfg(feature = "std")]
+#[path = "tic-tac-toe/net.rs"]
+pub mod net;
+#[path = "tic-tac-toe/notakto.rs"]
+pub mod notakto;
+#[path = "tic-tac-toe/order_chaos.rs"]
+pub mod order_chaos;
+#[cfg(feature = "std")]
+#[path = "tic-tac-toe/protocol.rs"]
+pub mod protocol;
+#[cfg(feature = "python")]
+#[path = "tic-tac-toe/python.rs"]
+pub mod python;
+#[path = "tic-tac-toe/quantum.rs"]
+pub mod quantum;
+#[path = "tic-tac-toe/qubic.rs"]
+pub mod qubic;
+#[path = "tic-tac-toe/render.rs"]
+pub mod render;
+#[cfg(feature = "std")]
+#[path = "tic-tac-toe/save.rs"]
+pub mod save;
+#[path = "tic-tac-toe/selfplay.rs"]
+pub mod selfplay;
+#[cfg(feature = "async")]
+#[path = "tic-tac-toe/session.rs"]
+pub mod session;
+#[cfg(feature = "std")]
+#[path = "tic-tac-toe/shared.rs"]
+pub mod shared;
+#[cfg(feature = "std")]
+#[path = "tic-tac-toe/spectate.rs"]
+pub mod spectate;
+#[cfg(feature = "stats")]
+#[path = "tic-tac-toe/stats.rs"]
+pub mod stats;
+#[path = "tic-tac-toe/tournament.rs"]
+pub mod tournament;
+#[cfg(feature = "tui")]
+#[path = "tic-tac-toe/tui.rs"]
+pub mod tui;
+#[path = "tic-tac-toe/ultimate.rs"]
+pub mod ultimate;
+#[cfg(feature = "wasm")]
+#[path = "tic-tac-toe/wasm.rs"]
+pub mod wasm;
+#[cfg(feature = "websocket")]
+#[path = "tic-tac-toe/websocket.rs"]
+pub mod websocket;
+
+// one of the players and the mark they play: crosses and noughts, and in
+// games for more players `+` and `*` after them, see `GameConfig::others`
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Player(u8);
+
+// the two sides of the standard game, named like variants since that's how
+// they get matched on
+#[allow(non_upper_case_globals)]
+impl Player {
+    pub const Nought: Player = Player(0);
+    pub const Cross: Player = Player(1);
 }
 
+// indexed by `Player::index`, which keeps noughts sorting before crosses
+const MARKS: [char; Player::MAX] = ['O', 'X', '+', '*'];
+
 impl Player {
-    pub fn toggle(self) -> Player {
+    // the most players a game can have
+    pub const MAX: usize = 4;
+
+    // the `n`th player in turn order, counting from 0: crosses, noughts and
+    // then the others; None from `Player::MAX` on
+    pub fn nth(n: usize) -> Option<Player> {
+        match n {
+            0 => Some(Player::Cross),
+            1 => Some(Player::Nought),
+            n if n < Player::MAX => Some(Player(n as u8)),
+            _ => None,
+        }
+    }
+
+    // where this player comes in turn order, the inverse of `nth`
+    pub fn number(self) -> usize {
         match self {
-            Player::Nought => Player::Cross,
-            Player::Cross => Player::Nought,
+            Player::Cross => 0,
+            Player::Nought => 1,
+            Player(n) => usize::from(n),
         }
     }
+
+    // the first `players` players, in turn order
+    pub fn all(players: usize) -> impl Iterator<Item = Player> {
+        (0..players.min(Player::MAX)).filter_map(Player::nth)
+    }
+
+    // who moves after this player in a game of `players`
+    pub fn next(self, players: usize) -> Player {
+        Player::nth((self.number() + 1) % players).unwrap()
+    }
+
+    // the other side of a two-player game; the third and fourth marks pair
+    // off the same way, so this is always a player with a mark
+    pub fn toggle(self) -> Player {
+        Player(self.0 ^ 1)
+    }
+
+    // for tables with one entry per player
+    fn index(self) -> usize {
+        usize::from(self.0)
+    }
+
+    fn mark(self) -> char {
+        MARKS[self.index()]
+    }
 }
 
 impl fmt::Display for Player {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Player::Nought => write!(f, "O"),
-            Player::Cross => write!(f, "X"),
-        }
+        write!(f, "{}", self.mark())
     }
 }
 
@@ -34,9 +177,13 @@ impl str::FromStr for Player {
     type Err = ParsePlayerError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "O" => Ok(Player::Nought),
-            "X" => Ok(Player::Cross),
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(mark), None) => MARKS
+                .iter()
+                .position(|&other| other == mark)
+                .map(|index| Player(index as u8))
+                .ok_or(ParsePlayerError {}),
             _ => Err(ParsePlayerError {}),
         }
     }
@@ -45,7 +192,15 @@ impl str::FromStr for Player {
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct ParsePlayerError {}
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+impl fmt::Display for ParsePlayerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid player, expected \"O\", \"X\", \"+\" or \"*\"")
+    }
+}
+
+impl error::Error for ParsePlayerError {}
+
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
 pub enum Cell {
     Occupied(Player),
     Vacant,
@@ -62,6 +217,14 @@ impl Cell {
             Cell::Vacant => true,
         }
     }
+
+    // the player's mark or `.`, for the compact one-line board notation
+    fn symbol(self) -> char {
+        match self {
+            Cell::Occupied(player) => player.mark(),
+            Cell::Vacant => '.',
+        }
+    }
 }
 
 impl fmt::Display for Cell {
@@ -77,7 +240,7 @@ impl fmt::Display for Cell {
 // 1 2 3
 // 4 5 6
 // 7 8 9
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct Pos {
     pos: usize,
 }
@@ -90,46 +253,471 @@ impl Pos {
             None
         }
     }
+
+    pub fn all() -> impl Iterator<Item = Pos> {
+        (1..=Board::SIZE).map(|pos| Pos { pos })
+    }
+
+    // chess-style coordinates: files a..c are columns from the left, ranks
+    // 1..3 are rows from the bottom, so a3 is position 1 and c1 is position 9
+    pub fn from_algebraic(s: &str) -> Option<Pos> {
+        let (file, rank) = match *s.as_bytes() {
+            [file, rank] => (file, rank),
+            _ => return None,
+        };
+        let column = usize::from(file.checked_sub(b'a')?);
+        let rank = usize::from(rank.checked_sub(b'1')?);
+        if column >= Board::WIDTH || rank >= Board::WIDTH {
+            return None;
+        }
+        Pos::new((Board::WIDTH - 1 - rank) * Board::WIDTH + column + 1)
+    }
+
+    // zero-based, counted from the top left
+    pub fn from_row_column(row: usize, column: usize) -> Option<Pos> {
+        if row < Board::WIDTH && column < Board::WIDTH {
+            Pos::new(row * Board::WIDTH + column + 1)
+        } else {
+            None
+        }
+    }
+
     pub fn get(self) -> usize {
         self.pos
     }
+
+    // zero-based, counted from the top
+    pub fn row(self) -> usize {
+        (self.pos - 1) / Board::WIDTH
+    }
+
+    // zero-based, counted from the left
+    pub fn column(self) -> usize {
+        (self.pos - 1) % Board::WIDTH
+    }
+
+    // where this position ends up when the board is transformed
+    pub fn transform(self, symmetry: Symmetry) -> Pos {
+        let (row, column) = (self.row(), self.column());
+        let last = Board::WIDTH - 1;
+        let (row, column) = match symmetry {
+            Symmetry::Identity => (row, column),
+            Symmetry::Rotate90 => (column, last - row),
+            Symmetry::Rotate180 => (last - row, last - column),
+            Symmetry::Rotate270 => (last - column, row),
+            Symmetry::ReflectHorizontal => (last - row, column),
+            Symmetry::ReflectVertical => (row, last - column),
+            Symmetry::ReflectDiagonal => (column, row),
+            Symmetry::ReflectAntiDiagonal => (last - column, last - row),
+        };
+        Pos {
+            pos: row * Board::WIDTH + column + 1,
+        }
+    }
+
+    // orthogonally and diagonally adjacent positions
+    pub fn neighbors(self) -> impl Iterator<Item = Pos> {
+        Pos::all().filter(move |other| {
+            *other != self
+                && self.row().abs_diff(other.row()) <= 1
+                && self.column().abs_diff(other.column()) <= 1
+        })
+    }
 }
 
+// `{}` prints the cell number, `{:#}` prints algebraic notation
 impl fmt::Display for Pos {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.get())
+        if f.alternate() {
+            let file = char::from(b'a' + self.column() as u8);
+            let rank = Board::WIDTH - self.row();
+            write!(f, "{}{}", file, rank)
+        } else {
+            write!(f, "{}", self.get())
+        }
+    }
+}
+
+// accepts either a cell number ("5") or algebraic notation ("b2")
+impl str::FromStr for Pos {
+    type Err = ParsePosError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let pos = match s.parse::<usize>() {
+            Ok(n) => Pos::new(n),
+            Err(_) => Pos::from_algebraic(s),
+        };
+        pos.ok_or(ParsePosError {})
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ParsePosError {}
+
+impl fmt::Display for ParsePosError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let bottom_left = Pos::new(Board::SIZE - Board::WIDTH + 1).unwrap();
+        let top_right = Pos::new(Board::WIDTH).unwrap();
+        write!(
+            f,
+            "invalid position, expected 1-{} or {:#}-{:#}",
+            Board::SIZE,
+            bottom_left,
+            top_right
+        )
+    }
+}
+
+impl error::Error for ParsePosError {}
+
+// one of the lines a player can win on
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum LineId {
+    // zero-based, counted from the top
+    Row(usize),
+    // zero-based, counted from the left
+    Column(usize),
+    // 1-5-9
+    Diagonal,
+    // 3-5-7
+    AntiDiagonal,
+    // on a toroidal board, the diagonal shifted right by the given number of
+    // columns, wrapping around, e.g. 2-6-7
+    WrappedDiagonal(usize),
+    // likewise for the anti-diagonal, e.g. 1-6-8
+    WrappedAntiDiagonal(usize),
+}
+
+impl LineId {
+    pub fn positions(self) -> [Pos; Board::WIDTH] {
+        let last = Board::WIDTH - 1;
+        array::from_fn(|i| {
+            let (row, column) = match self {
+                LineId::Row(row) => (row, i),
+                LineId::Column(column) => (i, column),
+                LineId::Diagonal => (i, i),
+                LineId::AntiDiagonal => (i, last - i),
+                LineId::WrappedDiagonal(shift) => (i, (i + shift) % Board::WIDTH),
+                LineId::WrappedAntiDiagonal(shift) => (i, (last - i + shift) % Board::WIDTH),
+            };
+            Pos::from_row_column(row, column).unwrap()
+        })
+    }
+}
+
+// one-based, as a person would count, e.g. "row 1" or "diagonal 1-5-9"
+impl fmt::Display for LineId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LineId::Row(row) => write!(f, "row {}", row + 1),
+            LineId::Column(column) => write!(f, "column {}", column + 1),
+            LineId::Diagonal => write!(f, "diagonal 1-5-9"),
+            LineId::AntiDiagonal => write!(f, "diagonal 3-5-7"),
+            LineId::WrappedDiagonal(_) | LineId::WrappedAntiDiagonal(_) => {
+                let [a, b, c] = self.positions();
+                write!(f, "wrapped diagonal {}-{}-{}", a, b, c)
+            }
+        }
+    }
+}
+
+// the symmetry group of the square board
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Symmetry {
+    Identity,
+    // rotations are clockwise
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    // swaps the top and bottom rows
+    ReflectHorizontal,
+    // swaps the left and right columns
+    ReflectVertical,
+    // mirrors across the 1-5-9 diagonal
+    ReflectDiagonal,
+    // mirrors across the 3-5-7 diagonal
+    ReflectAntiDiagonal,
+}
+
+impl Symmetry {
+    pub const ALL: [Symmetry; 8] = [
+        Symmetry::Identity,
+        Symmetry::Rotate90,
+        Symmetry::Rotate180,
+        Symmetry::Rotate270,
+        Symmetry::ReflectHorizontal,
+        Symmetry::ReflectVertical,
+        Symmetry::ReflectDiagonal,
+        Symmetry::ReflectAntiDiagonal,
+    ];
+
+    pub fn inverse(self) -> Symmetry {
+        match self {
+            Symmetry::Rotate90 => Symmetry::Rotate270,
+            Symmetry::Rotate270 => Symmetry::Rotate90,
+            symmetry => symmetry,
+        }
     }
 }
 
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct Board {
     // row-major layer
     cells: [Cell; Board::SIZE],
+    // `ZobristTable::STANDARD` hash of `cells`, kept up to date on every change
+    hash: u64,
 }
 
 impl Board {
     pub const WIDTH: usize = 3;
     pub const SIZE: usize = Board::WIDTH * Board::WIDTH;
 
-    pub fn new() -> Board {
+    pub const fn new() -> Board {
         Board {
             cells: [Cell::Vacant; Board::SIZE],
+            hash: 0,
+        }
+    }
+
+    fn with_cells(cells: [Cell; Board::SIZE]) -> Board {
+        let mut board = Board { cells, hash: 0 };
+        board.hash = ZobristTable::STANDARD.hash(&board);
+        board
+    }
+
+    // accepts only layouts that can arise in a legal two-player game,
+    // whoever moved first
+    pub fn from_cells(cells: [Cell; Board::SIZE]) -> Result<Board, InvalidBoardError> {
+        let board = Board::with_cells(cells);
+        if let Some(player) = cells.iter().find_map(|&cell| match cell {
+            Cell::Occupied(player) if player.number() >= 2 => Some(player),
+            _ => None,
+        }) {
+            return Err(InvalidBoardError::ExtraPlayer { player });
+        }
+        let Occupancy {
+            crosses, noughts, ..
+        } = board.occupancy();
+
+        if crosses.abs_diff(noughts) > 1 {
+            return Err(InvalidBoardError::PieceCount { crosses, noughts });
+        }
+        match (board.wins(Player::Cross), board.wins(Player::Nought)) {
+            (true, true) => Err(InvalidBoardError::TwoWinners),
+            (true, false) if crosses < noughts => Err(InvalidBoardError::MovedAfterWin {
+                winner: Player::Cross,
+            }),
+            (false, true) if noughts < crosses => Err(InvalidBoardError::MovedAfterWin {
+                winner: Player::Nought,
+            }),
+            _ => Ok(board),
+        }
+    }
+
+    // the starting board for `handicap`, checked against `rules`: no stone
+    // may sit on another or, under gravity, float, and the stones mustn't
+    // already decide the game
+    pub fn with_handicap(handicap: &Handicap, rules: Rules) -> Result<Board, HandicapError> {
+        let mut stones: Vec<(usize, Pos)> = handicap.stones.iter().copied().enumerate().collect();
+        // bottom row first, so under gravity each stone lands on the last
+        stones.sort_by_key(|&(_, pos)| core::cmp::Reverse(pos.row()));
+        let mut board = Board::new();
+        for (index, pos) in stones {
+            rules
+                .check(&board, pos)
+                .and_then(|_| board.place(pos, handicap.player))
+                .map_err(|error| HandicapError::Stone { index, error })?;
+        }
+        if board.is_over() {
+            return Err(HandicapError::Decided);
         }
+        Ok(board)
+    }
+
+    // `pieces` random moves from here, alternating from `player`, that these
+    // rules allow and that leave the game undecided; None if there are none
+    pub fn random_moves(
+        &self,
+        player: Player,
+        pieces: usize,
+        rules: Rules,
+        rng: &mut Rng,
+    ) -> Option<Vec<Pos>> {
+        if pieces == 0 {
+            return Some(Vec::new());
+        }
+        let mut moves: Vec<Pos> = rules.legal_moves(self).collect();
+        rng.shuffle(&mut moves);
+        // backtracks out of dead ends, e.g. where every move would win
+        for pos in moves {
+            let mut next = *self;
+            next.place(pos, player).unwrap();
+            if rules.winner(&next, player).is_some() || next.is_complete() {
+                continue;
+            }
+            if let Some(mut rest) = next.random_moves(player.toggle(), pieces - 1, rules, rng) {
+                rest.insert(0, pos);
+                return Some(rest);
+            }
+        }
+        None
     }
 
     pub fn place(&mut self, pos: Pos, player: Player) -> Result<(), PlaceError> {
+        if self.is_over() {
+            return Err(PlaceError::GameOver);
+        }
+
         let cell = &mut self.cells[pos.get() - 1];
         match *cell {
-            Cell::Occupied(player) => Err(PlaceError {
+            Cell::Occupied(player) => Err(PlaceError::Occupied {
                 pos,
                 occupied_by: player,
             }),
             Cell::Vacant => {
                 *cell = Cell::Occupied(player);
+                self.hash ^= ZobristTable::STANDARD.key(pos, player);
                 Ok(())
             }
         }
     }
 
+    // either every move is placed or, on the first invalid one, none are
+    pub fn apply_moves(&mut self, moves: &[(Pos, Player)]) -> Result<(), ApplyError> {
+        let mut board = *self;
+        for (index, &(pos, player)) in moves.iter().enumerate() {
+            board
+                .place(pos, player)
+                .map_err(|error| ApplyError { index, error })?;
+        }
+        *self = board;
+        Ok(())
+    }
+
+    pub fn remove(&mut self, pos: Pos) -> Result<Player, RemoveError> {
+        let cell = &mut self.cells[pos.get() - 1];
+        match *cell {
+            Cell::Occupied(player) => {
+                *cell = Cell::Vacant;
+                self.hash ^= ZobristTable::STANDARD.key(pos, player);
+                Ok(player)
+            }
+            Cell::Vacant => Err(RemoveError { pos }),
+        }
+    }
+
+    // every winning line: rows, then columns, then the two diagonals
+    pub fn lines() -> impl Iterator<Item = (LineId, [Pos; Board::WIDTH])> {
+        let rows = (0..Board::WIDTH).map(LineId::Row);
+        let columns = (0..Board::WIDTH).map(LineId::Column);
+        let diagonals = [LineId::Diagonal, LineId::AntiDiagonal];
+        rows.chain(columns)
+            .chain(diagonals)
+            .map(|line| (line, line.positions()))
+    }
+
+    pub fn transform(&self, symmetry: Symmetry) -> Board {
+        let mut cells = [Cell::Vacant; Board::SIZE];
+        for pos in Pos::all() {
+            cells[pos.transform(symmetry).get() - 1] = self.cells[pos.get() - 1];
+        }
+        Board::with_cells(cells)
+    }
+
+    pub fn rotate90(&self) -> Board {
+        self.transform(Symmetry::Rotate90)
+    }
+
+    pub fn rotate180(&self) -> Board {
+        self.transform(Symmetry::Rotate180)
+    }
+
+    pub fn rotate270(&self) -> Board {
+        self.transform(Symmetry::Rotate270)
+    }
+
+    pub fn reflect_horizontal(&self) -> Board {
+        self.transform(Symmetry::ReflectHorizontal)
+    }
+
+    pub fn reflect_vertical(&self) -> Board {
+        self.transform(Symmetry::ReflectVertical)
+    }
+
+    pub fn reflect_diagonal(&self) -> Board {
+        self.transform(Symmetry::ReflectDiagonal)
+    }
+
+    pub fn reflect_anti_diagonal(&self) -> Board {
+        self.transform(Symmetry::ReflectAntiDiagonal)
+    }
+
+    // the lexicographically smallest of the board's symmetric images, so
+    // that equivalent positions compare equal
+    pub fn canonical(&self) -> Board {
+        self.canonical_with_symmetry().0
+    }
+
+    // also returns the symmetry that takes this board to its canonical form
+    pub fn canonical_with_symmetry(&self) -> (Board, Symmetry) {
+        Symmetry::ALL
+            .into_iter()
+            .map(|symmetry| (self.transform(symmetry), symmetry))
+            .min_by(|(a, _), (b, _)| a.cells.cmp(&b.cells))
+            .unwrap()
+    }
+
+    pub fn count(&self, player: Player) -> usize {
+        self.cells
+            .iter()
+            .filter(|&&cell| cell == Cell::Occupied(player))
+            .count()
+    }
+
+    // the number of moves played so far
+    pub fn move_number(&self) -> usize {
+        self.cells.iter().filter(|cell| cell.is_occupied()).count()
+    }
+
+    pub fn occupancy(&self) -> Occupancy {
+        Occupancy {
+            crosses: self.count(Player::Cross),
+            noughts: self.count(Player::Nought),
+            vacant: Board::SIZE - self.move_number(),
+        }
+    }
+
+    // (position, cell here, cell in `other`) for every cell that differs
+    pub fn diff(&self, other: &Board) -> Vec<(Pos, Cell, Cell)> {
+        Pos::all()
+            .map(|pos| (pos, self.cells[pos.get() - 1], other.cells[pos.get() - 1]))
+            .filter(|(_, before, after)| before != after)
+            .collect()
+    }
+
+    pub fn display_with<'a>(&'a self, theme: &'a BoardTheme) -> ThemedBoard<'a> {
+        ThemedBoard {
+            board: self,
+            theme,
+            colored: false,
+        }
+    }
+
+    // like `display_with`, but with ANSI colours for each player and the
+    // winning line highlighted
+    #[cfg(feature = "color")]
+    pub fn display_colored<'a>(&'a self, theme: &'a BoardTheme) -> ThemedBoard<'a> {
+        ThemedBoard {
+            board: self,
+            theme,
+            colored: true,
+        }
+    }
+
+    pub fn zobrist(&self) -> u64 {
+        self.hash
+    }
+
     pub fn wins(&self, player: Player) -> bool {
         self.rows().any(|row| occupied_by(row, player))
             || self.columns().any(|column| occupied_by(column, player))
@@ -139,7 +727,72 @@ impl Board {
     }
 
     pub fn is_draw(&self) -> bool {
-        self.is_complete() && !self.wins(Player::Nought) && !self.wins(Player::Cross)
+        self.is_complete() && self.winner().is_none()
+    }
+
+    // the vacant cells, or none once the game is over
+    pub fn legal_moves(&self) -> impl Iterator<Item = Pos> {
+        let board = *self;
+        let over = self.is_over();
+        Pos::all().filter(move |pos| !over && board.cells[pos.get() - 1].is_vacant())
+    }
+
+    // each legal move for `player` with the board it leads to
+    pub fn successors(&self, player: Player) -> impl Iterator<Item = (Pos, Board)> {
+        let board = *self;
+        self.legal_moves().map(move |pos| {
+            let mut next = board;
+            next.place(pos, player).unwrap();
+            (pos, next)
+        })
+    }
+
+    // the lowest vacant cell in `column`, counted from zero, which is where a
+    // piece dropped into it lands under gravity
+    pub fn landing(&self, column: usize) -> Option<Pos> {
+        (0..Board::WIDTH)
+            .rev()
+            .filter_map(|row| Pos::from_row_column(row, column))
+            .find(|pos| self.cells[pos.get() - 1].is_vacant())
+    }
+
+    // a vacant cell that would complete a line for `player`
+    pub fn winning_move(&self, player: Player) -> Option<Pos> {
+        self.threats(player).next()
+    }
+
+    // every vacant cell that would complete a line for `player`, i.e. the
+    // cells the opponent has to block
+    pub fn threats(&self, player: Player) -> impl Iterator<Item = Pos> {
+        let board = *self;
+        Pos::all().filter(move |&pos| {
+            let mut board = board;
+            board.place(pos, player).is_ok() && board.wins(player)
+        })
+    }
+
+    // `player` threatens to win in more than one place, so cannot be stopped
+    pub fn has_fork(&self, player: Player) -> bool {
+        self.threats(player).nth(1).is_some()
+    }
+
+    pub fn winning_line(&self) -> Option<(LineId, [Pos; Board::WIDTH])> {
+        Board::lines().find(|(_, positions)| {
+            let cells = positions.map(|pos| self.cells[pos.get() - 1]);
+            cells[0].is_occupied() && cells.iter().all(|&cell| cell == cells[0])
+        })
+    }
+
+    pub fn winner(&self) -> Option<Player> {
+        let (_, positions) = self.winning_line()?;
+        match self.cells[positions[0].get() - 1] {
+            Cell::Occupied(player) => Some(player),
+            Cell::Vacant => None,
+        }
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.is_complete() || self.winner().is_some()
     }
 
     fn rows(&self) -> impl Iterator<Item = impl Iterator<Item = &Cell>> {
@@ -150,6 +803,7 @@ impl Board {
         (0..Board::WIDTH).map(move |n| self.cells.iter().skip(n).step_by(Board::WIDTH))
     }
 
+    #[allow(clippy::iter_skip_zero)]
     fn diagonals(&self) -> impl Iterator<Item = impl Iterator<Item = &Cell>> {
         // major and minor have the same type
         let major = iter::once(
@@ -172,138 +826,3033 @@ impl Board {
     fn is_complete(&self) -> bool {
         self.cells.iter().all(|cell| cell.is_occupied())
     }
+
+    // the cells in order as marks and `.`, e.g. "X...O....", for passing a
+    // board around as one word; `chat::decode` reads it back
+    pub fn to_compact(&self) -> String {
+        self.cells.iter().map(|cell| cell.symbol()).collect()
+    }
 }
 
+impl Default for Board {
+    fn default() -> Board {
+        Board::new()
+    }
+}
+
+// `{:#}` adds file and rank labels and numbers the vacant cells, so players
+// can see what to type
 impl fmt::Display for Board {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        writeln!(f, "+{}+", ["---"; Board::WIDTH].join("+"))?;
+        if !f.alternate() {
+            return write!(f, "{}", self.display_with(&BoardTheme::default()));
+        }
 
-        for row in self.rows() {
-            writeln!(f, "| {} |", row.format(" | "))?;
-            writeln!(f, "+{}+", ["---"; Board::WIDTH].join("+"))?;
+        let files: Vec<String> = (0..Board::WIDTH)
+            .map(|column| char::from(b'a' + column as u8).to_string())
+            .collect();
+        writeln!(f, "    {}", files.join("   "))?;
+        writeln!(f, "  +{}+", ["---"; Board::WIDTH].join("+"))?;
+
+        for row in 0..Board::WIDTH {
+            let cells: Vec<String> = (0..Board::WIDTH)
+                .map(|column| {
+                    let pos = Pos::from_row_column(row, column).unwrap();
+                    match self.cells[pos.get() - 1] {
+                        Cell::Vacant => pos.to_string(),
+                        cell => cell.to_string(),
+                    }
+                })
+                .collect();
+            writeln!(f, "{} | {} |", Board::WIDTH - row, cells.join(" | "))?;
+            writeln!(f, "  +{}+", ["---"; Board::WIDTH].join("+"))?;
         }
 
         Ok(())
     }
 }
 
-fn occupied_by<'a, I: Iterator<Item = &'a Cell>>(mut cells: I, player: Player) -> bool {
-    cells.all(|cell| *cell == Cell::Occupied(player))
-}
-
-#[derive(Debug, Eq, PartialEq)]
-pub struct PlaceError {
-    pub pos: Pos,
-    pub occupied_by: Player,
+// glyphs and spacing for drawing a board; the default is the plain `{}` look
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BoardTheme {
+    pub cross: String,
+    pub nought: String,
+    pub vacant: String,
+    pub horizontal: char,
+    pub vertical: char,
+    // where borders meet: top, middle and bottom rows of left, inner and
+    // right junctions
+    pub junctions: [[char; 3]; 3],
+    // spaces either side of a glyph
+    pub padding: usize,
+    // columns a glyph takes up in the terminal, 2 for most emoji
+    pub glyph_width: usize,
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+impl BoardTheme {
+    pub fn ascii() -> BoardTheme {
+        BoardTheme {
+            cross: Player::Cross.to_string(),
+            nought: Player::Nought.to_string(),
+            vacant: " ".to_string(),
+            horizontal: '-',
+            vertical: '|',
+            junctions: [['+'; 3]; 3],
+            padding: 1,
+            glyph_width: 1,
+        }
+    }
 
-    #[test]
-    fn player_toggle() {
-        assert_eq!(Player::Nought, Player::Cross.toggle());
-        assert_eq!(Player::Cross, Player::Nought.toggle());
+    // box-drawing borders, e.g. ┌───┬───┬───┐
+    pub fn unicode() -> BoardTheme {
+        BoardTheme {
+            horizontal: '─',
+            vertical: '│',
+            junctions: [['┌', '┬', '┐'], ['├', '┼', '┤'], ['└', '┴', '┘']],
+            ..BoardTheme::ascii()
+        }
     }
+}
 
-    #[test]
-    fn player_display() {
-        assert_eq!("O", format!("{}", Player::Nought));
-        assert_eq!("X", format!("{}", Player::Cross));
+impl Default for BoardTheme {
+    fn default() -> BoardTheme {
+        BoardTheme::ascii()
     }
+}
 
-    #[test]
-    fn player_parse() {
-        assert_eq!(Ok(Player::Nought), "O".parse());
-        assert_eq!(Ok(Player::Cross), "X".parse());
+// returned by `Board::display_with`
+pub struct ThemedBoard<'a> {
+    board: &'a Board,
+    theme: &'a BoardTheme,
+    colored: bool,
+}
 
-        assert!("".parse::<Player>().is_err());
-        assert!("a".parse::<Player>().is_err());
-        assert!("o".parse::<Player>().is_err());
-        assert!("XXX".parse::<Player>().is_err());
-    }
+impl fmt::Display for ThemedBoard<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let theme = self.theme;
+        let segment = theme
+            .horizontal
+            .to_string()
+            .repeat(theme.glyph_width + 2 * theme.padding);
+        let segments = vec![segment; Board::WIDTH];
+        let border = |[left, inner, right]: [char; 3]| {
+            format!("{}{}{}", left, segments.join(&inner.to_string()), right)
+        };
+        let padding = " ".repeat(theme.padding);
+        let winning_line = match self.board.winning_line() {
+            Some((_, positions)) if self.colored => positions.to_vec(),
+            _ => Vec::new(),
+        };
 
-    #[test]
-    fn cell() {
-        assert!(Cell::Occupied(Player::Nought).is_occupied());
-        assert!(Cell::Occupied(Player::Cross).is_occupied());
-        assert!(!Cell::Vacant.is_occupied());
+        writeln!(f, "{}", border(theme.junctions[0]))?;
 
-        assert!(!Cell::Occupied(Player::Nought).is_vacant());
-        assert!(!Cell::Occupied(Player::Cross).is_vacant());
-        assert!(Cell::Vacant.is_vacant());
-    }
+        for (row, cells) in self.board.rows().enumerate() {
+            write!(f, "{}", theme.vertical)?;
+            for (column, cell) in cells.enumerate() {
+                let other;
+                let glyph = match cell {
+                    Cell::Occupied(Player::Cross) => &theme.cross,
+                    Cell::Occupied(Player::Nought) => &theme.nought,
+                    // themes only cover the two-player game
+                    Cell::Occupied(player) => {
+                        other = player.to_string();
+                        &other
+                    }
+                    Cell::Vacant => &theme.vacant,
+                };
+                write!(f, "{}", padding)?;
+                if self.colored {
+                    // red crosses, blue noughts, green for anyone else, bold
+                    // and inverted if winning
+                    let color = match cell {
+                        Cell::Occupied(Player::Cross) => "31",
+                        Cell::Occupied(Player::Nought) => "34",
+                        Cell::Occupied(_) => "32",
+                        Cell::Vacant => "39",
+                    };
+                    let pos = Pos::from_row_column(row, column).unwrap();
+                    let highlight = if winning_line.contains(&pos) {
+                        "1;7;"
+                    } else {
+                        ""
+                    };
+                    write!(f, "\x1b[{}{}m{}\x1b[0m", highlight, color, glyph)?;
+                } else {
+                    write!(f, "{}", glyph)?;
+                }
+                write!(f, "{}{}", padding, theme.vertical)?;
+            }
+            writeln!(f)?;
+            let junctions = if row + 1 < Board::WIDTH {
+                theme.junctions[1]
+            } else {
+                theme.junctions[2]
+            };
+            writeln!(f, "{}", border(junctions))?;
+        }
 
-    #[test]
-    fn cell_display() {
-        assert_eq!("O", format!("{}", Cell::Occupied(Player::Nought)));
-        assert_eq!("X", format!("{}", Cell::Occupied(Player::Cross)));
-        assert_eq!(" ", format!("{}", Cell::Vacant));
+        Ok(())
     }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Occupancy {
+    pub crosses: usize,
+    pub noughts: usize,
+    pub vacant: usize,
+}
+
+impl Occupancy {
+    // whose turn it is, given who moved first; None if the board is full,
+    // the counts can't come from alternating moves or `first` isn't crosses
+    // or noughts
+    pub fn to_move(&self, first: Player) -> Option<Player> {
+        if first.number() >= 2 {
+            return None;
+        }
+        let count = |player| {
+            if player == Player::Cross {
+                self.crosses
+            } else {
+                self.noughts
+            }
+        };
+        let second = first.toggle();
+        if self.vacant == 0 {
+            None
+        } else if count(first) == count(second) {
+            Some(first)
+        } else if count(first) == count(second) + 1 {
+            Some(second)
+        } else {
+            None
+        }
+    }
+}
+
+// random keys per (cell, player), xor-ed together to hash a position
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ZobristTable {
+    keys: [[u64; Player::MAX]; Board::SIZE],
+}
+
+impl ZobristTable {
+    // the table behind `Board::zobrist`
+    pub const STANDARD: ZobristTable = ZobristTable::new(0x2545_f491_4f6c_dd1d);
+
+    // keys come from splitmix64, so the same seed always gives the same table
+    pub const fn new(seed: u64) -> ZobristTable {
+        let mut keys = [[0; Player::MAX]; Board::SIZE];
+        let mut rng = Rng::new(seed);
+        let mut i = 0;
+        while i < Board::SIZE * 2 {
+            keys[i / 2][i % 2] = rng.next_u64();
+            i += 1;
+        }
+        // the extra players' keys come after, so two-player hashes are the
+        // same as they were before there were any
+        let mut i = 0;
+        while i < Board::SIZE * (Player::MAX - 2) {
+            keys[i / (Player::MAX - 2)][2 + i % (Player::MAX - 2)] = rng.next_u64();
+            i += 1;
+        }
+        ZobristTable { keys }
+    }
+
+    pub fn key(&self, pos: Pos, player: Player) -> u64 {
+        self.keys[pos.get() - 1][player.index()]
+    }
+
+    // hashes the whole board from scratch
+    pub fn hash(&self, board: &Board) -> u64 {
+        Pos::all()
+            .filter_map(|pos| match board.cells[pos.get() - 1] {
+                Cell::Occupied(player) => Some(self.key(pos, player)),
+                Cell::Vacant => None,
+            })
+            .fold(0, |hash, key| hash ^ key)
+    }
+}
+
+// a small, fast, seedable generator (splitmix64) for the engines and
+// generators in this crate; not suitable for anything security related
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub const fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub const fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // uniform in 0..n; n must not be zero
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "empty range");
+        ((u128::from(self.next_u64()) * n as u128) >> 64) as usize
+    }
+
+    // uniform in [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            None
+        } else {
+            Some(&items[self.below(items.len())])
+        }
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+fn occupied_by<'a, I: Iterator<Item = &'a Cell>>(mut cells: I, player: Player) -> bool {
+    cells.all(|cell| *cell == Cell::Occupied(player))
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum PlaceError {
+    Occupied { pos: Pos, occupied_by: Player },
+    // someone has already won or the board is full
+    GameOver,
+    // only reported by `Game`, which knows whose turn it is
+    NotYourTurn { player: Player },
+    // the move came too late and `player` lost on time
+    OutOfTime { player: Player },
+    // only wild games let a player put down the other mark
+    WrongMark { mark: Player },
+    // under gravity, `pos` has a vacant cell below it
+    Unsupported { pos: Pos },
+    // under gravity, nothing more fits in `column`
+    ColumnFull { column: usize },
+    // a swap is only allowed as the reply to the first move, under the pie
+    // rule
+    CannotSwap,
+    // resigning, timeouts and the engines need a single opponent
+    TwoPlayersOnly,
+}
+
+impl fmt::Display for PlaceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PlaceError::Occupied { pos, occupied_by } => {
+                write!(f, "position {} is already occupied by {}", pos, occupied_by)
+            }
+            PlaceError::GameOver => write!(f, "the game is already over"),
+            PlaceError::NotYourTurn { player } => write!(f, "it is not {}'s turn", player),
+            PlaceError::OutOfTime { player } => write!(f, "{} ran out of time", player),
+            PlaceError::WrongMark { mark } => {
+                write!(f, "{} can only be played in a wild game", mark)
+            }
+            PlaceError::Unsupported { pos } => {
+                write!(f, "position {} has nothing under it", pos)
+            }
+            PlaceError::ColumnFull { column } => write!(f, "column {} is full", column + 1),
+            PlaceError::CannotSwap => write!(f, "swapping sides is not allowed now"),
+            PlaceError::TwoPlayersOnly => write!(f, "only a two-player game allows that"),
+        }
+    }
+}
+
+impl error::Error for PlaceError {}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct RemoveError {
+    pub pos: Pos,
+}
+
+impl fmt::Display for RemoveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "position {} is already vacant", self.pos)
+    }
+}
+
+impl error::Error for RemoveError {}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum InvalidBoardError {
+    // players alternate, so the counts can differ by at most one
+    PieceCount { crosses: usize, noughts: usize },
+    TwoWinners,
+    // the loser placed a piece after the winner completed a line
+    MovedAfterWin { winner: Player },
+    // a mark from a game for more players
+    ExtraPlayer { player: Player },
+}
+
+impl fmt::Display for InvalidBoardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InvalidBoardError::PieceCount { crosses, noughts } => write!(
+                f,
+                "unbalanced piece counts: {} X and {} O",
+                crosses, noughts
+            ),
+            InvalidBoardError::TwoWinners => write!(f, "both players have a line"),
+            InvalidBoardError::MovedAfterWin { winner } => {
+                write!(f, "a piece was placed after {} won", winner)
+            }
+            InvalidBoardError::ExtraPlayer { player } => {
+                write!(f, "{} doesn't play in a two-player game", player)
+            }
+        }
+    }
+}
+
+impl error::Error for InvalidBoardError {}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum HandicapError {
+    // the stone at `index` of `Handicap::stones` can't be placed
+    Stone { index: usize, error: PlaceError },
+    // the stones complete a line or fill the board
+    Decided,
+}
+
+impl fmt::Display for HandicapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HandicapError::Stone { index, error } => {
+                write!(f, "handicap stone {}: {}", index + 1, error)
+            }
+            HandicapError::Decided => write!(f, "the handicap stones decide the game"),
+        }
+    }
+}
+
+impl error::Error for HandicapError {}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum ConfigError {
+    Handicap(HandicapError),
+    // there is no way to play `pieces` moves without deciding the game
+    RandomStart { pieces: usize },
+    // more than `Player::MAX`
+    Players { players: usize },
+    // `first` or the handicapped player isn't one of the players
+    NotPlaying { player: Player },
+    // misère, wild, pie, timed and random-start games all assume a single
+    // opponent
+    TwoPlayersOnly { players: usize },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Handicap(error) => write!(f, "{}", error),
+            ConfigError::RandomStart { pieces } => {
+                write!(f, "no undecided position with {} random pieces", pieces)
+            }
+            ConfigError::Players { players } => {
+                write!(f, "{} players, at most {} can play", players, Player::MAX)
+            }
+            ConfigError::NotPlaying { player } => write!(f, "{} isn't playing", player),
+            ConfigError::TwoPlayersOnly { players } => {
+                write!(f, "these rules are for two players, not {}", players)
+            }
+        }
+    }
+}
+
+impl error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ConfigError::Handicap(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct ApplyError {
+    // index of the offending move in the slice
+    pub index: usize,
+    pub error: PlaceError,
+}
+
+impl fmt::Display for ApplyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid move at index {}", self.index)
+    }
+}
+
+impl error::Error for ApplyError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GameEvent {
+    MovePlayed { player: Player, pos: Pos },
+    GameWon { winner: Player },
+    Draw,
+    // the players swapped sides under the pie rule
+    Swapped,
+    // a timed move was charged to `player`'s clock; it follows the move's
+    // own events
+    ClockUpdated { player: Player, remaining: Duration },
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum WinReason {
+    Line,
+    Resignation,
+    Timeout,
+    // the loser broke the rules of the match, e.g. a bot that crashed or
+    // sent an illegal move
+    Forfeit,
+    // the board filled up without a line: a draw, except in Order and Chaos,
+    // where Chaos wins by it
+    BoardFull,
+    // misère play: the loser completed a line
+    MisereLine,
+    // Order and Chaos: Order made five of either mark in a row
+    FiveInARow,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct GameResult {
+    // None for a draw
+    pub winner: Option<Player>,
+    pub reason: WinReason,
+}
+
+impl fmt::Display for GameResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.winner {
+            Some(winner) if self.reason == WinReason::MisereLine => {
+                write!(
+                    f,
+                    "{} wins as {} made three in a row",
+                    winner,
+                    winner.toggle()
+                )
+            }
+            Some(winner) => {
+                let reason = match self.reason {
+                    WinReason::Line => "three in a row",
+                    WinReason::Resignation => "resignation",
+                    WinReason::Timeout => "timeout",
+                    WinReason::Forfeit => "forfeit",
+                    WinReason::BoardFull => "a full board",
+                    WinReason::FiveInARow => "five in a row",
+                    WinReason::MisereLine => unreachable!(),
+                };
+                write!(f, "{} wins by {}", winner, reason)
+            }
+            None => write!(f, "draw"),
+        }
+    }
+}
+
+// a suggested move for the player to move, and why
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Hint {
+    pub pos: Pos,
+    pub reason: HintReason,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum HintReason {
+    Wins(LineId),
+    Blocks(LineId),
+    CreatesFork,
+    BlocksFork,
+    TakesCentre,
+    TakesCorner,
+    // nothing simpler explains it, but it is what the engine would play
+    BestMove,
+}
+
+impl fmt::Display for Hint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.pos, self.reason)
+    }
+}
+
+impl fmt::Display for HintReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HintReason::Wins(line) => write!(f, "wins along {}", line),
+            HintReason::Blocks(line) => write!(f, "blocks opponent's {}", line),
+            HintReason::CreatesFork => write!(f, "creates a fork"),
+            HintReason::BlocksFork => write!(f, "stops the opponent's fork"),
+            HintReason::TakesCentre => write!(f, "takes the centre"),
+            HintReason::TakesCorner => write!(f, "takes a corner"),
+            HintReason::BestMove => write!(f, "the engine's choice"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct TimeControl {
+    // each player's time for the whole game
+    pub initial: Duration,
+    // added to a player's clock after each of their moves
+    pub increment: Duration,
+}
+
+// variations on the rules; the default is the standard game
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Rules {
+    // three in a row loses instead of winning
+    pub misere: bool,
+    // either player may play either mark, and a line counts for whoever
+    // completed it
+    pub wild: bool,
+    // pieces drop to the lowest vacant cell of their column, as in Connect
+    // Four
+    pub gravity: bool,
+    // lines wrap around the edges, so the diagonals can be broken, e.g.
+    // 2-6-7; a first-player win on 3x3
+    pub toroidal: bool,
+    // after the first move, the second player may swap sides instead of
+    // answering it, so there's no point making the first move too strong
+    pub pie: bool,
+}
+
+impl Rules {
+    // who has won `board` under these rules, `mover` having just moved; the
+    // board itself only knows which mark made a line
+    pub fn winner(self, board: &Board, mover: Player) -> Option<Player> {
+        let mark = board.winner().or_else(|| {
+            let (_, positions) = self.winning_line(board)?;
+            match board.cells[positions[0].get() - 1] {
+                Cell::Occupied(mark) => Some(mark),
+                Cell::Vacant => None,
+            }
+        })?;
+        let completed = if self.wild { mover } else { mark };
+        Some(if self.misere {
+            completed.toggle()
+        } else {
+            completed
+        })
+    }
+
+    // `Board::lines`, plus the broken diagonals on a toroidal board
+    pub fn lines(self) -> impl Iterator<Item = (LineId, [Pos; Board::WIDTH])> {
+        let wrapped = (1..Board::WIDTH)
+            .flat_map(|shift| {
+                [
+                    LineId::WrappedDiagonal(shift),
+                    LineId::WrappedAntiDiagonal(shift),
+                ]
+            })
+            .filter(move |_| self.toroidal)
+            .map(|line| (line, line.positions()));
+        Board::lines().chain(wrapped)
+    }
+
+    // like `Board::winning_line`, over `lines`
+    pub fn winning_line(self, board: &Board) -> Option<(LineId, [Pos; Board::WIDTH])> {
+        self.lines().find(|(_, positions)| {
+            let cells = positions.map(|pos| board.cells[pos.get() - 1]);
+            cells[0].is_occupied() && cells.iter().all(|&cell| cell == cells[0])
+        })
+    }
+
+    // whether these rules let a piece go in `pos` beyond it being vacant
+    pub fn check(self, board: &Board, pos: Pos) -> Result<(), PlaceError> {
+        let vacant = board.cells[pos.get() - 1].is_vacant();
+        if self.gravity && vacant && board.landing(pos.column()) != Some(pos) {
+            return Err(PlaceError::Unsupported { pos });
+        }
+        Ok(())
+    }
+
+    // `Board::legal_moves` less anything these rules forbid
+    pub fn legal_moves(self, board: &Board) -> impl Iterator<Item = Pos> + '_ {
+        board
+            .legal_moves()
+            .filter(move |&pos| self.check(board, pos).is_ok())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum PlayerKind {
+    Human,
+    Ai,
+}
+
+// who sits behind one of the marks
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Seat {
+    pub name: String,
+    pub kind: PlayerKind,
+}
+
+// help for the weaker side, e.g. a human against a perfect engine
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Handicap {
+    // who gets the help
+    pub player: Player,
+    // placed for `player` before the first move
+    pub stones: Vec<Pos>,
+    // `player` moves twice on their first turn
+    pub double_move: bool,
+}
+
+// starts the game from a random undecided position, e.g. for quick games or
+// practice; the same seed always gives the same position
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct RandomStart {
+    // placed alternately, `GameConfig::first` first
+    pub pieces: usize,
+    pub seed: u64,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct GameConfig {
+    pub cross: Seat,
+    pub nought: Seat,
+    // anyone playing after crosses and noughts, in turn order, so that with
+    // one here the game is for three
+    pub others: Vec<Seat>,
+    pub first: Player,
+    pub time_control: Option<TimeControl>,
+    pub rules: Rules,
+    pub handicap: Option<Handicap>,
+    pub random_start: Option<RandomStart>,
+}
+
+impl GameConfig {
+    // the default config with human seats for `players` players, named
+    // "Player 3" on from the third
+    pub fn with_players(players: usize) -> GameConfig {
+        let others = (2..players)
+            .map(|n| Seat {
+                name: format!("Player {}", n + 1),
+                kind: PlayerKind::Human,
+            })
+            .collect();
+        GameConfig {
+            others,
+            ..GameConfig::default()
+        }
+    }
+
+    pub fn players(&self) -> usize {
+        2 + self.others.len()
+    }
+
+    // panics if `player` isn't in the game
+    pub fn seat(&self, player: Player) -> &Seat {
+        match player {
+            Player::Cross => &self.cross,
+            Player::Nought => &self.nought,
+            other => &self.others[other.number() - 2],
+        }
+    }
+
+    pub fn seat_mut(&mut self, player: Player) -> &mut Seat {
+        match player {
+            Player::Cross => &mut self.cross,
+            Player::Nought => &mut self.nought,
+            other => &mut self.others[other.number() - 2],
+        }
+    }
+
+    // e.g. "Alice (X wins by resignation)"
+    pub fn describe(&self, result: GameResult) -> String {
+        match result.winner {
+            Some(winner) => format!("{} ({})", self.seat(winner).name, result),
+            None => {
+                let names: Vec<&str> = Player::all(self.players())
+                    .map(|player| self.seat(player).name.as_str())
+                    .collect();
+                format!("{}: {}", names.join(" vs "), result)
+            }
+        }
+    }
+}
+
+// two humans, crosses first, untimed
+impl Default for GameConfig {
+    fn default() -> GameConfig {
+        GameConfig {
+            cross: Seat {
+                name: "Cross".to_string(),
+                kind: PlayerKind::Human,
+            },
+            nought: Seat {
+                name: "Nought".to_string(),
+                kind: PlayerKind::Human,
+            },
+            others: Vec::new(),
+            first: Player::Cross,
+            time_control: None,
+            rules: Rules::default(),
+            handicap: None,
+            random_start: None,
+        }
+    }
+}
+
+// a board together with whose turn it is, set up by a `GameConfig`
+pub struct Game {
+    config: GameConfig,
+    board: Board,
+    to_move: Player,
+    transcript: Transcript,
+    result: Option<GameResult>,
+    // indexed by `Player::index`
+    remaining: [Duration; 2],
+    // who still has the extra move from a double-move handicap
+    bonus: Option<Player>,
+    subscribers: Vec<Box<dyn FnMut(GameEvent) + Send>>,
+}
+
+impl Game {
+    pub fn new() -> Game {
+        Game::with_config(GameConfig::default())
+    }
+
+    // panics if the handicap or random start is invalid; see
+    // `try_with_config`
+    pub fn with_config(config: GameConfig) -> Game {
+        Game::try_with_config(config).expect("invalid game config")
+    }
+
+    pub fn try_with_config(config: GameConfig) -> Result<Game, ConfigError> {
+        let players = config.players();
+        if players > Player::MAX {
+            return Err(ConfigError::Players { players });
+        }
+        let rules = config.rules;
+        if players > 2
+            && (rules.misere
+                || rules.wild
+                || rules.pie
+                || config.time_control.is_some()
+                || config.random_start.is_some())
+        {
+            return Err(ConfigError::TwoPlayersOnly { players });
+        }
+        let handicapped = config.handicap.as_ref().map(|handicap| handicap.player);
+        if let Some(player) = iter::once(config.first)
+            .chain(handicapped)
+            .find(|player| player.number() >= players)
+        {
+            return Err(ConfigError::NotPlaying { player });
+        }
+        let initial = config
+            .time_control
+            .map_or(Duration::ZERO, |time_control| time_control.initial);
+        let mut board = match &config.handicap {
+            Some(handicap) => {
+                Board::with_handicap(handicap, config.rules).map_err(ConfigError::Handicap)?
+            }
+            None => Board::new(),
+        };
+        let mut to_move = config.first;
+        // the random pieces are part of the starting position, so they
+        // aren't in the transcript
+        if let Some(RandomStart { pieces, seed }) = config.random_start {
+            let moves = board
+                .random_moves(to_move, pieces, config.rules, &mut Rng::new(seed))
+                .ok_or(ConfigError::RandomStart { pieces })?;
+            for pos in moves {
+                board.place(pos, to_move).unwrap();
+                to_move = to_move.toggle();
+            }
+        }
+        let bonus = config
+            .handicap
+            .as_ref()
+            .filter(|handicap| handicap.double_move)
+            .map(|handicap| handicap.player);
+        Ok(Game {
+            board,
+            to_move,
+            transcript: Transcript::with_config(config.clone()),
+            result: None,
+            remaining: [initial; 2],
+            bonus,
+            subscribers: Vec::new(),
+            config,
+        })
+    }
+
+    pub fn with_time_control(time_control: TimeControl) -> Game {
+        Game::with_config(GameConfig {
+            time_control: Some(time_control),
+            ..GameConfig::default()
+        })
+    }
+
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    pub fn time_control(&self) -> Option<TimeControl> {
+        self.config.time_control
+    }
+
+    // None for untimed games
+    pub fn remaining(&self, player: Player) -> Option<Duration> {
+        self.config
+            .time_control
+            .map(|_| self.remaining[player.index()])
+    }
+
+    // `subscriber` is called after every move, and once more when the move
+    // ends the game
+    pub fn subscribe(&mut self, subscriber: impl FnMut(GameEvent) + Send + 'static) {
+        self.subscribers.push(Box::new(subscriber));
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn to_move(&self) -> Player {
+        self.to_move
+    }
+
+    pub fn transcript(&self) -> &Transcript {
+        &self.transcript
+    }
+
+    pub fn last_move(&self) -> Option<(Player, Pos)> {
+        self.transcript.moves().last().copied()
+    }
+
+    // the number of moves played so far
+    pub fn move_number(&self) -> usize {
+        self.transcript.len()
+    }
+
+    // set once the game has ended, however it ended
+    pub fn result(&self) -> Option<GameResult> {
+        self.result
+    }
+
+    pub fn play(&mut self, pos: Pos) -> Result<(), PlaceError> {
+        self.play_mark(pos, self.to_move)
+    }
+
+    // like `play`, but puts down `mark`, which in wild games needn't be the
+    // mover's own
+    pub fn play_mark(&mut self, pos: Pos, mark: Player) -> Result<(), PlaceError> {
+        if self.result.is_some() {
+            return Err(PlaceError::GameOver);
+        }
+        let playing = mark.number() < self.config.players();
+        if !playing || (mark != self.to_move && !self.config.rules.wild) {
+            return Err(PlaceError::WrongMark { mark });
+        }
+        self.config.rules.check(&self.board, pos)?;
+        self.board.place(pos, mark)?;
+        self.record(self.to_move, mark, pos);
+        (self.to_move, self.bonus) = self.next_turn(self.to_move, self.bonus);
+        Ok(())
+    }
+
+    // like `play`, but charges `elapsed` to the mover's clock first; if that
+    // runs the clock out, the move is rejected and the mover loses on time
+    pub fn play_timed(&mut self, pos: Pos, elapsed: Duration) -> Result<(), PlaceError> {
+        let Some(time_control) = self.config.time_control else {
+            return self.play(pos);
+        };
+        if self.result.is_some() {
+            return Err(PlaceError::GameOver);
+        }
+
+        let player = self.to_move;
+        let remaining = self.remaining[player.index()];
+        if elapsed >= remaining {
+            self.remaining[player.index()] = Duration::ZERO;
+            self.emit(GameEvent::ClockUpdated {
+                player,
+                remaining: Duration::ZERO,
+            });
+            self.time_out()?;
+            return Err(PlaceError::OutOfTime { player });
+        }
+
+        self.play(pos)?;
+        let remaining = remaining - elapsed + time_control.increment;
+        self.remaining[player.index()] = remaining;
+        self.emit(GameEvent::ClockUpdated { player, remaining });
+        Ok(())
+    }
+
+    // a good move for whoever is to move, explained in the simplest terms
+    // that fit; None once the game is over, and in games for more than two,
+    // which the engines don't play
+    pub fn hint(&self) -> Option<Hint> {
+        if self.result.is_some() || self.config.players() > 2 {
+            return None;
+        }
+        let board = &self.board;
+        let me = self.to_move;
+        // the explanations below only make sense in the standard game
+        let rules = self.config.rules;
+        if rules != Rules::default() {
+            let (pos, _) = ai::Engine::with_rules(rules).best_move_with_mark(board, me)?;
+            let reason = HintReason::BestMove;
+            return Some(Hint { pos, reason });
+        }
+        let opponent = me.toggle();
+        let line_through = |pos, player| {
+            let mut board = *board;
+            board.place(pos, player).unwrap();
+            board.winning_line().unwrap().0
+        };
+
+        if let Some(pos) = board.winning_move(me) {
+            let reason = HintReason::Wins(line_through(pos, me));
+            return Some(Hint { pos, reason });
+        }
+        if let Some(pos) = board.winning_move(opponent) {
+            let reason = HintReason::Blocks(line_through(pos, opponent));
+            return Some(Hint { pos, reason });
+        }
+        if let Some((pos, _)) = board.successors(me).find(|(_, next)| next.has_fork(me)) {
+            let reason = HintReason::CreatesFork;
+            return Some(Hint { pos, reason });
+        }
+
+        // the opponent could fork next move, and the engine's reply deals with
+        // that, by blocking it or by forcing them to block instead
+        let opponent_forks = board
+            .successors(opponent)
+            .any(|(_, next)| next.has_fork(opponent));
+        let pos = ai::best_move(board, me)?;
+        let corner = |i| i == 0 || i == Board::WIDTH - 1;
+        let reason = if opponent_forks {
+            HintReason::BlocksFork
+        } else if pos.row() == Board::WIDTH / 2 && pos.column() == Board::WIDTH / 2 {
+            HintReason::TakesCentre
+        } else if corner(pos.row()) && corner(pos.column()) {
+            HintReason::TakesCorner
+        } else {
+            HintReason::BestMove
+        };
+        Some(Hint { pos, reason })
+    }
+
+    // drops a piece for whoever is to move into `column`, counted from zero,
+    // and returns where it landed; meant for gravity games, though it works
+    // in any
+    pub fn drop_piece(&mut self, column: usize) -> Result<Pos, PlaceError> {
+        if self.result.is_some() {
+            return Err(PlaceError::GameOver);
+        }
+        let pos = self
+            .board
+            .landing(column)
+            .ok_or(PlaceError::ColumnFull { column })?;
+        self.play(pos)?;
+        Ok(pos)
+    }
+
+    // under the pie rule, the second player may take over the first player's
+    // side, first move and all, leaving the first player to answer it
+    pub fn can_swap(&self) -> bool {
+        self.config.rules.pie
+            && self.result.is_none()
+            && self.transcript.len() == 1
+            && self.to_move != self.transcript.moves()[0].0
+            && !self.transcript.swapped
+    }
+
+    // see `can_swap`; the seats and their clocks change sides, and the
+    // board stays as it is
+    pub fn swap(&mut self) -> Result<(), PlaceError> {
+        if self.result.is_some() {
+            return Err(PlaceError::GameOver);
+        }
+        if !self.can_swap() {
+            return Err(PlaceError::CannotSwap);
+        }
+        mem::swap(&mut self.config.cross, &mut self.config.nought);
+        self.remaining.swap(0, 1);
+        self.transcript.swapped = true;
+        self.emit(GameEvent::Swapped);
+        Ok(())
+    }
+
+    // lets `ai` move for whoever is to move, and returns the move it played;
+    // the engines only play two-player games
+    pub fn play_ai(&mut self, ai: &mut dyn ai::AiPlayer) -> Result<Pos, PlaceError> {
+        if self.result.is_some() {
+            return Err(PlaceError::GameOver);
+        }
+        if self.config.players() > 2 {
+            return Err(PlaceError::TwoPlayersOnly);
+        }
+        let (pos, mark) = ai.choose_mark(&self.board, self.to_move, self.config.rules);
+        self.play_mark(pos, mark)?;
+        Ok(pos)
+    }
+
+    // the player to move has run out of time without moving, e.g. because a
+    // bot stopped responding
+    pub fn time_out(&mut self) -> Result<GameResult, PlaceError> {
+        if self.result.is_some() {
+            return Err(PlaceError::GameOver);
+        }
+        if self.config.players() > 2 {
+            return Err(PlaceError::TwoPlayersOnly);
+        }
+        let result = GameResult {
+            winner: Some(self.to_move.toggle()),
+            reason: WinReason::Timeout,
+        };
+        self.finish(result);
+        Ok(result)
+    }
+
+    // like `play`, but checks that `player` is the one to move
+    pub fn play_as(&mut self, player: Player, pos: Pos) -> Result<(), PlaceError> {
+        if player != self.to_move {
+            return Err(PlaceError::NotYourTurn { player });
+        }
+        self.play(pos)
+    }
+
+    // `player` gives up and the opponent wins, whoever's turn it is
+    pub fn resign(&mut self, player: Player) -> Result<GameResult, PlaceError> {
+        self.concede(player, WinReason::Resignation)
+    }
+
+    // `player` is disqualified and the opponent wins, whoever's turn it is
+    pub fn forfeit(&mut self, player: Player) -> Result<GameResult, PlaceError> {
+        self.concede(player, WinReason::Forfeit)
+    }
+
+    fn concede(&mut self, player: Player, reason: WinReason) -> Result<GameResult, PlaceError> {
+        if self.result.is_some() {
+            return Err(PlaceError::GameOver);
+        }
+        if self.config.players() > 2 {
+            return Err(PlaceError::TwoPlayersOnly);
+        }
+        let result = GameResult {
+            winner: Some(player.toggle()),
+            reason,
+        };
+        self.finish(result);
+        Ok(result)
+    }
+
+    // plays the moves in turn order, atomically like `Board::apply_moves`
+    pub fn apply(&mut self, moves: &[Pos]) -> Result<(), ApplyError> {
+        if self.result.is_some() && !moves.is_empty() {
+            return Err(ApplyError {
+                index: 0,
+                error: PlaceError::GameOver,
+            });
+        }
+        let mut turn = (self.to_move, self.bonus);
+        let players = iter::from_fn(|| {
+            let player = turn.0;
+            turn = self.next_turn(turn.0, turn.1);
+            Some(player)
+        });
+        let moves: Vec<(Pos, Player)> = moves.iter().copied().zip(players).collect();
+        // check the whole sequence first so a bad one leaves the game untouched;
+        // `Board::place` only knows the standard lines, so wins along the
+        // rules' other lines end the game here
+        let rules = self.config.rules;
+        let mut board = self.board;
+        let mut over = false;
+        for (index, &(pos, player)) in moves.iter().enumerate() {
+            if over {
+                return Err(ApplyError {
+                    index,
+                    error: PlaceError::GameOver,
+                });
+            }
+            rules
+                .check(&board, pos)
+                .and_then(|_| board.place(pos, player))
+                .map_err(|error| ApplyError { index, error })?;
+            over = rules.winner(&board, player).is_some();
+        }
+        for &(pos, player) in &moves {
+            self.board.place(pos, player).unwrap();
+            self.record(player, player, pos);
+            (self.to_move, self.bonus) = self.next_turn(player, self.bonus);
+        }
+        Ok(())
+    }
+
+    // who moves after `mover`, and who then still has an extra move
+    fn next_turn(&self, mover: Player, bonus: Option<Player>) -> (Player, Option<Player>) {
+        if bonus == Some(mover) {
+            (mover, None)
+        } else {
+            (mover.next(self.config.players()), bonus)
+        }
+    }
+
+    // called once `mover` has put `mark` on the board
+    fn record(&mut self, mover: Player, mark: Player, pos: Pos) {
+        self.transcript.push(mark, pos);
+        self.emit(GameEvent::MovePlayed { player: mark, pos });
+        if let Some(winner) = self.config.rules.winner(&self.board, mover) {
+            let reason = if self.config.rules.misere {
+                WinReason::MisereLine
+            } else {
+                WinReason::Line
+            };
+            self.finish(GameResult {
+                winner: Some(winner),
+                reason,
+            });
+        } else if self.board.is_over() {
+            self.finish(GameResult {
+                winner: None,
+                reason: WinReason::BoardFull,
+            });
+        }
+    }
+
+    fn finish(&mut self, result: GameResult) {
+        self.result = Some(result);
+        match result.winner {
+            Some(winner) => self.emit(GameEvent::GameWon { winner }),
+            None => self.emit(GameEvent::Draw),
+        }
+    }
+
+    fn emit(&mut self, event: GameEvent) {
+        for subscriber in &mut self.subscribers {
+            subscriber(event);
+        }
+    }
+}
+
+// subscribers are not carried over to the clone
+impl Clone for Game {
+    fn clone(&self) -> Game {
+        Game {
+            config: self.config.clone(),
+            board: self.board,
+            to_move: self.to_move,
+            transcript: self.transcript.clone(),
+            result: self.result,
+            remaining: self.remaining,
+            bonus: self.bonus,
+            subscribers: Vec::new(),
+        }
+    }
+}
+
+impl fmt::Debug for Game {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Game")
+            .field("config", &self.config)
+            .field("board", &self.board)
+            .field("to_move", &self.to_move)
+            .field("transcript", &self.transcript)
+            .field("result", &self.result)
+            .field("remaining", &self.remaining)
+            .field("bonus", &self.bonus)
+            .field("subscribers", &self.subscribers.len())
+            .finish()
+    }
+}
+
+impl Default for Game {
+    fn default() -> Game {
+        Game::new()
+    }
+}
+
+// the moves of a game in the order they were played, written as e.g.
+// "X5 O1 X9", or "Xb2 Oa3 Xc1" with `{:#}`
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Transcript {
+    config: GameConfig,
+    moves: Vec<(Player, Pos)>,
+    // the players swapped sides after the first move
+    swapped: bool,
+}
+
+impl Transcript {
+    pub fn new() -> Transcript {
+        Transcript::with_config(GameConfig::default())
+    }
+
+    pub fn with_config(config: GameConfig) -> Transcript {
+        Transcript {
+            config,
+            moves: Vec::new(),
+            swapped: false,
+        }
+    }
+
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    pub fn swapped(&self) -> bool {
+        self.swapped
+    }
+
+    pub fn push(&mut self, player: Player, pos: Pos) {
+        self.moves.push((player, pos));
+    }
+
+    pub fn moves(&self) -> &[(Player, Pos)] {
+        &self.moves
+    }
+
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    // plays the moves onto a fresh game, stopping at the first illegal one
+    pub fn replay(&self) -> Result<Game, ApplyError> {
+        let mut game = Game::with_config(self.config.clone());
+        for (index, &(player, pos)) in self.moves.iter().enumerate() {
+            // wild games record the marks played, whoever played them
+            let played = if self.config.rules.wild {
+                game.play_mark(pos, player)
+            } else {
+                game.play_as(player, pos)
+            };
+            played.map_err(|error| ApplyError { index, error })?;
+            if index == 0 && self.swapped {
+                game.swap()
+                    .map_err(|error| ApplyError { index: 1, error })?;
+            }
+        }
+        Ok(game)
+    }
+}
+
+impl fmt::Display for Transcript {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let alternate = f.alternate();
+        let moves: Vec<String> = self
+            .moves
+            .iter()
+            .map(|(player, pos)| {
+                if alternate {
+                    format!("{}{:#}", player, pos)
+                } else {
+                    format!("{}{}", player, pos)
+                }
+            })
+            .collect();
+        if self.swapped && !moves.is_empty() {
+            write!(f, "{} swap", moves[0])?;
+            if moves.len() > 1 {
+                write!(f, " {}", moves[1..].join(" "))?;
+            }
+            return Ok(());
+        }
+        write!(f, "{}", moves.join(" "))
+    }
+}
+
+// whitespace-separated moves, each a player followed by a cell number or
+// algebraic position, with `swap` straight after the first move if the
+// players swapped sides; the first move decides who goes first, but legality
+// is only checked by `replay`
+impl str::FromStr for Transcript {
+    type Err = ParseTranscriptError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut transcript = Transcript::new();
+        for (index, token) in s.split_whitespace().enumerate() {
+            let error = || ParseTranscriptError {
+                index,
+                token: token.to_string(),
+            };
+            if token == "swap" {
+                if index != 1 {
+                    return Err(error());
+                }
+                transcript.swapped = true;
+                continue;
+            }
+            let split = token.chars().next().map_or(0, char::len_utf8);
+            let (player, pos) = token.split_at(split);
+            let player = player.parse().map_err(|_| error())?;
+            let pos = pos.parse().map_err(|_| error())?;
+            if index == 0 {
+                transcript.config.first = player;
+            }
+            transcript.push(player, pos);
+        }
+        Ok(transcript)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseTranscriptError {
+    // index of the offending move, not its byte offset
+    pub index: usize,
+    pub token: String,
+}
+
+impl fmt::Display for ParseTranscriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid move {:?} at index {}", self.token, self.index)
+    }
+}
+
+impl error::Error for ParseTranscriptError {}
+
+// generates only boards reachable in a legal game: a shuffled move order is
+// played from a random first player until a random move count or a win
+#[cfg(feature = "proptest")]
+impl proptest::arbitrary::Arbitrary for Board {
+    type Parameters = ();
+    type Strategy = proptest::strategy::BoxedStrategy<Board>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        use proptest::prelude::*;
+
+        let order = Just(Pos::all().collect::<Vec<Pos>>()).prop_shuffle();
+        (order, 0..=Board::SIZE, any::<Player>())
+            .prop_map(|(order, moves, first)| {
+                let mut board = Board::new();
+                let players = iter::successors(Some(first), |player| Some(player.toggle()));
+                for (pos, player) in order.into_iter().zip(players).take(moves) {
+                    if board.place(pos, player).is_err() {
+                        break;
+                    }
+                }
+                board
+            })
+            .boxed()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl proptest::arbitrary::Arbitrary for Player {
+    type Parameters = ();
+    type Strategy = proptest::strategy::BoxedStrategy<Player>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        use proptest::prelude::*;
+
+        prop_oneof![Just(Player::Nought), Just(Player::Cross)].boxed()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl proptest::arbitrary::Arbitrary for Pos {
+    type Parameters = ();
+    type Strategy = proptest::strategy::BoxedStrategy<Pos>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        use proptest::prelude::*;
+
+        (1..=Board::SIZE).prop_map(|pos| Pos { pos }).boxed()
+    }
+}
+
+// helpers for the tests of every module
+#[cfg(test)]
+pub(crate) mod test_util {
+    use crate::{Board, Pos, Transcript};
+
+    // the board after `moves`, e.g. "X5 O1"
+    pub(crate) fn board(moves: &str) -> Board {
+        *moves
+            .parse::<Transcript>()
+            .unwrap()
+            .replay()
+            .unwrap()
+            .board()
+    }
+
+    pub(crate) fn pos(n: usize) -> Pos {
+        Pos::new(n).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn player_toggle() {
+        assert_eq!(Player::Nought, Player::Cross.toggle());
+        assert_eq!(Player::Cross, Player::Nought.toggle());
+        let [third, fourth] = [2, 3].map(|n| Player::nth(n).unwrap());
+        assert_eq!(fourth, third.toggle());
+        assert_eq!('+', fourth.toggle().mark());
+    }
+
+    #[test]
+    fn player_display() {
+        assert_eq!("O", format!("{}", Player::Nought));
+        assert_eq!("X", format!("{}", Player::Cross));
+    }
+
+    #[test]
+    fn player_parse() {
+        assert_eq!(Ok(Player::Nought), "O".parse());
+        assert_eq!(Ok(Player::Cross), "X".parse());
+
+        assert!("".parse::<Player>().is_err());
+        assert!("a".parse::<Player>().is_err());
+        assert!("o".parse::<Player>().is_err());
+        assert!("XXX".parse::<Player>().is_err());
+    }
+
+    #[test]
+    fn player_turn_order() {
+        let plus = Player::nth(2).unwrap();
+        let star = Player::nth(3).unwrap();
+        assert_eq!(
+            vec![Player::Cross, Player::Nought, plus, star],
+            Player::all(4).collect::<Vec<_>>()
+        );
+        assert_eq!(None, Player::nth(Player::MAX));
+        assert_eq!(Player::Nought, Player::Cross.next(3));
+        assert_eq!(plus, Player::Nought.next(3));
+        assert_eq!(Player::Cross, plus.next(3));
+        assert_eq!(Player::Cross, Player::Nought.next(2));
+        assert_eq!("+", plus.to_string());
+        assert_eq!(Ok(star), "*".parse());
+        assert_eq!(3, star.number());
+    }
+
+    #[test]
+    fn error_display() {
+        assert_eq!(
+            "invalid player, expected \"O\", \"X\", \"+\" or \"*\"",
+            ParsePlayerError {}.to_string()
+        );
+        assert_eq!(
+            "invalid position, expected 1-9 or a1-c3",
+            ParsePosError {}.to_string()
+        );
+        assert_eq!(
+            "position 5 is already occupied by X",
+            PlaceError::Occupied {
+                pos: Pos::new(5).unwrap(),
+                occupied_by: Player::Cross
+            }
+            .to_string()
+        );
+        assert_eq!("the game is already over", PlaceError::GameOver.to_string());
+        assert_eq!(
+            "it is not O's turn",
+            PlaceError::NotYourTurn {
+                player: Player::Nought
+            }
+            .to_string()
+        );
+        assert_eq!(
+            "X ran out of time",
+            PlaceError::OutOfTime {
+                player: Player::Cross
+            }
+            .to_string()
+        );
+        assert_eq!(
+            "position 3 is already vacant",
+            RemoveError {
+                pos: Pos::new(3).unwrap()
+            }
+            .to_string()
+        );
+
+        let err = ApplyError {
+            index: 2,
+            error: PlaceError::GameOver,
+        };
+        assert_eq!("invalid move at index 2", err.to_string());
+        assert_eq!(
+            "the game is already over",
+            error::Error::source(&err).unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn cell() {
+        assert!(Cell::Occupied(Player::Nought).is_occupied());
+        assert!(Cell::Occupied(Player::Cross).is_occupied());
+        assert!(!Cell::Vacant.is_occupied());
+
+        assert!(!Cell::Occupied(Player::Nought).is_vacant());
+        assert!(!Cell::Occupied(Player::Cross).is_vacant());
+        assert!(Cell::Vacant.is_vacant());
+    }
+
+    #[test]
+    fn cell_display() {
+        assert_eq!("O", format!("{}", Cell::Occupied(Player::Nought)));
+        assert_eq!("X", format!("{}", Cell::Occupied(Player::Cross)));
+        assert_eq!(" ", format!("{}", Cell::Vacant));
+    }
+
+    #[test]
+    fn pos() {
+        assert_eq!(1, Pos::new(1).unwrap().get());
+        assert_eq!(4, Pos::new(4).unwrap().get());
+        assert_eq!(9, Pos::new(9).unwrap().get());
 
-    #[test]
-    fn pos() {
-        assert_eq!(1, Pos::new(1).unwrap().get());
-        assert_eq!(4, Pos::new(4).unwrap().get());
-        assert_eq!(9, Pos::new(9).unwrap().get());
-
         assert!(Pos::new(0).is_none());
         assert!(Pos::new(10).is_none());
         assert!(Pos::new(usize::MAX).is_none());
     }
 
     #[test]
-    fn board_new() {
+    fn pos_row_column() {
+        let pos = Pos::new(1).unwrap();
+        assert_eq!((0, 0), (pos.row(), pos.column()));
+        let pos = Pos::new(6).unwrap();
+        assert_eq!((1, 2), (pos.row(), pos.column()));
+        let pos = Pos::new(8).unwrap();
+        assert_eq!((2, 1), (pos.row(), pos.column()));
+    }
+
+    #[test]
+    fn pos_all() {
+        let all: Vec<usize> = Pos::all().map(Pos::get).collect();
+        assert_eq!(vec![1, 2, 3, 4, 5, 6, 7, 8, 9], all);
+    }
+
+    #[test]
+    fn pos_neighbors() {
+        let neighbors =
+            |n| -> Vec<usize> { Pos::new(n).unwrap().neighbors().map(Pos::get).collect() };
+        assert_eq!(vec![2, 4, 5], neighbors(1));
+        assert_eq!(vec![1, 3, 4, 5, 6], neighbors(2));
+        assert_eq!(vec![1, 2, 3, 4, 6, 7, 8, 9], neighbors(5));
+        assert_eq!(vec![2, 3, 5, 8, 9], neighbors(6));
+        assert_eq!(vec![5, 6, 8], neighbors(9));
+    }
+
+    #[test]
+    fn pos_from_row_column() {
+        assert_eq!(Pos::new(1), Pos::from_row_column(0, 0));
+        assert_eq!(Pos::new(6), Pos::from_row_column(1, 2));
+        assert_eq!(Pos::new(8), Pos::from_row_column(2, 1));
+        assert!(Pos::from_row_column(3, 0).is_none());
+        assert!(Pos::from_row_column(0, 3).is_none());
+    }
+
+    #[test]
+    fn pos_transform() {
+        let transformed = |symmetry| -> Vec<usize> {
+            Pos::all()
+                .map(|pos| pos.transform(symmetry).get())
+                .collect()
+        };
+        assert_eq!(
+            vec![1, 2, 3, 4, 5, 6, 7, 8, 9],
+            transformed(Symmetry::Identity)
+        );
+        assert_eq!(
+            vec![3, 6, 9, 2, 5, 8, 1, 4, 7],
+            transformed(Symmetry::Rotate90)
+        );
+        assert_eq!(
+            vec![9, 8, 7, 6, 5, 4, 3, 2, 1],
+            transformed(Symmetry::Rotate180)
+        );
+        assert_eq!(
+            vec![7, 4, 1, 8, 5, 2, 9, 6, 3],
+            transformed(Symmetry::Rotate270)
+        );
+        assert_eq!(
+            vec![7, 8, 9, 4, 5, 6, 1, 2, 3],
+            transformed(Symmetry::ReflectHorizontal)
+        );
+        assert_eq!(
+            vec![3, 2, 1, 6, 5, 4, 9, 8, 7],
+            transformed(Symmetry::ReflectVertical)
+        );
+        assert_eq!(
+            vec![1, 4, 7, 2, 5, 8, 3, 6, 9],
+            transformed(Symmetry::ReflectDiagonal)
+        );
+        assert_eq!(
+            vec![9, 6, 3, 8, 5, 2, 7, 4, 1],
+            transformed(Symmetry::ReflectAntiDiagonal)
+        );
+
+        for symmetry in Symmetry::ALL {
+            for pos in Pos::all() {
+                assert_eq!(pos, pos.transform(symmetry).transform(symmetry.inverse()));
+            }
+        }
+    }
+
+    #[test]
+    fn pos_from_algebraic() {
+        assert_eq!(Pos::new(1), Pos::from_algebraic("a3"));
+        assert_eq!(Pos::new(3), Pos::from_algebraic("c3"));
+        assert_eq!(Pos::new(5), Pos::from_algebraic("b2"));
+        assert_eq!(Pos::new(7), Pos::from_algebraic("a1"));
+        assert_eq!(Pos::new(9), Pos::from_algebraic("c1"));
+
+        assert!(Pos::from_algebraic("").is_none());
+        assert!(Pos::from_algebraic("a").is_none());
+        assert!(Pos::from_algebraic("d1").is_none());
+        assert!(Pos::from_algebraic("a0").is_none());
+        assert!(Pos::from_algebraic("a4").is_none());
+        assert!(Pos::from_algebraic("A1").is_none());
+        assert!(Pos::from_algebraic("a1 ").is_none());
+    }
+
+    #[test]
+    fn pos_display() {
+        assert_eq!("1", format!("{}", Pos::new(1).unwrap()));
+        assert_eq!("a3", format!("{:#}", Pos::new(1).unwrap()));
+        assert_eq!("b2", format!("{:#}", Pos::new(5).unwrap()));
+        assert_eq!("c1", format!("{:#}", Pos::new(9).unwrap()));
+
+        for n in 1..=Board::SIZE {
+            let pos = Pos::new(n).unwrap();
+            assert_eq!(Some(pos), Pos::from_algebraic(&format!("{:#}", pos)));
+        }
+    }
+
+    #[test]
+    fn pos_parse() {
+        assert_eq!(Ok(Pos::new(5).unwrap()), "5".parse());
+        assert_eq!(Ok(Pos::new(7).unwrap()), "a1".parse());
+
+        assert!("".parse::<Pos>().is_err());
+        assert!("0".parse::<Pos>().is_err());
+        assert!("10".parse::<Pos>().is_err());
+        assert!("z9".parse::<Pos>().is_err());
+    }
+
+    #[test]
+    fn board_new() {
+        let board = Board::new();
+        assert_eq!([Cell::Vacant; 9], board.cells);
+    }
+
+    #[test]
+    fn board_const() {
+        static EMPTY: Board = Board::new();
+        assert_eq!([Cell::Vacant; 9], EMPTY.cells);
+        assert_eq!(EMPTY, Board::default());
+    }
+
+    #[test]
+    fn board_from_cells() {
+        let x = Cell::Occupied(Player::Cross);
+        let o = Cell::Occupied(Player::Nought);
+        let v = Cell::Vacant;
+
+        assert_eq!(Ok(Board::new()), Board::from_cells([v; 9]));
+
+        let cells = [o, x, v, x, v, o, v, o, x];
+        assert_eq!(Ok(Board::with_cells(cells)), Board::from_cells(cells));
+
+        assert_eq!(
+            Err(InvalidBoardError::PieceCount {
+                crosses: 2,
+                noughts: 0
+            }),
+            Board::from_cells([x, x, v, v, v, v, v, v, v])
+        );
+        assert_eq!(
+            Err(InvalidBoardError::TwoWinners),
+            Board::from_cells([x, x, x, o, o, o, v, v, v])
+        );
+        assert_eq!(
+            Err(InvalidBoardError::MovedAfterWin {
+                winner: Player::Cross
+            }),
+            Board::from_cells([x, x, x, o, o, v, o, o, v])
+        );
+        assert_eq!(
+            Err(InvalidBoardError::MovedAfterWin {
+                winner: Player::Nought
+            }),
+            Board::from_cells([o, o, o, x, x, v, x, x, v])
+        );
+
+        // whoever moved first may be one piece ahead
+        assert!(Board::from_cells([x, x, x, o, o, v, v, v, v]).is_ok());
+        assert!(Board::from_cells([o, o, o, x, x, v, v, v, v]).is_ok());
+        assert!(Board::from_cells([o, o, o, x, x, v, x, v, o]).is_ok());
+    }
+
+    #[test]
+    fn board_place() {
+        let mut board = Board::new();
+
+        board.place(Pos::new(1).unwrap(), Player::Nought).unwrap();
+        assert_eq!(
+            [
+                Cell::Occupied(Player::Nought),
+                Cell::Vacant,
+                Cell::Vacant,
+                Cell::Vacant,
+                Cell::Vacant,
+                Cell::Vacant,
+                Cell::Vacant,
+                Cell::Vacant,
+                Cell::Vacant,
+            ],
+            board.cells
+        );
+        board.place(Pos::new(5).unwrap(), Player::Cross).unwrap();
+        board.place(Pos::new(9).unwrap(), Player::Nought).unwrap();
+        assert_eq!(
+            [
+                Cell::Occupied(Player::Nought),
+                Cell::Vacant,
+                Cell::Vacant,
+                Cell::Vacant,
+                Cell::Occupied(Player::Cross),
+                Cell::Vacant,
+                Cell::Vacant,
+                Cell::Vacant,
+                Cell::Occupied(Player::Nought),
+            ],
+            board.cells
+        );
+
+        assert_eq!(
+            PlaceError::Occupied {
+                pos: Pos::new(1).unwrap(),
+                occupied_by: Player::Nought,
+            },
+            board
+                .place(Pos::new(1).unwrap(), Player::Cross)
+                .unwrap_err()
+        );
+    }
+
+    #[test]
+    fn board_place_after_game_over() {
+        let mut board = Board::new();
+        board
+            .apply_moves(&[
+                (Pos::new(1).unwrap(), Player::Cross),
+                (Pos::new(2).unwrap(), Player::Cross),
+                (Pos::new(3).unwrap(), Player::Cross),
+            ])
+            .unwrap();
+        assert_eq!(
+            Err(PlaceError::GameOver),
+            board.place(Pos::new(5).unwrap(), Player::Nought)
+        );
+
+        let mut board = Board::with_cells([
+            Cell::Occupied(Player::Nought),
+            Cell::Occupied(Player::Cross),
+            Cell::Occupied(Player::Cross),
+            Cell::Occupied(Player::Cross),
+            Cell::Occupied(Player::Nought),
+            Cell::Occupied(Player::Nought),
+            Cell::Occupied(Player::Cross),
+            Cell::Occupied(Player::Nought),
+            Cell::Occupied(Player::Cross),
+        ]);
+        assert_eq!(
+            Err(PlaceError::GameOver),
+            board.place(Pos::new(5).unwrap(), Player::Nought)
+        );
+    }
+
+    #[test]
+    fn board_remove() {
+        let mut board = Board::new();
+        board.place(Pos::new(1).unwrap(), Player::Nought).unwrap();
+        board.place(Pos::new(5).unwrap(), Player::Cross).unwrap();
+
+        assert_eq!(Ok(Player::Cross), board.remove(Pos::new(5).unwrap()));
+        assert_eq!(
+            [
+                Cell::Occupied(Player::Nought),
+                Cell::Vacant,
+                Cell::Vacant,
+                Cell::Vacant,
+                Cell::Vacant,
+                Cell::Vacant,
+                Cell::Vacant,
+                Cell::Vacant,
+                Cell::Vacant,
+            ],
+            board.cells
+        );
+
+        assert_eq!(
+            Err(RemoveError {
+                pos: Pos::new(5).unwrap()
+            }),
+            board.remove(Pos::new(5).unwrap())
+        );
+
+        assert_eq!(Ok(Player::Nought), board.remove(Pos::new(1).unwrap()));
+        assert_eq!([Cell::Vacant; 9], board.cells);
+    }
+
+    #[test]
+    fn board_apply_moves() {
+        let mut board = Board::new();
+        board
+            .apply_moves(&[
+                (Pos::new(1).unwrap(), Player::Cross),
+                (Pos::new(5).unwrap(), Player::Nought),
+            ])
+            .unwrap();
+        assert_eq!(Cell::Occupied(Player::Cross), board.cells[0]);
+        assert_eq!(Cell::Occupied(Player::Nought), board.cells[4]);
+
+        let before = board;
+        assert_eq!(
+            Err(ApplyError {
+                index: 1,
+                error: PlaceError::Occupied {
+                    pos: Pos::new(5).unwrap(),
+                    occupied_by: Player::Nought,
+                },
+            }),
+            board.apply_moves(&[
+                (Pos::new(9).unwrap(), Player::Cross),
+                (Pos::new(5).unwrap(), Player::Cross),
+            ])
+        );
+        assert_eq!(before, board);
+    }
+
+    #[test]
+    fn game_play() {
+        let mut game = Game::new();
+        assert_eq!(Player::Cross, game.to_move());
+
+        game.play(Pos::new(5).unwrap()).unwrap();
+        assert_eq!(Player::Nought, game.to_move());
+        assert_eq!(Cell::Occupied(Player::Cross), game.board().cells[4]);
+
+        assert!(game.play(Pos::new(5).unwrap()).is_err());
+        assert_eq!(Player::Nought, game.to_move());
+
+        game.play(Pos::new(1).unwrap()).unwrap();
+        assert_eq!(Player::Cross, game.to_move());
+        assert_eq!(Cell::Occupied(Player::Nought), game.board().cells[0]);
+    }
+
+    #[test]
+    fn game_play_as() {
+        let mut game = Game::new();
+        assert_eq!(
+            Err(PlaceError::NotYourTurn {
+                player: Player::Nought
+            }),
+            game.play_as(Player::Nought, Pos::new(5).unwrap())
+        );
+        game.play_as(Player::Cross, Pos::new(5).unwrap()).unwrap();
+        assert_eq!(
+            Err(PlaceError::NotYourTurn {
+                player: Player::Cross
+            }),
+            game.play_as(Player::Cross, Pos::new(1).unwrap())
+        );
+        game.play_as(Player::Nought, Pos::new(1).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn game_play_after_win() {
+        let mut game = Game::new();
+        game.apply(&[
+            Pos::new(1).unwrap(),
+            Pos::new(4).unwrap(),
+            Pos::new(2).unwrap(),
+            Pos::new(5).unwrap(),
+            Pos::new(3).unwrap(),
+        ])
+        .unwrap();
+        assert_eq!(Some(Player::Cross), game.board().winner());
+        assert_eq!(Err(PlaceError::GameOver), game.play(Pos::new(6).unwrap()));
+    }
+
+    #[test]
+    fn game_result() {
+        let mut game = Game::new();
+        assert_eq!(None, game.result());
+
+        game.apply(&[
+            Pos::new(1).unwrap(),
+            Pos::new(4).unwrap(),
+            Pos::new(2).unwrap(),
+            Pos::new(5).unwrap(),
+            Pos::new(3).unwrap(),
+        ])
+        .unwrap();
+        let result = GameResult {
+            winner: Some(Player::Cross),
+            reason: WinReason::Line,
+        };
+        assert_eq!(Some(result), game.result());
+        assert_eq!("X wins by three in a row", result.to_string());
+
+        let game = "X1 O2 X3 O5 X4 O7 X8 O6 X9"
+            .parse::<Transcript>()
+            .unwrap()
+            .replay()
+            .unwrap();
+        let result = GameResult {
+            winner: None,
+            reason: WinReason::BoardFull,
+        };
+        assert_eq!(Some(result), game.result());
+        assert_eq!("draw", result.to_string());
+    }
+
+    #[test]
+    fn game_resign() {
+        let mut game = Game::new();
+        game.play(Pos::new(5).unwrap()).unwrap();
+
+        let result = GameResult {
+            winner: Some(Player::Nought),
+            reason: WinReason::Resignation,
+        };
+        assert_eq!(Ok(result), game.resign(Player::Cross));
+        assert_eq!(Some(result), game.result());
+        assert_eq!("O wins by resignation", result.to_string());
+
+        assert_eq!(Err(PlaceError::GameOver), game.resign(Player::Nought));
+        assert_eq!(Err(PlaceError::GameOver), game.play(Pos::new(1).unwrap()));
+        assert_eq!(
+            PlaceError::GameOver,
+            game.apply(&[Pos::new(1).unwrap()]).unwrap_err().error
+        );
+        assert_eq!(1, game.transcript().len());
+    }
+
+    #[test]
+    fn game_forfeit() {
+        let mut game = Game::new();
+        let result = game.forfeit(Player::Nought).unwrap();
+        assert_eq!(Some(Player::Cross), result.winner);
+        assert_eq!("X wins by forfeit", result.to_string());
+        assert_eq!(Err(PlaceError::GameOver), game.forfeit(Player::Cross));
+    }
+
+    #[test]
+    fn game_play_timed() {
+        let mut game = Game::with_time_control(TimeControl {
+            initial: Duration::from_secs(10),
+            increment: Duration::from_secs(1),
+        });
+        assert_eq!(Some(Duration::from_secs(10)), game.remaining(Player::Cross));
+
+        game.play_timed(Pos::new(5).unwrap(), Duration::from_secs(4))
+            .unwrap();
+        assert_eq!(Some(Duration::from_secs(7)), game.remaining(Player::Cross));
+        assert_eq!(
+            Some(Duration::from_secs(10)),
+            game.remaining(Player::Nought)
+        );
+
+        // an illegal move costs nothing
+        assert!(game
+            .play_timed(Pos::new(5).unwrap(), Duration::from_secs(2))
+            .is_err());
+        assert_eq!(
+            Some(Duration::from_secs(10)),
+            game.remaining(Player::Nought)
+        );
+
+        game.play_timed(Pos::new(1).unwrap(), Duration::from_secs(2))
+            .unwrap();
+        assert_eq!(Some(Duration::from_secs(9)), game.remaining(Player::Nought));
+
+        assert_eq!(
+            Err(PlaceError::OutOfTime {
+                player: Player::Cross
+            }),
+            game.play_timed(Pos::new(9).unwrap(), Duration::from_secs(7))
+        );
+        assert_eq!(Some(Duration::ZERO), game.remaining(Player::Cross));
+        assert_eq!(
+            Some(GameResult {
+                winner: Some(Player::Nought),
+                reason: WinReason::Timeout,
+            }),
+            game.result()
+        );
+        assert_eq!(Cell::Vacant, game.board().cells[8]);
+        assert_eq!(
+            Err(PlaceError::GameOver),
+            game.play_timed(Pos::new(9).unwrap(), Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn game_untimed() {
+        let mut game = Game::new();
+        assert_eq!(None, game.time_control());
+        assert_eq!(None, game.remaining(Player::Cross));
+        game.play_timed(Pos::new(5).unwrap(), Duration::from_secs(3600))
+            .unwrap();
+        assert_eq!(None, game.result());
+
+        assert_eq!(
+            Ok(GameResult {
+                winner: Some(Player::Cross),
+                reason: WinReason::Timeout,
+            }),
+            game.time_out()
+        );
+    }
+
+    #[test]
+    fn game_config() {
+        let config = GameConfig {
+            cross: Seat {
+                name: "Alice".to_string(),
+                kind: PlayerKind::Human,
+            },
+            nought: Seat {
+                name: "Bot".to_string(),
+                kind: PlayerKind::Ai,
+            },
+            others: Vec::new(),
+            first: Player::Nought,
+            time_control: None,
+            rules: Rules::default(),
+            handicap: None,
+            random_start: None,
+        };
+        let mut game = Game::with_config(config.clone());
+        assert_eq!(&config, game.config());
+        assert_eq!(Player::Nought, game.to_move());
+        assert_eq!(PlayerKind::Ai, game.config().seat(Player::Nought).kind);
+
+        game.play(Pos::new(5).unwrap()).unwrap();
+        assert_eq!(Cell::Occupied(Player::Nought), game.board().cells[4]);
+        assert_eq!(&config, game.transcript().config());
+
+        let replayed = game.transcript().replay().unwrap();
+        assert_eq!(&config, replayed.config());
+        assert_eq!(game.board(), replayed.board());
+
+        let result = game.resign(Player::Nought).unwrap();
+        assert_eq!(
+            "Alice (X wins by resignation)",
+            game.config().describe(result)
+        );
+        assert_eq!(
+            "Alice vs Bot: draw",
+            config.describe(GameResult {
+                winner: None,
+                reason: WinReason::BoardFull
+            })
+        );
+    }
+
+    #[test]
+    fn game_misere() {
+        let mut game = Game::with_config(GameConfig {
+            rules: Rules {
+                misere: true,
+                ..Rules::default()
+            },
+            ..GameConfig::default()
+        });
+        let moves: Vec<Pos> = [1, 4, 2, 5, 3].map(|n| Pos::new(n).unwrap()).to_vec();
+        game.apply(&moves).unwrap();
+        let result = game.result().unwrap();
+        assert_eq!(Some(Player::Nought), result.winner);
+        assert_eq!(WinReason::MisereLine, result.reason);
+        assert_eq!("O wins as X made three in a row", result.to_string());
+        assert_eq!(Some(Player::Cross), game.board().winner());
+        assert_eq!(
+            Some(Player::Nought),
+            game.config().rules.winner(game.board(), Player::Cross)
+        );
+
+        // the hint steers clear of completing a line
+        let mut game = Game::with_config(game.config().clone());
+        game.apply(&moves[..4]).unwrap();
+        let hint = game.hint().unwrap();
+        assert_ne!(Pos::new(3).unwrap(), hint.pos);
+        assert_eq!(HintReason::BestMove, hint.reason);
+    }
+
+    #[test]
+    fn game_wild() {
+        let pos = |n| Pos::new(n).unwrap();
+        let mut game = Game::new();
+        assert_eq!(
+            Err(PlaceError::WrongMark {
+                mark: Player::Nought
+            }),
+            game.play_mark(pos(1), Player::Nought)
+        );
+
+        let config = GameConfig {
+            rules: Rules {
+                wild: true,
+                ..Rules::default()
+            },
+            ..GameConfig::default()
+        };
+        let mut game = Game::with_config(config.clone());
+        game.play_mark(pos(1), Player::Nought).unwrap();
+        game.play_mark(pos(5), Player::Cross).unwrap();
+        game.play(pos(9)).unwrap();
+        assert_eq!(Player::Nought, game.to_move());
+        // noughts complete a line of crosses, and win with it
+        game.play_mark(pos(3), Player::Cross).unwrap();
+        game.play_mark(pos(2), Player::Nought).unwrap();
+        assert_eq!(None, game.result());
+        game.play_mark(pos(7), Player::Cross).unwrap();
+        let result = game.result().unwrap();
+        assert_eq!(Some(Player::Nought), result.winner);
+        assert_eq!(WinReason::Line, result.reason);
+        assert_eq!("O1 X5 X9 X3 O2 X7", game.transcript().to_string());
+
+        let mut transcript: Transcript = "O1 X5 X9 X3 O2 X7".parse().unwrap();
+        transcript.config = config.clone();
+        let replayed = transcript.replay().unwrap();
+        assert_eq!(game.board(), replayed.board());
+        assert_eq!(game.result(), replayed.result());
+
+        // the engine finishes the line for the other side too
+        let mut game = Game::with_config(config);
+        game.apply(&[pos(1), pos(5), pos(2)]).unwrap();
+        let mut engine = ai::Engine::with_rules(game.config().rules);
+        assert_eq!(pos(3), game.play_ai(&mut engine).unwrap());
+        assert_eq!(Some(Player::Nought), game.result().unwrap().winner);
+        assert_eq!(Some((Player::Cross, pos(3))), game.last_move());
+    }
+
+    #[test]
+    fn game_random_start() {
+        let config = |pieces, seed| GameConfig {
+            random_start: Some(RandomStart { pieces, seed }),
+            ..GameConfig::default()
+        };
+        for seed in 0..20 {
+            for pieces in 0..Board::SIZE {
+                let game = Game::try_with_config(config(pieces, seed)).unwrap();
+                let board = game.board();
+                assert_eq!(pieces, board.move_number());
+                assert!(!board.is_over());
+                assert_eq!(
+                    board.occupancy().to_move(Player::Cross),
+                    Some(game.to_move())
+                );
+                assert_eq!(0, game.transcript().len());
+                // the seed decides the position
+                let again = Game::with_config(config(pieces, seed));
+                assert_eq!(board, again.board());
+            }
+        }
+
+        // seven pieces on top of two handicap stones would fill the board
+        let config = GameConfig {
+            handicap: Some(Handicap {
+                player: Player::Nought,
+                stones: vec![Pos::new(1).unwrap(), Pos::new(2).unwrap()],
+                double_move: false,
+            }),
+            ..config(Board::SIZE - 2, 0)
+        };
+        assert_eq!(
+            Err(ConfigError::RandomStart { pieces: 7 }),
+            Game::try_with_config(config).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn game_toroidal() {
+        let toroidal = Rules {
+            toroidal: true,
+            ..Rules::default()
+        };
+        assert_eq!(8, Rules::default().lines().count());
+        assert_eq!(12, toroidal.lines().count());
+        let line = LineId::WrappedDiagonal(1);
+        assert_eq!("wrapped diagonal 2-6-7", line.to_string());
+        assert_eq!(
+            "wrapped diagonal 1-6-8",
+            LineId::WrappedAntiDiagonal(1).to_string()
+        );
+
+        let mut game = Game::with_config(GameConfig {
+            rules: toroidal,
+            ..GameConfig::default()
+        });
+        for n in [2, 1, 6, 3, 7] {
+            game.play(Pos::new(n).unwrap()).unwrap();
+        }
+        assert_eq!(None, game.board().winner());
+        assert_eq!(
+            Some(GameResult {
+                winner: Some(Player::Cross),
+                reason: WinReason::Line
+            }),
+            game.result()
+        );
+        assert_eq!(
+            Some((line, line.positions())),
+            toroidal.winning_line(game.board())
+        );
+    }
+
+    #[test]
+    fn game_gravity() {
+        let pos = |n| Pos::new(n).unwrap();
+        let rules = Rules {
+            gravity: true,
+            ..Rules::default()
+        };
+        let mut game = Game::with_config(GameConfig {
+            rules,
+            ..GameConfig::default()
+        });
+        assert_eq!(
+            vec![pos(7), pos(8), pos(9)],
+            rules.legal_moves(game.board()).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Err(PlaceError::Unsupported { pos: pos(5) }),
+            game.play(pos(5))
+        );
+        assert_eq!(Ok(pos(8)), game.drop_piece(1));
+        assert_eq!(Ok(pos(5)), game.drop_piece(1));
+        game.play(pos(2)).unwrap();
+        assert_eq!(
+            Err(PlaceError::ColumnFull { column: 1 }),
+            game.drop_piece(1)
+        );
+        assert_eq!(
+            Err(ApplyError {
+                index: 1,
+                error: PlaceError::Unsupported { pos: pos(6) }
+            }),
+            game.apply(&[pos(7), pos(6)])
+        );
+        assert_eq!(3, game.transcript().len());
+
+        // noughts build the diagonal up from the bottom
+        game.apply(&[pos(7), pos(9), pos(6)]).unwrap();
+        assert_eq!(Ok(pos(4)), game.drop_piece(0));
+        assert_eq!(None, game.result());
+        assert_eq!(Ok(pos(3)), game.drop_piece(2));
+        assert_eq!(Some(Player::Nought), game.result().unwrap().winner);
+
+        // every AI only considers cells it can reach, whatever rules it was
+        // built with
+        let config = game.config().clone();
+        let play_out = |ai: &mut dyn ai::AiPlayer| {
+            let mut game = Game::with_config(config.clone());
+            while game.result().is_none() {
+                game.play_ai(ai).unwrap();
+            }
+            assert!(game.transcript().replay().is_ok());
+        };
+        play_out(&mut ai::Engine::with_rules(rules));
+        play_out(&mut ai::Engine::new());
+        play_out(&mut ai::PerfectAi);
+        for seed in 0..10 {
+            play_out(&mut ai::RandomAi::new(seed));
+            play_out(&mut ai::DifficultyAi::new(ai::Difficulty::Easy, seed));
+        }
+    }
+
+    #[test]
+    fn game_three_players() {
+        let pos = |n| Pos::new(n).unwrap();
+        let plus = Player::nth(2).unwrap();
+        let mut game = Game::with_config(GameConfig::with_players(3));
+        game.apply(&[pos(1), pos(2), pos(3)]).unwrap();
+        assert_eq!(Player::Cross, game.to_move());
+        assert_eq!(Cell::Occupied(plus), game.board().cells[2]);
+        assert_eq!(
+            Err(PlaceError::WrongMark {
+                mark: Player::nth(3).unwrap()
+            }),
+            game.play_mark(pos(4), Player::nth(3).unwrap())
+        );
+
+        // the engines and anything else needing an opponent stay out of it
+        assert_eq!(None, game.hint());
+        assert_eq!(
+            Err(PlaceError::TwoPlayersOnly),
+            game.play_ai(&mut ai::PerfectAi)
+        );
+        assert_eq!(Err(PlaceError::TwoPlayersOnly), game.resign(plus));
+
+        // the third player takes the right-hand column
+        game.apply(&[pos(4), pos(5), pos(6), pos(8), pos(7)])
+            .unwrap();
+        assert_eq!(None, game.result());
+        game.play(pos(9)).unwrap();
+        assert_eq!(
+            Some(GameResult {
+                winner: Some(plus),
+                reason: WinReason::Line
+            }),
+            game.result()
+        );
+        assert_eq!("X1 O2 +3 X4 O5 +6 X8 O7 +9", game.transcript().to_string());
+        assert_eq!(
+            Ok(&game.board),
+            game.transcript().replay().as_ref().map(Game::board)
+        );
+
+        let mut game = Game::with_config(GameConfig::with_players(3));
+        game.apply(&[
+            pos(1),
+            pos(2),
+            pos(3),
+            pos(6),
+            pos(4),
+            pos(5),
+            pos(7),
+            pos(9),
+            pos(8),
+        ])
+        .unwrap();
+        let result = game.result().unwrap();
+        assert_eq!(None, result.winner);
+        assert_eq!(
+            "Cross vs Nought vs Player 3: draw",
+            game.config().describe(result)
+        );
+
+        let config = |config| Game::try_with_config(config).map(|_| ());
+        assert_eq!(
+            Err(ConfigError::TwoPlayersOnly { players: 3 }),
+            config(GameConfig {
+                rules: Rules {
+                    misere: true,
+                    ..Rules::default()
+                },
+                ..GameConfig::with_players(3)
+            })
+        );
+        assert_eq!(
+            Err(ConfigError::Players { players: 5 }),
+            config(GameConfig::with_players(5))
+        );
+        assert_eq!(
+            Err(ConfigError::NotPlaying {
+                player: Player::nth(3).unwrap()
+            }),
+            config(GameConfig {
+                first: Player::nth(3).unwrap(),
+                ..GameConfig::with_players(3)
+            })
+        );
+        let mut cells = [Cell::Vacant; Board::SIZE];
+        cells[0] = Cell::Occupied(plus);
+        assert_eq!(
+            Err(InvalidBoardError::ExtraPlayer { player: plus }),
+            Board::from_cells(cells)
+        );
+    }
+
+    #[test]
+    fn game_handicap() {
+        let pos = |n| Pos::new(n).unwrap();
+        let handicap = |player, stones: &[usize], double_move| {
+            let config = GameConfig {
+                handicap: Some(Handicap {
+                    player,
+                    stones: stones.iter().map(|&n| pos(n)).collect(),
+                    double_move,
+                }),
+                ..GameConfig::default()
+            };
+            Game::try_with_config(config)
+        };
+
+        let mut game = handicap(Player::Nought, &[1, 9], false).unwrap();
+        assert_eq!(Cell::Occupied(Player::Nought), game.board().cells[0]);
+        assert_eq!(Cell::Occupied(Player::Nought), game.board().cells[8]);
+        assert_eq!(Player::Cross, game.to_move());
+        assert_eq!(0, game.move_number());
+        game.play(pos(5)).unwrap();
+        game.play(pos(3)).unwrap();
+        let replayed = game.transcript().replay().unwrap();
+        assert_eq!(game.board(), replayed.board());
+
+        // crosses play 5 and 1 before noughts get a turn
+        let mut game = handicap(Player::Cross, &[], true).unwrap();
+        game.play(pos(5)).unwrap();
+        assert_eq!(Player::Cross, game.to_move());
+        game.play(pos(1)).unwrap();
+        assert_eq!(Player::Nought, game.to_move());
+        game.play(pos(9)).unwrap();
+        assert_eq!(Player::Cross, game.to_move());
+        let mut applied = handicap(Player::Cross, &[], true).unwrap();
+        applied.apply(&[pos(5), pos(1), pos(9)]).unwrap();
+        assert_eq!(game.board(), applied.board());
+        assert_eq!(Player::Cross, applied.to_move());
+
+        assert_eq!(
+            Err(ConfigError::Handicap(HandicapError::Stone {
+                index: 1,
+                error: PlaceError::Occupied {
+                    pos: pos(1),
+                    occupied_by: Player::Nought
+                }
+            })),
+            handicap(Player::Nought, &[1, 1], false).map(|_| ())
+        );
+        assert_eq!(
+            Err(ConfigError::Handicap(HandicapError::Decided)),
+            handicap(Player::Nought, &[1, 2, 3], false).map(|_| ())
+        );
+
+        let gravity = Rules {
+            gravity: true,
+            ..Rules::default()
+        };
+        let handicap = Handicap {
+            player: Player::Nought,
+            stones: vec![pos(4), pos(7)],
+            double_move: false,
+        };
+        assert!(Board::with_handicap(&handicap, gravity).is_ok());
+        let handicap = Handicap {
+            stones: vec![pos(4)],
+            ..handicap
+        };
+        assert_eq!(
+            Err(HandicapError::Stone {
+                index: 0,
+                error: PlaceError::Unsupported { pos: pos(4) }
+            }),
+            Board::with_handicap(&handicap, gravity)
+        );
+    }
+
+    #[test]
+    fn game_swap() {
+        let pos = |n| Pos::new(n).unwrap();
+        let mut game = Game::new();
+        game.play(pos(5)).unwrap();
+        assert!(!game.can_swap());
+        assert_eq!(Err(PlaceError::CannotSwap), game.swap());
+
+        let config = GameConfig {
+            cross: Seat {
+                name: "Alice".to_string(),
+                kind: PlayerKind::Human,
+            },
+            nought: Seat {
+                name: "Bob".to_string(),
+                kind: PlayerKind::Human,
+            },
+            rules: Rules {
+                pie: true,
+                ..Rules::default()
+            },
+            ..GameConfig::default()
+        };
+        let mut game = Game::with_config(config.clone());
+        assert!(!game.can_swap());
+        game.play(pos(5)).unwrap();
+        assert!(game.can_swap());
+        game.swap().unwrap();
+        // Bob now owns the centre, and Alice has to answer it
+        assert_eq!("Bob", game.config().cross.name);
+        assert_eq!("Alice", game.config().nought.name);
+        assert_eq!(Player::Nought, game.to_move());
+        assert_eq!(Err(PlaceError::CannotSwap), game.swap());
+        game.play(pos(1)).unwrap();
+        assert_eq!("X5 swap O1", game.transcript().to_string());
+        assert!(game.transcript().swapped());
+
+        let mut transcript: Transcript = "X5 swap O1".parse().unwrap();
+        transcript.config = config;
+        let replayed = transcript.replay().unwrap();
+        assert_eq!(game.board(), replayed.board());
+        assert_eq!("Bob", replayed.config().cross.name);
+        assert_eq!("X5 swap O1", transcript.to_string());
+        assert_eq!(
+            Err(ParseTranscriptError {
+                index: 0,
+                token: "swap".to_string()
+            }),
+            "swap X5".parse::<Transcript>()
+        );
+        // without the pie rule the swap can't be replayed
+        let transcript: Transcript = "X5 swap".parse().unwrap();
+        assert_eq!(
+            ApplyError {
+                index: 1,
+                error: PlaceError::CannotSwap
+            },
+            transcript.replay().unwrap_err()
+        );
+    }
+
+    #[test]
+    fn game_last_move() {
+        let mut game = Game::new();
+        assert_eq!(None, game.last_move());
+        assert_eq!(0, game.move_number());
+
+        game.play(Pos::new(5).unwrap()).unwrap();
+        assert_eq!(
+            Some((Player::Cross, Pos::new(5).unwrap())),
+            game.last_move()
+        );
+        assert_eq!(1, game.move_number());
+
+        game.play(Pos::new(3).unwrap()).unwrap();
+        assert!(game.play(Pos::new(3).unwrap()).is_err());
+        assert_eq!(
+            Some((Player::Nought, Pos::new(3).unwrap())),
+            game.last_move()
+        );
+        assert_eq!(2, game.move_number());
+        assert_eq!(game.board().move_number(), game.move_number());
+    }
+
+    #[test]
+    fn game_hint() {
+        let hint = |moves: &str| {
+            let game = moves.parse::<Transcript>().unwrap().replay().unwrap();
+            game.hint().map(|hint| hint.to_string())
+        };
+        assert_eq!(Some("5: takes the centre".to_string()), hint(""));
+        assert_eq!(Some("5: takes the centre".to_string()), hint("X1"));
+        assert_eq!(Some("3: wins along row 1".to_string()), hint("X1 O4 X2 O5"));
+        assert_eq!(
+            Some("3: blocks opponent's row 1".to_string()),
+            hint("X1 O5 X2")
+        );
+        assert_eq!(
+            Some("9: blocks opponent's diagonal 1-5-9".to_string()),
+            hint("X1 O2 X5")
+        );
+        assert_eq!(Some("4: creates a fork".to_string()), hint("X1 O2 X5 O9"));
+        // opposite corners around the centre: only an edge stops the fork
+        assert_eq!(
+            Some("2: stops the opponent's fork".to_string()),
+            hint("X1 O5 X9")
+        );
+        assert_eq!(None, hint("X1 O4 X2 O5 X3"));
+
+        assert_eq!("row 1", LineId::Row(0).to_string());
+        assert_eq!("column 3", LineId::Column(2).to_string());
+        assert_eq!("diagonal 3-5-7", LineId::AntiDiagonal.to_string());
+    }
+
+    #[test]
+    fn game_play_ai() {
+        let mut game = Game::new();
+        let mut cross = ai::HeuristicAi::default();
+        let mut nought = ai::PerfectAi;
+        while game.result().is_none() {
+            if game.to_move() == Player::Cross {
+                game.play_ai(&mut cross).unwrap();
+            } else {
+                game.play_ai(&mut nought).unwrap();
+            }
+        }
+        assert_eq!(Board::SIZE, game.transcript().len());
+        assert_eq!(None, game.result().unwrap().winner);
+        assert_eq!(Err(PlaceError::GameOver), game.play_ai(&mut cross));
+    }
+
+    #[test]
+    fn game_apply() {
+        use std::sync::{Arc, Mutex};
+
+        let mut game = Game::new();
+        game.apply(&[
+            Pos::new(5).unwrap(),
+            Pos::new(1).unwrap(),
+            Pos::new(9).unwrap(),
+        ])
+        .unwrap();
+        assert_eq!(Player::Nought, game.to_move());
+        assert_eq!(Cell::Occupied(Player::Cross), game.board().cells[4]);
+        assert_eq!(Cell::Occupied(Player::Nought), game.board().cells[0]);
+        assert_eq!(Cell::Occupied(Player::Cross), game.board().cells[8]);
+
+        let err = game
+            .apply(&[Pos::new(2).unwrap(), Pos::new(9).unwrap()])
+            .unwrap_err();
+        assert_eq!(1, err.index);
+        assert_eq!(Player::Nought, game.to_move());
+        assert_eq!(Cell::Vacant, game.board().cells[1]);
+
+        // crosses win along a wrapped diagonal, so nothing comes after 7
+        let mut game = Game::with_config(GameConfig {
+            rules: Rules {
+                toroidal: true,
+                ..Rules::default()
+            },
+            ..GameConfig::default()
+        });
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        game.subscribe(move |event| sink.lock().unwrap().push(event));
+        let moves = [2, 1, 6, 5, 7, 4].map(|n| Pos::new(n).unwrap());
+        assert_eq!(
+            Err(ApplyError {
+                index: 5,
+                error: PlaceError::GameOver
+            }),
+            game.apply(&moves)
+        );
+        assert!(game.transcript().is_empty());
+        game.apply(&moves[..5]).unwrap();
+        assert_eq!(Some(Player::Cross), game.result().unwrap().winner);
+        let won = events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|event| matches!(event, GameEvent::GameWon { .. }))
+            .count();
+        assert_eq!(1, won);
+    }
+
+    #[test]
+    fn board_occupancy() {
+        let x = Cell::Occupied(Player::Cross);
+        let o = Cell::Occupied(Player::Nought);
+        let v = Cell::Vacant;
+
         let board = Board::new();
-        assert_eq!([Cell::Vacant; 9], board.cells);
+        assert_eq!(0, board.count(Player::Cross));
+        assert_eq!(0, board.move_number());
+        assert_eq!(
+            Occupancy {
+                crosses: 0,
+                noughts: 0,
+                vacant: 9
+            },
+            board.occupancy()
+        );
+        assert_eq!(
+            Some(Player::Cross),
+            board.occupancy().to_move(Player::Cross)
+        );
+
+        let board = Board::with_cells([x, o, v, v, x, v, v, v, v]);
+        assert_eq!(2, board.count(Player::Cross));
+        assert_eq!(1, board.count(Player::Nought));
+        assert_eq!(3, board.move_number());
+        assert_eq!(
+            Some(Player::Nought),
+            board.occupancy().to_move(Player::Cross)
+        );
+        assert_eq!(None, board.occupancy().to_move(Player::Nought));
+        let third = Player::nth(2).unwrap();
+        assert_eq!(None, Board::new().occupancy().to_move(third));
+
+        let board = Board::with_cells([x, x, x, v, v, v, v, v, v]);
+        assert_eq!(None, board.occupancy().to_move(Player::Cross));
+
+        let board = Board::with_cells([x, o, x, x, o, o, o, x, x]);
+        assert_eq!(9, board.move_number());
+        assert_eq!(None, board.occupancy().to_move(Player::Cross));
     }
 
     #[test]
-    fn board_place() {
+    fn board_diff() {
         let mut board = Board::new();
+        assert!(board.diff(&Board::new()).is_empty());
 
+        board.place(Pos::new(5).unwrap(), Player::Cross).unwrap();
+        let before = board;
         board.place(Pos::new(1).unwrap(), Player::Nought).unwrap();
         assert_eq!(
-            [
-                Cell::Occupied(Player::Nought),
-                Cell::Vacant,
-                Cell::Vacant,
-                Cell::Vacant,
-                Cell::Vacant,
-                Cell::Vacant,
-                Cell::Vacant,
-                Cell::Vacant,
+            vec![(
+                Pos::new(1).unwrap(),
                 Cell::Vacant,
+                Cell::Occupied(Player::Nought)
+            )],
+            before.diff(&board)
+        );
+
+        board.remove(Pos::new(5).unwrap()).unwrap();
+        assert_eq!(
+            vec![
+                (
+                    Pos::new(1).unwrap(),
+                    Cell::Vacant,
+                    Cell::Occupied(Player::Nought)
+                ),
+                (
+                    Pos::new(5).unwrap(),
+                    Cell::Occupied(Player::Cross),
+                    Cell::Vacant
+                ),
             ],
-            board.cells
+            before.diff(&board)
         );
+    }
+
+    #[test]
+    fn board_zobrist() {
+        let mut board = Board::new();
+        assert_eq!(0, board.zobrist());
+
         board.place(Pos::new(5).unwrap(), Player::Cross).unwrap();
-        board.place(Pos::new(9).unwrap(), Player::Nought).unwrap();
+        board.place(Pos::new(1).unwrap(), Player::Nought).unwrap();
+        assert_eq!(ZobristTable::STANDARD.hash(&board), board.zobrist());
+        assert_ne!(0, board.zobrist());
+
+        // the hash depends on the position, not on how it was reached
+        let mut other = Board::new();
+        other.place(Pos::new(1).unwrap(), Player::Nought).unwrap();
+        other.place(Pos::new(9).unwrap(), Player::Cross).unwrap();
+        assert_ne!(board.zobrist(), other.zobrist());
+        other.remove(Pos::new(9).unwrap()).unwrap();
+        other.place(Pos::new(5).unwrap(), Player::Cross).unwrap();
+        assert_eq!(board.zobrist(), other.zobrist());
+
+        board.remove(Pos::new(5).unwrap()).unwrap();
+        board.remove(Pos::new(1).unwrap()).unwrap();
+        assert_eq!(0, board.zobrist());
+
+        let rotated = other.rotate90();
+        assert_eq!(ZobristTable::STANDARD.hash(&rotated), rotated.zobrist());
+    }
+
+    #[test]
+    fn zobrist_table() {
+        let table = ZobristTable::new(1);
+        assert_eq!(table, ZobristTable::new(1));
+        assert_ne!(table, ZobristTable::new(2));
+
+        let mut keys: Vec<u64> = Pos::all()
+            .flat_map(|pos| {
+                [
+                    table.key(pos, Player::Nought),
+                    table.key(pos, Player::Cross),
+                ]
+            })
+            .collect();
+        keys.sort_unstable();
+        keys.dedup();
+        assert_eq!(2 * Board::SIZE, keys.len());
+    }
+
+    #[test]
+    fn game_transcript() {
+        let mut game = Game::new();
+        assert!(game.transcript().is_empty());
+
+        game.play(Pos::new(5).unwrap()).unwrap();
+        game.apply(&[Pos::new(1).unwrap(), Pos::new(9).unwrap()])
+            .unwrap();
+        assert!(game.play(Pos::new(5).unwrap()).is_err());
+        assert_eq!(
+            &[
+                (Player::Cross, Pos::new(5).unwrap()),
+                (Player::Nought, Pos::new(1).unwrap()),
+                (Player::Cross, Pos::new(9).unwrap()),
+            ],
+            game.transcript().moves()
+        );
+        assert_eq!("X5 O1 X9", game.transcript().to_string());
+    }
+
+    #[test]
+    fn game_subscribe() {
+        use std::sync::{Arc, Mutex};
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let mut game = Game::new();
+        let sink = Arc::clone(&events);
+        game.subscribe(move |event| sink.lock().unwrap().push(event));
+
+        game.play(Pos::new(1).unwrap()).unwrap();
+        assert!(game.play(Pos::new(1).unwrap()).is_err());
+        assert_eq!(
+            vec![GameEvent::MovePlayed {
+                player: Player::Cross,
+                pos: Pos::new(1).unwrap()
+            }],
+            *events.lock().unwrap()
+        );
+
+        events.lock().unwrap().clear();
+        game.apply(&[
+            Pos::new(4).unwrap(),
+            Pos::new(2).unwrap(),
+            Pos::new(5).unwrap(),
+            Pos::new(3).unwrap(),
+        ])
+        .unwrap();
+        assert_eq!(
+            vec![
+                GameEvent::MovePlayed {
+                    player: Player::Nought,
+                    pos: Pos::new(4).unwrap()
+                },
+                GameEvent::MovePlayed {
+                    player: Player::Cross,
+                    pos: Pos::new(2).unwrap()
+                },
+                GameEvent::MovePlayed {
+                    player: Player::Nought,
+                    pos: Pos::new(5).unwrap()
+                },
+                GameEvent::MovePlayed {
+                    player: Player::Cross,
+                    pos: Pos::new(3).unwrap()
+                },
+                GameEvent::GameWon {
+                    winner: Player::Cross
+                },
+            ],
+            *events.lock().unwrap()
+        );
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let mut game: Game = "X1 O2 X3 O5 X4 O7 X8 O6"
+            .parse::<Transcript>()
+            .unwrap()
+            .replay()
+            .unwrap();
+        let sink = Arc::clone(&events);
+        game.subscribe(move |event| sink.lock().unwrap().push(event));
+        game.play(Pos::new(9).unwrap()).unwrap();
+        assert_eq!(Some(&GameEvent::Draw), events.lock().unwrap().last());
+    }
+
+    #[test]
+    fn transcript_replay() {
+        let mut transcript = Transcript::new();
+        assert_eq!("", transcript.to_string());
+
+        transcript.push(Player::Cross, Pos::new(1).unwrap());
+        transcript.push(Player::Nought, Pos::new(5).unwrap());
+        transcript.push(Player::Cross, Pos::new(9).unwrap());
+        let game = transcript.replay().unwrap();
+        assert_eq!(Player::Nought, game.to_move());
+        assert_eq!(&transcript, game.transcript());
+
+        transcript.push(Player::Cross, Pos::new(2).unwrap());
+        assert_eq!(
+            ApplyError {
+                index: 3,
+                error: PlaceError::NotYourTurn {
+                    player: Player::Cross
+                },
+            },
+            transcript.replay().unwrap_err()
+        );
+
+        let mut transcript = Transcript::new();
+        transcript.push(Player::Cross, Pos::new(1).unwrap());
+        transcript.push(Player::Nought, Pos::new(1).unwrap());
+        assert_eq!(1, transcript.replay().unwrap_err().index);
+    }
+
+    #[test]
+    fn transcript_display_alternate() {
+        let transcript: Transcript = "X5 O1 X9".parse().unwrap();
+        assert_eq!("Xb2 Oa3 Xc1", format!("{:#}", transcript));
+    }
+
+    #[test]
+    fn transcript_parse() {
+        let transcript: Transcript = "X5 O1 X9 O3".parse().unwrap();
         assert_eq!(
-            [
-                Cell::Occupied(Player::Nought),
-                Cell::Vacant,
-                Cell::Vacant,
-                Cell::Vacant,
-                Cell::Occupied(Player::Cross),
-                Cell::Vacant,
-                Cell::Vacant,
-                Cell::Vacant,
-                Cell::Occupied(Player::Nought),
+            &[
+                (Player::Cross, Pos::new(5).unwrap()),
+                (Player::Nought, Pos::new(1).unwrap()),
+                (Player::Cross, Pos::new(9).unwrap()),
+                (Player::Nought, Pos::new(3).unwrap()),
             ],
-            board.cells
+            transcript.moves()
         );
+        assert_eq!("X5 O1 X9 O3", transcript.to_string());
 
+        let algebraic: Transcript = " Xb2\tOa3\n Xc1  Oc3 ".parse().unwrap();
+        assert_eq!(transcript, algebraic);
+
+        assert_eq!(Ok(Transcript::new()), "".parse());
         assert_eq!(
-            PlaceError {
-                pos: Pos::new(1).unwrap(),
-                occupied_by: Player::Nought,
-            },
-            board
-                .place(Pos::new(1).unwrap(), Player::Cross)
-                .unwrap_err()
+            Err(ParseTranscriptError {
+                index: 1,
+                token: "Q1".to_string()
+            }),
+            "X5 Q1".parse::<Transcript>()
+        );
+        assert_eq!(
+            Err(ParseTranscriptError {
+                index: 2,
+                token: "X10".to_string()
+            }),
+            "X5 O1 X10".parse::<Transcript>()
+        );
+        assert_eq!(
+            Err(ParseTranscriptError {
+                index: 0,
+                token: "é5".to_string()
+            }),
+            "é5".parse::<Transcript>()
+        );
+        assert_eq!(
+            "invalid move \"X\" at index 0",
+            "X".parse::<Transcript>().unwrap_err().to_string()
         );
+
+        // the notation records who went first
+        let transcript: Transcript = "O5 X1".parse().unwrap();
+        assert_eq!(Player::Nought, transcript.config().first);
+        assert!(transcript.replay().is_ok());
+
+        // well-formed but illegal transcripts parse, and fail on replay
+        let illegal: Transcript = "X5 X1".parse().unwrap();
+        assert!(illegal.replay().is_err());
+    }
+
+    #[test]
+    fn rng() {
+        let mut a = Rng::new(7);
+        let mut b = Rng::new(7);
+        let first: Vec<u64> = (0..4).map(|_| a.next_u64()).collect();
+        let second: Vec<u64> = (0..4).map(|_| b.next_u64()).collect();
+        assert_eq!(first, second);
+        assert_ne!(first[0], Rng::new(8).next_u64());
+
+        let mut rng = Rng::new(1);
+        let mut seen = [false; 5];
+        for _ in 0..200 {
+            let n = rng.below(5);
+            seen[n] = true;
+            let f = rng.next_f64();
+            assert!((0.0..1.0).contains(&f));
+        }
+        assert!(seen.iter().all(|&seen| seen));
+
+        assert_eq!(None, rng.choose::<u8>(&[]));
+        assert_eq!(Some(&3), rng.choose(&[3]));
+
+        let mut items = [1, 2, 3, 4, 5, 6];
+        rng.shuffle(&mut items);
+        items.sort_unstable();
+        assert_eq!([1, 2, 3, 4, 5, 6], items);
     }
 
     #[test]
@@ -323,20 +3872,123 @@ mod tests {
     }
 
     #[test]
-    fn board_rows() {
-        let board = Board {
-            cells: [
-                Cell::Occupied(Player::Nought),
-                Cell::Occupied(Player::Cross),
-                Cell::Vacant,
-                Cell::Occupied(Player::Cross),
-                Cell::Vacant,
-                Cell::Occupied(Player::Nought),
-                Cell::Vacant,
-                Cell::Occupied(Player::Nought),
-                Cell::Occupied(Player::Cross),
-            ],
+    fn board_to_compact() {
+        assert_eq!(".........", Board::new().to_compact());
+        let mut board = Board::new();
+        board.place(Pos::new(1).unwrap(), Player::Cross).unwrap();
+        board.place(Pos::new(5).unwrap(), Player::Nought).unwrap();
+        assert_eq!("X...O....", board.to_compact());
+    }
+
+    #[test]
+    fn board_display_with() {
+        let mut board = Board::new();
+        board.place(Pos::new(1).unwrap(), Player::Cross).unwrap();
+        board.place(Pos::new(9).unwrap(), Player::Nought).unwrap();
+
+        assert_eq!(
+            format!("{}", board),
+            format!("{}", board.display_with(&BoardTheme::default()))
+        );
+
+        let theme = BoardTheme {
+            cross: "❌".to_string(),
+            nought: "⭕".to_string(),
+            vacant: "  ".to_string(),
+            horizontal: '=',
+            vertical: ':',
+            junctions: [['#'; 3]; 3],
+            padding: 0,
+            glyph_width: 2,
         };
+        assert_eq!(
+            "\
+            #==#==#==#\n\
+            :❌:  :  :\n\
+            #==#==#==#\n\
+            :  :  :  :\n\
+            #==#==#==#\n\
+            :  :  :⭕:\n\
+            #==#==#==#\n\
+            ",
+            format!("{}", board.display_with(&theme)),
+        );
+    }
+
+    #[test]
+    fn board_display_unicode() {
+        let mut board = Board::new();
+        board.place(Pos::new(5).unwrap(), Player::Cross).unwrap();
+        assert_eq!(
+            "\
+            ┌───┬───┬───┐\n\
+            │   │   │   │\n\
+            ├───┼───┼───┤\n\
+            │   │ X │   │\n\
+            ├───┼───┼───┤\n\
+            │   │   │   │\n\
+            └───┴───┴───┘\n\
+            ",
+            format!("{}", board.display_with(&BoardTheme::unicode())),
+        );
+    }
+
+    #[cfg(feature = "color")]
+    #[test]
+    fn board_display_colored() {
+        let x = Cell::Occupied(Player::Cross);
+        let o = Cell::Occupied(Player::Nought);
+        let v = Cell::Vacant;
+
+        let board = Board::with_cells([x, x, x, o, o, v, v, v, v]);
+        let theme = BoardTheme::ascii();
+        let rendered = format!("{}", board.display_colored(&theme));
+        let mut lines = rendered.lines();
+
+        assert_eq!(Some("+---+---+---+"), lines.next());
+        assert_eq!(
+            Some("| \x1b[1;7;31mX\x1b[0m | \x1b[1;7;31mX\x1b[0m | \x1b[1;7;31mX\x1b[0m |"),
+            lines.next()
+        );
+        assert_eq!(Some("+---+---+---+"), lines.next());
+        assert_eq!(
+            Some("| \x1b[34mO\x1b[0m | \x1b[34mO\x1b[0m | \x1b[39m \x1b[0m |"),
+            lines.next()
+        );
+    }
+
+    #[test]
+    fn board_display_labeled() {
+        let mut board = Board::new();
+        board.place(Pos::new(2).unwrap(), Player::Cross).unwrap();
+        board.place(Pos::new(5).unwrap(), Player::Nought).unwrap();
+        assert_eq!(
+            "    a   b   c\n\
+            \x20 +---+---+---+\n\
+            3 | 1 | X | 3 |\n\
+            \x20 +---+---+---+\n\
+            2 | 4 | O | 6 |\n\
+            \x20 +---+---+---+\n\
+            1 | 7 | 8 | 9 |\n\
+            \x20 +---+---+---+\n\
+            ",
+            format!("{:#}", board),
+        );
+    }
+
+    #[test]
+    fn board_rows() {
+        let board = Board::with_cells([
+            Cell::Occupied(Player::Nought),
+            Cell::Occupied(Player::Cross),
+            Cell::Vacant,
+            Cell::Occupied(Player::Cross),
+            Cell::Vacant,
+            Cell::Occupied(Player::Nought),
+            Cell::Vacant,
+            Cell::Occupied(Player::Nought),
+            Cell::Occupied(Player::Cross),
+        ]);
 
         let mut rows = board.rows();
 
@@ -363,19 +4015,17 @@ mod tests {
 
     #[test]
     fn board_columns() {
-        let board = Board {
-            cells: [
-                Cell::Occupied(Player::Nought),
-                Cell::Occupied(Player::Cross),
-                Cell::Vacant,
-                Cell::Occupied(Player::Cross),
-                Cell::Vacant,
-                Cell::Occupied(Player::Nought),
-                Cell::Vacant,
-                Cell::Occupied(Player::Nought),
-                Cell::Occupied(Player::Cross),
-            ],
-        };
+        let board = Board::with_cells([
+            Cell::Occupied(Player::Nought),
+            Cell::Occupied(Player::Cross),
+            Cell::Vacant,
+            Cell::Occupied(Player::Cross),
+            Cell::Vacant,
+            Cell::Occupied(Player::Nought),
+            Cell::Vacant,
+            Cell::Occupied(Player::Nought),
+            Cell::Occupied(Player::Cross),
+        ]);
 
         let mut columns = board.columns();
 
@@ -402,19 +4052,17 @@ mod tests {
 
     #[test]
     fn board_diagonals() {
-        let board = Board {
-            cells: [
-                Cell::Occupied(Player::Nought),
-                Cell::Occupied(Player::Cross),
-                Cell::Vacant,
-                Cell::Occupied(Player::Cross),
-                Cell::Vacant,
-                Cell::Occupied(Player::Nought),
-                Cell::Vacant,
-                Cell::Occupied(Player::Nought),
-                Cell::Occupied(Player::Cross),
-            ],
-        };
+        let board = Board::with_cells([
+            Cell::Occupied(Player::Nought),
+            Cell::Occupied(Player::Cross),
+            Cell::Vacant,
+            Cell::Occupied(Player::Cross),
+            Cell::Vacant,
+            Cell::Occupied(Player::Nought),
+            Cell::Vacant,
+            Cell::Occupied(Player::Nought),
+            Cell::Occupied(Player::Cross),
+        ]);
 
         let mut diagonals = board.diagonals();
 
@@ -433,115 +4081,319 @@ mod tests {
         assert!(diagonals.next().is_none());
     }
 
+    #[test]
+    fn board_transform() {
+        let x = Cell::Occupied(Player::Cross);
+        let o = Cell::Occupied(Player::Nought);
+        let v = Cell::Vacant;
+
+        let board = Board::with_cells([x, o, v, v, v, v, v, v, v]);
+        assert_eq!([v, v, x, v, v, o, v, v, v], board.rotate90().cells);
+        assert_eq!([v, v, v, v, v, v, v, o, x], board.rotate180().cells);
+        assert_eq!([v, v, v, o, v, v, x, v, v], board.rotate270().cells);
+        assert_eq!(
+            [v, v, v, v, v, v, x, o, v],
+            board.reflect_horizontal().cells
+        );
+        assert_eq!([v, o, x, v, v, v, v, v, v], board.reflect_vertical().cells);
+        assert_eq!([x, v, v, o, v, v, v, v, v], board.reflect_diagonal().cells);
+        assert_eq!(
+            [v, v, v, v, v, o, v, v, x],
+            board.reflect_anti_diagonal().cells
+        );
+
+        assert_eq!(board, board.rotate90().rotate270());
+        assert_eq!(board.rotate180(), board.rotate90().rotate90());
+        for symmetry in Symmetry::ALL {
+            assert_eq!(
+                board,
+                board.transform(symmetry).transform(symmetry.inverse())
+            );
+        }
+    }
+
+    #[test]
+    fn board_canonical() {
+        let x = Cell::Occupied(Player::Cross);
+        let o = Cell::Occupied(Player::Nought);
+        let v = Cell::Vacant;
+
+        assert_eq!(Board::new(), Board::new().canonical());
+
+        // all four corner openings are the same position
+        let corners = [1, 3, 7, 9].map(|n| {
+            let mut board = Board::new();
+            board.place(Pos::new(n).unwrap(), Player::Cross).unwrap();
+            board.canonical()
+        });
+        assert!(corners.iter().all(|board| *board == corners[0]));
+        assert_eq!([x, v, v, v, v, v, v, v, v], corners[0].cells);
+
+        let board = Board::with_cells([v, v, v, v, x, v, o, v, v]);
+        let (canonical, symmetry) = board.canonical_with_symmetry();
+        assert_eq!([o, v, v, v, x, v, v, v, v], canonical.cells);
+        assert_eq!(canonical, board.transform(symmetry));
+        for symmetry in Symmetry::ALL {
+            assert_eq!(canonical, board.transform(symmetry).canonical());
+        }
+
+        let edge = Board::with_cells([v, x, v, v, v, v, v, v, v]);
+        assert_ne!(corners[0], edge.canonical());
+    }
+
+    #[test]
+    fn board_lines() {
+        let lines: Vec<(LineId, Vec<usize>)> = Board::lines()
+            .map(|(line, positions)| (line, positions.iter().map(|pos| pos.get()).collect()))
+            .collect();
+        assert_eq!(
+            vec![
+                (LineId::Row(0), vec![1, 2, 3]),
+                (LineId::Row(1), vec![4, 5, 6]),
+                (LineId::Row(2), vec![7, 8, 9]),
+                (LineId::Column(0), vec![1, 4, 7]),
+                (LineId::Column(1), vec![2, 5, 8]),
+                (LineId::Column(2), vec![3, 6, 9]),
+                (LineId::Diagonal, vec![1, 5, 9]),
+                (LineId::AntiDiagonal, vec![3, 5, 7]),
+            ],
+            lines
+        );
+
+        // the same lines `wins` looks at
+        for (_, positions) in Board::lines() {
+            let mut board = Board::new();
+            for pos in positions {
+                board.place(pos, Player::Cross).unwrap();
+            }
+            assert!(board.wins(Player::Cross));
+        }
+    }
+
     #[test]
     fn board_is_complete() {
-        let board = Board {
-            cells: [Cell::Occupied(Player::Cross); 9],
-        };
+        let board = Board::with_cells([Cell::Occupied(Player::Cross); 9]);
         assert!(board.is_complete());
 
-        let board = Board {
-            cells: [Cell::Vacant; 9],
-        };
+        let board = Board::with_cells([Cell::Vacant; 9]);
         assert!(!board.is_complete());
 
-        let board = Board {
-            cells: [
-                Cell::Occupied(Player::Cross),
-                Cell::Occupied(Player::Nought),
-                Cell::Occupied(Player::Cross),
-                Cell::Occupied(Player::Nought),
-                Cell::Vacant,
-                Cell::Occupied(Player::Nought),
-                Cell::Occupied(Player::Cross),
-                Cell::Occupied(Player::Nought),
-                Cell::Occupied(Player::Cross),
-            ],
-        };
+        let board = Board::with_cells([
+            Cell::Occupied(Player::Cross),
+            Cell::Occupied(Player::Nought),
+            Cell::Occupied(Player::Cross),
+            Cell::Occupied(Player::Nought),
+            Cell::Vacant,
+            Cell::Occupied(Player::Nought),
+            Cell::Occupied(Player::Cross),
+            Cell::Occupied(Player::Nought),
+            Cell::Occupied(Player::Cross),
+        ]);
         assert!(!board.is_complete());
     }
 
     #[test]
     fn board_wins() {
-        let board = Board {
-            cells: [
-                Cell::Occupied(Player::Nought),
-                Cell::Occupied(Player::Cross),
-                Cell::Vacant,
-                Cell::Occupied(Player::Cross),
-                Cell::Vacant,
-                Cell::Occupied(Player::Nought),
-                Cell::Vacant,
-                Cell::Occupied(Player::Nought),
-                Cell::Occupied(Player::Cross),
-            ],
-        };
+        let board = Board::with_cells([
+            Cell::Occupied(Player::Nought),
+            Cell::Occupied(Player::Cross),
+            Cell::Vacant,
+            Cell::Occupied(Player::Cross),
+            Cell::Vacant,
+            Cell::Occupied(Player::Nought),
+            Cell::Vacant,
+            Cell::Occupied(Player::Nought),
+            Cell::Occupied(Player::Cross),
+        ]);
         assert!(!board.wins(Player::Nought));
         assert!(!board.wins(Player::Cross));
 
-        let board = Board {
-            cells: [
-                Cell::Occupied(Player::Nought),
-                Cell::Occupied(Player::Cross),
-                Cell::Occupied(Player::Cross),
-                Cell::Occupied(Player::Cross),
-                Cell::Occupied(Player::Nought),
-                Cell::Occupied(Player::Nought),
-                Cell::Occupied(Player::Cross),
-                Cell::Occupied(Player::Nought),
-                Cell::Occupied(Player::Nought),
-            ],
-        };
+        let board = Board::with_cells([
+            Cell::Occupied(Player::Nought),
+            Cell::Occupied(Player::Cross),
+            Cell::Occupied(Player::Cross),
+            Cell::Occupied(Player::Cross),
+            Cell::Occupied(Player::Nought),
+            Cell::Occupied(Player::Nought),
+            Cell::Occupied(Player::Cross),
+            Cell::Occupied(Player::Nought),
+            Cell::Occupied(Player::Nought),
+        ]);
         assert!(board.wins(Player::Nought));
         assert!(!board.wins(Player::Cross));
+        assert_eq!(Some(Player::Nought), board.winner());
+        assert!(board.is_over());
+
+        assert_eq!(None, Board::new().winner());
+        assert!(!Board::new().is_over());
     }
 
     #[test]
-    fn board_is_draw() {
-        let board = Board {
-            cells: [
-                Cell::Occupied(Player::Nought),
-                Cell::Occupied(Player::Cross),
-                Cell::Vacant,
-                Cell::Occupied(Player::Cross),
-                Cell::Vacant,
-                Cell::Occupied(Player::Nought),
-                Cell::Vacant,
-                Cell::Occupied(Player::Nought),
-                Cell::Occupied(Player::Cross),
+    fn board_legal_moves() {
+        let x = Cell::Occupied(Player::Cross);
+        let o = Cell::Occupied(Player::Nought);
+        let v = Cell::Vacant;
+
+        assert_eq!(9, Board::new().legal_moves().count());
+
+        let board = Board::with_cells([x, o, v, v, x, o, v, v, v]);
+        let moves: Vec<usize> = board.legal_moves().map(Pos::get).collect();
+        assert_eq!(vec![3, 4, 7, 8, 9], moves);
+
+        let board = Board::with_cells([x, x, x, o, o, v, v, v, v]);
+        assert_eq!(0, board.legal_moves().count());
+    }
+
+    #[test]
+    fn board_successors() {
+        let x = Cell::Occupied(Player::Cross);
+        let o = Cell::Occupied(Player::Nought);
+        let v = Cell::Vacant;
+
+        let board = Board::with_cells([x, o, x, o, x, o, v, v, v]);
+        let successors: Vec<(Pos, Board)> = board.successors(Player::Nought).collect();
+        assert_eq!(
+            vec![
+                (
+                    Pos::new(7).unwrap(),
+                    Board::with_cells([x, o, x, o, x, o, o, v, v])
+                ),
+                (
+                    Pos::new(8).unwrap(),
+                    Board::with_cells([x, o, x, o, x, o, v, o, v])
+                ),
+                (
+                    Pos::new(9).unwrap(),
+                    Board::with_cells([x, o, x, o, x, o, v, v, o])
+                ),
             ],
-        };
+            successors
+        );
+
+        let board = Board::with_cells([x, x, x, o, o, v, v, v, v]);
+        assert_eq!(0, board.successors(Player::Nought).count());
+    }
+
+    #[test]
+    fn board_winning_move() {
+        let x = Cell::Occupied(Player::Cross);
+        let o = Cell::Occupied(Player::Nought);
+        let v = Cell::Vacant;
+
+        assert_eq!(None, Board::new().winning_move(Player::Cross));
+
+        let board = Board::with_cells([x, o, v, v, x, o, v, v, v]);
+        assert_eq!(Pos::new(9), board.winning_move(Player::Cross));
+        assert_eq!(None, board.winning_move(Player::Nought));
+
+        let board = Board::with_cells([o, x, x, o, x, v, v, v, v]);
+        assert_eq!(Pos::new(7), board.winning_move(Player::Cross));
+        assert_eq!(Pos::new(7), board.winning_move(Player::Nought));
+
+        // nothing left to win once the game is over
+        let board = Board::with_cells([x, x, x, o, o, v, v, v, v]);
+        assert_eq!(None, board.winning_move(Player::Nought));
+    }
+
+    #[test]
+    fn board_threats() {
+        let x = Cell::Occupied(Player::Cross);
+        let o = Cell::Occupied(Player::Nought);
+        let v = Cell::Vacant;
+
+        let threats =
+            |board: &Board, player| -> Vec<usize> { board.threats(player).map(Pos::get).collect() };
+
+        assert!(threats(&Board::new(), Player::Cross).is_empty());
+        assert!(!Board::new().has_fork(Player::Cross));
+
+        let board = Board::with_cells([x, o, v, v, x, o, v, v, v]);
+        assert_eq!(vec![9], threats(&board, Player::Cross));
+        assert!(threats(&board, Player::Nought).is_empty());
+        assert!(!board.has_fork(Player::Cross));
+
+        let board = Board::with_cells([x, o, v, v, x, v, x, v, o]);
+        assert_eq!(vec![3, 4], threats(&board, Player::Cross));
+        assert!(board.has_fork(Player::Cross));
+        assert!(!board.has_fork(Player::Nought));
+    }
+
+    #[test]
+    fn board_winning_line() {
+        let x = Cell::Occupied(Player::Cross);
+        let o = Cell::Occupied(Player::Nought);
+        let v = Cell::Vacant;
+
+        assert_eq!(None, Board::new().winning_line());
+
+        let board = Board::with_cells([o, x, x, v, x, o, x, o, v]);
+        assert_eq!(
+            Some((LineId::AntiDiagonal, LineId::AntiDiagonal.positions())),
+            board.winning_line()
+        );
+
+        let board = Board::with_cells([o, x, x, o, x, v, o, v, v]);
+        assert_eq!(
+            Some((LineId::Column(0), LineId::Column(0).positions())),
+            board.winning_line()
+        );
+    }
+
+    #[test]
+    fn board_is_draw() {
+        let board = Board::with_cells([
+            Cell::Occupied(Player::Nought),
+            Cell::Occupied(Player::Cross),
+            Cell::Vacant,
+            Cell::Occupied(Player::Cross),
+            Cell::Vacant,
+            Cell::Occupied(Player::Nought),
+            Cell::Vacant,
+            Cell::Occupied(Player::Nought),
+            Cell::Occupied(Player::Cross),
+        ]);
         assert!(!board.is_draw());
 
-        let board = Board {
-            cells: [
-                Cell::Occupied(Player::Nought),
-                Cell::Occupied(Player::Cross),
-                Cell::Occupied(Player::Cross),
-                Cell::Occupied(Player::Cross),
-                Cell::Occupied(Player::Nought),
-                Cell::Occupied(Player::Nought),
-                Cell::Occupied(Player::Cross),
-                Cell::Occupied(Player::Nought),
-                Cell::Occupied(Player::Nought),
-            ],
-        };
+        let board = Board::with_cells([
+            Cell::Occupied(Player::Nought),
+            Cell::Occupied(Player::Cross),
+            Cell::Occupied(Player::Cross),
+            Cell::Occupied(Player::Cross),
+            Cell::Occupied(Player::Nought),
+            Cell::Occupied(Player::Nought),
+            Cell::Occupied(Player::Cross),
+            Cell::Occupied(Player::Nought),
+            Cell::Occupied(Player::Nought),
+        ]);
         assert!(!board.is_draw());
 
-        let board = Board {
-            cells: [
-                Cell::Occupied(Player::Nought),
-                Cell::Occupied(Player::Cross),
-                Cell::Occupied(Player::Cross),
-                Cell::Occupied(Player::Cross),
-                Cell::Occupied(Player::Nought),
-                Cell::Occupied(Player::Nought),
-                Cell::Occupied(Player::Cross),
-                Cell::Occupied(Player::Nought),
-                Cell::Occupied(Player::Cross),
-            ],
-        };
+        let board = Board::with_cells([
+            Cell::Occupied(Player::Nought),
+            Cell::Occupied(Player::Cross),
+            Cell::Occupied(Player::Cross),
+            Cell::Occupied(Player::Cross),
+            Cell::Occupied(Player::Nought),
+            Cell::Occupied(Player::Nought),
+            Cell::Occupied(Player::Cross),
+            Cell::Occupied(Player::Nought),
+            Cell::Occupied(Player::Cross),
+        ]);
         eprintln!("{}", board);
         assert!(board.is_draw());
     }
+
+    #[cfg(feature = "proptest")]
+    proptest::proptest! {
+        #[test]
+        fn arbitrary_board_is_reachable(board: Board) {
+            proptest::prop_assert_eq!(Ok(board), Board::from_cells(board.cells));
+            proptest::prop_assert_eq!(ZobristTable::STANDARD.hash(&board), board.zobrist());
+        }
+
+        #[test]
+        fn arbitrary_pos_round_trips(pos: Pos) {
+            proptest::prop_assert_eq!(Ok(pos), pos.to_string().parse());
+            proptest::prop_assert_eq!(Some(pos), Pos::from_algebraic(&format!("{:#}", pos)));
+        }
+    }
 }
@@ -0,0 +1,162 @@
+// simple opponents to test against, or to give beginners a chance
+
+use alloc::vec::Vec;
+
+use super::AiPlayer;
+use crate::{Board, Player, Pos, Rng, Rules};
+
+// plays any legal move, uniformly at random
+#[derive(Clone, Debug)]
+pub struct RandomAi {
+    pub(super) rng: Rng,
+}
+
+impl RandomAi {
+    pub fn new(seed: u64) -> RandomAi {
+        RandomAi {
+            rng: Rng::new(seed),
+        }
+    }
+}
+
+impl AiPlayer for RandomAi {
+    fn choose(&mut self, board: &Board, _me: Player, rules: Rules) -> Pos {
+        let moves: Vec<Pos> = rules.legal_moves(board).collect();
+        *self.rng.choose(&moves).expect("no legal moves")
+    }
+}
+
+// scores every legal move by what it achieves and plays the best; the
+// default weights win if it can, otherwise block, otherwise take the centre,
+// then a corner, then whatever is left, which is easy to beat with a fork
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct HeuristicAi {
+    pub weights: HeuristicWeights,
+}
+
+// what each feature of a move is worth
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct HeuristicWeights {
+    // completes a line
+    pub win: i32,
+    // stops the opponent completing one
+    pub block: i32,
+    // leaves two ways to win
+    pub fork: i32,
+    pub centre: i32,
+    pub corner: i32,
+    pub edge: i32,
+}
+
+impl Default for HeuristicWeights {
+    fn default() -> HeuristicWeights {
+        HeuristicWeights {
+            win: 1000,
+            block: 100,
+            fork: 0,
+            centre: 3,
+            corner: 2,
+            edge: 1,
+        }
+    }
+}
+
+impl HeuristicAi {
+    pub fn new(weights: HeuristicWeights) -> HeuristicAi {
+        HeuristicAi { weights }
+    }
+
+    fn score(&self, board: &Board, me: Player, pos: Pos) -> i32 {
+        let weights = &self.weights;
+        let mut next = *board;
+        next.place(pos, me).unwrap();
+
+        let mut score = match super::move_order(pos) {
+            0 => weights.centre,
+            1 => weights.corner,
+            _ => weights.edge,
+        };
+        if next.wins(me) {
+            score += weights.win;
+        }
+        if board.threats(me.toggle()).any(|threat| threat == pos) {
+            score += weights.block;
+        }
+        if next.has_fork(me) {
+            score += weights.fork;
+        }
+        score
+    }
+}
+
+impl AiPlayer for HeuristicAi {
+    fn choose(&mut self, board: &Board, me: Player, rules: Rules) -> Pos {
+        rules
+            .legal_moves(board)
+            .map(|pos| (pos, self.score(board, me, pos)))
+            .fold(None, |best, (pos, score)| match best {
+                Some((_, best_score)) if best_score >= score => best,
+                _ => Some((pos, score)),
+            })
+            .expect("no legal moves")
+            .0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::board;
+
+    #[test]
+    fn heuristic_weights() {
+        let board = board("X1 O2 X5 O9");
+        // without a fork bonus, crosses just take the first corner going
+        assert_eq!(
+            Pos::new(3).unwrap(),
+            HeuristicAi::default().choose(&board, Player::Cross, Rules::default())
+        );
+        let mut forker = HeuristicAi::new(HeuristicWeights {
+            fork: 50,
+            ..HeuristicWeights::default()
+        });
+        assert_eq!(
+            Pos::new(7).unwrap(),
+            forker.choose(&board, Player::Cross, Rules::default())
+        );
+    }
+
+    #[test]
+    fn random_plays_legal_moves() {
+        let mut ai = RandomAi::new(5);
+        let board = board("X1 O5 X9");
+        for _ in 0..50 {
+            let pos = ai.choose(&board, Player::Nought, Rules::default());
+            assert!(board.legal_moves().any(|legal| legal == pos));
+        }
+    }
+
+    #[test]
+    fn heuristic_priorities() {
+        let mut ai = HeuristicAi::default();
+        // win rather than block
+        assert_eq!(
+            Pos::new(6).unwrap(),
+            ai.choose(&board("X1 O4 X2 O5 X9"), Player::Nought, Rules::default())
+        );
+        // block
+        assert_eq!(
+            Pos::new(3).unwrap(),
+            ai.choose(&board("X1 O5 X2"), Player::Nought, Rules::default())
+        );
+        // centre, then corner
+        assert_eq!(
+            Pos::new(5).unwrap(),
+            ai.choose(&Board::new(), Player::Cross, Rules::default())
+        );
+        assert_eq!(
+            Pos::new(1).unwrap(),
+            ai.choose(&board("X5"), Player::Nought, Rules::default())
+        );
+    }
+}
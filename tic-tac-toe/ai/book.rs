@@ -0,0 +1,274 @@
+// hand-picked opening moves, so an engine doesn't open the same way every
+// game; positions are stored canonically, so one entry covers every
+// symmetric copy of a position
+
+use alloc::vec::Vec;
+use core::{error, fmt, str};
+
+use crate::{Board, Cell, Player, Pos, Rng};
+
+// one position per line: the cells row by row as `X`, `O` or `.`, the player
+// to move, and the replies with their weights, e.g.
+//
+//     X........ O 5:1
+//
+// blank lines and lines starting with `#` are ignored
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct OpeningBook {
+    entries: Vec<BookEntry>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct BookEntry {
+    board: Board,
+    to_move: Player,
+    replies: Vec<(Pos, u32)>,
+}
+
+impl OpeningBook {
+    pub fn new() -> OpeningBook {
+        OpeningBook::default()
+    }
+
+    // a few sound replies to the first move or two, for either player
+    pub fn standard() -> OpeningBook {
+        const STANDARD: &str = "\
+# any first move draws, the centre gives the opponent the most rope
+......... X 5:4 1:1 3:1 7:1 9:1
+......... O 5:4 1:1 3:1 7:1 9:1
+# only the centre holds against a corner
+X........ O 5:1
+O........ X 5:1
+# only a corner holds against the centre
+....X.... O 1:1 3:1 7:1 9:1
+....O.... X 1:1 3:1 7:1 9:1
+# against an edge
+.X....... O 5:2 1:1 3:1 8:1
+.O....... X 5:2 1:1 3:1 8:1
+";
+        STANDARD.parse().unwrap()
+    }
+
+    // adds `weight` to the chance of replying `pos` to `board`
+    pub fn insert(&mut self, board: &Board, to_move: Player, pos: Pos, weight: u32) {
+        let (board, symmetry) = board.canonical_with_symmetry();
+        let pos = pos.transform(symmetry);
+        let index = match self
+            .entries
+            .iter()
+            .position(|entry| entry.board == board && entry.to_move == to_move)
+        {
+            Some(index) => index,
+            None => {
+                self.entries.push(BookEntry {
+                    board,
+                    to_move,
+                    replies: Vec::new(),
+                });
+                self.entries.len() - 1
+            }
+        };
+        let replies = &mut self.entries[index].replies;
+        match replies.iter_mut().find(|(reply, _)| *reply == pos) {
+            Some((_, total)) => *total += weight,
+            None => replies.push((pos, weight)),
+        }
+    }
+
+    // the replies to `board` and their weights, in `board`'s orientation
+    pub fn replies(&self, board: &Board, to_move: Player) -> Vec<(Pos, u32)> {
+        let (canonical, symmetry) = board.canonical_with_symmetry();
+        let inverse = symmetry.inverse();
+        self.entries
+            .iter()
+            .find(|entry| entry.board == canonical && entry.to_move == to_move)
+            .map(|entry| {
+                entry
+                    .replies
+                    .iter()
+                    .map(|&(pos, weight)| (pos.transform(inverse), weight))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    // a reply picked at random in proportion to its weight, or None if the
+    // position isn't in the book
+    pub fn choose(&self, board: &Board, to_move: Player, rng: &mut Rng) -> Option<Pos> {
+        let replies = self.replies(board, to_move);
+        let total: u32 = replies.iter().map(|&(_, weight)| weight).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut pick = rng.below(total as usize) as u32;
+        replies.into_iter().find_map(|(pos, weight)| {
+            if pick < weight {
+                Some(pos)
+            } else {
+                pick -= weight;
+                None
+            }
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl fmt::Display for OpeningBook {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for entry in &self.entries {
+            write!(f, "{} {}", entry.board.to_compact(), entry.to_move)?;
+            for (pos, weight) in &entry.replies {
+                write!(f, " {}:{}", pos, weight)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl str::FromStr for OpeningBook {
+    type Err = ParseBookError;
+
+    fn from_str(s: &str) -> Result<OpeningBook, ParseBookError> {
+        let mut book = OpeningBook::new();
+        for (index, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let err = ParseBookError { line: index + 1 };
+            let mut fields = line.split_whitespace();
+            let board = fields.next().and_then(parse_board).ok_or(err.clone())?;
+            let to_move: Player = fields
+                .next()
+                .and_then(|player| player.parse().ok())
+                .ok_or(err.clone())?;
+            for reply in fields {
+                let (pos, weight): (Pos, u32) = reply
+                    .split_once(':')
+                    .and_then(|(pos, weight)| Some((pos.parse().ok()?, weight.parse().ok()?)))
+                    .ok_or(err.clone())?;
+                if board.cells[pos.get() - 1] != Cell::Vacant {
+                    return Err(err);
+                }
+                book.insert(&board, to_move, pos, weight);
+            }
+        }
+        Ok(book)
+    }
+}
+
+fn parse_board(s: &str) -> Option<Board> {
+    let chars: Vec<char> = s.chars().collect();
+    if chars.len() != Board::SIZE {
+        return None;
+    }
+    let mut cells = [Cell::Vacant; Board::SIZE];
+    for (cell, c) in cells.iter_mut().zip(chars) {
+        *cell = match c {
+            'X' => Cell::Occupied(Player::Cross),
+            'O' => Cell::Occupied(Player::Nought),
+            '.' => Cell::Vacant,
+            _ => return None,
+        };
+    }
+    Board::from_cells(cells).ok()
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseBookError {
+    pub line: usize,
+}
+
+impl fmt::Display for ParseBookError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid opening book entry on line {}", self.line)
+    }
+}
+
+impl error::Error for ParseBookError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::Tablebase;
+
+    #[test]
+    fn standard_book_is_sound() {
+        let book = OpeningBook::standard();
+        let tablebase = Tablebase::solve();
+        for entry in &book.entries {
+            let optimal = tablebase.optimal_moves(&entry.board, entry.to_move);
+            for (pos, _) in &entry.replies {
+                assert!(optimal.contains(pos), "{} {} {}", book, entry.to_move, pos);
+            }
+        }
+    }
+
+    #[test]
+    fn replies_follow_symmetry() {
+        let book = OpeningBook::standard();
+        let mut board = Board::new();
+        board.place(Pos::new(9).unwrap(), Player::Cross).unwrap();
+        assert_eq!(
+            vec![(Pos::new(5).unwrap(), 1)],
+            book.replies(&board, Player::Nought)
+        );
+
+        let mut board = Board::new();
+        board.place(Pos::new(6).unwrap(), Player::Cross).unwrap();
+        let replies = book.replies(&board, Player::Nought);
+        let mut positions: Vec<usize> = replies.iter().map(|(pos, _)| pos.get()).collect();
+        positions.sort_unstable();
+        assert_eq!(vec![3, 4, 5, 9], positions);
+    }
+
+    #[test]
+    fn choose() {
+        let book = OpeningBook::standard();
+        let mut rng = Rng::new(3);
+        let mut seen = Vec::new();
+        for _ in 0..100 {
+            let pos = book.choose(&Board::new(), Player::Cross, &mut rng).unwrap();
+            if !seen.contains(&pos) {
+                seen.push(pos);
+            }
+        }
+        assert_eq!(5, seen.len());
+
+        let mut board = Board::new();
+        board.place(Pos::new(1).unwrap(), Player::Cross).unwrap();
+        board.place(Pos::new(5).unwrap(), Player::Nought).unwrap();
+        assert_eq!(None, book.choose(&board, Player::Cross, &mut rng));
+    }
+
+    #[test]
+    fn parse() {
+        let book = OpeningBook::standard();
+        assert_eq!(Ok(book.clone()), book.to_string().parse());
+
+        assert_eq!(
+            Err(ParseBookError { line: 2 }),
+            "\n......... Z 5:1".parse::<OpeningBook>()
+        );
+        assert_eq!(
+            Err(ParseBookError { line: 1 }),
+            "X........ O 1:1".parse::<OpeningBook>()
+        );
+        assert_eq!(
+            Err(ParseBookError { line: 1 }),
+            "XX....... O 5:1".parse::<OpeningBook>()
+        );
+        assert_eq!(
+            "invalid opening book entry on line 4",
+            ParseBookError { line: 4 }.to_string()
+        );
+    }
+}
@@ -0,0 +1,152 @@
+// perfect play with deliberate mistakes, so the built-in AI can be beaten
+
+use alloc::vec::Vec;
+use core::str::FromStr;
+use core::{error, fmt};
+
+use super::AiPlayer;
+use crate::{Board, Player, Pos, Rng, Rules};
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    // how often the AI ignores the search and plays a random move instead
+    pub fn blunder_chance(self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.5,
+            Difficulty::Medium => 0.2,
+            Difficulty::Hard => 0.0,
+        }
+    }
+}
+
+// "easy", "medium" or "hard", as the front ends spell them
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = ParseDifficultyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "easy" => Ok(Difficulty::Easy),
+            "medium" => Ok(Difficulty::Medium),
+            "hard" => Ok(Difficulty::Hard),
+            _ => Err(ParseDifficultyError {}),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ParseDifficultyError {}
+
+impl fmt::Display for ParseDifficultyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown difficulty, expected easy, medium or hard")
+    }
+}
+
+impl error::Error for ParseDifficultyError {}
+
+#[derive(Clone, Debug)]
+pub struct DifficultyAi {
+    blunder_chance: f64,
+    rng: Rng,
+}
+
+impl DifficultyAi {
+    pub fn new(difficulty: Difficulty, seed: u64) -> DifficultyAi {
+        DifficultyAi::with_blunder_chance(difficulty.blunder_chance(), seed)
+    }
+
+    // `chance` is clamped to 0..=1
+    pub fn with_blunder_chance(chance: f64, seed: u64) -> DifficultyAi {
+        DifficultyAi {
+            blunder_chance: chance.clamp(0.0, 1.0),
+            rng: Rng::new(seed),
+        }
+    }
+
+    pub fn blunder_chance(&self) -> f64 {
+        self.blunder_chance
+    }
+}
+
+impl AiPlayer for DifficultyAi {
+    fn choose(&mut self, board: &Board, me: Player, rules: Rules) -> Pos {
+        if self.rng.next_f64() < self.blunder_chance {
+            let moves: Vec<Pos> = rules.legal_moves(board).collect();
+            *self.rng.choose(&moves).expect("no legal moves")
+        } else {
+            super::PerfectAi.choose(board, me, rules)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::board;
+
+    #[test]
+    fn hard_never_blunders() {
+        let mut ai = DifficultyAi::new(Difficulty::Hard, 1);
+        let board = board("X5 O1");
+        for _ in 0..20 {
+            assert_eq!(
+                super::super::best_move(&board, Player::Cross).unwrap(),
+                ai.choose(&board, Player::Cross, Rules::default())
+            );
+        }
+    }
+
+    #[test]
+    fn easy_blunders() {
+        let mut ai = DifficultyAi::new(Difficulty::Easy, 2);
+        let board = board("X5 O1");
+        let best = super::super::best_move(&board, Player::Cross).unwrap();
+        let blunders = (0..100)
+            .filter(|_| ai.choose(&board, Player::Cross, Rules::default()) != best)
+            .count();
+        assert!((20..80).contains(&blunders), "{blunders} blunders");
+    }
+
+    #[test]
+    fn parse() {
+        for difficulty in [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard] {
+            assert_eq!(Ok(difficulty), difficulty.to_string().parse());
+        }
+        assert_eq!(Ok(Difficulty::Easy), "easy".parse());
+        assert_eq!(Err(ParseDifficultyError {}), "Hard".parse::<Difficulty>());
+        assert_eq!(
+            "unknown difficulty, expected easy, medium or hard",
+            ParseDifficultyError {}.to_string()
+        );
+    }
+
+    #[test]
+    fn blunder_chance_is_clamped() {
+        assert_eq!(
+            1.0,
+            DifficultyAi::with_blunder_chance(3.0, 0).blunder_chance()
+        );
+        assert_eq!(
+            0.0,
+            DifficultyAi::with_blunder_chance(-1.0, 0).blunder_chance()
+        );
+    }
+}
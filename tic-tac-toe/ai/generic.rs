@@ -0,0 +1,107 @@
+// searching games other than the plain 3x3 one; the variants implement
+// `Position` and get a depth-limited search and simple opponents for free
+
+use alloc::vec::Vec;
+
+use super::{RandomAi, WIN};
+use crate::Player;
+
+// a two-player game position: whose turn it is, what they can do and who
+// has won
+pub trait Position: Clone {
+    type Move: Copy;
+
+    fn to_move(&self) -> Player;
+
+    // every legal move, or none once the game is over
+    fn moves(&self) -> Vec<Self::Move>;
+
+    // plays a move from `moves`
+    fn play(&mut self, mv: Self::Move);
+
+    fn winner(&self) -> Option<Player>;
+
+    // a guess at how good an unfinished position is for `player`, used where
+    // the search has to stop looking; it must stay well inside (-WIN, WIN)
+    fn heuristic(&self, _player: Player) -> i32 {
+        0
+    }
+}
+
+// picks moves in any `Position`, the counterpart of `AiPlayer`
+pub trait Agent<P: Position> {
+    fn choose(&mut self, position: &P) -> P::Move;
+}
+
+// the best move looking `depth` moves ahead and its score, scored like
+// `evaluate` where the search reaches the end of the game and by
+// `Position::heuristic` where it doesn't; None once the game is over
+pub fn alpha_beta<P: Position>(position: &P, depth: u32) -> Option<(P::Move, i32)> {
+    let mut best = None;
+    let mut alpha = -WIN;
+    for mv in position.moves() {
+        let mut next = position.clone();
+        next.play(mv);
+        let score = -negamax(&next, 1, depth.saturating_sub(1), -WIN, -alpha);
+        if best.is_none_or(|(_, best_score)| score > best_score) {
+            best = Some((mv, score));
+            alpha = alpha.max(score);
+        }
+    }
+    best
+}
+
+fn negamax<P: Position>(position: &P, ply: i32, remaining: u32, mut alpha: i32, beta: i32) -> i32 {
+    let player = position.to_move();
+    if let Some(winner) = position.winner() {
+        return if winner == player {
+            WIN - ply
+        } else {
+            ply - WIN
+        };
+    }
+    let moves = position.moves();
+    if moves.is_empty() {
+        return 0;
+    }
+    if remaining == 0 {
+        return position.heuristic(player);
+    }
+
+    let mut best = -WIN;
+    for mv in moves {
+        let mut next = position.clone();
+        next.play(mv);
+        let score = -negamax(&next, ply + 1, remaining - 1, -beta, -alpha);
+        best = best.max(score);
+        alpha = alpha.max(score);
+        if alpha >= beta {
+            break;
+        }
+    }
+    best
+}
+
+// `alpha_beta` as an `Agent`
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Searcher {
+    pub depth: u32,
+}
+
+impl Searcher {
+    pub fn new(depth: u32) -> Searcher {
+        Searcher { depth }
+    }
+}
+
+impl<P: Position> Agent<P> for Searcher {
+    fn choose(&mut self, position: &P) -> P::Move {
+        alpha_beta(position, self.depth).expect("no legal moves").0
+    }
+}
+
+impl<P: Position> Agent<P> for RandomAi {
+    fn choose(&mut self, position: &P) -> P::Move {
+        *self.rng.choose(&position.moves()).expect("no legal moves")
+    }
+}
@@ -0,0 +1,303 @@
+// Monte Carlo tree search: builds a search tree guided by random playouts,
+// so it gives a reasonable move for any budget instead of needing to search
+// to the end of the game. It plays the plain board under any `Rules`, and
+// any other `Position`, e.g. the bigger boards, through `search`
+
+use alloc::vec::Vec;
+
+use super::generic::{Agent, Position};
+use super::AiPlayer;
+use crate::{Board, Player, Pos, Rng, Rules};
+
+#[derive(Clone, Debug)]
+pub struct Mcts {
+    playouts: u32,
+    exploration: f64,
+    // which moves the tree and the playouts may make on a `Board`; other
+    // positions know their own rules
+    rules: Rules,
+    rng: Rng,
+}
+
+struct Node<M> {
+    // the move that led here, None at the root
+    mv: Option<M>,
+    // the player who made that move
+    player: Player,
+    visits: u32,
+    // from `player`'s point of view: 1 per win, a half per draw
+    score: f64,
+    children: Vec<usize>,
+    untried: Vec<M>,
+}
+
+impl<M> Node<M> {
+    fn new<P: Position<Move = M>>(mv: Option<M>, player: Player, position: &P) -> Node<M> {
+        Node {
+            mv,
+            player,
+            visits: 0,
+            score: 0.0,
+            children: Vec::new(),
+            untried: position.moves(),
+        }
+    }
+}
+
+// a plain board as a `Position`, with `rules` deciding the moves and who
+// has won, including the wins only they see
+#[derive(Clone)]
+struct RuledBoard {
+    board: Board,
+    to_move: Player,
+    rules: Rules,
+}
+
+impl Position for RuledBoard {
+    type Move = Pos;
+
+    fn to_move(&self) -> Player {
+        self.to_move
+    }
+
+    fn moves(&self) -> Vec<Pos> {
+        if self.winner().is_some() {
+            return Vec::new();
+        }
+        self.rules.legal_moves(&self.board).collect()
+    }
+
+    fn play(&mut self, pos: Pos) {
+        self.board.place(pos, self.to_move).unwrap();
+        self.to_move = self.to_move.toggle();
+    }
+
+    // the player not to move made the last move
+    fn winner(&self) -> Option<Player> {
+        self.rules.winner(&self.board, self.to_move.toggle())
+    }
+}
+
+impl Mcts {
+    // sqrt(2), the textbook exploration constant for UCT
+    pub const DEFAULT_EXPLORATION: f64 = core::f64::consts::SQRT_2;
+
+    pub fn new(playouts: u32, exploration: f64, seed: u64) -> Mcts {
+        Mcts {
+            playouts,
+            exploration,
+            rules: Rules::default(),
+            rng: Rng::new(seed),
+        }
+    }
+
+    pub fn rules(&self) -> Rules {
+        self.rules
+    }
+
+    pub fn set_rules(&mut self, rules: Rules) {
+        self.rules = rules;
+    }
+
+    pub fn playouts(&self) -> u32 {
+        self.playouts
+    }
+
+    pub fn exploration(&self) -> f64 {
+        self.exploration
+    }
+
+    // `search` on a plain board under `rules`
+    pub fn best_move(&mut self, board: &Board, player: Player) -> Option<Pos> {
+        self.search(&RuledBoard {
+            board: *board,
+            to_move: player,
+            rules: self.rules,
+        })
+    }
+
+    // the most visited move after `playouts` rounds of search, or None once
+    // the game is over
+    pub fn search<P: Position>(&mut self, position: &P) -> Option<P::Move> {
+        let root = Node::new(None, position.to_move().toggle(), position);
+        if root.untried.is_empty() {
+            return None;
+        }
+
+        let mut nodes = vec![root];
+        for _ in 0..self.playouts.max(1) {
+            self.playout(&mut nodes, position.clone());
+        }
+
+        nodes[0]
+            .children
+            .iter()
+            .map(|&child| &nodes[child])
+            .max_by_key(|node| node.visits)
+            .and_then(|node| node.mv)
+    }
+
+    fn playout<P: Position>(&mut self, nodes: &mut Vec<Node<P::Move>>, mut position: P) {
+        let mut path = vec![0];
+        let mut current = 0;
+
+        // selection: descend through fully expanded nodes
+        while nodes[current].untried.is_empty() && !nodes[current].children.is_empty() {
+            current = self.select(nodes, current);
+            position.play(nodes[current].mv.unwrap());
+            path.push(current);
+        }
+
+        // expansion: add one untried move
+        if !nodes[current].untried.is_empty() {
+            let untried = &mut nodes[current].untried;
+            let mv = untried.swap_remove(self.rng.below(untried.len()));
+            let player = position.to_move();
+            position.play(mv);
+            nodes.push(Node::new(Some(mv), player, &position));
+            let child = nodes.len() - 1;
+            nodes[current].children.push(child);
+            path.push(child);
+        }
+
+        // simulation: play randomly to the end
+        loop {
+            let moves = position.moves();
+            let Some(&mv) = self.rng.choose(&moves) else {
+                break;
+            };
+            position.play(mv);
+        }
+
+        // backpropagation
+        let winner = position.winner();
+        for index in path {
+            let node = &mut nodes[index];
+            node.visits += 1;
+            node.score += match winner {
+                Some(winner) if winner == node.player => 1.0,
+                Some(_) => 0.0,
+                None => 0.5,
+            };
+        }
+    }
+
+    // the child with the best upper confidence bound
+    fn select<M>(&self, nodes: &[Node<M>], parent: usize) -> usize {
+        let parent_visits = f64::from(nodes[parent].visits);
+        let ucb = |child: usize| {
+            let node = &nodes[child];
+            let visits = f64::from(node.visits);
+            node.score / visits + self.exploration * (parent_visits.ln() / visits).sqrt()
+        };
+        nodes[parent]
+            .children
+            .iter()
+            .copied()
+            .max_by(|&a, &b| ucb(a).total_cmp(&ucb(b)))
+            .unwrap()
+    }
+}
+
+impl AiPlayer for Mcts {
+    fn choose(&mut self, board: &Board, me: Player, rules: Rules) -> Pos {
+        self.set_rules(rules);
+        self.best_move(board, me).expect("no legal moves")
+    }
+}
+
+impl<P: Position> Agent<P> for Mcts {
+    fn choose(&mut self, position: &P) -> P::Move {
+        self.search(position).expect("no legal moves")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::qubic::Board3d;
+    use crate::test_util::board;
+
+    #[test]
+    fn takes_the_win() {
+        let mut mcts = Mcts::new(500, Mcts::DEFAULT_EXPLORATION, 1);
+        let board = board("X1 O4 X2 O5");
+        assert_eq!(Pos::new(3), mcts.best_move(&board, Player::Cross));
+    }
+
+    #[test]
+    fn blocks_the_opponent() {
+        let mut mcts = Mcts::new(2000, Mcts::DEFAULT_EXPLORATION, 2);
+        let board = board("X1 O5 X2");
+        assert_eq!(Pos::new(3), mcts.best_move(&board, Player::Nought));
+    }
+
+    #[test]
+    fn deterministic_for_a_seed() {
+        let board = board("X5");
+        let first = Mcts::new(300, 1.0, 42).best_move(&board, Player::Nought);
+        let second = Mcts::new(300, 1.0, 42).best_move(&board, Player::Nought);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn game_over() {
+        let mut mcts = Mcts::new(10, Mcts::DEFAULT_EXPLORATION, 3);
+        assert_eq!(
+            None,
+            mcts.best_move(&board("X1 O4 X2 O5 X3"), Player::Nought)
+        );
+    }
+
+    #[test]
+    fn plays_by_the_rules() {
+        // under misère rules crosses must not complete the top row
+        let mut mcts = Mcts::new(2000, Mcts::DEFAULT_EXPLORATION, 5);
+        mcts.set_rules(Rules {
+            misere: true,
+            ..Rules::default()
+        });
+        let top_row = board("X1 O5 X2 O9");
+        assert_ne!(Pos::new(3), mcts.best_move(&top_row, Player::Cross));
+
+        // a broken diagonal ends a toroidal game
+        let mut mcts = Mcts::new(10, Mcts::DEFAULT_EXPLORATION, 6);
+        mcts.set_rules(Rules {
+            toroidal: true,
+            ..Rules::default()
+        });
+        let wrapped = board("X2 O1 X6 O5 X7");
+        assert_eq!(None, mcts.best_move(&wrapped, Player::Nought));
+    }
+
+    #[test]
+    fn any_position() {
+        // crosses complete the bottom row of the cube's top layer
+        let mut board = Board3d::new();
+        for pos in ["111", "211", "112", "212", "113", "213"] {
+            board.place(pos.parse().unwrap(), board.to_move()).unwrap();
+        }
+        let mut mcts = Mcts::new(500, Mcts::DEFAULT_EXPLORATION, 7);
+        assert_eq!(Ok(Agent::choose(&mut mcts, &board)), "114".parse());
+    }
+
+    #[test]
+    fn draws_against_perfect_play() {
+        let mut mcts = Mcts::new(3000, Mcts::DEFAULT_EXPLORATION, 4);
+        let mut board = Board::new();
+        let mut player = Player::Cross;
+        loop {
+            let pos = if player == Player::Cross {
+                mcts.best_move(&board, player)
+            } else {
+                super::super::best_move(&board, player)
+            };
+            let Some(pos) = pos else {
+                break;
+            };
+            board.place(pos, player).unwrap();
+            player = player.toggle();
+        }
+        assert!(board.is_draw());
+    }
+}
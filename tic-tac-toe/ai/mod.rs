@@ -0,0 +1,979 @@
+// game-playing engines for `Board`
+
+use alloc::{vec, vec::Vec};
+
+#[cfg(feature = "std")]
+use core::mem;
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+use self::tt::{Bound, Entry};
+use crate::{Board, Player, Pos, Rng, Rules};
+
+mod baseline;
+mod book;
+mod difficulty;
+mod generic;
+#[cfg(feature = "std")]
+mod mcts;
+mod puzzle;
+mod qlearning;
+mod tablebase;
+mod tt;
+mod tuner;
+
+pub use self::baseline::{HeuristicAi, HeuristicWeights, RandomAi};
+pub use self::book::{OpeningBook, ParseBookError};
+pub use self::difficulty::{Difficulty, DifficultyAi, ParseDifficultyError};
+pub use self::generic::{alpha_beta, Agent, Position, Searcher};
+#[cfg(feature = "std")]
+pub use self::mcts::Mcts;
+pub use self::puzzle::{puzzles, random_puzzle, Puzzle};
+pub use self::qlearning::{ParseQTableError, QLearner, QTable};
+pub use self::tablebase::{Outcome, ParseTablebaseError, Tablebase};
+pub use self::tt::{Replacement, TranspositionTable};
+pub use self::tuner::Tuner;
+
+// anything that can pick a move for `me` out of those `rules` allow; `board`
+// always has at least one, see `Game::play_ai`
+pub trait AiPlayer {
+    fn choose(&mut self, board: &Board, me: Player, rules: Rules) -> Pos;
+
+    // the move and the mark to put there; only wild games allow anything
+    // other than `me`
+    fn choose_mark(&mut self, board: &Board, me: Player, rules: Rules) -> (Pos, Player) {
+        (self.choose(board, me, rules), me)
+    }
+}
+
+// `best_move` as an `AiPlayer`, searching afresh under whatever rules it's
+// given
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PerfectAi;
+
+impl AiPlayer for PerfectAi {
+    fn choose(&mut self, board: &Board, me: Player, rules: Rules) -> Pos {
+        Engine::with_rules(rules)
+            .best_move(board, me)
+            .expect("no legal moves")
+    }
+
+    fn choose_mark(&mut self, board: &Board, me: Player, rules: Rules) -> (Pos, Player) {
+        Engine::with_rules(rules)
+            .best_move_with_mark(board, me)
+            .expect("no legal moves")
+    }
+}
+
+// a search depth that always reaches the end of the game
+const FULL: i32 = Board::SIZE as i32;
+
+// the score of a won position; a win `n` moves away scores `WIN - n`, so
+// quicker wins score higher
+pub const WIN: i32 = 100;
+
+// a move with the best outcome for `player` under perfect play, preferring
+// quicker wins and slower losses; None once the game is over
+pub fn best_move(board: &Board, player: Player) -> Option<Pos> {
+    Engine::new().best_move(board, player)
+}
+
+// what a search found: the move to play, its score as in `evaluate`, the line
+// of best play it expects to follow, starting with `best`, and how many moves
+// ahead the search had to look
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SearchResult {
+    pub best: Pos,
+    pub score: i32,
+    pub pv: Vec<Pos>,
+    pub depth: u8,
+}
+
+// how much work a search did, for measuring changes to the search; the
+// elapsed time is only measured with the `std` feature and is zero otherwise
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct SearchStats {
+    // positions visited, not counting the root
+    pub nodes: u64,
+    // times a move was good enough that its siblings needn't be searched
+    pub cutoffs: u64,
+    // positions whose score came from the transposition table, at least as
+    // a bound
+    pub tt_hits: u64,
+    // the most moves ahead of the root the search looked
+    pub max_depth: u8,
+    pub elapsed: Duration,
+}
+
+impl SearchStats {
+    // nodes per second, or None if no time was measured
+    pub fn nodes_per_second(&self) -> Option<f64> {
+        if self.elapsed.is_zero() {
+            return None;
+        }
+        Some(self.nodes as f64 / self.elapsed.as_secs_f64())
+    }
+
+    #[cfg(feature = "rayon")]
+    fn merge(&mut self, other: &SearchStats) {
+        self.nodes += other.nodes;
+        self.cutoffs += other.cutoffs;
+        self.tt_hits += other.tt_hits;
+        self.max_depth = self.max_depth.max(other.max_depth);
+    }
+}
+
+// the value of `board` for `player`, who is to move, under perfect play:
+// `WIN - n` if they win in `n` moves, `n - WIN` if they lose in `n`, and 0 for
+// a draw
+pub fn evaluate(board: &Board, player: Player) -> i32 {
+    Engine::new().evaluate(board, player)
+}
+
+// a perfect-play searcher that keeps its transposition table between
+// searches, so analysing a whole game only searches each position once
+#[derive(Clone, Debug)]
+pub struct Engine {
+    table: TranspositionTable,
+    book: Option<OpeningBook>,
+    // what the search plays for, e.g. to avoid lines under misère rules
+    rules: Rules,
+    // picks between book moves
+    rng: Rng,
+    // what the current or last search did
+    stats: SearchStats,
+    // when a timed search has to stop
+    #[cfg(feature = "std")]
+    deadline: Option<Instant>,
+    // set once the deadline has passed; the search unwinds without trusting
+    // or storing any more scores
+    aborted: bool,
+}
+
+impl Engine {
+    pub fn new() -> Engine {
+        Engine::default()
+    }
+
+    pub fn with_table(table: TranspositionTable) -> Engine {
+        Engine {
+            table,
+            book: None,
+            rules: Rules::default(),
+            rng: Rng::new(0),
+            stats: SearchStats::default(),
+            #[cfg(feature = "std")]
+            deadline: None,
+            aborted: false,
+        }
+    }
+
+    // plays from `book` while the game is still in it, and searches after
+    pub fn with_book(book: OpeningBook) -> Engine {
+        Engine {
+            book: Some(book),
+            ..Engine::default()
+        }
+    }
+
+    // plays to win under `rules`; the opening books assume the standard
+    // rules, so this doesn't use one
+    pub fn with_rules(rules: Rules) -> Engine {
+        Engine {
+            rules,
+            ..Engine::default()
+        }
+    }
+
+    pub fn rules(&self) -> Rules {
+        self.rules
+    }
+
+    // plays under `rules` from now on; the table's scores were for the old
+    // rules, so it starts again empty
+    pub fn set_rules(&mut self, rules: Rules) {
+        if rules != self.rules {
+            self.rules = rules;
+            self.table.clear();
+        }
+    }
+
+    pub fn table(&self) -> &TranspositionTable {
+        &self.table
+    }
+
+    pub fn book(&self) -> Option<&OpeningBook> {
+        self.book.as_ref()
+    }
+
+    // statistics for the last call to `evaluate`, `best_move` or one of the
+    // searches, including working out the principal variation
+    pub fn stats(&self) -> &SearchStats {
+        &self.stats
+    }
+
+    // changes which book moves get picked; the same seed gives the same games
+    pub fn reseed(&mut self, seed: u64) {
+        self.rng = Rng::new(seed);
+    }
+
+    // see `evaluate`
+    pub fn evaluate(&mut self, board: &Board, player: Player) -> i32 {
+        let timer = self.start();
+        let score = self.negamax(board, player, 0, FULL, -WIN, WIN);
+        self.stop(timer);
+        score
+    }
+
+    // see `best_move`; a book move if there is one
+    pub fn best_move(&mut self, board: &Board, player: Player) -> Option<Pos> {
+        if let Some(pos) = self
+            .book
+            .as_ref()
+            // the books assume the standard rules
+            .filter(|_| self.rules == Rules::default())
+            .and_then(|book| book.choose(board, player, &mut self.rng))
+        {
+            return Some(pos);
+        }
+        let timer = self.start();
+        let best = self.root(board, player, FULL).map(|(pos, _, _)| pos);
+        self.stop(timer);
+        best
+    }
+
+    // under the pie rule, whether `player`, to answer the first move, does
+    // better by swapping sides and taking that move over
+    pub fn should_swap(&mut self, board: &Board, player: Player) -> bool {
+        self.rules.pie && board.move_number() == 1 && self.evaluate(board, player) < 0
+    }
+
+    // like `best_move`, with the mark to play; in wild games that may be the
+    // opponent's
+    pub fn best_move_with_mark(&mut self, board: &Board, player: Player) -> Option<(Pos, Player)> {
+        if !self.rules.wild {
+            return self.best_move(board, player).map(|pos| (pos, player));
+        }
+        let timer = self.start();
+        let best = self
+            .root(board, player, FULL)
+            .map(|(pos, mark, _)| (pos, mark));
+        self.stop(timer);
+        best
+    }
+
+    // the best move with its score and expected continuation; None once the
+    // game is over
+    pub fn search(&mut self, board: &Board, player: Player) -> Option<SearchResult> {
+        let timer = self.start();
+        let (best, mark, score) = self.root(board, player, FULL)?;
+        let depth = self.stats.max_depth;
+        let pv = self.pv(board, player, (best, mark), FULL);
+        self.stop(timer);
+        Some(SearchResult {
+            best,
+            score,
+            pv,
+            depth,
+        })
+    }
+
+    // searches one move deeper at a time until `budget` runs out, and returns
+    // the result of the deepest search that finished; positions beyond the
+    // horizon score as draws. Only None once the game is over
+    #[cfg(feature = "std")]
+    pub fn search_timed(
+        &mut self,
+        board: &Board,
+        player: Player,
+        budget: Duration,
+    ) -> Option<SearchResult> {
+        let timer = self.start();
+        let deadline = Instant::now() + budget;
+        let mut result = None;
+        for depth in 1..=board.occupancy().vacant as i32 {
+            self.deadline = Some(deadline);
+            self.aborted = false;
+            let found = self.root(board, player, depth);
+            self.deadline = None;
+            let aborted = mem::take(&mut self.aborted);
+
+            // an unfinished search is only better than nothing at all
+            if aborted && result.is_some() {
+                break;
+            }
+            let (best, mark, score) = found?;
+            let pv = if aborted {
+                vec![best]
+            } else {
+                self.pv(board, player, (best, mark), depth)
+            };
+            result = Some(SearchResult {
+                best,
+                score,
+                pv,
+                depth: depth as u8,
+            });
+            if aborted {
+                break;
+            }
+        }
+        self.stop(timer);
+        result
+    }
+
+    // like `search`, but searches each legal move on its own thread; the
+    // threads share the best score so far to narrow their windows, but not
+    // their transposition tables
+    #[cfg(feature = "rayon")]
+    pub fn search_parallel(&mut self, board: &Board, player: Player) -> Option<SearchResult> {
+        use core::sync::atomic::{AtomicI32, Ordering};
+        use rayon::prelude::*;
+
+        let timer = self.start();
+        let best_score = AtomicI32::new(-WIN);
+        let scores: Vec<(Pos, Player, i32, SearchStats)> =
+            ordered_successors(board, player, self.rules)
+                .into_par_iter()
+                .map_with(self.clone(), |engine, (pos, mark, next)| {
+                    engine.stats = SearchStats::default();
+                    // one below the best so far, so that every move that ties
+                    // with the best gets an exact score and the first of them
+                    // wins, just as in the sequential search
+                    let alpha = best_score.load(Ordering::Relaxed) - 1;
+                    let score = -engine.negamax(&next, player.toggle(), 1, FULL - 1, -WIN, -alpha);
+                    best_score.fetch_max(score, Ordering::Relaxed);
+                    (pos, mark, score, engine.stats)
+                })
+                .collect();
+
+        let (best, mark, score, _) =
+            scores
+                .iter()
+                .copied()
+                .reduce(|best, next| if next.2 > best.2 { next } else { best })?;
+        for (_, _, _, stats) in &scores {
+            self.stats.merge(stats);
+        }
+        let depth = self.stats.max_depth;
+        let pv = self.pv(board, player, (best, mark), FULL);
+        self.stop(timer);
+        Some(SearchResult {
+            best,
+            score,
+            pv,
+            depth,
+        })
+    }
+
+    // following the best move from each position in turn gives the
+    // principal variation; the table makes this cheap
+    fn pv(
+        &mut self,
+        board: &Board,
+        player: Player,
+        (best, mark): (Pos, Player),
+        remaining: i32,
+    ) -> Vec<Pos> {
+        let mut pv = vec![best];
+        let mut board = *board;
+        let mut player = player;
+        board.place(best, mark).unwrap();
+        player = player.toggle();
+        for remaining in (1..remaining).rev() {
+            let Some((pos, mark, _)) = self.root(&board, player, remaining) else {
+                break;
+            };
+            pv.push(pos);
+            board.place(pos, mark).unwrap();
+            player = player.toggle();
+        }
+        pv
+    }
+
+    // the best move, the mark to play there and its score, looking
+    // `remaining` moves ahead
+    fn root(
+        &mut self,
+        board: &Board,
+        player: Player,
+        remaining: i32,
+    ) -> Option<(Pos, Player, i32)> {
+        // under the pie rule the first move should be as close to even as
+        // possible, or the opponent swaps and takes it over, so every move
+        // needs an exact score
+        let pie = self.rules.pie && board.move_number() == 0;
+        let mut best = None;
+        let mut alpha = -WIN;
+        for (pos, mark, next) in ordered_successors(board, player, self.rules) {
+            if pie {
+                let score = -self.negamax(&next, player.toggle(), 1, remaining - 1, -WIN, WIN);
+                if best.is_none_or(|(_, _, best_score): (_, _, i32)| score.abs() < best_score.abs())
+                {
+                    best = Some((pos, mark, score));
+                }
+                continue;
+            }
+            let score = -self.negamax(&next, player.toggle(), 1, remaining - 1, -WIN, -alpha);
+            if best.is_none_or(|(_, _, best_score)| score > best_score) {
+                best = Some((pos, mark, score));
+                alpha = alpha.max(score);
+            }
+        }
+        best
+    }
+
+    // the value of `board` for `player`, who is to move, `depth` moves into
+    // the search and looking `remaining` moves further; exact inside
+    // (alpha, beta), otherwise only a bound
+    fn negamax(
+        &mut self,
+        board: &Board,
+        player: Player,
+        depth: i32,
+        remaining: i32,
+        mut alpha: i32,
+        mut beta: i32,
+    ) -> i32 {
+        if depth > 0 {
+            self.stats.nodes += 1;
+        }
+        self.stats.max_depth = self.stats.max_depth.max(depth as u8);
+        if let Some(winner) = self.rules.winner(board, player.toggle()) {
+            return if winner == player {
+                WIN - depth
+            } else {
+                depth - WIN
+            };
+        }
+        if remaining <= 0 || self.out_of_time() {
+            return 0;
+        }
+
+        // searching past the end of the game changes nothing
+        let remaining = remaining.min(board.occupancy().vacant as i32);
+        let key = TranspositionTable::key(board, player);
+        if let Some(entry) = self
+            .table
+            .get(key)
+            .filter(|entry| i32::from(entry.depth) >= remaining)
+        {
+            self.stats.tt_hits += 1;
+            let score = from_table(entry.score, depth);
+            match entry.bound {
+                Bound::Exact => return score,
+                Bound::Lower => alpha = alpha.max(score),
+                Bound::Upper => beta = beta.min(score),
+            }
+            if alpha >= beta {
+                return score;
+            }
+        }
+
+        let original_alpha = alpha;
+        let mut best = None;
+        for (_, _, next) in ordered_successors(board, player, self.rules) {
+            let score = -self.negamax(
+                &next,
+                player.toggle(),
+                depth + 1,
+                remaining - 1,
+                -beta,
+                -alpha,
+            );
+            best = best.max(Some(score));
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                self.stats.cutoffs += 1;
+                break;
+            }
+        }
+        let best = best.unwrap_or(0);
+        if self.aborted {
+            return best;
+        }
+
+        let bound = if best <= original_alpha {
+            Bound::Upper
+        } else if best >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        let entry = Entry {
+            score: to_table(best, depth),
+            bound,
+            depth: remaining as u8,
+        };
+        self.table.insert(key, entry);
+        best
+    }
+
+    // clears the statistics for a new search and starts timing it
+    fn start(&mut self) -> Timer {
+        self.stats = SearchStats::default();
+        Timer(
+            #[cfg(feature = "std")]
+            Instant::now(),
+        )
+    }
+
+    fn stop(&mut self, timer: Timer) {
+        #[cfg(feature = "std")]
+        {
+            self.stats.elapsed = timer.0.elapsed();
+        }
+        #[cfg(not(feature = "std"))]
+        let _ = timer;
+    }
+
+    #[cfg(feature = "std")]
+    fn out_of_time(&mut self) -> bool {
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            self.aborted = true;
+        }
+        self.aborted
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn out_of_time(&mut self) -> bool {
+        false
+    }
+}
+
+// when a search started; without the `std` feature there is no clock, and
+// searches aren't timed
+struct Timer(#[cfg(feature = "std")] Instant);
+
+impl Default for Engine {
+    fn default() -> Engine {
+        Engine::with_table(TranspositionTable::default())
+    }
+}
+
+// the engine switches to the game's rules, see `set_rules`
+impl AiPlayer for Engine {
+    fn choose(&mut self, board: &Board, me: Player, rules: Rules) -> Pos {
+        self.set_rules(rules);
+        self.best_move(board, me).expect("no legal moves")
+    }
+
+    fn choose_mark(&mut self, board: &Board, me: Player, rules: Rules) -> (Pos, Player) {
+        self.set_rules(rules);
+        self.best_move_with_mark(board, me).expect("no legal moves")
+    }
+}
+
+// scores are stored relative to the position rather than the root of the
+// search, so a win in two stays a win in two wherever the position appears
+fn to_table(score: i32, depth: i32) -> i32 {
+    match score.signum() {
+        1 => score + depth,
+        -1 => score - depth,
+        _ => 0,
+    }
+}
+
+fn from_table(score: i32, depth: i32) -> i32 {
+    match score.signum() {
+        1 => score - depth,
+        -1 => score + depth,
+        _ => 0,
+    }
+}
+
+// each move `rules` allow with the mark played and the board it leads to;
+// centre first, then corners, then edges, which tends to find the best move
+// early and prune more. Wild games try both marks, the player's own first
+fn ordered_successors(board: &Board, player: Player, rules: Rules) -> Vec<(Pos, Player, Board)> {
+    let marks = if rules.wild {
+        vec![player, player.toggle()]
+    } else {
+        vec![player]
+    };
+    let mut successors = Vec::new();
+    for mark in marks {
+        for pos in rules.legal_moves(board) {
+            let mut next = *board;
+            next.place(pos, mark).unwrap();
+            successors.push((pos, mark, next));
+        }
+    }
+    successors.sort_by_key(|&(pos, _, _)| move_order(pos));
+    successors
+}
+
+fn move_order(pos: Pos) -> u8 {
+    let on_edge = |i| i == 0 || i == Board::WIDTH - 1;
+    match (on_edge(pos.row()), on_edge(pos.column())) {
+        (false, false) => 0,
+        (true, true) => 1,
+        _ => 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::board;
+
+    // plain minimax, to check the pruned search against
+    fn minimax(board: &Board, player: Player, depth: i32) -> i32 {
+        if let Some(winner) = board.winner() {
+            return if winner == player {
+                WIN - depth
+            } else {
+                depth - WIN
+            };
+        }
+        board
+            .successors(player)
+            .map(|(_, next)| -minimax(&next, player.toggle(), depth + 1))
+            .max()
+            .unwrap_or(0)
+    }
+
+    #[test]
+    fn takes_the_win() {
+        let board = board("X1 O4 X2 O5");
+        assert_eq!(Pos::new(3), best_move(&board, Player::Cross));
+        // noughts would rather win than block
+        assert_eq!(Pos::new(6), best_move(&board, Player::Nought));
+    }
+
+    #[test]
+    fn blocks_the_opponent() {
+        let board = board("X1 O5 X2");
+        assert_eq!(Pos::new(3), best_move(&board, Player::Nought));
+    }
+
+    #[test]
+    fn answers_a_corner_with_the_centre() {
+        let board = board("X1");
+        assert_eq!(Pos::new(5), best_move(&board, Player::Nought));
+    }
+
+    #[test]
+    fn opens_in_the_centre() {
+        assert_eq!(Pos::new(5), best_move(&Board::new(), Player::Cross));
+    }
+
+    #[test]
+    fn game_over() {
+        assert_eq!(None, best_move(&board("X1 O4 X2 O5 X3"), Player::Nought));
+        assert_eq!(
+            None,
+            best_move(&board("X1 O2 X3 O5 X4 O7 X8 O6 X9"), Player::Nought)
+        );
+    }
+
+    #[test]
+    fn perfect_play_draws() {
+        let mut board = Board::new();
+        let mut player = Player::Cross;
+        while let Some(pos) = best_move(&board, player) {
+            board.place(pos, player).unwrap();
+            player = player.toggle();
+        }
+        assert!(board.is_draw());
+    }
+
+    #[test]
+    fn pruning_matches_minimax() {
+        // every distinct position after one move each
+        let mut openings: Vec<Board> = Board::new()
+            .successors(Player::Cross)
+            .flat_map(|(_, board)| {
+                board
+                    .successors(Player::Nought)
+                    .map(|(_, board)| board.canonical())
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        openings.sort_by_key(|board| board.zobrist());
+        openings.dedup();
+
+        for board in openings {
+            let pos = best_move(&board, Player::Cross).unwrap();
+            let mut next = board;
+            next.place(pos, Player::Cross).unwrap();
+            assert_eq!(
+                minimax(&board, Player::Cross, 0),
+                -minimax(&next, Player::Nought, 1)
+            );
+            for table in [
+                TranspositionTable::new(0),
+                TranspositionTable::new(64),
+                TranspositionTable::with_replacement(64, Replacement::Deeper),
+            ] {
+                let mut engine = Engine::with_table(table);
+                assert_eq!(
+                    minimax(&board, Player::Cross, 0),
+                    engine.negamax(&board, Player::Cross, 0, FULL, -WIN, WIN)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn engine_reuses_its_table() {
+        let mut engine = Engine::new();
+        assert!(engine.table().is_empty());
+        let mut board = Board::new();
+        let mut player = Player::Cross;
+        while let Some(pos) = engine.best_move(&board, player) {
+            assert_eq!(Some(pos), best_move(&board, player));
+            board.place(pos, player).unwrap();
+            player = player.toggle();
+        }
+        assert!(board.is_draw());
+        assert!(!engine.table().is_empty());
+    }
+
+    #[test]
+    fn evaluate() {
+        assert_eq!(0, super::evaluate(&Board::new(), Player::Cross));
+        assert_eq!(
+            WIN - 1,
+            super::evaluate(&board("X1 O4 X2 O5"), Player::Cross)
+        );
+        assert_eq!(
+            WIN - 1,
+            super::evaluate(&board("X1 O4 X2 O5"), Player::Nought)
+        );
+        // the edge reply to a corner loses to a fork
+        assert_eq!(WIN - 5, super::evaluate(&board("X1 O2"), Player::Cross));
+        assert_eq!(4 - WIN, super::evaluate(&board("X1 O2 X5"), Player::Nought));
+        assert_eq!(
+            -WIN,
+            super::evaluate(&board("X1 O4 X2 O5 X3"), Player::Nought)
+        );
+        assert_eq!(
+            0,
+            super::evaluate(&board("X1 O2 X3 O5 X4 O7 X8 O6 X9"), Player::Nought)
+        );
+    }
+
+    #[test]
+    fn search() {
+        let mut engine = Engine::new();
+        let result = engine.search(&board("X1 O4 X2 O5"), Player::Cross).unwrap();
+        assert_eq!(Pos::new(3).unwrap(), result.best);
+        assert_eq!(WIN - 1, result.score);
+        assert_eq!(vec![result.best], result.pv);
+        assert!(result.depth >= 1);
+
+        let start = board("X1 O2");
+        let result = engine.search(&start, Player::Cross).unwrap();
+        assert_eq!(WIN - 5, result.score);
+        assert_eq!(5, result.pv.len());
+        let mut game = "X1 O2"
+            .parse::<crate::Transcript>()
+            .unwrap()
+            .replay()
+            .unwrap();
+        game.apply(&result.pv).unwrap();
+        assert_eq!(Some(Player::Cross), game.board().winner());
+
+        let result = engine.search(&Board::new(), Player::Cross).unwrap();
+        assert_eq!(0, result.score);
+        assert_eq!(Board::SIZE, result.pv.len());
+        assert_eq!(Board::SIZE as u8, result.depth);
+
+        assert_eq!(
+            None,
+            engine.search(&board("X1 O4 X2 O5 X3"), Player::Nought)
+        );
+    }
+
+    #[test]
+    fn stats() {
+        let mut engine = Engine::new();
+        engine.search(&Board::new(), Player::Cross).unwrap();
+        let stats = *engine.stats();
+        assert!(stats.nodes > 0);
+        assert!(stats.cutoffs > 0);
+        assert!(stats.tt_hits > 0);
+        assert_eq!(Board::SIZE as u8, stats.max_depth);
+
+        // the second search mostly hits the table the first one filled
+        engine.search(&Board::new(), Player::Cross).unwrap();
+        assert!(engine.stats().nodes < stats.nodes);
+        assert!(engine.stats().tt_hits > 0);
+
+        // without a table transpositions are searched again
+        let mut engine = Engine::with_table(TranspositionTable::new(0));
+        engine.search(&Board::new(), Player::Cross).unwrap();
+        assert_eq!(0, engine.stats().tt_hits);
+        assert!(engine.stats().nodes > stats.nodes);
+    }
+
+    #[test]
+    fn misere() {
+        let misere = Rules {
+            misere: true,
+            ..Rules::default()
+        };
+        let mut engine = Engine::with_rules(misere);
+        // still a draw with perfect play
+        assert_eq!(0, engine.evaluate(&Board::new(), Player::Cross));
+        // crosses must not complete the row; noughts would rather not either
+        let board = board("X1 O4 X2 O5");
+        let pos = engine.best_move(&board, Player::Cross).unwrap();
+        assert_ne!(Pos::new(3).unwrap(), pos);
+        // with nothing else left, crosses are forced to complete a line
+        let board = self::board("X1 O2 X5 O3 X6 O4 X7 O8");
+        assert_eq!(Pos::new(9), engine.best_move(&board, Player::Cross));
+        assert_eq!(-WIN + 1, engine.evaluate(&board, Player::Cross));
+        assert_eq!(misere, engine.rules());
+    }
+
+    #[test]
+    fn wild() {
+        let wild = Rules {
+            wild: true,
+            ..Rules::default()
+        };
+        let mut engine = Engine::with_rules(wild);
+        // the first player wins wild tic-tac-toe
+        assert!(engine.evaluate(&Board::new(), Player::Cross) > 0);
+        // noughts finish crosses' row for them
+        let board = board("X1 O5 X2");
+        assert_eq!(
+            Some((Pos::new(3).unwrap(), Player::Cross)),
+            engine.best_move_with_mark(&board, Player::Nought)
+        );
+        assert_eq!(WIN - 1, engine.evaluate(&board, Player::Nought));
+        let result = engine.search(&board, Player::Nought).unwrap();
+        assert_eq!(vec![result.best], result.pv);
+    }
+
+    #[test]
+    fn toroidal() {
+        let mut engine = Engine::with_rules(Rules {
+            toroidal: true,
+            ..Rules::default()
+        });
+        // every cell is on four lines, so crosses win from anywhere
+        for pos in Pos::all() {
+            let mut board = Board::new();
+            board.place(pos, Player::Cross).unwrap();
+            assert!(engine.evaluate(&board, Player::Nought) < 0);
+        }
+        assert!(engine.evaluate(&Board::new(), Player::Cross) > 0);
+    }
+
+    #[test]
+    fn pie() {
+        let pie = Rules {
+            pie: true,
+            wild: true,
+            ..Rules::default()
+        };
+        let mut engine = Engine::with_rules(pie);
+        // only the centre wins wild tic-tac-toe, so under the pie rule it
+        // would just be swapped away
+        let (pos, _) = engine
+            .best_move_with_mark(&Board::new(), Player::Cross)
+            .unwrap();
+        assert_ne!(Pos::new(5).unwrap(), pos);
+        assert!(engine.should_swap(&board("X5"), Player::Nought));
+        assert!(!engine.should_swap(&board("X1"), Player::Nought));
+        // never after the first move
+        assert!(!engine.should_swap(&board("X1 O5"), Player::Cross));
+        // and not without the pie rule
+        let mut engine = Engine::with_rules(Rules {
+            wild: true,
+            ..Rules::default()
+        });
+        assert!(!engine.should_swap(&board("X5"), Player::Nought));
+        assert_eq!(
+            Some((Pos::new(5).unwrap(), Player::Cross)),
+            engine.best_move_with_mark(&Board::new(), Player::Cross)
+        );
+    }
+
+    #[test]
+    fn engine_with_book() {
+        let mut openings = Vec::new();
+        for seed in 0..8 {
+            let mut engine = Engine::with_book(OpeningBook::standard());
+            engine.reseed(seed);
+            let mut game = crate::Game::new();
+            while game.result().is_none() {
+                game.play_ai(&mut engine).unwrap();
+            }
+            assert_eq!(None, game.result().unwrap().winner);
+            let opening = game.transcript().moves()[0].1;
+            if !openings.contains(&opening) {
+                openings.push(opening);
+            }
+        }
+        assert!(openings.len() > 1);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn search_timed() {
+        let mut engine = Engine::new();
+        let start = board("X1 O2");
+        let result = engine
+            .search_timed(&start, Player::Cross, Duration::from_secs(60))
+            .unwrap();
+        assert_eq!(
+            Engine::new().search(&start, Player::Cross).unwrap().score,
+            result.score
+        );
+        assert_eq!(WIN - 5, result.score);
+        assert_eq!(7, result.depth);
+
+        // no time at all still gives a legal move
+        let result = Engine::new()
+            .search_timed(&Board::new(), Player::Cross, Duration::ZERO)
+            .unwrap();
+        assert!(Board::new().legal_moves().any(|pos| pos == result.best));
+        assert_eq!(1, result.depth);
+
+        let over = board("X1 O4 X2 O5 X3");
+        assert_eq!(
+            None,
+            engine.search_timed(&over, Player::Nought, Duration::from_secs(1))
+        );
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn search_parallel() {
+        for moves in ["", "X1", "X2", "X5", "X1 O2", "X1 O5 X9"] {
+            let board = board(moves);
+            let player = board.occupancy().to_move(Player::Cross).unwrap();
+            let sequential = Engine::new().search(&board, player).unwrap();
+            let parallel = Engine::new().search_parallel(&board, player).unwrap();
+            assert_eq!(sequential.best, parallel.best, "{moves}");
+            assert_eq!(sequential.score, parallel.score, "{moves}");
+            assert_eq!(sequential.pv, parallel.pv, "{moves}");
+        }
+        assert_eq!(
+            None,
+            Engine::new().search_parallel(&board("X1 O4 X2 O5 X3"), Player::Nought)
+        );
+    }
+
+    #[test]
+    fn perfect_ai() {
+        assert_eq!(
+            Pos::new(3).unwrap(),
+            PerfectAi.choose(&board("X1 O5 X2"), Player::Nought, Rules::default())
+        );
+    }
+
+    #[test]
+    fn move_order() {
+        let order: Vec<u8> = Pos::all().map(super::move_order).collect();
+        assert_eq!(vec![1, 2, 1, 2, 0, 2, 1, 2, 1], order);
+    }
+}
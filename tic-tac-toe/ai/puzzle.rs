@@ -0,0 +1,132 @@
+// "to move and win" positions for practising tactics, found by searching
+// every position that can come up in a game
+
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+use core::fmt;
+
+use super::{Engine, WIN};
+use crate::{Board, Player, Pos, Rng};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Puzzle {
+    pub board: Board,
+    pub to_move: Player,
+    // how many of their own moves `to_move` needs to win
+    pub win_in: u32,
+    // the winning line, both sides' moves, starting with the move to find
+    pub solution: Vec<Pos>,
+}
+
+impl fmt::Display for Puzzle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} to move and win in {}", self.to_move, self.win_in)?;
+        write!(f, "{:#}", self.board)
+    }
+}
+
+// every position, up to symmetry, where the player to move can force a win
+// in exactly `win_in` of their own moves but no sooner, crosses having moved
+// first; in the order they come up in a game
+pub fn puzzles(win_in: u32) -> Vec<Puzzle> {
+    if win_in == 0 {
+        return Vec::new();
+    }
+    let target = WIN - (2 * win_in as i32 - 1);
+    let mut engine = Engine::new();
+    let mut puzzles = Vec::new();
+    let mut seen = BTreeSet::new();
+    let mut frontier = Vec::from([Board::new()]);
+    let mut to_move = Player::Cross;
+    while !frontier.is_empty() {
+        let mut next = Vec::new();
+        for board in frontier {
+            let Some(result) = engine.search(&board, to_move) else {
+                continue;
+            };
+            if result.score == target {
+                puzzles.push(Puzzle {
+                    board,
+                    to_move,
+                    win_in,
+                    solution: result.pv,
+                });
+            }
+            for (_, successor) in board.successors(to_move) {
+                let successor = successor.canonical();
+                if seen.insert(successor.zobrist()) {
+                    next.push(successor);
+                }
+            }
+        }
+        frontier = next;
+        to_move = to_move.toggle();
+    }
+    puzzles
+}
+
+// one of `puzzles(win_in)`, picked at random
+pub fn random_puzzle(win_in: u32, rng: &mut Rng) -> Option<Puzzle> {
+    let puzzles = puzzles(win_in);
+    rng.choose(&puzzles).cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn win_in_one() {
+        let puzzles = puzzles(1);
+        assert!(!puzzles.is_empty());
+        for puzzle in &puzzles {
+            assert_eq!(1, puzzle.solution.len());
+            let mut board = puzzle.board;
+            board.place(puzzle.solution[0], puzzle.to_move).unwrap();
+            assert_eq!(Some(puzzle.to_move), board.winner());
+        }
+    }
+
+    #[test]
+    fn win_in_two() {
+        let puzzles = puzzles(2);
+        assert!(!puzzles.is_empty());
+        for puzzle in &puzzles {
+            assert_eq!(None, puzzle.board.winning_move(puzzle.to_move));
+            assert_eq!(3, puzzle.solution.len());
+            let mut board = puzzle.board;
+            let mut player = puzzle.to_move;
+            for &pos in &puzzle.solution {
+                board.place(pos, player).unwrap();
+                player = player.toggle();
+            }
+            assert_eq!(Some(puzzle.to_move), board.winner());
+        }
+        // the corner opening answered on the edge is lost, but only in three
+        let mut board = Board::new();
+        board.place(Pos::new(1).unwrap(), Player::Cross).unwrap();
+        board.place(Pos::new(2).unwrap(), Player::Nought).unwrap();
+        assert!(!puzzles
+            .iter()
+            .any(|puzzle| puzzle.board == board.canonical()));
+        assert!(super::puzzles(3)
+            .iter()
+            .any(|puzzle| puzzle.board == board.canonical()));
+    }
+
+    #[test]
+    fn win_in_zero() {
+        assert!(puzzles(0).is_empty());
+    }
+
+    #[test]
+    fn random() {
+        let mut a = Rng::new(9);
+        let mut b = Rng::new(9);
+        let puzzle = random_puzzle(2, &mut a).unwrap();
+        assert_eq!(Some(puzzle.clone()), random_puzzle(2, &mut b));
+        assert!(puzzle
+            .to_string()
+            .starts_with(&alloc::format!("{} to move and win in 2\n", puzzle.to_move)));
+    }
+}
@@ -0,0 +1,278 @@
+// tabular Q-learning: learns a value for every move in every position by
+// playing itself, with no knowledge of the game beyond its rules
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::{error, fmt};
+
+use super::tt::TranspositionTable;
+use super::AiPlayer;
+use crate::{Board, Player, Pos, Rng, Rules, Symmetry};
+
+// the learned values, from the mover's side: close to 1 for a move that
+// wins, 0 for one that draws and -1 for one that loses. Positions are stored
+// up to symmetry, which makes the table eight times smaller
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct QTable {
+    values: BTreeMap<u64, [f64; Board::SIZE]>,
+}
+
+impl QTable {
+    const MAGIC: &'static [u8; 4] = b"TTTQ";
+    const ENTRY_LEN: usize = 8 + 8 * Board::SIZE;
+
+    pub fn new() -> QTable {
+        QTable::default()
+    }
+
+    // the number of positions with learned values
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    // the value of playing `pos`, 0 if it hasn't been learned yet
+    pub fn value(&self, board: &Board, to_move: Player, pos: Pos) -> f64 {
+        let (key, symmetry) = QTable::key(board, to_move);
+        self.lookup(key, symmetry, pos)
+    }
+
+    fn lookup(&self, key: u64, symmetry: Symmetry, pos: Pos) -> f64 {
+        self.values
+            .get(&key)
+            .map_or(0.0, |values| values[pos.transform(symmetry).get() - 1])
+    }
+
+    fn value_mut(&mut self, key: u64, symmetry: Symmetry, pos: Pos) -> &mut f64 {
+        let values = self.values.entry(key).or_insert([0.0; Board::SIZE]);
+        &mut values[pos.transform(symmetry).get() - 1]
+    }
+
+    // the move `rules` allow with the highest value, the first of any ties
+    fn best(&self, board: &Board, to_move: Player, rules: Rules) -> Option<(Pos, f64)> {
+        let (key, symmetry) = QTable::key(board, to_move);
+        self.best_at(board, key, symmetry, rules)
+    }
+
+    // `best` with the key already worked out
+    fn best_at(
+        &self,
+        board: &Board,
+        key: u64,
+        symmetry: Symmetry,
+        rules: Rules,
+    ) -> Option<(Pos, f64)> {
+        rules
+            .legal_moves(board)
+            .map(|pos| (pos, self.lookup(key, symmetry, pos)))
+            .fold(None, |best, (pos, value)| match best {
+                Some((_, best_value)) if best_value >= value => best,
+                _ => Some((pos, value)),
+            })
+    }
+
+    fn key(board: &Board, to_move: Player) -> (u64, Symmetry) {
+        let (canonical, symmetry) = board.canonical_with_symmetry();
+        (
+            TranspositionTable::canonical_key(&canonical, to_move),
+            symmetry,
+        )
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(4 + self.values.len() * QTable::ENTRY_LEN);
+        bytes.extend_from_slice(QTable::MAGIC);
+        for (key, values) in &self.values {
+            bytes.extend_from_slice(&key.to_le_bytes());
+            for value in values {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<QTable, ParseQTableError> {
+        let entries = bytes
+            .strip_prefix(QTable::MAGIC)
+            .filter(|entries| entries.len() % QTable::ENTRY_LEN == 0)
+            .ok_or(ParseQTableError {})?;
+        let word = |bytes: &[u8]| <[u8; 8]>::try_from(bytes).unwrap();
+        let values = entries
+            .chunks(QTable::ENTRY_LEN)
+            .map(|entry| {
+                let key = u64::from_le_bytes(word(&entry[..8]));
+                let mut values = [0.0; Board::SIZE];
+                for (value, bytes) in values.iter_mut().zip(entry[8..].chunks(8)) {
+                    *value = f64::from_le_bytes(word(bytes));
+                }
+                (key, values)
+            })
+            .collect();
+        Ok(QTable { values })
+    }
+
+    #[cfg(feature = "std")]
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_bytes())
+    }
+
+    #[cfg(feature = "std")]
+    pub fn load(path: impl AsRef<std::path::Path>) -> std::io::Result<QTable> {
+        let bytes = std::fs::read(path)?;
+        QTable::from_bytes(&bytes)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseQTableError {}
+
+impl fmt::Display for ParseQTableError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid Q-table data")
+    }
+}
+
+impl error::Error for ParseQTableError {}
+
+// plays greedily from its table, and improves it with `train`
+#[derive(Clone, Debug)]
+pub struct QLearner {
+    table: QTable,
+    // how far each update moves a value towards its target
+    learning_rate: f64,
+    // how much a result one move later is worth now
+    discount: f64,
+    // how often training plays a random move to explore
+    exploration: f64,
+    rng: Rng,
+}
+
+impl QLearner {
+    pub fn new(seed: u64) -> QLearner {
+        QLearner::with_table(QTable::new(), seed)
+    }
+
+    pub fn with_table(table: QTable, seed: u64) -> QLearner {
+        QLearner {
+            table,
+            learning_rate: 0.5,
+            discount: 0.95,
+            exploration: 0.3,
+            rng: Rng::new(seed),
+        }
+    }
+
+    pub fn set_learning_rate(&mut self, learning_rate: f64) {
+        self.learning_rate = learning_rate;
+    }
+
+    pub fn set_discount(&mut self, discount: f64) {
+        self.discount = discount;
+    }
+
+    pub fn set_exploration(&mut self, exploration: f64) {
+        self.exploration = exploration;
+    }
+
+    pub fn table(&self) -> &QTable {
+        &self.table
+    }
+
+    pub fn into_table(self) -> QTable {
+        self.table
+    }
+
+    // plays `games` games against itself, alternating who starts, updating
+    // the table after every move
+    pub fn train(&mut self, games: usize) {
+        for game in 0..games {
+            let mut board = Board::new();
+            let mut player = if game % 2 == 0 {
+                Player::Cross
+            } else {
+                Player::Nought
+            };
+            // canonicalising is the slow part, so each key is worked out once
+            let (mut key, mut symmetry) = QTable::key(&board, player);
+            while !board.is_over() {
+                let pos = if self.rng.next_f64() < self.exploration {
+                    let moves: Vec<Pos> = board.legal_moves().collect();
+                    *self.rng.choose(&moves).unwrap()
+                } else {
+                    self.table
+                        .best_at(&board, key, symmetry, Rules::default())
+                        .unwrap()
+                        .0
+                };
+
+                let mut next = board;
+                next.place(pos, player).unwrap();
+                let (next_key, next_symmetry) = QTable::key(&next, player.toggle());
+                // the opponent moves next, so the position is worth the
+                // opposite of their best move there
+                let target = if next.winner().is_some() {
+                    1.0
+                } else if next.is_over() {
+                    0.0
+                } else {
+                    let (_, reply) = self
+                        .table
+                        .best_at(&next, next_key, next_symmetry, Rules::default())
+                        .unwrap();
+                    -self.discount * reply
+                };
+                let learning_rate = self.learning_rate;
+                let value = self.table.value_mut(key, symmetry, pos);
+                *value += learning_rate * (target - *value);
+
+                board = next;
+                player = player.toggle();
+                (key, symmetry) = (next_key, next_symmetry);
+            }
+        }
+    }
+}
+
+impl AiPlayer for QLearner {
+    fn choose(&mut self, board: &Board, me: Player, rules: Rules) -> Pos {
+        self.table.best(board, me, rules).expect("no legal moves").0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::{PerfectAi, RandomAi};
+    use crate::tournament::play_match;
+
+    fn trained() -> QLearner {
+        let mut learner = QLearner::new(1);
+        learner.train(20_000);
+        learner
+    }
+
+    #[test]
+    fn learns_to_draw_perfect_play() {
+        let mut learner = trained();
+        let stats = play_match(&mut learner, &mut PerfectAi, 2);
+        assert_eq!(0, stats.losses, "{stats}");
+        let stats = play_match(&mut learner, &mut RandomAi::new(2), 50);
+        assert_eq!(0, stats.losses, "{stats}");
+    }
+
+    #[test]
+    fn bytes() {
+        let mut learner = QLearner::new(3);
+        learner.train(100);
+        let table = learner.into_table();
+        assert!(!table.is_empty());
+        let bytes = table.to_bytes();
+        assert_eq!(Ok(table), QTable::from_bytes(&bytes));
+        assert!(QTable::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(QTable::from_bytes(b"nope").is_err());
+    }
+}
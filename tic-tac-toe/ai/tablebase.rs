@@ -0,0 +1,257 @@
+// every position reachable in a legal game, solved once up front; looking a
+// position up is then free, and the whole table is small enough to ship
+
+use alloc::{vec, vec::Vec};
+use core::{error, fmt};
+
+use crate::{Board, Cell, Player, Pos};
+
+// the result of a position under perfect play, for the player to move
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Outcome {
+    Loss,
+    Draw,
+    Win,
+}
+
+impl Outcome {
+    // the same result from the other player's side
+    pub fn flip(self) -> Outcome {
+        match self {
+            Outcome::Loss => Outcome::Win,
+            Outcome::Draw => Outcome::Draw,
+            Outcome::Win => Outcome::Loss,
+        }
+    }
+
+    fn to_byte(self) -> u8 {
+        match self {
+            Outcome::Loss => 1,
+            Outcome::Draw => 2,
+            Outcome::Win => 3,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Option<Outcome> {
+        match byte {
+            1 => Some(Outcome::Loss),
+            2 => Some(Outcome::Draw),
+            3 => Some(Outcome::Win),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Outcome::Loss => write!(f, "loss"),
+            Outcome::Draw => write!(f, "draw"),
+            Outcome::Win => write!(f, "win"),
+        }
+    }
+}
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct Tablebase {
+    // one byte per board and player to move, 0 for unreachable positions
+    values: Vec<u8>,
+}
+
+impl Tablebase {
+    const MAGIC: &'static [u8; 4] = b"TTTB";
+    const LEN: usize = 3usize.pow(Board::SIZE as u32) * 2;
+
+    // solves every position reachable from the empty board, whoever starts
+    pub fn solve() -> Tablebase {
+        let mut tablebase = Tablebase {
+            values: vec![0; Tablebase::LEN],
+        };
+        tablebase.solve_from(&Board::new(), Player::Cross);
+        tablebase.solve_from(&Board::new(), Player::Nought);
+        tablebase
+    }
+
+    fn solve_from(&mut self, board: &Board, to_move: Player) -> Outcome {
+        let index = Tablebase::index(board, to_move);
+        if let Some(outcome) = Outcome::from_byte(self.values[index]) {
+            return outcome;
+        }
+
+        let outcome = match board.winner() {
+            Some(winner) if winner == to_move => Outcome::Win,
+            Some(_) => Outcome::Loss,
+            None => board
+                .successors(to_move)
+                .map(|(_, next)| self.solve_from(&next, to_move.toggle()).flip())
+                .max()
+                .unwrap_or(Outcome::Draw),
+        };
+        self.values[index] = outcome.to_byte();
+        outcome
+    }
+
+    // None for positions that cannot come up in a legal game
+    pub fn value(&self, board: &Board, to_move: Player) -> Option<Outcome> {
+        Outcome::from_byte(self.values[Tablebase::index(board, to_move)])
+    }
+
+    // every move that keeps the best result for `to_move`
+    pub fn optimal_moves(&self, board: &Board, to_move: Player) -> Vec<Pos> {
+        let Some(value) = self.value(board, to_move) else {
+            return Vec::new();
+        };
+        board
+            .successors(to_move)
+            .filter(|(_, next)| {
+                self.value(next, to_move.toggle()).map(Outcome::flip) == Some(value)
+            })
+            .map(|(pos, _)| pos)
+            .collect()
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Tablebase::MAGIC.len() + self.values.len());
+        bytes.extend_from_slice(Tablebase::MAGIC);
+        bytes.extend_from_slice(&self.values);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Tablebase, ParseTablebaseError> {
+        let values = bytes
+            .strip_prefix(Tablebase::MAGIC)
+            .filter(|values| values.len() == Tablebase::LEN)
+            .filter(|values| values.iter().all(|&byte| byte <= 3))
+            .ok_or(ParseTablebaseError {})?;
+        Ok(Tablebase {
+            values: values.to_vec(),
+        })
+    }
+
+    #[cfg(feature = "std")]
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_bytes())
+    }
+
+    #[cfg(feature = "std")]
+    pub fn load(path: impl AsRef<std::path::Path>) -> std::io::Result<Tablebase> {
+        let bytes = std::fs::read(path)?;
+        Tablebase::from_bytes(&bytes)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+    }
+
+    // the board read as a base 3 number, doubled to make room for the player
+    fn index(board: &Board, to_move: Player) -> usize {
+        let cells = board.cells.iter().fold(0, |index, cell| {
+            index * 3
+                + match cell {
+                    Cell::Vacant => 0,
+                    Cell::Occupied(Player::Cross) => 1,
+                    // noughts; the table only has two-player positions
+                    Cell::Occupied(_) => 2,
+                }
+        });
+        cells * 2 + to_move.index()
+    }
+}
+
+// the table is large, so only show how much of it is filled in
+impl fmt::Debug for Tablebase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let solved = self.values.iter().filter(|&&value| value != 0).count();
+        f.debug_struct("Tablebase")
+            .field("solved", &solved)
+            .finish()
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseTablebaseError {}
+
+impl fmt::Display for ParseTablebaseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid tablebase data")
+    }
+}
+
+impl error::Error for ParseTablebaseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::board;
+
+    #[test]
+    fn values() {
+        let tablebase = Tablebase::solve();
+        assert_eq!(
+            Some(Outcome::Draw),
+            tablebase.value(&Board::new(), Player::Cross)
+        );
+        assert_eq!(
+            Some(Outcome::Win),
+            tablebase.value(&board("X1 O4 X2 O5"), Player::Cross)
+        );
+        // an edge reply to a corner opening loses
+        assert_eq!(
+            Some(Outcome::Win),
+            tablebase.value(&board("X1 O2"), Player::Cross)
+        );
+        assert_eq!(
+            Some(Outcome::Loss),
+            tablebase.value(&board("X1 O4 X2 O5 X3"), Player::Nought)
+        );
+        // crosses cannot have moved twice in a row
+        assert_eq!(None, tablebase.value(&board("X1"), Player::Cross));
+    }
+
+    #[test]
+    fn optimal_moves() {
+        let tablebase = Tablebase::solve();
+        assert_eq!(
+            vec![Pos::new(5).unwrap()],
+            tablebase.optimal_moves(&board("X1"), Player::Nought)
+        );
+        assert!(tablebase
+            .optimal_moves(&board("X1 O4 X2 O5"), Player::Cross)
+            .contains(&Pos::new(3).unwrap()));
+        assert!(tablebase
+            .optimal_moves(&board("X1 O4 X2 O5 X3"), Player::Nought)
+            .is_empty());
+    }
+
+    #[test]
+    fn agrees_with_search() {
+        let tablebase = Tablebase::solve();
+        for (_, board) in Board::new().successors(Player::Cross) {
+            let pos = super::super::best_move(&board, Player::Nought).unwrap();
+            assert!(tablebase
+                .optimal_moves(&board, Player::Nought)
+                .contains(&pos));
+        }
+    }
+
+    #[test]
+    fn bytes() {
+        let tablebase = Tablebase::solve();
+        let bytes = tablebase.to_bytes();
+        assert_eq!(Ok(tablebase), Tablebase::from_bytes(&bytes));
+
+        assert!(Tablebase::from_bytes(&bytes[1..]).is_err());
+        assert!(Tablebase::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        let mut corrupt = bytes;
+        corrupt[10] = 7;
+        assert!(Tablebase::from_bytes(&corrupt).is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn save_and_load() {
+        let tablebase = Tablebase::solve();
+        let path = std::env::temp_dir().join(format!("tablebase-{}.bin", std::process::id()));
+        tablebase.save(&path).unwrap();
+        let loaded = Tablebase::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(tablebase, loaded.unwrap());
+    }
+}
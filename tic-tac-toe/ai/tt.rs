@@ -0,0 +1,183 @@
+// caches search results so positions reached by different move orders, or
+// symmetric to one already seen, are only searched once
+
+use alloc::{vec, vec::Vec};
+
+use crate::{Board, Player};
+
+// which entry survives when two positions map to the same slot
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Replacement {
+    // the newest entry always wins
+    #[default]
+    Always,
+    // keep whichever entry was searched deeper, since it saved more work
+    Deeper,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) enum Bound {
+    Exact,
+    // the real score is at least this
+    Lower,
+    // the real score is at most this
+    Upper,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) struct Entry {
+    pub(super) score: i32,
+    pub(super) bound: Bound,
+    // how many moves ahead the score looked
+    pub(super) depth: u8,
+}
+
+#[derive(Clone, Debug)]
+pub struct TranspositionTable {
+    slots: Vec<Option<(u64, Entry)>>,
+    replacement: Replacement,
+}
+
+impl TranspositionTable {
+    pub const DEFAULT_CAPACITY: usize = 1 << 12;
+
+    // a capacity of zero disables caching
+    pub fn new(capacity: usize) -> TranspositionTable {
+        TranspositionTable::with_replacement(capacity, Replacement::default())
+    }
+
+    pub fn with_replacement(capacity: usize, replacement: Replacement) -> TranspositionTable {
+        TranspositionTable {
+            slots: vec![None; capacity],
+            replacement,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn replacement(&self) -> Replacement {
+        self.replacement
+    }
+
+    // the number of positions currently stored
+    pub fn len(&self) -> usize {
+        self.slots.iter().filter(|slot| slot.is_some()).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.fill(None);
+    }
+
+    // the same for every symmetry of `board`, but different for each player
+    pub(super) fn key(board: &Board, to_move: Player) -> u64 {
+        TranspositionTable::canonical_key(&board.canonical(), to_move)
+    }
+
+    // `key` for a board that is already canonical
+    pub(super) fn canonical_key(canonical: &Board, to_move: Player) -> u64 {
+        let side = if to_move == Player::Cross {
+            0
+        } else {
+            0x9e37_79b9_7f4a_7c15
+        };
+        canonical.zobrist() ^ side
+    }
+
+    pub(super) fn get(&self, key: u64) -> Option<Entry> {
+        let slot = self.slots.get(self.index(key)?)?;
+        slot.filter(|&(stored, _)| stored == key)
+            .map(|(_, entry)| entry)
+    }
+
+    pub(super) fn insert(&mut self, key: u64, entry: Entry) {
+        let Some(index) = self.index(key) else {
+            return;
+        };
+        let slot = &mut self.slots[index];
+        let replace = match (self.replacement, *slot) {
+            (Replacement::Deeper, Some((stored, old))) => stored == key || entry.depth >= old.depth,
+            _ => true,
+        };
+        if replace {
+            *slot = Some((key, entry));
+        }
+    }
+
+    fn index(&self, key: u64) -> Option<usize> {
+        if self.slots.is_empty() {
+            None
+        } else {
+            Some((key % self.slots.len() as u64) as usize)
+        }
+    }
+}
+
+impl Default for TranspositionTable {
+    fn default() -> TranspositionTable {
+        TranspositionTable::new(TranspositionTable::DEFAULT_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(depth: u8) -> Entry {
+        Entry {
+            score: 0,
+            bound: Bound::Exact,
+            depth,
+        }
+    }
+
+    #[test]
+    fn key_ignores_symmetry() {
+        let mut corner = Board::new();
+        corner
+            .place(crate::Pos::new(1).unwrap(), Player::Cross)
+            .unwrap();
+        let mut other = Board::new();
+        other
+            .place(crate::Pos::new(9).unwrap(), Player::Cross)
+            .unwrap();
+        assert_eq!(
+            TranspositionTable::key(&corner, Player::Nought),
+            TranspositionTable::key(&other, Player::Nought)
+        );
+        assert_ne!(
+            TranspositionTable::key(&corner, Player::Nought),
+            TranspositionTable::key(&corner, Player::Cross)
+        );
+    }
+
+    #[test]
+    fn replacement() {
+        let mut table = TranspositionTable::new(1);
+        table.insert(1, entry(5));
+        table.insert(2, entry(3));
+        assert_eq!(None, table.get(1));
+        assert_eq!(Some(entry(3)), table.get(2));
+
+        let mut table = TranspositionTable::with_replacement(1, Replacement::Deeper);
+        table.insert(1, entry(5));
+        table.insert(2, entry(3));
+        assert_eq!(Some(entry(5)), table.get(1));
+        assert_eq!(None, table.get(2));
+        table.insert(1, entry(4));
+        assert_eq!(Some(entry(4)), table.get(1));
+    }
+
+    #[test]
+    fn zero_capacity() {
+        let mut table = TranspositionTable::new(0);
+        table.insert(1, entry(1));
+        assert_eq!(None, table.get(1));
+        assert!(table.is_empty());
+    }
+}
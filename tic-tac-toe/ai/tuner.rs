@@ -0,0 +1,125 @@
+// finds good `HeuristicWeights` by evolution: a population of weight sets
+// plays a round robin each generation, the better half survives, and the
+// rest of the population is refilled with mutated copies of the survivors
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use super::{AiPlayer, HeuristicAi, HeuristicWeights, RandomAi};
+use crate::tournament::round_robin;
+use crate::Rng;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Tuner {
+    // weight sets per generation, at least two
+    pub population: usize,
+    pub generations: usize,
+    // games each pair plays per generation, colours alternating
+    pub games_per_pair: u32,
+    // the most a weight changes in one mutation, either way
+    pub mutation: i32,
+}
+
+impl Default for Tuner {
+    fn default() -> Tuner {
+        Tuner {
+            population: 8,
+            generations: 10,
+            games_per_pair: 4,
+            mutation: 20,
+        }
+    }
+}
+
+impl Tuner {
+    // the strongest weights of the last generation; the defaults always
+    // start in the population, so they only lose their place to something
+    // that did better
+    pub fn run(&self, seed: u64) -> HeuristicWeights {
+        let mut rng = Rng::new(seed);
+        let size = self.population.max(2);
+        let mut population = Vec::from([HeuristicWeights::default()]);
+        while population.len() < size {
+            population.push(self.mutate(&HeuristicWeights::default(), &mut rng));
+        }
+
+        for generation in 0..self.generations {
+            // a random player too, since heuristic players mostly draw
+            // against each other and the ranking needs some wins to go on
+            let mut players: Vec<Box<dyn AiPlayer>> = population
+                .iter()
+                .map(|&weights| Box::new(HeuristicAi::new(weights)) as Box<dyn AiPlayer>)
+                .collect();
+            players.push(Box::new(RandomAi::new(rng.next_u64())));
+            let table = round_robin(players, self.games_per_pair);
+
+            let ranked: Vec<HeuristicWeights> = table
+                .standings()
+                .into_iter()
+                .filter(|&player| player < size)
+                .map(|player| population[player])
+                .collect();
+            if generation + 1 == self.generations {
+                return ranked[0];
+            }
+
+            population = ranked[..size / 2].to_vec();
+            let mut parent = 0;
+            while population.len() < size {
+                let child = self.mutate(&population[parent], &mut rng);
+                population.push(child);
+                parent = (parent + 1) % (size / 2);
+            }
+        }
+        population[0]
+    }
+
+    fn mutate(&self, weights: &HeuristicWeights, rng: &mut Rng) -> HeuristicWeights {
+        let range = self.mutation.max(0) as usize;
+        let mut nudge = |weight: i32| weight + rng.below(2 * range + 1) as i32 - range as i32;
+        HeuristicWeights {
+            win: nudge(weights.win),
+            block: nudge(weights.block),
+            fork: nudge(weights.fork),
+            centre: nudge(weights.centre),
+            corner: nudge(weights.corner),
+            edge: nudge(weights.edge),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tournament::play_match;
+
+    fn tuner() -> Tuner {
+        Tuner {
+            population: 4,
+            generations: 3,
+            games_per_pair: 2,
+            mutation: 30,
+        }
+    }
+
+    #[test]
+    fn deterministic_for_a_seed() {
+        assert_eq!(tuner().run(5), tuner().run(5));
+    }
+
+    #[test]
+    fn no_generations_keeps_the_defaults() {
+        let tuner = Tuner {
+            generations: 0,
+            ..tuner()
+        };
+        assert_eq!(HeuristicWeights::default(), tuner.run(1));
+    }
+
+    #[test]
+    fn tuned_weights_still_play_sensibly() {
+        let weights = tuner().run(7);
+        let stats = play_match(&mut HeuristicAi::new(weights), &mut RandomAi::new(3), 20);
+        assert!(stats.wins > stats.losses, "{stats}");
+    }
+}
@@ -0,0 +1,319 @@
+// tools for studying the game rather than playing it
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+
+use crate::ai::{Engine, Outcome};
+use crate::{ApplyError, Board, Game, PlaceError, Player, Pos, Transcript};
+
+// the game tree below `board` as a Graphviz digraph, `depth` moves deep,
+// with crosses having moved first. Symmetric positions share a node, drawn
+// the way it was first reached, and edges are labelled with the moves on
+// the parent as drawn. Every node shows the result under perfect play,
+// coloured red for an X win, blue for an O win and grey for a draw
+pub fn to_dot(board: &Board, depth: usize) -> String {
+    let mut out = String::new();
+    out.push_str("digraph game {\n");
+    out.push_str("    node [shape=box, fontname=\"monospace\"];\n");
+
+    let Some(to_move) = board.occupancy().to_move(Player::Cross) else {
+        out.push_str("}\n");
+        return out;
+    };
+    let mut engine = Engine::new();
+    let mut seen = BTreeSet::from([node_id(board)]);
+    write_node(&mut out, &mut engine, board, to_move);
+
+    let mut level = Vec::from([*board]);
+    let mut player = to_move;
+    for _ in 0..depth {
+        let mut next_level = Vec::new();
+        for parent in &level {
+            // several moves can lead to the same position up to symmetry
+            let mut edges: BTreeMap<u64, (Board, Vec<String>)> = BTreeMap::new();
+            for (pos, child) in parent.successors(player) {
+                edges
+                    .entry(node_id(&child))
+                    .or_insert((child, Vec::new()))
+                    .1
+                    .push(format!("{}", pos));
+            }
+            for (id, (child, moves)) in edges {
+                if seen.insert(id) {
+                    write_node(&mut out, &mut engine, &child, player.toggle());
+                    next_level.push(child);
+                }
+                writeln!(
+                    out,
+                    "    n{:016x} -> n{:016x} [label=\"{}\"];",
+                    node_id(parent),
+                    id,
+                    moves.join(",")
+                )
+                .unwrap();
+            }
+        }
+        level = next_level;
+        player = player.toggle();
+    }
+
+    out.push_str("}\n");
+    out
+}
+
+// the number of move sequences exactly `depth` moves long from `board`, with
+// crosses having moved first; games that end sooner don't count. A quick
+// check that move generation is still right after touching it
+pub fn perft(board: &Board, depth: usize) -> u64 {
+    match board.occupancy().to_move(Player::Cross) {
+        Some(to_move) => count_leaves(board, to_move, depth),
+        None => 0,
+    }
+}
+
+fn count_leaves(board: &Board, to_move: Player, depth: usize) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    board
+        .successors(to_move)
+        .map(|(_, next)| count_leaves(&next, to_move.toggle(), depth - 1))
+        .sum()
+}
+
+// a move from a transcript, judged by perfect play
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Annotation {
+    // the mark played, which in wild games needn't be the mover's own
+    pub player: Player,
+    pub pos: Pos,
+    // the best result the mover could still get before the move
+    pub before: Outcome,
+    // and after it
+    pub after: Outcome,
+    // a move that would have kept `before`, if this one didn't
+    pub better: Option<Pos>,
+}
+
+impl Annotation {
+    // the move threw away a win or a draw
+    pub fn is_blunder(&self) -> bool {
+        self.after < self.before
+    }
+}
+
+// e.g. "X5", or "O2?? draw -> loss, 5 was better"
+impl fmt::Display for Annotation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.player, self.pos)?;
+        if self.is_blunder() {
+            write!(f, "?? {} -> {}", self.before, self.after)?;
+            if let Some(better) = self.better {
+                write!(f, ", {} was better", better)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// replays `transcript` and judges every move against perfect play; the
+// engine only judges two-player games
+pub fn annotate(transcript: &Transcript) -> Result<Vec<Annotation>, ApplyError> {
+    transcript.replay()?;
+    if transcript.config().players() > 2 {
+        return Err(ApplyError {
+            index: 0,
+            error: PlaceError::TwoPlayersOnly,
+        });
+    }
+
+    let rules = transcript.config().rules;
+    let mut engine = Engine::with_rules(rules);
+    // how `game` stands for `player`, who after a double move is still the
+    // one to move
+    let mut outcome = |game: &Game, player: Player| {
+        if let Some(result) = game.result() {
+            return match result.winner {
+                Some(winner) if winner == player => Outcome::Win,
+                Some(_) => Outcome::Loss,
+                None => Outcome::Draw,
+            };
+        }
+        let to_move = game.to_move();
+        let outcome = match engine.evaluate(game.board(), to_move) {
+            score if score > 0 => Outcome::Win,
+            score if score < 0 => Outcome::Loss,
+            _ => Outcome::Draw,
+        };
+        if to_move == player {
+            outcome
+        } else {
+            outcome.flip()
+        }
+    };
+
+    let mut annotations = Vec::new();
+    for (index, &(mark, pos)) in transcript.moves().iter().enumerate() {
+        // the game before this move, with its handicap, random start and so
+        // on, or after `pos` in its place
+        let game = |pos: Option<Pos>| {
+            let mut game = transcript.clone();
+            game.moves.truncate(index);
+            game.moves.extend(pos.map(|pos| (mark, pos)));
+            game.replay()
+        };
+        // the whole transcript replayed above, so its beginnings do too
+        let played = game(None).unwrap();
+        let player = played.to_move();
+        let before = outcome(&played, player);
+        let after = outcome(&game(Some(pos)).unwrap(), player);
+        let better = if after < before {
+            rules
+                .legal_moves(played.board())
+                .find(|&pos| game(Some(pos)).is_ok_and(|next| outcome(&next, player) == before))
+        } else {
+            None
+        };
+        annotations.push(Annotation {
+            player: mark,
+            pos,
+            before,
+            after,
+            better,
+        });
+    }
+    Ok(annotations)
+}
+
+// the same for symmetric positions
+fn node_id(board: &Board) -> u64 {
+    board.canonical().zobrist()
+}
+
+fn write_node(out: &mut String, engine: &mut Engine, board: &Board, to_move: Player) {
+    let winner = match board.winner() {
+        Some(winner) => Some(winner),
+        None => match engine.evaluate(board, to_move).signum() {
+            1 => Some(to_move),
+            -1 => Some(to_move.toggle()),
+            _ => None,
+        },
+    };
+    let (outcome, color) = match winner {
+        Some(Player::Cross) => ("X wins", "red"),
+        // noughts, as only two-player games get analysed
+        Some(_) => ("O wins", "blue"),
+        None => ("draw", "grey"),
+    };
+
+    let mut label = String::new();
+    for row in board.cells.chunks(Board::WIDTH) {
+        for cell in row {
+            label.push(cell.symbol());
+        }
+        label.push_str("\\n");
+    }
+    label.push_str(outcome);
+    writeln!(
+        out,
+        "    n{:016x} [label=\"{}\", color={}];",
+        node_id(board),
+        label,
+        color
+    )
+    .unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Handicap;
+    use alloc::vec;
+
+    #[test]
+    fn to_dot() {
+        let dot = super::to_dot(&Board::new(), 1);
+        assert!(dot.starts_with("digraph game {\n"));
+        assert!(dot.ends_with("}\n"));
+        // the empty board and the centre, corner and edge openings
+        assert_eq!(4, dot.matches(", color=").count());
+        assert_eq!(3, dot.matches(" -> ").count());
+        assert_eq!(4, dot.matches("draw\"").count());
+        // four corners lead to the same node
+        assert_eq!(1, dot.matches("label=\"1,3,7,9\"").count());
+    }
+
+    #[test]
+    fn perft() {
+        // the whole tree is too slow for a debug build, so stop at six
+        let counts: Vec<u64> = (0..=6)
+            .map(|depth| super::perft(&Board::new(), depth))
+            .collect();
+        assert_eq!(vec![1, 9, 72, 504, 3024, 15120, 54720], counts);
+
+        let mut board = Board::new();
+        board.place(Pos::new(5).unwrap(), Player::Cross).unwrap();
+        assert_eq!(8 * 7, super::perft(&board, 2));
+        board.place(Pos::new(1).unwrap(), Player::Cross).unwrap();
+        assert_eq!(0, super::perft(&board, 1));
+    }
+
+    #[test]
+    fn annotate() {
+        let transcript: Transcript = "X1 O2 X5 O9 X4 O7 X6".parse().unwrap();
+        let annotations = super::annotate(&transcript).unwrap();
+        assert_eq!(7, annotations.len());
+        let blunders: Vec<String> = annotations
+            .iter()
+            .filter(|annotation| annotation.is_blunder())
+            .map(|annotation| annotation.to_string())
+            .collect();
+        assert_eq!(vec!["O2?? draw -> loss, 5 was better"], blunders);
+        assert_eq!("X5", annotations[2].to_string());
+        assert_eq!(Outcome::Win, annotations[2].before);
+
+        // crosses miss a win in one and lose instead
+        let transcript: Transcript = "X1 O4 X2 O5 X9 O6".parse().unwrap();
+        let annotations = super::annotate(&transcript).unwrap();
+        assert_eq!("X9?? win -> loss, 3 was better", annotations[4].to_string());
+        assert!(!annotations[5].is_blunder());
+
+        let invalid: Transcript = "X1 O1".parse().unwrap();
+        assert_eq!(1, super::annotate(&invalid).unwrap_err().index);
+
+        // noughts start with a stone in the corner, so only the centre
+        // holds the draw
+        let mut transcript: Transcript = "X2 O5".parse().unwrap();
+        transcript.config.handicap = Some(Handicap {
+            player: Player::Nought,
+            stones: vec![Pos::new(1).unwrap()],
+            double_move: false,
+        });
+        let annotations = super::annotate(&transcript).unwrap();
+        assert_eq!(
+            "X2?? draw -> loss, 5 was better",
+            annotations[0].to_string()
+        );
+        assert_eq!(Outcome::Win, annotations[1].after);
+    }
+
+    #[test]
+    fn outcomes() {
+        let mut board = Board::new();
+        board.place(Pos::new(1).unwrap(), Player::Cross).unwrap();
+        board.place(Pos::new(2).unwrap(), Player::Nought).unwrap();
+        let dot = super::to_dot(&board, 0);
+        assert!(dot.contains("X wins\", color=red]"));
+        assert_eq!(0, dot.matches(" -> ").count());
+
+        // nothing to show for a position that can't come up
+        board.place(Pos::new(3).unwrap(), Player::Nought).unwrap();
+        assert_eq!(
+            "digraph game {\n    node [shape=box, fontname=\"monospace\"];\n}\n",
+            super::to_dot(&board, 2)
+        );
+    }
+}
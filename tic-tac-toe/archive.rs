@@ -0,0 +1,487 @@
+// many games in one file, e.g. a whole tournament, in a format like chess's
+// PGN. Each game is a block of `[Name "value"]` headers followed by a line
+// of moves, with a blank line between games:
+//
+//     [Cross "Alice"]
+//     [Nought "Bob"]
+//     [Date "2026.10.16"]
+//     [Result "X"]
+//     X5 O1 X9 O3 X7 O4 X8
+//
+// `Result` is the winner's mark, draw, or * for an unfinished game, and
+// games for more than two name the others in `Player3` and `Player4`.
+// `Rules`, `TimeControl`, `Handicap`, `RandomStart` and `Termination`
+// (resignation, timeout or forfeit) only appear when they matter, written as
+// a save file writes them, and any other headers, e.g. Event or Round, are
+// kept as they are
+
+use std::fmt;
+use std::io::{self, BufRead, Lines, Write};
+use std::string::{String, ToString};
+use std::vec::Vec;
+
+use crate::save::{
+    handicap_text, parse_handicap, parse_random_start, parse_reason, parse_rules,
+    parse_time_control, random_start_text, reason_name, rule_names, time_control_text,
+};
+use crate::{
+    ApplyError, ConfigError, Game, GameResult, ParseTranscriptError, PlaceError, Player,
+    PlayerKind, Seat, Transcript,
+};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ArchivedGame {
+    // in the order they're written
+    pub headers: Vec<(String, String)>,
+    pub transcript: Transcript,
+}
+
+impl ArchivedGame {
+    // the players, result and anything needed to replay `game`; add e.g. a
+    // Date with `set_header`
+    pub fn from_game(game: &Game) -> ArchivedGame {
+        let transcript = game.transcript().clone();
+        let config = transcript.config();
+        let mut archived = ArchivedGame {
+            headers: Vec::new(),
+            transcript: Transcript::new(),
+        };
+        archived.set_header("Cross", &config.cross.name);
+        archived.set_header("Nought", &config.nought.name);
+        for (n, seat) in config.others.iter().enumerate() {
+            archived.set_header(&other_header(n), &seat.name);
+        }
+        let result = match game.result() {
+            Some(GameResult {
+                winner: Some(winner),
+                ..
+            }) => winner.to_string(),
+            Some(_) => "draw".to_string(),
+            None => "*".to_string(),
+        };
+        archived.set_header("Result", &result);
+        let rules = rule_names(config.rules);
+        if !rules.is_empty() {
+            archived.set_header("Rules", &rules.join(" "));
+        }
+        if let Some(time_control) = config.time_control {
+            archived.set_header("TimeControl", &time_control_text(time_control));
+        }
+        if let Some(handicap) = &config.handicap {
+            archived.set_header("Handicap", &handicap_text(handicap));
+        }
+        if let Some(random_start) = config.random_start {
+            archived.set_header("RandomStart", &random_start_text(random_start));
+        }
+        if let Some(reason) = game.result().and_then(|result| reason_name(result.reason)) {
+            archived.set_header("Termination", reason);
+        }
+        archived.transcript = transcript;
+        archived
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        let header = self.headers.iter().find(|(key, _)| key == name);
+        header.map(|(_, value)| value.as_str())
+    }
+
+    // replaces the header if it's already there, and adds it at the end if
+    // not
+    pub fn set_header(&mut self, name: &str, value: &str) {
+        match self.headers.iter_mut().find(|(key, _)| key == name) {
+            Some((_, old)) => *old = value.to_string(),
+            None => self.headers.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    // who won, or None for a draw or an unfinished game
+    pub fn winner(&self) -> Option<Player> {
+        self.header("Result")?.parse().ok()
+    }
+
+    // plays the game through, ending it as `Termination` says if the moves
+    // didn't
+    pub fn replay(&self) -> Result<Game, ApplyError> {
+        let mut game = self.transcript.replay()?;
+        let reason = self.header("Termination").and_then(parse_reason);
+        if let (Some(reason), Some(winner)) = (reason, self.winner()) {
+            let error = |error| ApplyError {
+                index: self.transcript.len(),
+                error,
+            };
+            // resigning, timeouts and forfeits need a single opponent
+            if winner.number() >= 2 {
+                return Err(error(PlaceError::TwoPlayersOnly));
+            }
+            game.concede(winner.toggle(), reason).map_err(error)?;
+        }
+        Ok(game)
+    }
+}
+
+// the header naming the `n`th player after crosses and noughts
+fn other_header(n: usize) -> String {
+    format!("Player{}", n + 3)
+}
+
+fn quote(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+pub struct ArchiveWriter<W> {
+    writer: W,
+    games: usize,
+}
+
+impl<W: Write> ArchiveWriter<W> {
+    pub fn new(writer: W) -> ArchiveWriter<W> {
+        ArchiveWriter { writer, games: 0 }
+    }
+
+    pub fn write(&mut self, game: &ArchivedGame) -> io::Result<()> {
+        if self.games > 0 {
+            writeln!(self.writer)?;
+        }
+        for (name, value) in &game.headers {
+            writeln!(self.writer, "[{} \"{}\"]", name, quote(value))?;
+        }
+        writeln!(self.writer, "{}", game.transcript)?;
+        self.games += 1;
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+// the games in an archive, one at a time; after an error the rest of the
+// archive can still be read
+pub struct ArchiveReader<R> {
+    lines: Lines<R>,
+    // of the line last read, counting from 1
+    number: usize,
+}
+
+impl<R: BufRead> ArchiveReader<R> {
+    pub fn new(reader: R) -> ArchiveReader<R> {
+        ArchiveReader {
+            lines: reader.lines(),
+            number: 0,
+        }
+    }
+
+    fn line(&mut self) -> Option<io::Result<String>> {
+        self.number += 1;
+        self.lines.next()
+    }
+
+    // `line` is the game's first line
+    fn game(&mut self, mut line: String) -> Result<ArchivedGame, ArchiveError> {
+        let mut headers = Vec::new();
+        while line.starts_with('[') {
+            let header = parse_header(&line).ok_or(ArchiveError::Line {
+                number: self.number,
+                text: line.clone(),
+            })?;
+            headers.push(header);
+            line = match self.line() {
+                Some(line) => line?,
+                // headers with no moves after them
+                None => String::new(),
+            };
+        }
+
+        let mut game = ArchivedGame {
+            headers,
+            transcript: line.parse().map_err(|error| ArchiveError::Transcript {
+                number: self.number,
+                error,
+            })?,
+        };
+        let config = &mut game.transcript.config;
+        if let Some(name) = game.headers.iter().find(|(key, _)| key == "Cross") {
+            config.cross.name = name.1.clone();
+        }
+        if let Some(name) = game.headers.iter().find(|(key, _)| key == "Nought") {
+            config.nought.name = name.1.clone();
+        }
+        for n in 0..Player::MAX - 2 {
+            let Some((_, name)) = game.headers.iter().find(|(key, _)| *key == other_header(n))
+            else {
+                break;
+            };
+            config.others.push(Seat {
+                name: name.clone(),
+                kind: PlayerKind::Human,
+            });
+        }
+        if let Some((_, rules)) = game.headers.iter().find(|(key, _)| key == "Rules") {
+            config.rules = parse_rules(rules.split_whitespace()).ok_or(ArchiveError::Rules {
+                rules: rules.clone(),
+            })?;
+        }
+        let header = |name: &str| game.headers.iter().find(|(key, _)| key == name);
+        let bad = |(name, value): &(String, String)| ArchiveError::Header {
+            name: name.clone(),
+            value: value.clone(),
+        };
+        if let Some(header) = header("TimeControl") {
+            let time_control = parse_time_control(header.1.split_whitespace());
+            config.time_control = Some(time_control.ok_or_else(|| bad(header))?);
+        }
+        if let Some(header) = header("Handicap") {
+            let handicap = parse_handicap(header.1.split_whitespace());
+            config.handicap = Some(handicap.ok_or_else(|| bad(header))?);
+        }
+        if let Some(header) = header("RandomStart") {
+            let random_start = parse_random_start(header.1.split_whitespace());
+            config.random_start = Some(random_start.ok_or_else(|| bad(header))?);
+        }
+        // `replay` panics on a bad config, so check it here
+        Game::try_with_config(config.clone()).map_err(ArchiveError::Config)?;
+        Ok(game)
+    }
+}
+
+impl<R: BufRead> Iterator for ArchiveReader<R> {
+    type Item = Result<ArchivedGame, ArchiveError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.line()? {
+                Ok(line) => line,
+                Err(err) => return Some(Err(ArchiveError::Io(err))),
+            };
+            if !line.trim().is_empty() {
+                return Some(self.game(line));
+            }
+        }
+    }
+}
+
+// `[Name "value"]`, with \" and \\ escaped in the value
+fn parse_header(line: &str) -> Option<(String, String)> {
+    let inner = line.trim_end().strip_prefix('[')?.strip_suffix(']')?;
+    let (name, quoted) = inner.split_once(' ')?;
+    let quoted = quoted.strip_prefix('"')?.strip_suffix('"')?;
+    let mut value = String::new();
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => value.push(chars.next()?),
+            '"' => return None,
+            c => value.push(c),
+        }
+    }
+    Some((name.to_string(), value))
+}
+
+#[derive(Debug)]
+pub enum ArchiveError {
+    Io(io::Error),
+    // a header that isn't `[Name "value"]`; `number` counts from 1
+    Line {
+        number: usize,
+        text: String,
+    },
+    Transcript {
+        number: usize,
+        error: ParseTranscriptError,
+    },
+    Rules {
+        rules: String,
+    },
+    // a TimeControl, Handicap or RandomStart header that can't be read
+    Header {
+        name: String,
+        value: String,
+    },
+    Config(ConfigError),
+}
+
+impl From<io::Error> for ArchiveError {
+    fn from(err: io::Error) -> ArchiveError {
+        ArchiveError::Io(err)
+    }
+}
+
+impl fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ArchiveError::Io(err) => write!(f, "{}", err),
+            ArchiveError::Line { number, text } => {
+                write!(f, "line {}: bad header {:?}", number, text)
+            }
+            ArchiveError::Transcript { number, error } => write!(f, "line {}: {}", number, error),
+            ArchiveError::Rules { rules } => write!(f, "unknown rules {:?}", rules),
+            ArchiveError::Header { name, value } => write!(f, "bad {} {:?}", name, value),
+            ArchiveError::Config(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for ArchiveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ArchiveError::Io(err) => Some(err),
+            ArchiveError::Transcript { error, .. } => Some(error),
+            ArchiveError::Config(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::pos;
+    use crate::{GameConfig, Handicap, Pos, Rules, TimeControl, WinReason};
+    use std::time::Duration;
+
+    fn game(config: GameConfig, moves: &[usize]) -> Game {
+        let mut game = Game::with_config(config);
+        let moves: Vec<Pos> = moves.iter().map(|&n| pos(n)).collect();
+        game.apply(&moves).unwrap();
+        game
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut config = GameConfig::default();
+        config.cross.name = "Alice \"the Great\"".to_string();
+        config.nought.name = "Bob".to_string();
+        let mut won = ArchivedGame::from_game(&game(config.clone(), &[1, 4, 2, 5, 3]));
+        won.set_header("Date", "2026.10.16");
+
+        let mut resigned = game(config.clone(), &[5]);
+        resigned.resign(Player::Nought).unwrap();
+        let resigned = ArchivedGame::from_game(&resigned);
+
+        config.rules = Rules {
+            gravity: true,
+            ..Rules::default()
+        };
+        let unfinished = ArchivedGame::from_game(&game(config, &[]));
+
+        let mut writer = ArchiveWriter::new(Vec::new());
+        for game in [&won, &resigned, &unfinished] {
+            writer.write(game).unwrap();
+        }
+        let text = String::from_utf8(writer.into_inner()).unwrap();
+        assert!(text.starts_with(
+            "[Cross \"Alice \\\"the Great\\\"\"]\n[Nought \"Bob\"]\n[Result \"X\"]\n[Date \"2026.10.16\"]\nX1 O4 X2 O5 X3\n\n"
+        ));
+        assert!(text.contains("[Result \"X\"]\n[Termination \"resignation\"]\nX5\n\n"));
+        assert!(text.ends_with("[Result \"*\"]\n[Rules \"gravity\"]\n\n"));
+
+        let read: Vec<ArchivedGame> = ArchiveReader::new(text.as_bytes())
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(vec![won, resigned, unfinished], read);
+        let replayed = read[1].replay().unwrap();
+        assert_eq!(
+            Some(GameResult {
+                winner: Some(Player::Cross),
+                reason: WinReason::Resignation
+            }),
+            replayed.result()
+        );
+        assert_eq!(Some(Player::Cross), read[0].winner());
+        assert_eq!(
+            Some(Player::Cross),
+            read[0].replay().unwrap().result().unwrap().winner
+        );
+        assert!(read[2].transcript.config().rules.gravity);
+    }
+
+    #[test]
+    fn three_players() {
+        let mut config = GameConfig::with_players(3);
+        config.others[0].name = "Carol".to_string();
+        let archived = ArchivedGame::from_game(&game(config, &[1, 2, 3, 4, 5, 6, 8, 7, 9]));
+        assert_eq!(Some("Carol"), archived.header("Player3"));
+        assert_eq!(Some(Player::nth(2).unwrap()), archived.winner());
+
+        let mut writer = ArchiveWriter::new(Vec::new());
+        writer.write(&archived).unwrap();
+        let text = String::from_utf8(writer.into_inner()).unwrap();
+        assert!(text.contains("[Player3 \"Carol\"]\n[Result \"+\"]\n"));
+        let read = ArchiveReader::new(text.as_bytes()).next().unwrap().unwrap();
+        assert_eq!(archived, read);
+        assert_eq!(3, read.transcript.config().players());
+        assert_eq!(
+            Some(Player::nth(2).unwrap()),
+            read.replay().unwrap().result().unwrap().winner
+        );
+    }
+
+    #[test]
+    fn handicap() {
+        let config = GameConfig {
+            time_control: Some(TimeControl {
+                initial: Duration::from_secs(60),
+                increment: Duration::from_millis(500),
+            }),
+            handicap: Some(Handicap {
+                player: Player::Nought,
+                stones: vec![pos(1), pos(2)],
+                double_move: false,
+            }),
+            ..GameConfig::default()
+        };
+        let archived = ArchivedGame::from_game(&game(config, &[5, 3]));
+        assert_eq!(Some("60000 500"), archived.header("TimeControl"));
+        assert_eq!(Some("O 1 2"), archived.header("Handicap"));
+
+        let mut writer = ArchiveWriter::new(Vec::new());
+        writer.write(&archived).unwrap();
+        let text = String::from_utf8(writer.into_inner()).unwrap();
+        let read = ArchiveReader::new(text.as_bytes()).next().unwrap().unwrap();
+        assert_eq!(archived, read);
+        // the stones won it, which the moves alone wouldn't
+        assert_eq!(
+            Some(Player::Nought),
+            read.replay().unwrap().result().unwrap().winner
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let text = "[Event \"open\"]\n[Cross Alice]\nX5\n\n[Event \"open\"]\nX5 Q1\n\n\n[Round \"3\"]\nX5 O1\n";
+        let read: Vec<Result<ArchivedGame, ArchiveError>> =
+            ArchiveReader::new(text.as_bytes()).collect();
+        let errors: Vec<String> = read
+            .iter()
+            .filter_map(|game| game.as_ref().err().map(ToString::to_string))
+            .collect();
+        // the first game's bad header leaves its moves to be read as a game
+        // of their own
+        assert_eq!(
+            vec![
+                r#"line 2: bad header "[Cross Alice]""#.to_string(),
+                r#"line 6: invalid move "Q1" at index 1"#.to_string()
+            ],
+            errors
+        );
+        let games: Vec<&ArchivedGame> = read.iter().filter_map(|game| game.as_ref().ok()).collect();
+        assert_eq!(2, games.len());
+        assert_eq!(Some("3"), games[1].header("Round"));
+        assert_eq!(2, games[1].transcript.len());
+    }
+
+    #[test]
+    fn bad_config() {
+        let text = "[Handicap \"Q 1\"]\nX5\n\n[Player3 \"Carol\"]\n[Rules \"misere\"]\nX5\n";
+        let errors: Vec<String> = ArchiveReader::new(text.as_bytes())
+            .map(|game| game.unwrap_err().to_string())
+            .collect();
+        assert_eq!(2, errors.len());
+        assert_eq!(r#"bad Handicap "Q 1""#, errors[0]);
+
+        // a resignation needs an opponent to win
+        let text = "[Result \"+\"]\n[Termination \"resignation\"]\nX5\n";
+        let read = ArchiveReader::new(text.as_bytes()).next().unwrap().unwrap();
+        assert_eq!(PlaceError::TwoPlayersOnly, read.replay().unwrap_err().error);
+    }
+}
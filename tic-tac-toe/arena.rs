@@ -0,0 +1,336 @@
+// referees games between external bot programs that speak the `protocol`,
+// so bots written in any language can play each other
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::spectate::Spectators;
+use crate::{Game, GameConfig, Player, PlayerKind, Pos, Seat};
+
+// slack on top of the move time for starting up and talking over pipes
+const GRACE: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct BotCommand {
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+impl BotCommand {
+    pub fn new(program: impl Into<String>) -> BotCommand {
+        BotCommand {
+            program: program.into(),
+            args: Vec::new(),
+        }
+    }
+
+    pub fn arg(mut self, arg: impl Into<String>) -> BotCommand {
+        self.args.push(arg.into());
+        self
+    }
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Arena {
+    pub first: BotCommand,
+    pub second: BotCommand,
+    // how long a bot may think about each move
+    pub move_time: Duration,
+    // how long a bot may take to answer `uci` after starting
+    pub startup_time: Duration,
+}
+
+impl Arena {
+    pub fn new(first: BotCommand, second: BotCommand) -> Arena {
+        Arena {
+            first,
+            second,
+            move_time: Duration::from_secs(1),
+            startup_time: Duration::from_secs(5),
+        }
+    }
+
+    // plays `games` games, the bots swapping colours every game with the
+    // first bot starting as crosses
+    pub fn play_match(&self, games: usize) -> io::Result<Vec<Game>> {
+        (0..games)
+            .map(|game| {
+                let first_plays = if game % 2 == 0 {
+                    Player::Cross
+                } else {
+                    Player::Nought
+                };
+                self.play_game(first_plays)
+            })
+            .collect()
+    }
+
+    // plays one game with a fresh process for each bot. A bot that doesn't
+    // answer in time loses on time, and one that exits, says something
+    // unexpected or plays an illegal move forfeits; only failing to start a
+    // bot at all is an error
+    pub fn play_game(&self, first_plays: Player) -> io::Result<Game> {
+        self.referee(first_plays, None)
+    }
+
+    // like `play_game`, streaming the game to `spectators` as it's played
+    pub fn play_game_watched(
+        &self,
+        first_plays: Player,
+        spectators: &Spectators,
+    ) -> io::Result<Game> {
+        self.referee(first_plays, Some(spectators))
+    }
+
+    fn referee(&self, first_plays: Player, spectators: Option<&Spectators>) -> io::Result<Game> {
+        let (cross, nought) = if first_plays == Player::Cross {
+            (&self.first, &self.second)
+        } else {
+            (&self.second, &self.first)
+        };
+        let seat = |command: &BotCommand| Seat {
+            name: command.program.clone(),
+            kind: PlayerKind::Ai,
+        };
+        let mut game = Game::with_config(GameConfig {
+            cross: seat(cross),
+            nought: seat(nought),
+            ..GameConfig::default()
+        });
+        if let Some(spectators) = spectators {
+            spectators.watch(&mut game);
+        }
+
+        let mut bots = [Bot::spawn(nought)?, Bot::spawn(cross)?];
+        for (bot, player) in bots.iter_mut().zip([Player::Nought, Player::Cross]) {
+            match bot.handshake(self.startup_time) {
+                Reply::Line(_) => {}
+                Reply::Timeout => return Ok(lose_on_time(game, player)),
+                Reply::Gone => {
+                    game.forfeit(player).unwrap();
+                    return Ok(game);
+                }
+            }
+        }
+
+        while game.result().is_none() {
+            let player = game.to_move();
+            let bot = &mut bots[player.index()];
+            let moves: Vec<String> = game
+                .transcript()
+                .moves()
+                .iter()
+                .map(|(_, pos)| pos.to_string())
+                .collect();
+            let reply = bot.think(&moves, self.move_time);
+            let pos = match reply {
+                Reply::Line(line) => line
+                    .strip_prefix("bestmove ")
+                    .and_then(|pos| pos.trim().parse::<Pos>().ok()),
+                Reply::Timeout => return Ok(lose_on_time(game, player)),
+                Reply::Gone => None,
+            };
+            if pos.is_none_or(|pos| game.play(pos).is_err()) {
+                game.forfeit(player).unwrap();
+            }
+        }
+        Ok(game)
+    }
+}
+
+fn lose_on_time(mut game: Game, player: Player) -> Game {
+    if game.to_move() != player {
+        // only the player to move can run out of time, so the other side
+        // timing out during the handshake is a forfeit
+        game.forfeit(player).unwrap();
+    } else {
+        game.time_out().unwrap();
+    }
+    game
+}
+
+enum Reply {
+    Line(String),
+    Timeout,
+    // the bot exited or closed its output
+    Gone,
+}
+
+struct Bot {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+}
+
+impl Bot {
+    fn spawn(command: &BotCommand) -> io::Result<Bot> {
+        let mut child = Command::new(&command.program)
+            .args(&command.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+
+        // reading blocks, so a thread forwards lines and the referee waits
+        // on the channel with a timeout instead
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Bot {
+            child,
+            stdin,
+            lines,
+        })
+    }
+
+    fn handshake(&mut self, limit: Duration) -> Reply {
+        if writeln!(self.stdin, "uci").is_err() {
+            return Reply::Gone;
+        }
+        self.expect("uciok", limit)
+    }
+
+    fn think(&mut self, moves: &[String], move_time: Duration) -> Reply {
+        let mut position = String::from("position startpos");
+        if !moves.is_empty() {
+            position.push_str(" moves ");
+            position.push_str(&moves.join(" "));
+        }
+        let sent = writeln!(self.stdin, "{}", position)
+            .and_then(|_| writeln!(self.stdin, "go movetime {}", move_time.as_millis()))
+            .and_then(|_| self.stdin.flush());
+        if sent.is_err() {
+            return Reply::Gone;
+        }
+        self.expect("bestmove", move_time)
+    }
+
+    // waits for a line starting with `prefix`, skipping any `info` and
+    // other chatter on the way
+    fn expect(&mut self, prefix: &str, limit: Duration) -> Reply {
+        let deadline = Instant::now() + limit + GRACE;
+        loop {
+            let left = deadline.saturating_duration_since(Instant::now());
+            match self.lines.recv_timeout(left) {
+                Ok(line) if line.starts_with(prefix) => return Reply::Line(line),
+                Ok(_) => {}
+                Err(RecvTimeoutError::Timeout) => return Reply::Timeout,
+                Err(RecvTimeoutError::Disconnected) => return Reply::Gone,
+            }
+        }
+    }
+}
+
+impl Drop for Bot {
+    fn drop(&mut self) {
+        let _ = writeln!(self.stdin, "quit");
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::{GameEvent, WinReason};
+
+    // a shell bot that plays `moves` in order, whatever the position
+    fn scripted(moves: &str) -> BotCommand {
+        let script = format!(
+            "set -- {}; while read line; do case $line in \
+             uci) echo uciok;; go*) echo \"bestmove $1\"; shift;; esac; done",
+            moves
+        );
+        BotCommand::new("sh").arg("-c").arg(script)
+    }
+
+    fn arena(first: BotCommand, second: BotCommand) -> Arena {
+        Arena {
+            move_time: Duration::from_millis(200),
+            startup_time: Duration::from_secs(2),
+            ..Arena::new(first, second)
+        }
+    }
+
+    #[test]
+    fn plays_a_game() {
+        let arena = arena(scripted("1 2 3"), scripted("4 5"));
+        let game = arena.play_game(Player::Cross).unwrap();
+        assert_eq!("X1 O4 X2 O5 X3", game.transcript().to_string());
+        let result = game.result().unwrap();
+        assert_eq!(Some(Player::Cross), result.winner);
+        assert_eq!(WinReason::Line, result.reason);
+        assert_eq!("sh", game.config().cross.name);
+    }
+
+    #[test]
+    fn spectators() {
+        let arena = arena(scripted("1 2 3"), scripted("4 5"));
+        let spectators = Spectators::new();
+        let events = spectators.subscribe();
+        arena.play_game_watched(Player::Cross, &spectators).unwrap();
+        drop(spectators);
+        let events: Vec<GameEvent> = events.into_iter().collect();
+        assert_eq!(6, events.len());
+        assert_eq!(
+            GameEvent::GameWon {
+                winner: Player::Cross
+            },
+            events[5]
+        );
+    }
+
+    #[test]
+    fn swaps_colours() {
+        let arena = arena(scripted("4 5"), scripted("1 2 3"));
+        let games = arena.play_match(2).unwrap();
+        assert_eq!(2, games.len());
+        // the second bot plays noughts, then crosses, and wins both
+        assert_eq!("X4 O1 X5 O2", games[0].transcript().to_string());
+        assert_eq!(Some(Player::Nought), games[0].result().unwrap().winner);
+        assert_eq!("X1 O4 X2 O5 X3", games[1].transcript().to_string());
+        assert_eq!(Some(Player::Cross), games[1].result().unwrap().winner);
+    }
+
+    #[test]
+    fn illegal_move_forfeits() {
+        let arena = arena(scripted("5 1"), scripted("5"));
+        let game = arena.play_game(Player::Cross).unwrap();
+        let result = game.result().unwrap();
+        assert_eq!(Some(Player::Cross), result.winner);
+        assert_eq!(WinReason::Forfeit, result.reason);
+        assert_eq!("X5", game.transcript().to_string());
+    }
+
+    #[test]
+    fn slow_bot_loses_on_time() {
+        let silent = BotCommand::new("sh")
+            .arg("-c")
+            .arg("while read line; do case $line in uci) echo uciok;; esac; done");
+        let game = arena(silent, scripted("5"))
+            .play_game(Player::Cross)
+            .unwrap();
+        let result = game.result().unwrap();
+        assert_eq!(Some(Player::Nought), result.winner);
+        assert_eq!(WinReason::Timeout, result.reason);
+    }
+
+    #[test]
+    fn missing_program() {
+        let arena = arena(BotCommand::new("/nonexistent/bot"), scripted("5"));
+        assert!(arena.play_game(Player::Cross).is_err());
+    }
+}
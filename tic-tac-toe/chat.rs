@@ -0,0 +1,196 @@
+// games for chat bots that keep no state of their own. The whole game is
+// the board, carried in the bot's last message as nine characters of X, O
+// and ., e.g. "X...O...." with crosses always moving first. The bot shows
+// it as an emoji grid, offers the vacant cells as number reactions, and
+// when someone reacts or replies, `next_state` turns the old encoding and
+// their move into the new one:
+//
+//     ❌⬜⬜
+//     ⬜⭕⬜
+//     ⬜⬜⬜
+//
+// only standard rules fit in nine characters
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::{Board, Cell, InvalidBoardError, Occupancy, PlaceError, Player, Pos};
+
+const CROSS: &str = "❌";
+const NOUGHT: &str = "⭕";
+const VACANT: &str = "⬜";
+
+// keycap emoji for cells 1-9, as used for reactions
+pub const KEYCAPS: [&str; Board::SIZE] = ["1️⃣", "2️⃣", "3️⃣", "4️⃣", "5️⃣", "6️⃣", "7️⃣", "8️⃣", "9️⃣"];
+
+pub fn encode(board: &Board) -> String {
+    board.to_compact()
+}
+
+// only boards that can come from a game with crosses moving first
+pub fn decode(encoded: &str) -> Result<Board, ChatError> {
+    let encoded = encoded.trim();
+    let mut cells = [Cell::Vacant; Board::SIZE];
+    if encoded.chars().count() != Board::SIZE {
+        return Err(ChatError::Encoding);
+    }
+    for (cell, symbol) in cells.iter_mut().zip(encoded.chars()) {
+        *cell = match symbol {
+            'X' => Cell::Occupied(Player::Cross),
+            'O' => Cell::Occupied(Player::Nought),
+            '.' => Cell::Vacant,
+            _ => return Err(ChatError::Encoding),
+        };
+    }
+    let board = Board::from_cells(cells).map_err(ChatError::Board)?;
+    let Occupancy {
+        crosses, noughts, ..
+    } = board.occupancy();
+    if noughts > crosses {
+        let counts = InvalidBoardError::PieceCount { crosses, noughts };
+        return Err(ChatError::Board(counts));
+    }
+    Ok(board)
+}
+
+// whose turn it is on a decoded board, or None once the game is over
+pub fn to_move(board: &Board) -> Option<Player> {
+    if board.is_over() {
+        return None;
+    }
+    board.occupancy().to_move(Player::Cross)
+}
+
+// three lines of emoji, with no trailing newline
+pub fn to_emoji(board: &Board) -> String {
+    let mut grid = String::new();
+    for (i, cell) in board.cells.iter().enumerate() {
+        if i > 0 && i % Board::WIDTH == 0 {
+            grid.push('\n');
+        }
+        grid.push_str(match cell {
+            Cell::Occupied(Player::Cross) => CROSS,
+            // noughts; `decode` lets no other marks through
+            Cell::Occupied(_) => NOUGHT,
+            Cell::Vacant => VACANT,
+        });
+    }
+    grid
+}
+
+// the keycaps to offer as reactions: one per legal move, none once it's
+// over
+pub fn reactions(board: &Board) -> Vec<&'static str> {
+    if board.is_over() {
+        return Vec::new();
+    }
+    board
+        .legal_moves()
+        .map(|pos| KEYCAPS[pos.get() - 1])
+        .collect()
+}
+
+// a keycap reaction, or a typed "5" or "b2"
+pub fn parse_move(text: &str) -> Option<Pos> {
+    let text = text.trim();
+    match KEYCAPS.iter().position(|&keycap| keycap == text) {
+        Some(index) => Pos::new(index + 1),
+        None => text.parse().ok(),
+    }
+}
+
+// plays `mv` for whoever is to move on the encoded board, and returns the
+// new encoding
+pub fn next_state(encoded: &str, mv: &str) -> Result<String, ChatError> {
+    let mut board = decode(encoded)?;
+    let player = to_move(&board).ok_or(ChatError::Place(PlaceError::GameOver))?;
+    let pos = parse_move(mv).ok_or(ChatError::Move)?;
+    board.place(pos, player).map_err(ChatError::Place)?;
+    Ok(encode(&board))
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum ChatError {
+    // not nine characters of X, O and .
+    Encoding,
+    // a layout no game with crosses first could reach
+    Board(InvalidBoardError),
+    // neither a keycap nor a cell
+    Move,
+    Place(PlaceError),
+}
+
+impl fmt::Display for ChatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ChatError::Encoding => write!(f, "not an encoded board"),
+            ChatError::Board(err) => write!(f, "{}", err),
+            ChatError::Move => write!(f, "not a move"),
+            ChatError::Place(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl core::error::Error for ChatError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            ChatError::Board(err) => Some(err),
+            ChatError::Place(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn emoji() {
+        let board = decode("X...O....").unwrap();
+        assert_eq!("❌⬜⬜\n⬜⭕⬜\n⬜⬜⬜", to_emoji(&board));
+        assert_eq!(
+            vec!["2️⃣", "3️⃣", "4️⃣", "6️⃣", "7️⃣", "8️⃣", "9️⃣"],
+            reactions(&board)
+        );
+        assert_eq!(Some(Player::Cross), to_move(&board));
+    }
+
+    #[test]
+    fn stateless() {
+        let mut encoded = encode(&Board::new());
+        assert_eq!(".........", encoded);
+        for mv in ["1️⃣", "4", "2️⃣", "b2"] {
+            encoded = next_state(&encoded, mv).unwrap();
+        }
+        assert_eq!("XX.OO....", encoded);
+        assert_eq!(
+            Err(ChatError::Place(PlaceError::Occupied {
+                pos: Pos::new(1).unwrap(),
+                occupied_by: Player::Cross
+            })),
+            next_state(&encoded, "1")
+        );
+        assert_eq!(Err(ChatError::Move), next_state(&encoded, "👍"));
+
+        encoded = next_state(&encoded, "3️⃣").unwrap();
+        let board = decode(&encoded).unwrap();
+        assert_eq!(Some(Player::Cross), board.winner());
+        assert_eq!(None, to_move(&board));
+        assert!(reactions(&board).is_empty());
+        assert_eq!(
+            Err(ChatError::Place(PlaceError::GameOver)),
+            next_state(&encoded, "9")
+        );
+    }
+
+    #[test]
+    fn bad_encodings() {
+        assert_eq!(Err(ChatError::Encoding), decode("X..."));
+        assert_eq!(Err(ChatError::Encoding), decode("X...Q...."));
+        // noughts never move first here
+        assert!(matches!(decode("O........"), Err(ChatError::Board(_))));
+        assert!(matches!(decode("XX......."), Err(ChatError::Board(_))));
+    }
+}
@@ -0,0 +1,214 @@
+// a C API for embedding the engine, declared in `tic_tac_toe.h` next to
+// this file. A game is an opaque pointer from `ttt_game_new`, owned by the
+// caller until they pass it to `ttt_game_free`. Cells are numbered 1-9 as
+// everywhere else, and passing NULL for a game is reported rather than
+// undefined
+
+use alloc::boxed::Box;
+
+use crate::ai::{Difficulty, DifficultyAi};
+use crate::{Cell, Game, PlaceError, Player, Pos};
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TttStatus {
+    Ok = 0,
+    NullGame = 1,
+    InvalidPos = 2,
+    Occupied = 3,
+    GameOver = 4,
+    InvalidDifficulty = 5,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TttCell {
+    Empty = 0,
+    Cross = 1,
+    Nought = 2,
+    // a position off the board, or no game
+    Invalid = -1,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TttState {
+    InProgress = 0,
+    CrossWon = 1,
+    NoughtWon = 2,
+    Draw = 3,
+    // no game
+    Invalid = -1,
+}
+
+// passed to `ttt_game_ai_move` as a plain integer, so that a value C made
+// up is an error rather than an enum Rust can't hold
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TttDifficulty {
+    Easy = 0,
+    Medium = 1,
+    Hard = 2,
+}
+
+fn difficulty(value: u32) -> Option<Difficulty> {
+    [
+        (TttDifficulty::Easy, Difficulty::Easy),
+        (TttDifficulty::Medium, Difficulty::Medium),
+        (TttDifficulty::Hard, Difficulty::Hard),
+    ]
+    .into_iter()
+    .find(|&(ffi, _)| ffi as u32 == value)
+    .map(|(_, difficulty)| difficulty)
+}
+
+// games from `ttt_game_new` only ever have crosses and noughts
+fn cell(player: Player) -> TttCell {
+    if player == Player::Cross {
+        TttCell::Cross
+    } else {
+        TttCell::Nought
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn ttt_game_new() -> Box<Game> {
+    Box::new(Game::new())
+}
+
+// NULL is ignored, like free()
+#[no_mangle]
+pub extern "C" fn ttt_game_free(game: Option<Box<Game>>) {
+    drop(game);
+}
+
+// plays `pos` for whoever is to move
+#[no_mangle]
+pub extern "C" fn ttt_game_play(game: Option<&mut Game>, pos: u32) -> TttStatus {
+    let Some(game) = game else {
+        return TttStatus::NullGame;
+    };
+    let Some(pos) = Pos::new(pos as usize) else {
+        return TttStatus::InvalidPos;
+    };
+    match game.play(pos) {
+        Ok(()) => TttStatus::Ok,
+        Err(PlaceError::Occupied { .. }) => TttStatus::Occupied,
+        // nothing else stops a plain game accepting a move
+        Err(_) => TttStatus::GameOver,
+    }
+}
+
+// lets the engine move for whoever is to move at one of the TttDifficulty
+// values, and puts the cell it chose in `pos` unless that is NULL
+#[no_mangle]
+pub extern "C" fn ttt_game_ai_move(
+    game: Option<&mut Game>,
+    difficulty: u32,
+    seed: u64,
+    pos: Option<&mut u32>,
+) -> TttStatus {
+    let Some(game) = game else {
+        return TttStatus::NullGame;
+    };
+    let Some(difficulty) = self::difficulty(difficulty) else {
+        return TttStatus::InvalidDifficulty;
+    };
+    let mut ai = DifficultyAi::new(difficulty, seed);
+    match game.play_ai(&mut ai) {
+        Ok(chosen) => {
+            if let Some(pos) = pos {
+                *pos = chosen.get() as u32;
+            }
+            TttStatus::Ok
+        }
+        // the engine only plays legal moves
+        Err(_) => TttStatus::GameOver,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn ttt_game_cell(game: Option<&Game>, pos: u32) -> TttCell {
+    let (Some(game), Some(pos)) = (game, Pos::new(pos as usize)) else {
+        return TttCell::Invalid;
+    };
+    match game.board().cells[pos.get() - 1] {
+        Cell::Occupied(player) => cell(player),
+        Cell::Vacant => TttCell::Empty,
+    }
+}
+
+// TTT_CELL_CROSS or TTT_CELL_NOUGHT, even once the game is over
+#[no_mangle]
+pub extern "C" fn ttt_game_to_move(game: Option<&Game>) -> TttCell {
+    game.map_or(TttCell::Invalid, |game| cell(game.to_move()))
+}
+
+#[no_mangle]
+pub extern "C" fn ttt_game_state(game: Option<&Game>) -> TttState {
+    let Some(game) = game else {
+        return TttState::Invalid;
+    };
+    match game.result() {
+        None => TttState::InProgress,
+        Some(result) => match result.winner {
+            Some(Player::Cross) => TttState::CrossWon,
+            Some(_) => TttState::NoughtWon,
+            None => TttState::Draw,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn game() {
+        let mut game = ttt_game_new();
+        assert_eq!(TttStatus::InvalidPos, ttt_game_play(Some(&mut game), 0));
+        for pos in [1, 4, 2, 5] {
+            assert_eq!(TttStatus::Ok, ttt_game_play(Some(&mut game), pos));
+        }
+        assert_eq!(TttStatus::Occupied, ttt_game_play(Some(&mut game), 4));
+        assert_eq!(TttCell::Cross, ttt_game_cell(Some(&game), 1));
+        assert_eq!(TttCell::Nought, ttt_game_cell(Some(&game), 4));
+        assert_eq!(TttCell::Empty, ttt_game_cell(Some(&game), 9));
+        assert_eq!(TttCell::Invalid, ttt_game_cell(Some(&game), 10));
+        assert_eq!(TttCell::Cross, ttt_game_to_move(Some(&game)));
+        assert_eq!(TttState::InProgress, ttt_game_state(Some(&game)));
+
+        let hard = TttDifficulty::Hard as u32;
+        assert_eq!(
+            TttStatus::InvalidDifficulty,
+            ttt_game_ai_move(Some(&mut game), 3, 0, None)
+        );
+        assert_eq!(TttState::InProgress, ttt_game_state(Some(&game)));
+        let mut pos = 0;
+        assert_eq!(
+            TttStatus::Ok,
+            ttt_game_ai_move(Some(&mut game), hard, 0, Some(&mut pos))
+        );
+        assert_eq!(3, pos);
+        assert_eq!(TttState::CrossWon, ttt_game_state(Some(&game)));
+        assert_eq!(TttStatus::GameOver, ttt_game_play(Some(&mut game), 9));
+        assert_eq!(
+            TttStatus::GameOver,
+            ttt_game_ai_move(Some(&mut game), hard, 0, None)
+        );
+        ttt_game_free(Some(game));
+    }
+
+    #[test]
+    fn null() {
+        assert_eq!(TttStatus::NullGame, ttt_game_play(None, 5));
+        assert_eq!(
+            TttStatus::NullGame,
+            ttt_game_ai_move(None, TttDifficulty::Easy as u32, 0, None)
+        );
+        assert_eq!(TttCell::Invalid, ttt_game_cell(None, 5));
+        assert_eq!(TttCell::Invalid, ttt_game_to_move(None));
+        assert_eq!(TttState::Invalid, ttt_game_state(None));
+        ttt_game_free(None);
+    }
+}
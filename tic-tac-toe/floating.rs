@@ -0,0 +1,232 @@
+// the limited-pieces variant: each player only has three pieces, and once
+// they're all on the board a turn moves the player's oldest piece to a
+// vacant cell instead. The board never fills up, so there are no draws, only
+// games that go on
+
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::ai::Position;
+use crate::{Board, PlaceError, Player, Pos};
+
+// how many pieces each player has
+pub const PIECES: usize = 3;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum FloatingMove {
+    Place(Pos),
+    // the oldest piece moves from `from` to `to`
+    Shift { from: Pos, to: Pos },
+}
+
+impl FloatingMove {
+    // where the piece ends up
+    pub fn to(self) -> Pos {
+        match self {
+            FloatingMove::Place(to) | FloatingMove::Shift { to, .. } => to,
+        }
+    }
+}
+
+// `5`, or `1-5` for the piece on 1 moving to 5
+impl fmt::Display for FloatingMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FloatingMove::Place(to) => write!(f, "{}", to),
+            FloatingMove::Shift { from, to } => write!(f, "{}-{}", from, to),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FloatingGame {
+    board: Board,
+    to_move: Player,
+    // each player's pieces, oldest first; indexed by `Player::index`
+    pieces: [VecDeque<Pos>; 2],
+    moves: Vec<(Player, FloatingMove)>,
+}
+
+impl FloatingGame {
+    // crosses move first
+    pub fn new() -> FloatingGame {
+        FloatingGame {
+            board: Board::new(),
+            to_move: Player::Cross,
+            pieces: [VecDeque::new(), VecDeque::new()],
+            moves: Vec::new(),
+        }
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn to_move(&self) -> Player {
+        self.to_move
+    }
+
+    pub fn moves(&self) -> &[(Player, FloatingMove)] {
+        &self.moves
+    }
+
+    // the piece `player` moves next, once all of theirs are on the board
+    pub fn oldest(&self, player: Player) -> Option<Pos> {
+        let pieces = &self.pieces[player.index()];
+        if pieces.len() < PIECES {
+            None
+        } else {
+            pieces.front().copied()
+        }
+    }
+
+    // none once the game is over
+    pub fn legal_moves(&self) -> impl Iterator<Item = FloatingMove> + '_ {
+        let from = self.oldest(self.to_move);
+        self.board.legal_moves().map(move |to| match from {
+            Some(from) => FloatingMove::Shift { from, to },
+            None => FloatingMove::Place(to),
+        })
+    }
+
+    // puts a piece on `to` for whoever is to move, taking it from their
+    // oldest cell if they've none left to place; `to` has to be vacant
+    // before the move, so the oldest piece can't stay where it is
+    pub fn play(&mut self, to: Pos) -> Result<FloatingMove, PlaceError> {
+        if self.is_over() {
+            return Err(PlaceError::GameOver);
+        }
+        let mut board = self.board;
+        board.place(to, self.to_move)?;
+
+        let player = self.to_move;
+        let mv = match self.oldest(player) {
+            Some(from) => {
+                board.remove(from).unwrap();
+                self.pieces[player.index()].pop_front();
+                FloatingMove::Shift { from, to }
+            }
+            None => FloatingMove::Place(to),
+        };
+        self.pieces[player.index()].push_back(to);
+        self.board = board;
+        self.moves.push((player, mv));
+        self.to_move = player.toggle();
+        Ok(mv)
+    }
+
+    pub fn winner(&self) -> Option<Player> {
+        self.board.winner()
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.winner().is_some()
+    }
+}
+
+impl Default for FloatingGame {
+    fn default() -> FloatingGame {
+        FloatingGame::new()
+    }
+}
+
+impl Position for FloatingGame {
+    type Move = FloatingMove;
+
+    fn to_move(&self) -> Player {
+        self.to_move
+    }
+
+    fn moves(&self) -> Vec<FloatingMove> {
+        self.legal_moves().collect()
+    }
+
+    fn play(&mut self, mv: FloatingMove) {
+        FloatingGame::play(self, mv.to()).unwrap();
+    }
+
+    fn winner(&self) -> Option<Player> {
+        FloatingGame::winner(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::alpha_beta;
+    use crate::test_util::pos;
+
+    fn game(moves: &[usize]) -> FloatingGame {
+        let mut game = FloatingGame::new();
+        for &n in moves {
+            game.play(pos(n)).unwrap();
+        }
+        game
+    }
+
+    #[test]
+    fn oldest_piece_moves() {
+        let mut game = game(&[1, 4, 2, 5, 9, 7]);
+        assert_eq!(Some(pos(1)), game.oldest(Player::Cross));
+        // crosses' own oldest piece is in the way
+        assert_eq!(
+            Err(PlaceError::Occupied {
+                pos: pos(1),
+                occupied_by: Player::Cross
+            }),
+            game.play(pos(1))
+        );
+        // 1-2-3 isn't a line any more once 1 has moved
+        let mv = game.play(pos(3)).unwrap();
+        assert_eq!(
+            FloatingMove::Shift {
+                from: pos(1),
+                to: pos(3)
+            },
+            mv
+        );
+        assert_eq!("1-3", mv.to_string());
+        assert_eq!(None, game.winner());
+        assert_eq!(3, game.board().count(Player::Cross));
+        assert_eq!(Some(pos(2)), game.oldest(Player::Cross));
+
+        // noughts take the cell crosses just left
+        game.play(pos(1)).unwrap();
+        game.play(pos(6)).unwrap();
+        assert_eq!(Some(Player::Cross), game.winner());
+        assert_eq!(Err(PlaceError::GameOver), game.play(pos(8)));
+        assert_eq!(
+            (Player::Cross, FloatingMove::Place(pos(1))),
+            game.moves()[0]
+        );
+        assert_eq!(9, game.moves().len());
+    }
+
+    #[test]
+    fn legal_moves() {
+        let game = game(&[1, 4]);
+        assert!(game
+            .legal_moves()
+            .all(|mv| matches!(mv, FloatingMove::Place(_))));
+        let game = self::game(&[1, 4, 2, 5, 9, 7]);
+        assert_eq!(3, game.legal_moves().count());
+        assert!(game
+            .legal_moves()
+            .all(|mv| matches!(mv, FloatingMove::Shift { from, .. } if from == pos(1))));
+    }
+
+    #[test]
+    fn search() {
+        // crosses complete 3-6-9 by moving the piece on 2
+        let game = game(&[1, 4, 2, 5, 9, 7, 3, 1]);
+        let (mv, _) = alpha_beta(&game, 1).unwrap();
+        assert_eq!(
+            FloatingMove::Shift {
+                from: pos(2),
+                to: pos(6)
+            },
+            mv
+        );
+    }
+}
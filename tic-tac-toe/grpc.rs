@@ -0,0 +1,317 @@
+// a gRPC service for other services to host games on, with the contract in
+// `tic_tac_toe.proto` next to this file:
+//
+//     CreateGame      optionally with the engine playing one side
+//     PlayMove        answered with the state, including any AI reply
+//     GetState
+//     StreamEvents    each move and the end of the game, as they happen
+//
+// it hosts games the way `http` does, and refuses what `http` refuses:
+// unknown games are NOT_FOUND, cells off the board INVALID_ARGUMENT and
+// moves the game won't take FAILED_PRECONDITION
+
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::string::{String, ToString};
+use std::sync::Mutex;
+
+use futures_util::Stream;
+use tokio::net::TcpListener;
+use tokio::sync::broadcast;
+use tonic::transport::server::TcpIncoming;
+use tonic::{Request, Response, Status};
+
+use crate::ai::Difficulty;
+use crate::hosting::{Games, Hosted};
+use crate::{GameEvent, Player, Pos};
+
+// the generated messages, client and server
+#[allow(clippy::all)]
+pub mod proto {
+    include!("tictactoe.rs");
+}
+
+use self::proto::tic_tac_toe_server::{TicTacToe, TicTacToeServer};
+
+// events a spectator can fall behind by before it skips ahead
+const BACKLOG: usize = 32;
+
+fn to_proto(player: Option<Player>) -> i32 {
+    let player = match player {
+        Some(Player::Cross) => proto::Player::Cross,
+        Some(Player::Nought) => proto::Player::Nought,
+        // the service only hosts two-player games
+        Some(_) | None => proto::Player::Unspecified,
+    };
+    player as i32
+}
+
+fn event(event: GameEvent) -> Option<proto::GameEvent> {
+    let event = match event {
+        GameEvent::MovePlayed { player, pos } => {
+            proto::game_event::Event::MovePlayed(proto::MovePlayed {
+                player: to_proto(Some(player)),
+                pos: pos.get() as u32,
+            })
+        }
+        GameEvent::GameWon { winner } => proto::game_event::Event::GameOver(proto::GameOver {
+            winner: to_proto(Some(winner)),
+        }),
+        GameEvent::Draw => proto::game_event::Event::GameOver(proto::GameOver {
+            winner: to_proto(None),
+        }),
+        // hosted games don't use the pie rule or clocks
+        GameEvent::Swapped | GameEvent::ClockUpdated { .. } => return None,
+    };
+    Some(proto::GameEvent { event: Some(event) })
+}
+
+// the game as every reply shows it
+fn state(hosted: &Hosted<Spectators>, id: u64) -> proto::GameState {
+    let game = &hosted.game;
+    let result = game.result();
+    proto::GameState {
+        id,
+        board: game.board().to_compact(),
+        to_move: to_proto(Some(game.to_move())),
+        moves: game
+            .transcript()
+            .moves()
+            .iter()
+            .map(|(_, pos)| pos.get() as u32)
+            .collect(),
+        ai: to_proto(hosted.ai.as_ref().map(|&(player, _)| player)),
+        winner: to_proto(result.and_then(|result| result.winner)),
+        result: result.map_or(String::new(), |result| result.to_string()),
+    }
+}
+
+// what each hosted game sends to `StreamEvents`
+type Spectators = broadcast::Sender<proto::GameEvent>;
+
+// the service's implementation; see `service`
+pub struct Server {
+    games: Mutex<Games<Spectators>>,
+}
+
+fn not_found(id: u64) -> Status {
+    Status::not_found(format!("no game {}", id))
+}
+
+#[tonic::async_trait]
+impl TicTacToe for Server {
+    async fn create_game(
+        &self,
+        request: Request<proto::CreateGameRequest>,
+    ) -> Result<Response<proto::GameState>, Status> {
+        let request = request.into_inner();
+        let side = match request.ai() {
+            proto::Player::Unspecified => None,
+            proto::Player::Cross => Some(Player::Cross),
+            proto::Player::Nought => Some(Player::Nought),
+        };
+        let difficulty = match request.difficulty() {
+            proto::Difficulty::Unspecified => Difficulty::default(),
+            proto::Difficulty::Easy => Difficulty::Easy,
+            proto::Difficulty::Medium => Difficulty::Medium,
+            proto::Difficulty::Hard => Difficulty::Hard,
+        };
+
+        let mut games = self.games.lock().unwrap();
+        let id = games.create(side, difficulty, |game| {
+            let (events, _) = broadcast::channel(BACKLOG);
+            let sender = events.clone();
+            game.subscribe(move |game_event| {
+                if let Some(event) = event(game_event) {
+                    // nobody may be watching
+                    let _ = sender.send(event);
+                }
+            });
+            events
+        });
+        Ok(Response::new(state(games.get(id).unwrap(), id)))
+    }
+
+    async fn play_move(
+        &self,
+        request: Request<proto::PlayMoveRequest>,
+    ) -> Result<Response<proto::GameState>, Status> {
+        let proto::PlayMoveRequest { id, pos } = request.into_inner();
+        let pos = Pos::new(pos as usize)
+            .ok_or_else(|| Status::invalid_argument(format!("there is no cell {}", pos)))?;
+
+        let mut games = self.games.lock().unwrap();
+        let hosted = games.get_mut(id).ok_or_else(|| not_found(id))?;
+        hosted
+            .play(pos)
+            .map_err(|err| Status::failed_precondition(err.to_string()))?;
+        Ok(Response::new(state(hosted, id)))
+    }
+
+    async fn get_state(
+        &self,
+        request: Request<proto::GetStateRequest>,
+    ) -> Result<Response<proto::GameState>, Status> {
+        let id = request.into_inner().id;
+        let games = self.games.lock().unwrap();
+        let hosted = games.get(id).ok_or_else(|| not_found(id))?;
+        Ok(Response::new(state(hosted, id)))
+    }
+
+    type StreamEventsStream = Pin<Box<dyn Stream<Item = Result<proto::GameEvent, Status>> + Send>>;
+
+    async fn stream_events(
+        &self,
+        request: Request<proto::StreamEventsRequest>,
+    ) -> Result<Response<Self::StreamEventsStream>, Status> {
+        let id = request.into_inner().id;
+        let games = self.games.lock().unwrap();
+        let hosted = games.get(id).ok_or_else(|| not_found(id))?;
+        // a finished game has nothing left to stream
+        let receiver = match hosted.game.result() {
+            None => Some(hosted.extra.subscribe()),
+            Some(_) => None,
+        };
+        let stream = futures_util::stream::unfold(receiver, |receiver| async move {
+            let mut receiver = receiver?;
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => {
+                        let over =
+                            matches!(event.event, Some(proto::game_event::Event::GameOver(_)));
+                        let receiver = if over { None } else { Some(receiver) };
+                        return Some((Ok(event), receiver));
+                    }
+                    // a slow spectator misses some moves rather than
+                    // holding up the game
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        });
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+// the service over a fresh set of games, for adding to a tonic server
+// alongside others; `seed` decides how the engines play
+pub fn service(seed: u64) -> TicTacToeServer<Server> {
+    TicTacToeServer::new(Server {
+        games: Mutex::new(Games::new(seed)),
+    })
+}
+
+// serves only this service on `addr` until the server fails
+pub async fn run(addr: SocketAddr, seed: u64) -> Result<(), tonic::transport::Error> {
+    tonic::transport::Server::builder()
+        .add_service(service(seed))
+        .serve(addr)
+        .await
+}
+
+// like `run`, on a listener that's already bound, e.g. to port 0
+pub async fn serve(listener: TcpListener, seed: u64) -> Result<(), tonic::transport::Error> {
+    tonic::transport::Server::builder()
+        .add_service(service(seed))
+        .serve_with_incoming(TcpIncoming::from(listener))
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+    use proto::tic_tac_toe_client::TicTacToeClient;
+    use std::vec::Vec;
+    use tonic::transport::Channel;
+    use tonic::Code;
+
+    async fn client() -> TicTacToeClient<Channel> {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener, 0));
+        TicTacToeClient::connect(format!("http://{}", addr))
+            .await
+            .unwrap()
+    }
+
+    fn play(id: u64, pos: u32) -> proto::PlayMoveRequest {
+        proto::PlayMoveRequest { id, pos }
+    }
+
+    #[tokio::test]
+    async fn against_ai() {
+        let mut client = client().await;
+        let created = client
+            .create_game(proto::CreateGameRequest {
+                ai: proto::Player::Nought as i32,
+                difficulty: proto::Difficulty::Hard as i32,
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(1, created.id);
+        assert_eq!(".........", created.board);
+        assert_eq!(proto::Player::Cross, created.to_move());
+        assert_eq!(proto::Player::Nought, created.ai());
+
+        let state = client.play_move(play(1, 5)).await.unwrap().into_inner();
+        // the engine has already answered
+        assert_eq!(2, state.moves.len());
+        assert_eq!(proto::Player::Cross, state.to_move());
+
+        let err = client.play_move(play(1, 5)).await.unwrap_err();
+        assert_eq!(Code::FailedPrecondition, err.code());
+        let err = client.play_move(play(1, 10)).await.unwrap_err();
+        assert_eq!(Code::InvalidArgument, err.code());
+        let err = client
+            .get_state(proto::GetStateRequest { id: 7 })
+            .await
+            .unwrap_err();
+        assert_eq!(Code::NotFound, err.code());
+        let got = client
+            .get_state(proto::GetStateRequest { id: 1 })
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(state, got);
+    }
+
+    #[tokio::test]
+    async fn events() {
+        let mut client = client().await;
+        let id = client
+            .create_game(proto::CreateGameRequest::default())
+            .await
+            .unwrap()
+            .into_inner()
+            .id;
+        let events = client
+            .stream_events(proto::StreamEventsRequest { id })
+            .await
+            .unwrap()
+            .into_inner();
+        for pos in [1, 4, 2, 5] {
+            client.play_move(play(id, pos)).await.unwrap();
+        }
+        let state = client.play_move(play(id, 3)).await.unwrap().into_inner();
+        assert_eq!(proto::Player::Cross, state.winner());
+        assert_eq!("X wins by three in a row", state.result);
+
+        let events: Vec<proto::GameEvent> = events.map(Result::unwrap).collect().await;
+        assert_eq!(6, events.len());
+        assert_eq!(
+            Some(proto::game_event::Event::MovePlayed(proto::MovePlayed {
+                player: proto::Player::Nought as i32,
+                pos: 4
+            })),
+            events[1].event
+        );
+        assert_eq!(
+            Some(proto::game_event::Event::GameOver(proto::GameOver {
+                winner: proto::Player::Cross as i32
+            })),
+            events[5].event
+        );
+    }
+}
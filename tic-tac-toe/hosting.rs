@@ -0,0 +1,98 @@
+// the games a server hosts for remote players, with the engine taking a
+// side if asked; `http` and `grpc` put their own protocols over the same
+// games
+
+use std::collections::HashMap;
+use std::string::ToString;
+
+use crate::ai::{Difficulty, DifficultyAi};
+use crate::{Game, GameConfig, PlaceError, Player, PlayerKind, Pos, Seat};
+
+pub(crate) struct Hosted<T> {
+    pub(crate) game: Game,
+    // the side the engine plays, if any
+    pub(crate) ai: Option<(Player, DifficultyAi)>,
+    // whatever else the server keeps per game, e.g. `grpc`'s spectators
+    #[cfg_attr(not(feature = "grpc"), allow(dead_code))]
+    pub(crate) extra: T,
+}
+
+impl<T> Hosted<T> {
+    // lets the engine move if it's its turn
+    fn reply(&mut self) {
+        if let Some((player, ai)) = &mut self.ai {
+            if self.game.result().is_none() && self.game.to_move() == *player {
+                self.game.play_ai(ai).unwrap();
+            }
+        }
+    }
+
+    // the remote player's move, and the engine's answer to it
+    pub(crate) fn play(&mut self, pos: Pos) -> Result<(), PlaceError> {
+        if let Some((player, _)) = self.ai {
+            if self.game.result().is_none() && self.game.to_move() == player {
+                return Err(PlaceError::NotYourTurn {
+                    player: player.toggle(),
+                });
+            }
+        }
+        self.game.play(pos)?;
+        self.reply();
+        Ok(())
+    }
+}
+
+// every game the server is hosting
+pub(crate) struct Games<T> {
+    games: HashMap<u64, Hosted<T>>,
+    next_id: u64,
+    // combined with each game's id to seed its engine
+    seed: u64,
+}
+
+impl<T> Games<T> {
+    // `seed` decides how the engines play
+    pub(crate) fn new(seed: u64) -> Games<T> {
+        Games {
+            games: HashMap::new(),
+            next_id: 1,
+            seed,
+        }
+    }
+
+    // starts a game with the engine playing `side`, if there is one, and
+    // returns its id; `setup` sees the game before anyone moves, and the
+    // engine opens if it plays crosses
+    pub(crate) fn create(
+        &mut self,
+        side: Option<Player>,
+        difficulty: Difficulty,
+        setup: impl FnOnce(&mut Game) -> T,
+    ) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        let mut config = GameConfig::default();
+        if let Some(side) = side {
+            let seat = Seat {
+                name: "Computer".to_string(),
+                kind: PlayerKind::Ai,
+            };
+            *config.seat_mut(side) = seat;
+        }
+        let mut game = Game::with_config(config);
+        let extra = setup(&mut game);
+        let ai = side.map(|side| (side, DifficultyAi::new(difficulty, self.seed ^ id)));
+        let mut hosted = Hosted { game, ai, extra };
+        hosted.reply();
+        self.games.insert(id, hosted);
+        id
+    }
+
+    pub(crate) fn get(&self, id: u64) -> Option<&Hosted<T>> {
+        self.games.get(&id)
+    }
+
+    pub(crate) fn get_mut(&mut self, id: u64) -> Option<&mut Hosted<T>> {
+        self.games.get_mut(&id)
+    }
+}
@@ -0,0 +1,249 @@
+// an HTTP API for driving games from a web page:
+//
+//     POST /games              {"ai":"O","difficulty":"hard"}, both optional
+//     POST /games/{id}/moves   {"pos":5}, or "b2"
+//     GET  /games/{id}
+//
+// every one answers with the game as JSON, e.g. {"id":1,"board":"X...O....",
+// "to_move":"X","moves":[1,5],"ai":"O","winner":null,"result":null}. When a
+// game has an AI opponent it replies as soon as it's its turn, so the
+// response to a move already includes the answer to it. Anything refused
+// comes back as {"error":"..."} with a 4xx status. Written against axum 0.8,
+// whose paths capture with `{id}` rather than 0.7's `:id`
+
+use std::io;
+use std::string::{String, ToString};
+use std::sync::{Arc, Mutex};
+use std::vec::Vec;
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde_json::{json, Value};
+use tokio::net::{TcpListener, ToSocketAddrs};
+
+use crate::ai::Difficulty;
+use crate::hosting::{Games, Hosted};
+use crate::{Player, Pos};
+
+// the game as every response shows it
+fn json(hosted: &Hosted<()>, id: u64) -> Value {
+    let game = &hosted.game;
+    let moves: Vec<usize> = game
+        .transcript()
+        .moves()
+        .iter()
+        .map(|(_, pos)| pos.get())
+        .collect();
+    let result = game.result();
+    json!({
+        "id": id,
+        "board": game.board().to_compact(),
+        "to_move": game.to_move().to_string(),
+        "moves": moves,
+        "ai": hosted.ai.as_ref().map(|(player, _)| player.to_string()),
+        "winner": result.and_then(|result| result.winner).map(|winner| winner.to_string()),
+        "result": result.map(|result| result.to_string()),
+    })
+}
+
+type Shared = Arc<Mutex<Games<()>>>;
+
+type Response = (StatusCode, Json<Value>);
+
+fn error(status: StatusCode, message: impl ToString) -> Response {
+    (status, Json(json!({ "error": message.to_string() })))
+}
+
+// a request body as a JSON object, with an empty body counting as {}
+fn object(body: &str) -> Result<serde_json::Map<String, Value>, Response> {
+    if body.trim().is_empty() {
+        return Ok(serde_json::Map::new());
+    }
+    match serde_json::from_str(body) {
+        Ok(Value::Object(object)) => Ok(object),
+        Ok(_) => Err(error(StatusCode::BAD_REQUEST, "expected a JSON object")),
+        Err(err) => Err(error(StatusCode::BAD_REQUEST, err)),
+    }
+}
+
+// the server's routes, over a fresh set of games; `seed` decides how the
+// engines play
+pub fn router(seed: u64) -> Router {
+    let games: Games<()> = Games::new(seed);
+    Router::new()
+        .route("/games", post(create))
+        .route("/games/{id}", get(show))
+        .route("/games/{id}/moves", post(play))
+        .with_state(Arc::new(Mutex::new(games)))
+}
+
+// listens on `addr` until the listener fails
+pub async fn run(addr: impl ToSocketAddrs, seed: u64) -> io::Result<()> {
+    serve(TcpListener::bind(addr).await?, seed).await
+}
+
+// like `run`, on a listener that's already bound, e.g. to port 0
+pub async fn serve(listener: TcpListener, seed: u64) -> io::Result<()> {
+    axum::serve(listener, router(seed)).await
+}
+
+async fn create(State(games): State<Shared>, body: String) -> Response {
+    let request = match object(&body) {
+        Ok(request) => request,
+        Err(response) => return response,
+    };
+    let side = match request.get("ai") {
+        None | Some(Value::Null) => None,
+        Some(Value::String(side)) => match side.to_uppercase().parse::<Player>() {
+            Ok(player) if player.number() < 2 => Some(player),
+            Ok(_) => return error(StatusCode::BAD_REQUEST, "\"ai\" should be \"X\" or \"O\""),
+            Err(err) => return error(StatusCode::BAD_REQUEST, format!("{}: {}", side, err)),
+        },
+        Some(_) => return error(StatusCode::BAD_REQUEST, "\"ai\" should be \"X\" or \"O\""),
+    };
+    let difficulty = match request.get("difficulty").and_then(Value::as_str) {
+        None => Difficulty::default(),
+        Some(name) => match name.parse() {
+            Ok(difficulty) => difficulty,
+            Err(err) => return error(StatusCode::BAD_REQUEST, format!("{}: {}", name, err)),
+        },
+    };
+
+    let mut games = games.lock().unwrap();
+    let id = games.create(side, difficulty, |_| ());
+    (StatusCode::CREATED, Json(json(games.get(id).unwrap(), id)))
+}
+
+async fn show(State(games): State<Shared>, Path(id): Path<u64>) -> Response {
+    let games = games.lock().unwrap();
+    match games.get(id) {
+        Some(hosted) => (StatusCode::OK, Json(json(hosted, id))),
+        None => error(StatusCode::NOT_FOUND, format!("no game {}", id)),
+    }
+}
+
+async fn play(State(games): State<Shared>, Path(id): Path<u64>, body: String) -> Response {
+    let request = match object(&body) {
+        Ok(request) => request,
+        Err(response) => return response,
+    };
+    let pos = match request.get("pos") {
+        Some(Value::Number(n)) => n.to_string().parse::<Pos>(),
+        Some(Value::String(pos)) => pos.parse::<Pos>(),
+        _ => return error(StatusCode::BAD_REQUEST, "expected a \"pos\""),
+    };
+    let pos = match pos {
+        Ok(pos) => pos,
+        Err(err) => return error(StatusCode::BAD_REQUEST, err),
+    };
+
+    let mut games = games.lock().unwrap();
+    let Some(hosted) = games.get_mut(id) else {
+        return error(StatusCode::NOT_FOUND, format!("no game {}", id));
+    };
+    if let Err(err) = hosted.play(pos) {
+        return error(StatusCode::CONFLICT, err);
+    }
+    (StatusCode::OK, Json(json(hosted, id)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    // a server on a free local port
+    async fn server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        tokio::spawn(serve(listener, 0));
+        addr
+    }
+
+    // sends one request and returns the status and the body as JSON
+    async fn request(addr: &str, method: &str, path: &str, body: &str) -> (u16, Value) {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let request = format!(
+            "{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{}",
+            method,
+            path,
+            addr,
+            body.len(),
+            body
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        let status = response[9..12].parse().unwrap();
+        let body = &response[response.find("\r\n\r\n").unwrap() + 4..];
+        (status, serde_json::from_str(body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn two_players() {
+        let addr = server().await;
+        let (status, game) = request(&addr, "POST", "/games", "").await;
+        assert_eq!(201, status);
+        assert_eq!(
+            json!({
+                "id": 1,
+                "board": ".........",
+                "to_move": "X",
+                "moves": [],
+                "ai": null,
+                "winner": null,
+                "result": null,
+            }),
+            game
+        );
+
+        for pos in ["1", "4", r#""b3""#, "5"] {
+            let body = format!(r#"{{"pos":{}}}"#, pos);
+            let (status, _) = request(&addr, "POST", "/games/1/moves", &body).await;
+            assert_eq!(200, status);
+        }
+        let (status, game) = request(&addr, "POST", "/games/1/moves", r#"{"pos":3}"#).await;
+        assert_eq!(200, status);
+        assert_eq!(json!([1, 4, 2, 5, 3]), game["moves"]);
+        assert_eq!("X", game["winner"]);
+        assert_eq!(game, request(&addr, "GET", "/games/1", "").await.1);
+
+        let (status, body) = request(&addr, "POST", "/games/1/moves", r#"{"pos":6}"#).await;
+        assert_eq!(409, status);
+        assert_eq!(json!({"error": "the game is already over"}), body);
+        assert_eq!(404, request(&addr, "GET", "/games/2", "").await.0);
+        let (status, _) = request(&addr, "POST", "/games/1/moves", r#"{"pos":10}"#).await;
+        assert_eq!(400, status);
+        assert_eq!(400, request(&addr, "POST", "/games", "[]").await.0);
+    }
+
+    #[tokio::test]
+    async fn against_the_engine() {
+        let addr = server().await;
+        let body = r#"{"ai":"X","difficulty":"hard"}"#;
+        let (status, game) = request(&addr, "POST", "/games", body).await;
+        assert_eq!(201, status);
+        assert_eq!("X", game["ai"]);
+        // the engine has already opened
+        assert_eq!(1, game["moves"].as_array().unwrap().len());
+        assert_eq!("O", game["to_move"]);
+
+        let free = game["board"].as_str().unwrap().find('.').unwrap() + 1;
+        let body = format!(r#"{{"pos":{}}}"#, free);
+        let (status, game) = request(&addr, "POST", "/games/1/moves", &body).await;
+        assert_eq!(200, status);
+        assert_eq!(3, game["moves"].as_array().unwrap().len());
+        assert_eq!("O", game["to_move"]);
+
+        let body = r#"{"difficulty":"impossible"}"#;
+        let (status, body) = request(&addr, "POST", "/games", body).await;
+        assert_eq!(400, status);
+        assert_eq!(
+            json!({"error": "impossible: unknown difficulty, expected easy, medium or hard"}),
+            body
+        );
+    }
+}
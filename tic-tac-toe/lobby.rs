@@ -0,0 +1,550 @@
+// a matchmaking server for `net` games, so players needn't agree on who
+// hosts. Everyone connects to the lobby under a name, and it pairs them up
+// and relays their game. Messages are framed as in `net`:
+//
+//     hello <name>          client -> lobby, first of all; answered welcome
+//     list                  answered `challenges <name>...`
+//     challenge             opens a challenge; answered waiting
+//     accept <name>         takes up <name>'s challenge
+//     rematch               after a game, asks for another, sides swapped
+//
+//     paired <name>         lobby -> both players, then `net`'s start
+//     refused <reason>      lobby -> client, to anything it can't do
+//
+// between `start` and the end of the game, each player's messages are
+// `net`'s, passed on to the other, so the game itself is a plain
+// `Connection`. The challenger plays crosses. A player who disconnects
+// mid-game resigns it
+
+use std::collections::HashMap;
+use std::io;
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::string::{String, ToString};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::vec::Vec;
+
+use crate::net::{
+    read_frame, read_message, write_frame, write_message, Connection, Message, NetError,
+};
+use crate::{Game, Player};
+
+// longer names are refused
+pub const MAX_NAME: usize = 20;
+
+type ClientId = u64;
+
+enum Event {
+    Joined(ClientId, TcpStream),
+    Frame(ClientId, String),
+    Left(ClientId),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum State {
+    // hasn't said hello yet
+    Anonymous,
+    Idle,
+    Challenging,
+    Playing {
+        game: u64,
+    },
+    // the game with `opponent` is over, and `rematch` says whether this
+    // player has asked for another
+    Finished {
+        opponent: ClientId,
+        side: Player,
+        rematch: bool,
+    },
+}
+
+struct Client {
+    stream: TcpStream,
+    name: String,
+    state: State,
+}
+
+struct Hosted {
+    game: Game,
+    // indexed by `Player::index`
+    players: [ClientId; 2],
+}
+
+#[derive(Default)]
+struct Lobby {
+    clients: HashMap<ClientId, Client>,
+    games: HashMap<u64, Hosted>,
+    next_game: u64,
+}
+
+impl Lobby {
+    // a client that's gone will be noticed by its reader, so a failed write
+    // can be ignored here
+    fn send(&mut self, id: ClientId, text: &str) {
+        if let Some(client) = self.clients.get_mut(&id) {
+            let _ = write_frame(&mut client.stream, text);
+        }
+    }
+
+    fn refuse(&mut self, id: ClientId, reason: &str) {
+        self.send(id, &format!("refused {}", reason));
+    }
+
+    fn set_state(&mut self, id: ClientId, state: State) {
+        if let Some(client) = self.clients.get_mut(&id) {
+            client.state = state;
+        }
+    }
+
+    fn state(&self, id: ClientId) -> Option<State> {
+        self.clients.get(&id).map(|client| client.state)
+    }
+
+    fn handle(&mut self, event: Event) {
+        match event {
+            Event::Joined(id, stream) => {
+                let client = Client {
+                    stream,
+                    name: String::new(),
+                    state: State::Anonymous,
+                };
+                self.clients.insert(id, client);
+            }
+            Event::Frame(id, text) => match self.state(id) {
+                Some(State::Playing { game }) => self.relay(id, game, &text),
+                Some(_) => self.command(id, &text),
+                None => {}
+            },
+            Event::Left(id) => {
+                match self.state(id) {
+                    Some(State::Playing { game }) => self.abandon(id, game),
+                    Some(State::Finished { .. }) => self.forget_rematch(id),
+                    _ => {}
+                }
+                self.clients.remove(&id);
+            }
+        }
+    }
+
+    fn command(&mut self, id: ClientId, text: &str) {
+        let words: Vec<&str> = text.split(' ').collect();
+        if self.state(id) == Some(State::Anonymous) {
+            match words.as_slice() {
+                ["hello", name] => self.hello(id, name),
+                _ => self.refuse(id, "say hello first"),
+            }
+            return;
+        }
+        match words.as_slice() {
+            ["hello", _] => self.refuse(id, "you already have a name"),
+            ["list"] => {
+                let mut names: Vec<&str> = self
+                    .clients
+                    .iter()
+                    .filter(|&(&other, client)| other != id && client.state == State::Challenging)
+                    .map(|(_, client)| client.name.as_str())
+                    .collect();
+                names.sort();
+                let mut reply = String::from("challenges");
+                for name in names {
+                    reply.push(' ');
+                    reply.push_str(name);
+                }
+                self.send(id, &reply);
+            }
+            ["challenge"] => {
+                self.forget_rematch(id);
+                self.set_state(id, State::Challenging);
+                self.send(id, "waiting");
+            }
+            ["accept", name] => {
+                let challenger = self.clients.iter().find(|&(&other, client)| {
+                    other != id && client.name == *name && client.state == State::Challenging
+                });
+                match challenger {
+                    Some((&challenger, _)) => {
+                        self.forget_rematch(id);
+                        self.start(challenger, id);
+                    }
+                    None => self.refuse(id, &format!("no challenge from {}", name)),
+                }
+            }
+            ["rematch"] => self.rematch(id),
+            _ => self.refuse(id, &format!("unknown command {:?}", text)),
+        }
+    }
+
+    fn hello(&mut self, id: ClientId, name: &str) {
+        if name.is_empty() || name.len() > MAX_NAME {
+            return self.refuse(id, &format!("names are 1 to {} bytes", MAX_NAME));
+        }
+        if self.clients.values().any(|client| client.name == name) {
+            return self.refuse(id, &format!("{} is taken", name));
+        }
+        if let Some(client) = self.clients.get_mut(&id) {
+            client.name = name.to_string();
+            client.state = State::Idle;
+        }
+        self.send(id, "welcome");
+    }
+
+    fn start(&mut self, cross: ClientId, nought: ClientId) {
+        let game = self.next_game;
+        self.next_game += 1;
+        let players = [(cross, Player::Cross), (nought, Player::Nought)];
+        for (id, side) in players {
+            let opponent = if id == cross { nought } else { cross };
+            let name = self.clients[&opponent].name.clone();
+            self.send(id, &format!("paired {}", name));
+            if let Some(client) = self.clients.get_mut(&id) {
+                let _ = write_message(&mut client.stream, &Message::Start { you: side });
+                client.state = State::Playing { game };
+            }
+        }
+        let mut ids = [0; 2];
+        for (id, side) in players {
+            ids[side.index()] = id;
+        }
+        let hosted = Hosted {
+            game: Game::new(),
+            players: ids,
+        };
+        self.games.insert(game, hosted);
+    }
+
+    // passes a player's message on, keeping score so the lobby knows when
+    // the game is over
+    fn relay(&mut self, id: ClientId, game: u64, text: &str) {
+        let Some(hosted) = self.games.get_mut(&game) else {
+            return;
+        };
+        let side = if hosted.players[Player::Cross.index()] == id {
+            Player::Cross
+        } else {
+            Player::Nought
+        };
+        let opponent = hosted.players[side.toggle().index()];
+        let over = match Message::parse(text) {
+            Some(Message::Move { pos, .. }) => {
+                // the opponent's `Connection` reports a move that's refused
+                let _ = hosted.game.play_as(side, pos);
+                hosted.game.result().is_some()
+            }
+            Some(Message::Resign) => {
+                let _ = hosted.game.resign(side);
+                true
+            }
+            // the game can't go on
+            Some(Message::Desync { .. }) => true,
+            _ => return self.refuse(id, "you're in a game"),
+        };
+        self.send(opponent, text);
+        if over {
+            self.games.remove(&game);
+            for (id, opponent, side) in [(id, opponent, side), (opponent, id, side.toggle())] {
+                let rematch = false;
+                self.set_state(
+                    id,
+                    State::Finished {
+                        opponent,
+                        side,
+                        rematch,
+                    },
+                );
+            }
+        }
+    }
+
+    // `id` left mid-game, which resigns it
+    fn abandon(&mut self, id: ClientId, game: u64) {
+        let Some(hosted) = self.games.remove(&game) else {
+            return;
+        };
+        let opponent = if hosted.players[0] == id {
+            hosted.players[1]
+        } else {
+            hosted.players[0]
+        };
+        if let Some(client) = self.clients.get_mut(&opponent) {
+            let _ = write_message(&mut client.stream, &Message::Resign);
+            client.state = State::Idle;
+        }
+    }
+
+    fn rematch(&mut self, id: ClientId) {
+        let Some(State::Finished { opponent, side, .. }) = self.state(id) else {
+            return self.refuse(id, "no game to rematch");
+        };
+        match self.state(opponent) {
+            Some(State::Finished {
+                opponent: other,
+                rematch: true,
+                ..
+            }) if other == id => {
+                // sides swapped
+                if side == Player::Cross {
+                    self.start(opponent, id)
+                } else {
+                    self.start(id, opponent)
+                }
+            }
+            Some(State::Finished {
+                opponent: other, ..
+            }) if other == id => {
+                let rematch = true;
+                self.set_state(
+                    id,
+                    State::Finished {
+                        opponent,
+                        side,
+                        rematch,
+                    },
+                );
+            }
+            _ => {
+                self.set_state(id, State::Idle);
+                self.refuse(id, "no rematch");
+            }
+        }
+    }
+
+    // `id` has moved on from its last game, so its opponent won't get a
+    // rematch
+    fn forget_rematch(&mut self, id: ClientId) {
+        let Some(State::Finished { opponent, .. }) = self.state(id) else {
+            return;
+        };
+        self.set_state(id, State::Idle);
+        if let Some(State::Finished {
+            opponent: other,
+            rematch,
+            ..
+        }) = self.state(opponent)
+        {
+            if other == id {
+                self.set_state(opponent, State::Idle);
+                if rematch {
+                    self.refuse(opponent, "no rematch");
+                }
+            }
+        }
+    }
+}
+
+// listens on `addr` until the listener fails
+pub fn run(addr: impl ToSocketAddrs) -> io::Result<()> {
+    serve(TcpListener::bind(addr)?)
+}
+
+// like `run`, on a listener that's already bound, e.g. to port 0
+pub fn serve(listener: TcpListener) -> io::Result<()> {
+    let (events, receiver) = mpsc::channel();
+    thread::spawn(move || lobby(receiver));
+    for (id, stream) in (0..).zip(listener.incoming()) {
+        let stream = stream?;
+        let reader = stream.try_clone()?;
+        if events.send(Event::Joined(id, stream)).is_err() {
+            break;
+        }
+        let events = events.clone();
+        thread::spawn(move || read_client(id, reader, events));
+    }
+    Ok(())
+}
+
+fn lobby(events: Receiver<Event>) {
+    let mut lobby = Lobby::default();
+    for event in events {
+        lobby.handle(event);
+    }
+}
+
+// reading blocks, so each client has a thread passing its messages on
+fn read_client(id: ClientId, mut stream: TcpStream, events: Sender<Event>) {
+    while let Ok(text) = read_frame(&mut stream) {
+        if events.send(Event::Frame(id, text)).is_err() {
+            return;
+        }
+    }
+    let _ = events.send(Event::Left(id));
+}
+
+// a game the lobby has set up
+#[derive(Debug)]
+pub struct Pairing {
+    pub opponent: String,
+    pub connection: Connection,
+}
+
+// a player's end of the lobby. Between games it talks to the lobby, and
+// during one the `Pairing`'s connection does, over the same socket
+pub struct LobbyClient {
+    stream: TcpStream,
+    name: String,
+}
+
+impl LobbyClient {
+    pub fn connect(addr: impl ToSocketAddrs, name: &str) -> Result<LobbyClient, NetError> {
+        let mut client = LobbyClient {
+            stream: TcpStream::connect(addr)?,
+            name: name.to_string(),
+        };
+        client.expect(&format!("hello {}", name), "welcome")?;
+        Ok(client)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // the names of everyone with an open challenge
+    pub fn challenges(&mut self) -> Result<Vec<String>, NetError> {
+        write_frame(&mut self.stream, "list")?;
+        let reply = self.reply()?;
+        let mut words = reply.split(' ');
+        if words.next() != Some("challenges") {
+            return Err(NetError::Protocol(format!("unexpected reply {:?}", reply)));
+        }
+        Ok(words.map(ToString::to_string).collect())
+    }
+
+    // opens a challenge and waits for someone to accept it
+    pub fn challenge(&mut self) -> Result<Pairing, NetError> {
+        self.expect("challenge", "waiting")?;
+        self.paired()
+    }
+
+    pub fn accept(&mut self, name: &str) -> Result<Pairing, NetError> {
+        write_frame(&mut self.stream, &format!("accept {}", name))?;
+        self.paired()
+    }
+
+    // once a game is over, waits for the opponent to ask for another too
+    pub fn rematch(&mut self) -> Result<Pairing, NetError> {
+        write_frame(&mut self.stream, "rematch")?;
+        self.paired()
+    }
+
+    fn reply(&mut self) -> Result<String, NetError> {
+        let reply = read_frame(&mut self.stream)?;
+        match reply.strip_prefix("refused ") {
+            Some(reason) => Err(NetError::Refused(reason.to_string())),
+            None => Ok(reply),
+        }
+    }
+
+    fn expect(&mut self, command: &str, reply: &str) -> Result<(), NetError> {
+        write_frame(&mut self.stream, command)?;
+        match self.reply()? {
+            text if text == reply => Ok(()),
+            text => Err(NetError::Protocol(format!("unexpected reply {:?}", text))),
+        }
+    }
+
+    fn paired(&mut self) -> Result<Pairing, NetError> {
+        let reply = self.reply()?;
+        let Some(opponent) = reply.strip_prefix("paired ") else {
+            return Err(NetError::Protocol(format!("unexpected reply {:?}", reply)));
+        };
+        match read_message(&mut self.stream)? {
+            Message::Start { you } => Ok(Pairing {
+                opponent: opponent.to_string(),
+                connection: Connection::new(self.stream.try_clone()?, you),
+            }),
+            message => Err(NetError::Unexpected(message)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::Remote;
+    use crate::test_util::pos;
+    use std::net::SocketAddr;
+
+    fn lobby() -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || serve(listener));
+        addr
+    }
+
+    // alice challenges and bob accepts
+    fn pair(addr: SocketAddr) -> (LobbyClient, Pairing, LobbyClient, Pairing) {
+        let mut alice = LobbyClient::connect(addr, "alice").unwrap();
+        let alice = thread::spawn(move || {
+            let pairing = alice.challenge().unwrap();
+            (alice, pairing)
+        });
+        let mut bob = LobbyClient::connect(addr, "bob").unwrap();
+        while bob.challenges().unwrap().is_empty() {
+            thread::yield_now();
+        }
+        assert_eq!(vec!["alice".to_string()], bob.challenges().unwrap());
+        let bobs = bob.accept("alice").unwrap();
+        let (alice, alices) = alice.join().unwrap();
+        (alice, alices, bob, bobs)
+    }
+
+    #[test]
+    fn match_and_rematch() {
+        let addr = lobby();
+        let (mut alice, mut alices, mut bob, mut bobs) = pair(addr);
+        assert_eq!("bob", alices.opponent);
+        assert_eq!("alice", bobs.opponent);
+        assert_eq!(Player::Cross, alices.connection.me());
+        assert_eq!(Player::Nought, bobs.connection.me());
+
+        for (x, o) in [(1, 4), (2, 5)] {
+            alices.connection.send_move(pos(x)).unwrap();
+            assert_eq!(Remote::Moved(pos(x)), bobs.connection.receive().unwrap());
+            bobs.connection.send_move(pos(o)).unwrap();
+            assert_eq!(Remote::Moved(pos(o)), alices.connection.receive().unwrap());
+        }
+        alices.connection.send_move(pos(3)).unwrap();
+        bobs.connection.receive().unwrap();
+        let winner = bobs.connection.game().result().unwrap().winner;
+        assert_eq!(Some(Player::Cross), winner);
+
+        let alice = thread::spawn(move || alice.rematch().unwrap());
+        let bobs = bob.rematch().unwrap();
+        let alices = alice.join().unwrap();
+        assert_eq!(Player::Nought, alices.connection.me());
+        assert_eq!(Player::Cross, bobs.connection.me());
+    }
+
+    #[test]
+    fn refusals() {
+        let addr = lobby();
+        let mut carol = LobbyClient::connect(addr, "carol").unwrap();
+        assert!(matches!(
+            LobbyClient::connect(addr, "carol"),
+            Err(NetError::Refused(_))
+        ));
+        assert!(matches!(
+            LobbyClient::connect(addr, "a name much too long for the lobby"),
+            Err(NetError::Refused(_))
+        ));
+        let Err(NetError::Refused(reason)) = carol.accept("dave") else {
+            panic!("expected a refusal");
+        };
+        assert_eq!("no challenge from dave", reason);
+        assert!(matches!(carol.rematch(), Err(NetError::Refused(_))));
+        assert!(carol.challenges().unwrap().is_empty());
+    }
+
+    #[test]
+    fn leaving_resigns() {
+        let addr = lobby();
+        let (alice, mut alices, mut bob, bobs) = pair(addr);
+        alices.connection.send_move(pos(5)).unwrap();
+        drop((alice, alices, bobs.connection));
+        // bob's connection is gone, so he hears through the lobby
+        let mut connection = Connection::new(bob.stream.try_clone().unwrap(), Player::Nought);
+        assert_eq!(Remote::Moved(pos(5)), connection.receive().unwrap());
+        assert_eq!(Remote::Resigned, connection.receive().unwrap());
+        // and is back in the lobby
+        assert!(bob.challenges().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,347 @@
+// Three Men's Morris and Achi: each player places a few pieces and, once
+// they're all down, slides one of them along a line of the board to a
+// neighbouring empty point each turn. Three in a row wins in either phase,
+// and a player who can't slide anything loses
+
+use alloc::vec::Vec;
+use core::error;
+use core::fmt;
+
+use crate::ai::Position;
+use crate::{Board, Cell, PlaceError, Player, Pos};
+
+// whether `from` and `to` are joined by a line of the board: next to each
+// other along a row or column, or along one of the diagonals through the
+// centre
+pub fn adjacent(from: Pos, to: Pos) -> bool {
+    let rows = from.row().abs_diff(to.row());
+    let columns = from.column().abs_diff(to.column());
+    let centre = |pos: Pos| pos.row() == Board::WIDTH / 2 && pos.column() == Board::WIDTH / 2;
+    match (rows, columns) {
+        (0, 1) | (1, 0) => true,
+        (1, 1) => centre(from) || centre(to),
+        _ => false,
+    }
+}
+
+// the points joined to `pos`, in cell order
+pub fn neighbours(pos: Pos) -> impl Iterator<Item = Pos> {
+    Pos::all().filter(move |&other| adjacent(pos, other))
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct MorrisRules {
+    // how many pieces each player places before sliding
+    pub pieces: usize,
+}
+
+impl MorrisRules {
+    pub fn three_mens_morris() -> MorrisRules {
+        MorrisRules { pieces: 3 }
+    }
+
+    // the board ends up with a single empty point
+    pub fn achi() -> MorrisRules {
+        MorrisRules { pieces: 4 }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Phase {
+    Placing,
+    Moving,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum MorrisMove {
+    Place(Pos),
+    Slide { from: Pos, to: Pos },
+}
+
+// `5`, or `1-5` for the piece on 1 sliding to 5
+impl fmt::Display for MorrisMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MorrisMove::Place(to) => write!(f, "{}", to),
+            MorrisMove::Slide { from, to } => write!(f, "{}-{}", from, to),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MorrisGame {
+    rules: MorrisRules,
+    board: Board,
+    to_move: Player,
+    moves: Vec<(Player, MorrisMove)>,
+}
+
+impl MorrisGame {
+    // crosses move first
+    pub fn new(rules: MorrisRules) -> Result<MorrisGame, InvalidPiecesError> {
+        // both sides' pieces have to fit with room left to slide into
+        if rules.pieces == 0 || 2 * rules.pieces >= Board::SIZE {
+            return Err(InvalidPiecesError {
+                pieces: rules.pieces,
+            });
+        }
+        Ok(MorrisGame {
+            rules,
+            board: Board::new(),
+            to_move: Player::Cross,
+            moves: Vec::new(),
+        })
+    }
+
+    pub fn rules(&self) -> MorrisRules {
+        self.rules
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn to_move(&self) -> Player {
+        self.to_move
+    }
+
+    pub fn moves(&self) -> &[(Player, MorrisMove)] {
+        &self.moves
+    }
+
+    // sliding starts once both players have placed all their pieces
+    pub fn phase(&self) -> Phase {
+        if self.moves.len() < 2 * self.rules.pieces {
+            Phase::Placing
+        } else {
+            Phase::Moving
+        }
+    }
+
+    // none once the game is over
+    pub fn legal_moves(&self) -> Vec<MorrisMove> {
+        if self.board.winner().is_some() {
+            return Vec::new();
+        }
+        let vacant = self.board.legal_moves();
+        match self.phase() {
+            Phase::Placing => vacant.map(MorrisMove::Place).collect(),
+            Phase::Moving => vacant
+                .flat_map(|to| {
+                    neighbours(to)
+                        .filter(|&from| {
+                            self.board.cells[from.get() - 1] == Cell::Occupied(self.to_move)
+                        })
+                        .map(move |from| MorrisMove::Slide { from, to })
+                })
+                .collect(),
+        }
+    }
+
+    pub fn play(&mut self, mv: MorrisMove) -> Result<(), MorrisError> {
+        if self.is_over() {
+            return Err(MorrisError::Place(PlaceError::GameOver));
+        }
+        let player = self.to_move;
+        let mut board = self.board;
+        match (self.phase(), mv) {
+            (Phase::Placing, MorrisMove::Place(to)) => {
+                board.place(to, player).map_err(MorrisError::Place)?;
+            }
+            (Phase::Moving, MorrisMove::Slide { from, to }) => {
+                if board.cells[from.get() - 1] != Cell::Occupied(player) {
+                    return Err(MorrisError::NotYours { pos: from });
+                }
+                board.place(to, player).map_err(MorrisError::Place)?;
+                if !adjacent(from, to) {
+                    return Err(MorrisError::NotAdjacent { from, to });
+                }
+                board.remove(from).unwrap();
+            }
+            (phase, _) => return Err(MorrisError::WrongPhase { phase }),
+        }
+        self.board = board;
+        self.moves.push((player, mv));
+        self.to_move = player.toggle();
+        Ok(())
+    }
+
+    // whoever made a line, or the opponent of a player with nothing to slide
+    pub fn winner(&self) -> Option<Player> {
+        self.board.winner().or_else(|| {
+            let blocked = self.phase() == Phase::Moving && self.legal_moves().is_empty();
+            blocked.then(|| self.to_move.toggle())
+        })
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.winner().is_some()
+    }
+}
+
+impl Position for MorrisGame {
+    type Move = MorrisMove;
+
+    fn to_move(&self) -> Player {
+        self.to_move
+    }
+
+    fn moves(&self) -> Vec<MorrisMove> {
+        self.legal_moves()
+    }
+
+    fn play(&mut self, mv: MorrisMove) {
+        MorrisGame::play(self, mv).unwrap();
+    }
+
+    fn winner(&self) -> Option<Player> {
+        MorrisGame::winner(self)
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct InvalidPiecesError {
+    pub pieces: usize,
+}
+
+impl fmt::Display for InvalidPiecesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} pieces each don't leave room to move", self.pieces)
+    }
+}
+
+impl error::Error for InvalidPiecesError {}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum MorrisError {
+    // a placement while sliding, or the other way round
+    WrongPhase { phase: Phase },
+    // there's no piece of the mover's on `pos`
+    NotYours { pos: Pos },
+    NotAdjacent { from: Pos, to: Pos },
+    Place(PlaceError),
+}
+
+impl fmt::Display for MorrisError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MorrisError::WrongPhase {
+                phase: Phase::Placing,
+            } => {
+                write!(f, "pieces are still being placed")
+            }
+            MorrisError::WrongPhase {
+                phase: Phase::Moving,
+            } => write!(f, "every piece has been placed, so one has to slide"),
+            MorrisError::NotYours { pos } => write!(f, "there's no piece of yours on {}", pos),
+            MorrisError::NotAdjacent { from, to } => {
+                write!(f, "{} and {} aren't joined by a line", from, to)
+            }
+            MorrisError::Place(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl error::Error for MorrisError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            MorrisError::Place(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::pos;
+
+    fn slide(from: usize, to: usize) -> MorrisMove {
+        MorrisMove::Slide {
+            from: pos(from),
+            to: pos(to),
+        }
+    }
+
+    fn game(rules: MorrisRules, placements: &[usize]) -> MorrisGame {
+        let mut game = MorrisGame::new(rules).unwrap();
+        for &n in placements {
+            game.play(MorrisMove::Place(pos(n))).unwrap();
+        }
+        game
+    }
+
+    #[test]
+    fn adjacency() {
+        let neighbours = |n| neighbours(pos(n)).map(Pos::get).collect::<Vec<_>>();
+        assert_eq!(vec![2, 4, 5], neighbours(1));
+        assert_eq!(vec![1, 3, 5], neighbours(2));
+        assert_eq!(vec![1, 2, 3, 4, 6, 7, 8, 9], neighbours(5));
+        assert!(!adjacent(pos(2), pos(4)));
+        assert!(!adjacent(pos(1), pos(3)));
+        assert!(!adjacent(pos(5), pos(5)));
+    }
+
+    #[test]
+    fn three_mens_morris() {
+        let mut game = game(MorrisRules::three_mens_morris(), &[1, 4, 2, 5, 6]);
+        assert_eq!(Phase::Placing, game.phase());
+        assert_eq!(
+            Err(MorrisError::WrongPhase {
+                phase: Phase::Placing
+            }),
+            game.play(slide(4, 7))
+        );
+        game.play(MorrisMove::Place(pos(9))).unwrap();
+        assert_eq!(Phase::Moving, game.phase());
+        assert_eq!(
+            Err(MorrisError::WrongPhase {
+                phase: Phase::Moving
+            }),
+            game.play(MorrisMove::Place(pos(3)))
+        );
+        assert_eq!(
+            Err(MorrisError::NotYours { pos: pos(4) }),
+            game.play(slide(4, 7))
+        );
+        assert_eq!(
+            Err(MorrisError::NotAdjacent {
+                from: pos(1),
+                to: pos(3)
+            }),
+            game.play(slide(1, 3))
+        );
+        assert_eq!(
+            Err(MorrisError::Place(PlaceError::Occupied {
+                pos: pos(5),
+                occupied_by: Player::Nought
+            })),
+            game.play(slide(6, 5))
+        );
+        let moves = game.legal_moves();
+        assert!(moves.contains(&slide(6, 3)));
+        assert!(!moves.contains(&slide(1, 3)));
+
+        game.play(slide(6, 3)).unwrap();
+        assert_eq!(Some(Player::Cross), game.winner());
+        assert_eq!(
+            Err(MorrisError::Place(PlaceError::GameOver)),
+            game.play(slide(4, 7))
+        );
+        assert!(game.legal_moves().is_empty());
+        assert_eq!("6-3", game.moves()[6].1.to_string());
+    }
+
+    #[test]
+    fn achi_blocked() {
+        // only 1 is empty, and none of crosses' pieces is next to it
+        let game = game(MorrisRules::achi(), &[3, 2, 6, 4, 7, 5, 8, 9]);
+        assert_eq!(None, game.board().winner());
+        assert!(game.legal_moves().is_empty());
+        assert_eq!(Some(Player::Nought), game.winner());
+        assert_eq!(
+            Err(InvalidPiecesError { pieces: 5 }),
+            MorrisGame::new(MorrisRules { pieces: 5 })
+        );
+    }
+}
@@ -0,0 +1,380 @@
+// games on a square board of any size, where the first to get `to_win` in a
+// row along a row, column or diagonal wins and each player picks their own
+// mark. `Game` covers the 3x3 board; this is for the bigger ones, with up to
+// `Player::MAX` players taking turns in `Player::nth` order
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::error;
+use core::fmt;
+
+use crate::Player;
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct MultiConfig {
+    pub width: usize,
+    pub to_win: usize,
+    // one per player, in turn order
+    pub marks: Vec<char>,
+}
+
+impl MultiConfig {
+    // three players, `X`, `O` and `+`, needing four in a row on 6x6
+    pub fn three_player() -> MultiConfig {
+        MultiConfig {
+            width: 6,
+            to_win: 4,
+            marks: vec!['X', 'O', '+'],
+        }
+    }
+
+    fn validate(&self) -> Result<(), InvalidConfigError> {
+        if self.marks.len() < 2 || self.marks.len() > Player::MAX {
+            return Err(InvalidConfigError::PlayerCount {
+                players: self.marks.len(),
+            });
+        }
+        for (i, &mark) in self.marks.iter().enumerate() {
+            if mark == EMPTY || self.marks[..i].contains(&mark) {
+                return Err(InvalidConfigError::Mark { mark });
+            }
+        }
+        if self.to_win == 0 || self.to_win > self.width {
+            return Err(InvalidConfigError::LineLength {
+                to_win: self.to_win,
+                width: self.width,
+            });
+        }
+        Ok(())
+    }
+}
+
+// how an empty cell is drawn, so no player may use it
+const EMPTY: char = '.';
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MultiGame {
+    config: MultiConfig,
+    // row-major
+    cells: Vec<Option<Player>>,
+    to_move: Player,
+    moves: Vec<(Player, usize, usize)>,
+    winner: Option<Player>,
+}
+
+impl MultiGame {
+    pub fn new(config: MultiConfig) -> Result<MultiGame, InvalidConfigError> {
+        config.validate()?;
+        Ok(MultiGame {
+            cells: vec![None; config.width * config.width],
+            config,
+            to_move: Player::Cross,
+            moves: Vec::new(),
+            winner: None,
+        })
+    }
+
+    pub fn config(&self) -> &MultiConfig {
+        &self.config
+    }
+
+    pub fn players(&self) -> impl Iterator<Item = Player> {
+        Player::all(self.config.marks.len())
+    }
+
+    // the mark `player` chose, not the one `Player` displays as
+    pub fn mark(&self, player: Player) -> char {
+        self.config.marks[player.number()]
+    }
+
+    pub fn to_move(&self) -> Player {
+        self.to_move
+    }
+
+    // zero-based, None off the board as well as for empty cells
+    pub fn get(&self, row: usize, column: usize) -> Option<Player> {
+        self.index(row, column).and_then(|index| self.cells[index])
+    }
+
+    // every move so far with who played it
+    pub fn moves(&self) -> &[(Player, usize, usize)] {
+        &self.moves
+    }
+
+    pub fn play(&mut self, row: usize, column: usize) -> Result<(), MultiPlaceError> {
+        if self.is_over() {
+            return Err(MultiPlaceError::GameOver);
+        }
+        let index = self
+            .index(row, column)
+            .ok_or(MultiPlaceError::OffBoard { row, column })?;
+        if let Some(occupied_by) = self.cells[index] {
+            return Err(MultiPlaceError::Occupied {
+                row,
+                column,
+                occupied_by,
+            });
+        }
+
+        let player = self.to_move;
+        self.cells[index] = Some(player);
+        self.moves.push((player, row, column));
+        if self.completes_line(row, column) {
+            self.winner = Some(player);
+        }
+        self.to_move = player.next(self.config.marks.len());
+        Ok(())
+    }
+
+    pub fn winner(&self) -> Option<Player> {
+        self.winner
+    }
+
+    pub fn is_draw(&self) -> bool {
+        self.winner.is_none() && self.cells.iter().all(Option::is_some)
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.winner.is_some() || self.is_draw()
+    }
+
+    fn index(&self, row: usize, column: usize) -> Option<usize> {
+        let width = self.config.width;
+        (row < width && column < width).then_some(row * width + column)
+    }
+
+    // whether the piece at (`row`, `column`) is part of a long enough line;
+    // only a line through the last move can be new
+    fn completes_line(&self, row: usize, column: usize) -> bool {
+        let player = self.get(row, column);
+        let run = |dr: isize, dc: isize| {
+            (1..)
+                .map(|step| {
+                    let r = row.checked_add_signed(dr * step)?;
+                    let c = column.checked_add_signed(dc * step)?;
+                    self.get(r, c)
+                })
+                .take_while(|&cell| cell.is_some() && cell == player)
+                .count()
+        };
+        [(0, 1), (1, 0), (1, 1), (1, -1)]
+            .into_iter()
+            .any(|(dr, dc)| 1 + run(dr, dc) + run(-dr, -dc) >= self.config.to_win)
+    }
+}
+
+impl fmt::Display for MultiGame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.cells.chunks(self.config.width) {
+            for (column, cell) in row.iter().enumerate() {
+                if column > 0 {
+                    write!(f, " ")?;
+                }
+                let mark = cell.map_or(EMPTY, |player| self.mark(player));
+                write!(f, "{}", mark)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum InvalidConfigError {
+    PlayerCount { players: usize },
+    // empty, or used by two players
+    Mark { mark: char },
+    LineLength { to_win: usize, width: usize },
+}
+
+impl fmt::Display for InvalidConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InvalidConfigError::PlayerCount { players } => {
+                write!(f, "{} players, expected 2 to {}", players, Player::MAX)
+            }
+            InvalidConfigError::Mark { mark } => write!(f, "mark {:?} can't be used", mark),
+            InvalidConfigError::LineLength { to_win, width } => write!(
+                f,
+                "{} in a row doesn't fit on a board {} wide",
+                to_win, width
+            ),
+        }
+    }
+}
+
+impl error::Error for InvalidConfigError {}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum MultiPlaceError {
+    OffBoard {
+        row: usize,
+        column: usize,
+    },
+    Occupied {
+        row: usize,
+        column: usize,
+        occupied_by: Player,
+    },
+    GameOver,
+}
+
+impl fmt::Display for MultiPlaceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MultiPlaceError::OffBoard { row, column } => {
+                write!(f, "({}, {}) is off the board", row, column)
+            }
+            MultiPlaceError::Occupied {
+                row,
+                column,
+                occupied_by,
+            } => write!(
+                f,
+                "({}, {}) is already occupied by player {}",
+                row,
+                column,
+                occupied_by.number()
+            ),
+            MultiPlaceError::GameOver => write!(f, "the game is already over"),
+        }
+    }
+}
+
+impl error::Error for MultiPlaceError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(moves: &[(usize, usize)]) -> MultiGame {
+        let mut game = MultiGame::new(MultiConfig::three_player()).unwrap();
+        for &(row, column) in moves {
+            game.play(row, column).unwrap();
+        }
+        game
+    }
+
+    #[test]
+    fn turns_rotate() {
+        let game = game(&[(0, 0), (1, 1), (2, 2)]);
+        let third = Player::nth(2).unwrap();
+        assert_eq!(Player::Cross, game.to_move());
+        assert_eq!(Some(third), game.get(2, 2));
+        assert_eq!('+', game.mark(third));
+        assert_eq!(3, game.players().count());
+        assert_eq!(None, game.get(6, 0));
+    }
+
+    #[test]
+    fn third_player_wins() {
+        // the third player runs down the right-hand column while the others
+        // spread out
+        let mut game = game(&[
+            (0, 0),
+            (5, 0),
+            (0, 5),
+            (0, 2),
+            (5, 2),
+            (1, 5),
+            (2, 0),
+            (5, 4),
+            (2, 5),
+            (2, 2),
+            (3, 2),
+        ]);
+        assert_eq!(None, game.winner());
+        game.play(3, 5).unwrap();
+        assert_eq!(Player::nth(2), game.winner());
+        assert!(game.is_over());
+        assert_eq!(Err(MultiPlaceError::GameOver), game.play(4, 4));
+    }
+
+    #[test]
+    fn errors() {
+        let mut game = game(&[(0, 0)]);
+        assert_eq!(
+            Err(MultiPlaceError::Occupied {
+                row: 0,
+                column: 0,
+                occupied_by: Player::Cross
+            }),
+            game.play(0, 0)
+        );
+        assert_eq!(
+            Err(MultiPlaceError::OffBoard { row: 0, column: 6 }),
+            game.play(0, 6)
+        );
+
+        let config = |marks: &[char], to_win| MultiConfig {
+            width: 3,
+            to_win,
+            marks: marks.to_vec(),
+        };
+        assert_eq!(
+            Err(InvalidConfigError::PlayerCount { players: 1 }),
+            MultiGame::new(config(&['X'], 3))
+        );
+        assert_eq!(
+            Err(InvalidConfigError::PlayerCount { players: 5 }),
+            MultiGame::new(config(&['A', 'B', 'C', 'D', 'E'], 3))
+        );
+        assert_eq!(
+            Err(InvalidConfigError::Mark { mark: 'X' }),
+            MultiGame::new(config(&['X', 'O', 'X'], 3))
+        );
+        assert_eq!(
+            Err(InvalidConfigError::Mark { mark: '.' }),
+            MultiGame::new(config(&['X', '.'], 3))
+        );
+        assert_eq!(
+            Err(InvalidConfigError::LineLength {
+                to_win: 4,
+                width: 3
+            }),
+            MultiGame::new(config(&['X', 'O'], 4))
+        );
+    }
+
+    #[test]
+    fn draw() {
+        let mut game = MultiGame::new(MultiConfig {
+            width: 3,
+            to_win: 3,
+            marks: vec!['X', 'O', '+'],
+        })
+        .unwrap();
+        let moves = [
+            (0, 0),
+            (0, 1),
+            (0, 2),
+            (1, 2),
+            (1, 0),
+            (1, 1),
+            (2, 0),
+            (2, 2),
+        ];
+        for (row, column) in moves {
+            game.play(row, column).unwrap();
+        }
+        assert!(!game.is_over());
+        game.play(2, 1).unwrap();
+        assert_eq!(None, game.winner());
+        assert!(game.is_draw());
+        assert_eq!("X O +\nO + X\nX + O\n", game.to_string());
+    }
+
+    #[test]
+    fn display() {
+        let game = MultiGame::new(MultiConfig {
+            width: 3,
+            to_win: 3,
+            marks: vec!['X', 'O', '+'],
+        });
+        let mut game = game.unwrap();
+        game.play(0, 0).unwrap();
+        game.play(1, 1).unwrap();
+        game.play(2, 2).unwrap();
+        assert_eq!("X . .\n. O .\n. . +\n", game.to_string());
+    }
+}
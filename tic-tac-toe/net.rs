@@ -0,0 +1,367 @@
+// two instances playing each other over TCP. Every message is a big-endian
+// u32 length followed by that many bytes of text:
+//
+//     start <X|O>          host -> joiner, which side the joiner plays
+//     move <pos> <hash>    the move, and the board's zobrist hash after it
+//     resign
+//     desync <hash>        the sender's hash didn't match; the game is off
+//
+// both sides keep their own `Game`, so a move that doesn't leave the boards
+// hashing the same means they've drifted apart, and the game stops there
+
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::string::{String, ToString};
+use std::vec::Vec;
+
+use crate::{Game, PlaceError, Player, Pos};
+
+// longer messages are refused rather than allocated for
+pub const MAX_MESSAGE: usize = 1024;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Message {
+    Start { you: Player },
+    Move { pos: Pos, hash: u64 },
+    Resign,
+    Desync { hash: u64 },
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Message::Start { you } => write!(f, "start {}", you),
+            Message::Move { pos, hash } => write!(f, "move {} {:016x}", pos, hash),
+            Message::Resign => write!(f, "resign"),
+            Message::Desync { hash } => write!(f, "desync {:016x}", hash),
+        }
+    }
+}
+
+impl Message {
+    pub(crate) fn parse(text: &str) -> Option<Message> {
+        let hash = |word: &str| u64::from_str_radix(word, 16).ok();
+        let words: Vec<&str> = text.split(' ').collect();
+        match words.as_slice() {
+            ["start", you] => Some(Message::Start {
+                you: you.parse().ok()?,
+            }),
+            ["move", pos, hash_word] => Some(Message::Move {
+                pos: pos.parse().ok()?,
+                hash: hash(hash_word)?,
+            }),
+            ["resign"] => Some(Message::Resign),
+            ["desync", hash_word] => Some(Message::Desync {
+                hash: hash(hash_word)?,
+            }),
+            _ => None,
+        }
+    }
+}
+
+pub fn write_message(writer: impl Write, message: &Message) -> io::Result<()> {
+    write_frame(writer, &message.to_string())
+}
+
+pub fn read_message(reader: impl Read) -> Result<Message, NetError> {
+    let text = read_frame(reader)?;
+    Message::parse(&text).ok_or(NetError::Protocol(format!("unknown message {:?}", text)))
+}
+
+// the framing on its own, for the `lobby`'s messages
+pub(crate) fn write_frame(mut writer: impl Write, text: &str) -> io::Result<()> {
+    writer.write_all(&(text.len() as u32).to_be_bytes())?;
+    writer.write_all(text.as_bytes())?;
+    writer.flush()
+}
+
+pub(crate) fn read_frame(mut reader: impl Read) -> Result<String, NetError> {
+    let mut length = [0; 4];
+    reader.read_exact(&mut length)?;
+    let length = u32::from_be_bytes(length) as usize;
+    if length > MAX_MESSAGE {
+        return Err(NetError::Protocol(format!("{}-byte message", length)));
+    }
+    let mut text = vec![0; length];
+    reader.read_exact(&mut text)?;
+    String::from_utf8(text).map_err(|_| NetError::Protocol("message isn't UTF-8".to_string()))
+}
+
+// what the opponent did, from `Connection::receive`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Remote {
+    Moved(Pos),
+    Resigned,
+}
+
+// one end of a game; the host plays crosses and moves first
+#[derive(Debug)]
+pub struct Connection {
+    stream: TcpStream,
+    game: Game,
+    me: Player,
+}
+
+// waits for one opponent on `addr`
+pub fn host(addr: impl ToSocketAddrs) -> Result<Connection, NetError> {
+    accept(&TcpListener::bind(addr)?)
+}
+
+// like `host`, on a listener that's already bound, e.g. to port 0
+pub fn accept(listener: &TcpListener) -> Result<Connection, NetError> {
+    let (mut stream, _) = listener.accept()?;
+    write_message(
+        &mut stream,
+        &Message::Start {
+            you: Player::Nought,
+        },
+    )?;
+    Ok(Connection::new(stream, Player::Cross))
+}
+
+pub fn join(addr: impl ToSocketAddrs) -> Result<Connection, NetError> {
+    let mut stream = TcpStream::connect(addr)?;
+    match read_message(&mut stream)? {
+        Message::Start { you } => Ok(Connection::new(stream, you)),
+        message => Err(NetError::Unexpected(message)),
+    }
+}
+
+impl Connection {
+    pub(crate) fn new(stream: TcpStream, me: Player) -> Connection {
+        Connection {
+            stream,
+            game: Game::new(),
+            me,
+        }
+    }
+
+    pub fn me(&self) -> Player {
+        self.me
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    // plays `pos` for this side and sends it
+    pub fn send_move(&mut self, pos: Pos) -> Result<(), NetError> {
+        if self.game.to_move() != self.me {
+            return Err(NetError::Place(PlaceError::NotYourTurn { player: self.me }));
+        }
+        self.game.play(pos).map_err(NetError::Place)?;
+        let hash = self.game.board().zobrist();
+        write_message(&mut self.stream, &Message::Move { pos, hash })?;
+        Ok(())
+    }
+
+    pub fn resign(&mut self) -> Result<(), NetError> {
+        self.game.resign(self.me).map_err(NetError::Place)?;
+        write_message(&mut self.stream, &Message::Resign)?;
+        Ok(())
+    }
+
+    // waits for the opponent's move or resignation and applies it; if the
+    // boards have drifted apart, tells the opponent and gives up
+    pub fn receive(&mut self) -> Result<Remote, NetError> {
+        let opponent = self.me.toggle();
+        match read_message(&mut self.stream)? {
+            Message::Move { pos, hash } => {
+                if self.game.to_move() != opponent {
+                    let player = opponent;
+                    return Err(NetError::Place(PlaceError::NotYourTurn { player }));
+                }
+                self.game.play(pos).map_err(NetError::Place)?;
+                let ours = self.game.board().zobrist();
+                if ours != hash {
+                    write_message(&mut self.stream, &Message::Desync { hash: ours })?;
+                    return Err(NetError::Desync { ours, theirs: hash });
+                }
+                Ok(Remote::Moved(pos))
+            }
+            Message::Resign => {
+                self.game
+                    .resign(opponent)
+                    .map_err(|_| NetError::Unexpected(Message::Resign))?;
+                Ok(Remote::Resigned)
+            }
+            Message::Desync { hash } => Err(NetError::Desync {
+                ours: self.game.board().zobrist(),
+                theirs: hash,
+            }),
+            message => Err(NetError::Unexpected(message)),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum NetError {
+    Io(io::Error),
+    // a message that couldn't be read
+    Protocol(String),
+    // a message that makes no sense at this point
+    Unexpected(Message),
+    // a move or resignation either side wasn't allowed to make
+    Place(PlaceError),
+    // the two sides' boards hash differently
+    Desync { ours: u64, theirs: u64 },
+    // the `lobby` said no, e.g. to a name that's taken
+    Refused(String),
+}
+
+impl From<io::Error> for NetError {
+    fn from(err: io::Error) -> NetError {
+        NetError::Io(err)
+    }
+}
+
+impl fmt::Display for NetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NetError::Io(err) => write!(f, "{}", err),
+            NetError::Protocol(message) => write!(f, "protocol error: {}", message),
+            NetError::Unexpected(message) => {
+                write!(f, "unexpected message {:?}", message.to_string())
+            }
+            NetError::Place(error) => write!(f, "{}", error),
+            NetError::Desync { ours, theirs } => write!(
+                f,
+                "boards out of sync: ours hashes to {:016x}, theirs to {:016x}",
+                ours, theirs
+            ),
+            NetError::Refused(reason) => write!(f, "refused: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for NetError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            NetError::Io(err) => Some(err),
+            NetError::Place(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::pos;
+    use crate::{GameResult, WinReason};
+    use std::io::Cursor;
+    use std::thread;
+
+    // a host on a free local port, accepting on another thread
+    fn pair() -> (Connection, Connection) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let host = thread::spawn(move || accept(&listener).unwrap());
+        let joiner = join(addr).unwrap();
+        (host.join().unwrap(), joiner)
+    }
+
+    #[test]
+    fn messages() {
+        let messages = [
+            Message::Start {
+                you: Player::Nought,
+            },
+            Message::Move {
+                pos: pos(5),
+                hash: 0x1234,
+            },
+            Message::Resign,
+            Message::Desync { hash: u64::MAX },
+        ];
+        let mut bytes = Vec::new();
+        for message in &messages {
+            write_message(&mut bytes, message).unwrap();
+        }
+        assert_eq!(b"\0\0\0\x07start O", &bytes[..11]);
+        let mut reader = Cursor::new(bytes);
+        for message in messages {
+            assert_eq!(message, read_message(&mut reader).unwrap());
+        }
+        assert!(matches!(read_message(&mut reader), Err(NetError::Io(_))));
+
+        let mut bytes = 5u32.to_be_bytes().to_vec();
+        bytes.extend(b"hello");
+        assert!(matches!(
+            read_message(Cursor::new(bytes)),
+            Err(NetError::Protocol(_))
+        ));
+        let bytes = u32::MAX.to_be_bytes();
+        assert!(matches!(
+            read_message(Cursor::new(bytes)),
+            Err(NetError::Protocol(_))
+        ));
+    }
+
+    #[test]
+    fn game() {
+        let (mut host, mut joiner) = pair();
+        assert_eq!(Player::Cross, host.me());
+        assert_eq!(Player::Nought, joiner.me());
+        assert!(matches!(
+            joiner.send_move(pos(1)),
+            Err(NetError::Place(PlaceError::NotYourTurn {
+                player: Player::Nought
+            }))
+        ));
+
+        for (x, o) in [(1, 4), (2, 5)] {
+            host.send_move(pos(x)).unwrap();
+            assert_eq!(Remote::Moved(pos(x)), joiner.receive().unwrap());
+            joiner.send_move(pos(o)).unwrap();
+            assert_eq!(Remote::Moved(pos(o)), host.receive().unwrap());
+        }
+        host.send_move(pos(3)).unwrap();
+        joiner.receive().unwrap();
+        assert_eq!(host.game().board(), joiner.game().board());
+        assert_eq!(Some(Player::Cross), joiner.game().result().unwrap().winner);
+    }
+
+    #[test]
+    fn resign() {
+        let (mut host, mut joiner) = pair();
+        host.send_move(pos(5)).unwrap();
+        joiner.receive().unwrap();
+        joiner.resign().unwrap();
+        assert_eq!(Remote::Resigned, host.receive().unwrap());
+        let result = GameResult {
+            winner: Some(Player::Cross),
+            reason: WinReason::Resignation,
+        };
+        assert_eq!(Some(result), host.game().result());
+        assert_eq!(Some(result), joiner.game().result());
+    }
+
+    #[test]
+    fn desync() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        // a host whose board has somehow picked up an extra piece
+        let host = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            write_message(
+                &mut stream,
+                &Message::Start {
+                    you: Player::Nought,
+                },
+            )
+            .unwrap();
+            let mut game = Game::new();
+            game.apply(&[pos(9), pos(1), pos(5)]).unwrap();
+            let hash = game.board().zobrist();
+            write_message(&mut stream, &Message::Move { pos: pos(5), hash }).unwrap();
+            read_message(&mut stream).unwrap()
+        });
+        let mut joiner = join(addr).unwrap();
+        let Err(NetError::Desync { ours, .. }) = joiner.receive() else {
+            panic!("expected a desync");
+        };
+        assert_eq!(Message::Desync { hash: ours }, host.join().unwrap());
+    }
+}
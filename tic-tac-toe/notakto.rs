@@ -0,0 +1,256 @@
+// Notakto: both players play crosses, on any number of boards. A board is
+// dead once it has three in a row and takes no more moves, and whoever kills
+// the last live board loses
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::error;
+use core::fmt;
+use core::str;
+
+use crate::ai::Position;
+use crate::{Board, ParsePosError, PlaceError, Player, Pos};
+
+// the only mark anyone plays
+const MARK: Player = Player::Cross;
+
+// a cell on one of the boards, which are numbered from zero
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Move {
+    pub board: usize,
+    pub pos: Pos,
+}
+
+// `2.5` is the centre of the third board; boards are shown one-based
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.board + 1, self.pos)
+    }
+}
+
+impl str::FromStr for Move {
+    type Err = ParsePosError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (board, pos) = s.split_once('.').ok_or(ParsePosError {})?;
+        let board: usize = board.parse().map_err(|_| ParsePosError {})?;
+        Ok(Move {
+            board: board.checked_sub(1).ok_or(ParsePosError {})?,
+            pos: pos.parse()?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Notakto {
+    boards: Vec<Board>,
+    to_move: Player,
+}
+
+impl Notakto {
+    // a game on `boards` empty boards; with none it is over before it starts
+    pub fn new(boards: usize) -> Notakto {
+        Notakto {
+            boards: vec![Board::new(); boards],
+            to_move: Player::Cross,
+        }
+    }
+
+    pub fn boards(&self) -> &[Board] {
+        &self.boards
+    }
+
+    // the player whose turn it is, though both play crosses
+    pub fn to_move(&self) -> Player {
+        self.to_move
+    }
+
+    pub fn is_dead(&self, board: usize) -> bool {
+        self.boards[board].winner().is_some()
+    }
+
+    pub fn legal_moves(&self) -> impl Iterator<Item = Move> + '_ {
+        self.boards
+            .iter()
+            .enumerate()
+            .flat_map(|(board, cells)| cells.legal_moves().map(move |pos| Move { board, pos }))
+    }
+
+    pub fn play(&mut self, mv: Move) -> Result<(), NotaktoError> {
+        if self.is_over() {
+            return Err(NotaktoError::Place(PlaceError::GameOver));
+        }
+        let Some(board) = self.boards.get_mut(mv.board) else {
+            return Err(NotaktoError::NoSuchBoard { board: mv.board });
+        };
+        if board.winner().is_some() {
+            return Err(NotaktoError::Dead { board: mv.board });
+        }
+        board.place(mv.pos, MARK).map_err(NotaktoError::Place)?;
+        self.to_move = self.to_move.toggle();
+        Ok(())
+    }
+
+    // every board is dead
+    pub fn is_over(&self) -> bool {
+        (0..self.boards.len()).all(|board| self.is_dead(board))
+    }
+
+    // the player who didn't kill the last board, once the game is over
+    pub fn winner(&self) -> Option<Player> {
+        self.is_over().then_some(self.to_move)
+    }
+}
+
+// each board in turn, marked once it is dead
+impl fmt::Display for Notakto {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (n, board) in self.boards.iter().enumerate() {
+            if n > 0 {
+                writeln!(f)?;
+            }
+            let dead = if self.is_dead(n) { " (dead)" } else { "" };
+            writeln!(f, "board {}{}", n + 1, dead)?;
+            for row in board.cells.chunks(Board::WIDTH) {
+                for (column, cell) in row.iter().enumerate() {
+                    if column > 0 {
+                        write!(f, " ")?;
+                    }
+                    write!(f, "{}", cell.symbol())?;
+                }
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Position for Notakto {
+    type Move = Move;
+
+    fn to_move(&self) -> Player {
+        self.to_move
+    }
+
+    fn moves(&self) -> Vec<Move> {
+        self.legal_moves().collect()
+    }
+
+    fn play(&mut self, mv: Move) {
+        Notakto::play(self, mv).unwrap();
+    }
+
+    fn winner(&self) -> Option<Player> {
+        Notakto::winner(self)
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum NotaktoError {
+    NoSuchBoard { board: usize },
+    // `board` already has three in a row
+    Dead { board: usize },
+    Place(PlaceError),
+}
+
+impl fmt::Display for NotaktoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NotaktoError::NoSuchBoard { board } => write!(f, "there is no board {}", board + 1),
+            NotaktoError::Dead { board } => write!(f, "board {} is dead", board + 1),
+            NotaktoError::Place(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl error::Error for NotaktoError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            NotaktoError::Place(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::{alpha_beta, WIN};
+
+    fn mv(s: &str) -> Move {
+        s.parse().unwrap()
+    }
+
+    fn notakto(boards: usize, moves: &str) -> Notakto {
+        let mut game = Notakto::new(boards);
+        for s in moves.split_whitespace() {
+            game.play(mv(s)).unwrap();
+        }
+        game
+    }
+
+    #[test]
+    fn parse_move() {
+        let parsed = mv("2.5");
+        assert_eq!(1, parsed.board);
+        assert_eq!(Pos::new(5).unwrap(), parsed.pos);
+        assert_eq!("2.5", parsed.to_string());
+        assert!("0.5".parse::<Move>().is_err());
+        assert!("25".parse::<Move>().is_err());
+    }
+
+    #[test]
+    fn dead_boards() {
+        let mut game = notakto(2, "1.1 1.2 2.5");
+        assert_eq!(Player::Nought, game.to_move());
+        game.play(mv("1.3")).unwrap();
+        assert!(game.is_dead(0));
+        assert!(!game.is_over());
+        assert_eq!(None, game.winner());
+        assert_eq!(8, game.legal_moves().count());
+        assert_eq!(Err(NotaktoError::Dead { board: 0 }), game.play(mv("1.9")));
+        assert_eq!(
+            Err(NotaktoError::NoSuchBoard { board: 2 }),
+            game.play(mv("3.1"))
+        );
+        assert_eq!(
+            Err(NotaktoError::Place(PlaceError::Occupied {
+                pos: Pos::new(5).unwrap(),
+                occupied_by: Player::Cross
+            })),
+            game.play(mv("2.5"))
+        );
+    }
+
+    #[test]
+    fn last_line_loses() {
+        // noughts kill the first board, crosses the second and last
+        let game = notakto(2, "1.1 1.2 2.5 1.3 2.1 2.2 2.9");
+        assert!(game.is_over());
+        assert_eq!(Some(Player::Nought), game.winner());
+        assert_eq!(0, game.legal_moves().count());
+    }
+
+    #[test]
+    fn display() {
+        let expected = "\
+board 1 (dead)
+X X X
+. . .
+. . .
+
+board 2
+. . .
+. X .
+. . .
+";
+        assert_eq!(expected, notakto(2, "1.1 1.2 2.5 1.3").to_string());
+    }
+
+    #[test]
+    fn first_player_wins_on_one_board() {
+        let (best, score) = alpha_beta(&Notakto::new(1), 9).unwrap();
+        assert_eq!(mv("1.5"), best);
+        assert!(score > 0 && score < WIN);
+    }
+}
@@ -0,0 +1,276 @@
+// Order and Chaos on a 6x6 board: both sides may put down either mark, and
+// Order wins by making five of the same mark in a row while Chaos wins by
+// filling the board before that happens, so there are no draws. The sides
+// are seats as in `Game` rather than marks: Order sits where crosses would
+// and moves first, Chaos where noughts would, and the result is a
+// `GameResult` like any other game's
+
+use alloc::vec::Vec;
+use core::error;
+use core::fmt;
+
+use crate::{GameResult, Player, WinReason};
+
+pub const WIDTH: usize = 6;
+pub const TO_WIN: usize = 5;
+
+pub const ORDER: Player = Player::Cross;
+pub const CHAOS: Player = Player::Nought;
+
+// "Order" or "Chaos"
+pub fn side_name(side: Player) -> &'static str {
+    if side == ORDER {
+        "Order"
+    } else {
+        "Chaos"
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OrderChaos {
+    // row-major
+    cells: [Option<Player>; WIDTH * WIDTH],
+    // the side, not the mark
+    to_move: Player,
+    moves: Vec<(Player, Player, usize, usize)>,
+    result: Option<GameResult>,
+}
+
+impl OrderChaos {
+    // Order moves first
+    pub fn new() -> OrderChaos {
+        OrderChaos {
+            cells: [None; WIDTH * WIDTH],
+            to_move: ORDER,
+            moves: Vec::new(),
+            result: None,
+        }
+    }
+
+    pub fn to_move(&self) -> Player {
+        self.to_move
+    }
+
+    // zero-based, None off the board as well as for empty cells
+    pub fn get(&self, row: usize, column: usize) -> Option<Player> {
+        index(row, column).and_then(|index| self.cells[index])
+    }
+
+    // every move so far: the side that played it, the mark and where
+    pub fn moves(&self) -> &[(Player, Player, usize, usize)] {
+        &self.moves
+    }
+
+    // puts down `mark` for whoever is to move
+    pub fn play(&mut self, row: usize, column: usize, mark: Player) -> Result<(), OrderChaosError> {
+        if self.is_over() {
+            return Err(OrderChaosError::GameOver);
+        }
+        let index = index(row, column).ok_or(OrderChaosError::OffBoard { row, column })?;
+        if let Some(occupied_by) = self.cells[index] {
+            return Err(OrderChaosError::Occupied {
+                row,
+                column,
+                occupied_by,
+            });
+        }
+
+        self.cells[index] = Some(mark);
+        self.moves.push((self.to_move, mark, row, column));
+        let result = |winner, reason| GameResult {
+            winner: Some(winner),
+            reason,
+        };
+        if self.completes_line(row, column) {
+            self.result = Some(result(ORDER, WinReason::FiveInARow));
+        } else if self.cells.iter().all(Option::is_some) {
+            self.result = Some(result(CHAOS, WinReason::BoardFull));
+        }
+        self.to_move = self.to_move.toggle();
+        Ok(())
+    }
+
+    pub fn result(&self) -> Option<GameResult> {
+        self.result
+    }
+
+    // the winning side
+    pub fn winner(&self) -> Option<Player> {
+        self.result.and_then(|result| result.winner)
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.result.is_some()
+    }
+
+    // whether the mark at (`row`, `column`) is part of five or more in a
+    // row; only a line through the last move can be new
+    fn completes_line(&self, row: usize, column: usize) -> bool {
+        let mark = self.get(row, column);
+        let run = |dr: isize, dc: isize| {
+            (1..)
+                .map(|step| {
+                    let r = row.checked_add_signed(dr * step)?;
+                    let c = column.checked_add_signed(dc * step)?;
+                    self.get(r, c)
+                })
+                .take_while(|&cell| cell.is_some() && cell == mark)
+                .count()
+        };
+        [(0, 1), (1, 0), (1, 1), (1, -1)]
+            .into_iter()
+            .any(|(dr, dc)| 1 + run(dr, dc) + run(-dr, -dc) >= TO_WIN)
+    }
+}
+
+impl Default for OrderChaos {
+    fn default() -> OrderChaos {
+        OrderChaos::new()
+    }
+}
+
+impl fmt::Display for OrderChaos {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.cells.chunks(WIDTH) {
+            for (column, cell) in row.iter().enumerate() {
+                if column > 0 {
+                    write!(f, " ")?;
+                }
+                match cell {
+                    Some(mark) => write!(f, "{}", mark)?,
+                    None => write!(f, ".")?,
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+fn index(row: usize, column: usize) -> Option<usize> {
+    (row < WIDTH && column < WIDTH).then_some(row * WIDTH + column)
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum OrderChaosError {
+    OffBoard {
+        row: usize,
+        column: usize,
+    },
+    Occupied {
+        row: usize,
+        column: usize,
+        occupied_by: Player,
+    },
+    GameOver,
+}
+
+impl fmt::Display for OrderChaosError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OrderChaosError::OffBoard { row, column } => {
+                write!(f, "({}, {}) is off the board", row, column)
+            }
+            OrderChaosError::Occupied {
+                row,
+                column,
+                occupied_by,
+            } => write!(
+                f,
+                "({}, {}) is already occupied by {}",
+                row, column, occupied_by
+            ),
+            OrderChaosError::GameOver => write!(f, "the game is already over"),
+        }
+    }
+}
+
+impl error::Error for OrderChaosError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn order_wins() {
+        let mut game = OrderChaos::new();
+        // Order lines up noughts along the second row while Chaos plays
+        // crosses elsewhere; either side's marks count
+        for column in 0..4 {
+            game.play(1, column, Player::Nought).unwrap();
+            game.play(5, column, Player::Cross).unwrap();
+        }
+        assert_eq!(None, game.winner());
+        assert_eq!(ORDER, game.to_move());
+        game.play(1, 4, Player::Nought).unwrap();
+        assert_eq!(Some(ORDER), game.winner());
+        assert_eq!(
+            "X wins by five in a row",
+            game.result().unwrap().to_string()
+        );
+        assert_eq!(
+            Err(OrderChaosError::GameOver),
+            game.play(0, 0, Player::Cross)
+        );
+        assert_eq!((CHAOS, Player::Cross, 5, 0), game.moves()[1]);
+
+        // a line Chaos completes still counts for Order
+        let mut game = OrderChaos::new();
+        for row in 0..4 {
+            game.play(row, row, Player::Cross).unwrap();
+            game.play(row, 5, Player::Nought).unwrap();
+        }
+        game.play(5, 0, Player::Nought).unwrap();
+        game.play(4, 4, Player::Cross).unwrap();
+        assert_eq!(Some(ORDER), game.winner());
+    }
+
+    #[test]
+    fn chaos_wins() {
+        let mut game = OrderChaos::new();
+        // two of each mark along every row, and no more than two in a row
+        // along the columns and diagonals either
+        for row in 0..WIDTH {
+            for column in 0..WIDTH {
+                let mark = if (2 * row + column) % 4 < 2 {
+                    Player::Cross
+                } else {
+                    Player::Nought
+                };
+                assert!(!game.is_over());
+                game.play(row, column, mark).unwrap();
+            }
+        }
+        assert_eq!(
+            Some(GameResult {
+                winner: Some(CHAOS),
+                reason: WinReason::BoardFull
+            }),
+            game.result()
+        );
+        assert_eq!("Chaos", side_name(CHAOS));
+        assert_eq!(
+            "X X O O X X\nO O X X O O\nX X O O X X\nO O X X O O\nX X O O X X\nO O X X O O\n",
+            game.to_string()
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let mut game = OrderChaos::new();
+        game.play(0, 0, Player::Cross).unwrap();
+        assert_eq!(
+            Err(OrderChaosError::Occupied {
+                row: 0,
+                column: 0,
+                occupied_by: Player::Cross
+            }),
+            game.play(0, 0, Player::Nought)
+        );
+        assert_eq!(
+            Err(OrderChaosError::OffBoard { row: 6, column: 0 }),
+            game.play(6, 0, Player::Nought)
+        );
+        assert_eq!(CHAOS, game.to_move());
+    }
+}
@@ -0,0 +1,191 @@
+// a line-based text protocol in the style of UCI, so that GUIs and referee
+// scripts can run the engine as a subprocess:
+//
+//     uci                                  -> id name ..., uciok
+//     isready                              -> readyok
+//     newgame                                 forgets the previous game
+//     position startpos [moves 5 1 ...]       crosses move first
+//     go [movetime <ms>]                   -> info ..., bestmove <pos>|none
+//     quit
+//
+// anything the engine can't make sense of is answered with an
+// `info string` line and otherwise ignored
+
+use std::io::{self, BufRead, Write};
+use std::time::Duration;
+use std::vec::Vec;
+
+use crate::ai::{Engine, SearchResult};
+use crate::{Board, Player, Pos};
+
+pub const NAME: &str = "tic-tac-toe";
+
+// serves commands from `input` until `quit` or the end of the input
+pub fn run(input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+    let mut session = Session::new();
+    for line in input.lines() {
+        let line = line?;
+        let mut words = line.split_whitespace();
+        match words.next() {
+            None => {}
+            Some("uci") => {
+                writeln!(output, "id name {}", NAME)?;
+                writeln!(output, "uciok")?;
+            }
+            Some("isready") => writeln!(output, "readyok")?,
+            Some("newgame" | "ucinewgame") => session = Session::new(),
+            Some("position") => {
+                if let Err(message) = session.position(words) {
+                    writeln!(output, "info string {}", message)?;
+                }
+            }
+            Some("go") => session.go(words, &mut output)?,
+            Some("quit") => break,
+            Some(command) => writeln!(output, "info string unknown command {}", command)?,
+        }
+        output.flush()?;
+    }
+    Ok(())
+}
+
+struct Session {
+    engine: Engine,
+    board: Board,
+    to_move: Player,
+}
+
+impl Session {
+    fn new() -> Session {
+        Session {
+            engine: Engine::new(),
+            board: Board::new(),
+            to_move: Player::Cross,
+        }
+    }
+
+    fn position<'a>(&mut self, mut words: impl Iterator<Item = &'a str>) -> Result<(), String> {
+        if words.next() != Some("startpos") {
+            return Err("expected position startpos".to_string());
+        }
+        let mut board = Board::new();
+        let mut to_move = Player::Cross;
+        match words.next() {
+            None => {}
+            Some("moves") => {
+                for word in words {
+                    let pos: Pos = word.parse().map_err(|_| format!("invalid move {}", word))?;
+                    board
+                        .place(pos, to_move)
+                        .map_err(|err| format!("illegal move {}: {}", word, err))?;
+                    to_move = to_move.toggle();
+                }
+            }
+            Some(word) => return Err(format!("unexpected {}", word)),
+        }
+        self.board = board;
+        self.to_move = to_move;
+        Ok(())
+    }
+
+    fn go<'a>(
+        &mut self,
+        mut words: impl Iterator<Item = &'a str>,
+        output: &mut impl Write,
+    ) -> io::Result<()> {
+        let result = match (words.next(), words.next().map(str::parse::<u64>)) {
+            (None, _) => self.engine.search(&self.board, self.to_move),
+            (Some("movetime"), Some(Ok(millis))) => {
+                let budget = Duration::from_millis(millis);
+                self.engine.search_timed(&self.board, self.to_move, budget)
+            }
+            _ => {
+                writeln!(output, "info string expected go [movetime <ms>]")?;
+                return Ok(());
+            }
+        };
+        match result {
+            Some(SearchResult {
+                best,
+                score,
+                pv,
+                depth,
+            }) => {
+                let pv: Vec<String> = pv.iter().map(Pos::to_string).collect();
+                let stats = self.engine.stats();
+                writeln!(
+                    output,
+                    "info depth {} nodes {} time {} score {} pv {}",
+                    depth,
+                    stats.nodes,
+                    stats.elapsed.as_millis(),
+                    score,
+                    pv.join(" ")
+                )?;
+                writeln!(output, "bestmove {}", best)
+            }
+            None => writeln!(output, "bestmove none"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(input: &str) -> String {
+        let mut output = Vec::new();
+        run(input.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn handshake() {
+        assert_eq!(
+            "id name tic-tac-toe\nuciok\nreadyok\n",
+            session("uci\nisready\n")
+        );
+    }
+
+    #[test]
+    fn go() {
+        let output = session("position startpos moves 1 4 2 5\ngo\n");
+        assert!(output.starts_with("info depth "), "{output}");
+        assert!(output.contains(" nodes "), "{output}");
+        assert!(output.ends_with(" score 99 pv 3\nbestmove 3\n"), "{output}");
+        let output = session("position startpos moves a3 b2 b3\ngo movetime 1000\n");
+        assert!(output.ends_with("bestmove 3\n"), "{output}");
+        assert_eq!(
+            "bestmove none\n",
+            session("position startpos moves 1 4 2 5 3\ngo\n")
+        );
+    }
+
+    #[test]
+    fn quit_stops_reading() {
+        assert_eq!("", session("quit\nisready\n"));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            "info string illegal move 1: position 1 is already occupied by X\n",
+            session("position startpos moves 1 1\n")
+        );
+        assert_eq!(
+            "info string invalid move z9\n",
+            session("position startpos moves z9\n")
+        );
+        assert_eq!(
+            "info string expected position startpos\n",
+            session("position\n")
+        );
+        assert_eq!("info string unknown command fly\n", session("fly\n"));
+        assert_eq!(
+            "info string expected go [movetime <ms>]\n",
+            session("go movetime soon\n")
+        );
+        // a bad position leaves the previous one in place
+        let output = session("position startpos moves 5\nposition startpos moves 5 5\ngo\n");
+        assert!(output.contains("score 0"), "{output}");
+    }
+}
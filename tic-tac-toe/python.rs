@@ -0,0 +1,244 @@
+// Python bindings, for scripting self-play and analysis from notebooks. Built
+// as an extension module with e.g. `maturin develop --features python`, then
+//
+//     import tic_tac_toe
+//     game = tic_tac_toe.Game()
+//     while game.result is None:
+//         game.ai_move("easy", seed=len(game.moves))
+//     print(game.board, game.result)
+//
+// players are the strings "X" and "O" and positions the numbers 1-9, and a
+// refused move raises ValueError with the same message the Rust error has
+
+use std::string::{String, ToString};
+use std::vec::Vec;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::ai::{Difficulty, DifficultyAi, Engine};
+use crate::{Board, Cell, Game, Player, Pos};
+
+fn value_error(err: impl ToString) -> PyErr {
+    PyValueError::new_err(err.to_string())
+}
+
+fn pos(n: usize) -> PyResult<Pos> {
+    Pos::new(n).ok_or_else(|| value_error(format!("there is no cell {}", n)))
+}
+
+fn player(symbol: &str) -> PyResult<Player> {
+    symbol.parse().map_err(value_error)
+}
+
+#[pyclass(name = "Board")]
+#[derive(Clone)]
+pub struct PyBoard(pub Board);
+
+#[pymethods]
+impl PyBoard {
+    #[new]
+    fn new() -> PyBoard {
+        PyBoard(Board::new())
+    }
+
+    // "X", "O" or None
+    fn cell(&self, n: usize) -> PyResult<Option<String>> {
+        Ok(match self.0.cells[pos(n)?.get() - 1] {
+            Cell::Occupied(player) => Some(player.to_string()),
+            Cell::Vacant => None,
+        })
+    }
+
+    fn place(&mut self, n: usize, symbol: &str) -> PyResult<()> {
+        self.0.place(pos(n)?, player(symbol)?).map_err(value_error)
+    }
+
+    fn legal_moves(&self) -> Vec<usize> {
+        self.0.legal_moves().map(Pos::get).collect()
+    }
+
+    fn winner(&self) -> Option<String> {
+        self.0.winner().map(|player| player.to_string())
+    }
+
+    fn is_over(&self) -> bool {
+        self.0.is_over()
+    }
+
+    fn __str__(&self) -> String {
+        self.0.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Board({:?})", self.0.to_compact())
+    }
+}
+
+// `Game` holds event callbacks that aren't Sync, so it stays on the
+// thread that made it
+#[pyclass(name = "Game", unsendable)]
+pub struct PyGame(pub Game);
+
+#[pymethods]
+impl PyGame {
+    #[new]
+    fn new() -> PyGame {
+        PyGame(Game::new())
+    }
+
+    fn play(&mut self, n: usize) -> PyResult<()> {
+        self.0.play(pos(n)?).map_err(value_error)
+    }
+
+    // lets the engine move for whoever is to move at "easy", "medium" or
+    // "hard", and returns the cell it chose
+    #[pyo3(signature = (difficulty = "hard", seed = 0))]
+    fn ai_move(&mut self, difficulty: &str, seed: u64) -> PyResult<usize> {
+        let difficulty: Difficulty = difficulty
+            .parse()
+            .map_err(|err| value_error(format!("{}: {}", difficulty, err)))?;
+        let mut ai = DifficultyAi::new(difficulty, seed);
+        let pos = self.0.play_ai(&mut ai).map_err(value_error)?;
+        Ok(pos.get())
+    }
+
+    fn resign(&mut self, symbol: &str) -> PyResult<()> {
+        self.0.resign(player(symbol)?).map_err(value_error)?;
+        Ok(())
+    }
+
+    #[getter]
+    fn board(&self) -> PyBoard {
+        PyBoard(*self.0.board())
+    }
+
+    #[getter]
+    fn to_move(&self) -> String {
+        self.0.to_move().to_string()
+    }
+
+    #[getter]
+    fn moves(&self) -> Vec<usize> {
+        let moves = self.0.transcript().moves();
+        moves.iter().map(|(_, pos)| pos.get()).collect()
+    }
+
+    #[getter]
+    fn winner(&self) -> Option<String> {
+        let result = self.0.result()?;
+        result.winner.map(|player| player.to_string())
+    }
+
+    // e.g. "X wins by three in a row", or None while the game goes on
+    #[getter]
+    fn result(&self) -> Option<String> {
+        self.0.result().map(|result| result.to_string())
+    }
+
+    fn __str__(&self) -> String {
+        self.0.transcript().to_string()
+    }
+}
+
+// the engine's choice for `symbol` to move on `board`, or None once it's
+// over
+#[pyfunction]
+fn best_move(board: &PyBoard, symbol: &str) -> PyResult<Option<usize>> {
+    let mut engine = Engine::new();
+    Ok(engine.best_move(&board.0, player(symbol)?).map(Pos::get))
+}
+
+// the minimax score from `symbol`'s side: positive wins, negative loses
+// and 0 draws with best play
+#[pyfunction]
+fn evaluate(board: &PyBoard, symbol: &str) -> PyResult<i32> {
+    let mut engine = Engine::new();
+    Ok(engine.evaluate(&board.0, player(symbol)?))
+}
+
+#[pymodule]
+fn tic_tac_toe(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyBoard>()?;
+    module.add_class::<PyGame>()?;
+    module.add_function(wrap_pyfunction!(best_move, module)?)?;
+    module.add_function(wrap_pyfunction!(evaluate, module)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::types::PyDict;
+    use std::ffi::CString;
+
+    // runs `code` with the module imported as `tic_tac_toe`, and returns
+    // what it left in `out`
+    fn run<T: for<'py> FromPyObject<'py>>(code: &str) -> PyResult<T> {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let module = PyModule::new(py, "tic_tac_toe")?;
+            tic_tac_toe(&module)?;
+            let locals = PyDict::new(py);
+            locals.set_item("tic_tac_toe", module)?;
+            py.run(&CString::new(code).unwrap(), None, Some(&locals))?;
+            locals.get_item("out")?.unwrap().extract()
+        })
+    }
+
+    #[test]
+    fn game() {
+        let code = r#"
+game = tic_tac_toe.Game()
+for n in [1, 4, 2, 5]:
+    game.play(n)
+try:
+    game.play(1)
+except ValueError as err:
+    refused = str(err)
+out = (refused, game.ai_move(), game.winner, game.result, game.moves, repr(game.board))
+"#;
+        let out: (String, usize, String, String, Vec<usize>, String) = run(code).unwrap();
+        assert_eq!(
+            (
+                "position 1 is already occupied by X".to_string(),
+                3,
+                "X".to_string(),
+                "X wins by three in a row".to_string(),
+                vec![1, 4, 2, 5, 3],
+                r#"Board("XXXOO....")"#.to_string()
+            ),
+            out
+        );
+    }
+
+    #[test]
+    fn self_play() {
+        // hard against hard always draws
+        let code = r#"
+game = tic_tac_toe.Game()
+while game.result is None:
+    game.ai_move("hard", seed=len(game.moves))
+out = (game.winner, len(game.moves))
+"#;
+        let out: (Option<String>, usize) = run(code).unwrap();
+        assert_eq!((None, 9), out);
+    }
+
+    #[test]
+    fn analysis() {
+        let code = r#"
+board = tic_tac_toe.Board()
+board.place(1, "X")
+board.place(2, "X")
+board.place(5, "O")
+out = (tic_tac_toe.best_move(board, "O"), tic_tac_toe.evaluate(board, "X") > 0, board.cell(5), board.legal_moves())
+"#;
+        let out: (Option<usize>, bool, Option<String>, Vec<usize>) = run(code).unwrap();
+        assert_eq!(
+            (Some(3), true, Some("O".to_string()), vec![3, 4, 6, 7, 8, 9]),
+            out
+        );
+        assert!(run::<usize>("out = tic_tac_toe.Board().cell(10)").is_err());
+    }
+}
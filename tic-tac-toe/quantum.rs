@@ -0,0 +1,427 @@
+// quantum tic-tac-toe: each move puts a "spooky" mark in two cells at once,
+// entangling them. When the entanglements form a cycle, the mark that closed
+// it has to collapse into one of its two cells, chosen by the other player,
+// and every mark sharing a cell with a collapsed one is pushed into its other
+// cell in turn. Only collapsed, classical marks make lines
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::error;
+use core::fmt;
+use core::str;
+
+use crate::{Board, ParsePosError, Player, Pos};
+
+// the two cells a move is spread across; when only one cell is left the
+// final move goes there classically, and both cells are that one
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct QuantumMove {
+    pub first: Pos,
+    pub second: Pos,
+}
+
+// `1+5`
+impl fmt::Display for QuantumMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}+{}", self.first, self.second)
+    }
+}
+
+impl str::FromStr for QuantumMove {
+    type Err = ParsePosError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (first, second) = s.split_once('+').ok_or(ParsePosError {})?;
+        Ok(QuantumMove {
+            first: first.parse()?,
+            second: second.parse()?,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Mark {
+    player: Player,
+    cells: [Pos; 2],
+    // the cell it ended up in, once it has collapsed
+    collapsed: Option<Pos>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct QuantumGame {
+    // every move so far; a mark's subscript is its index plus one
+    marks: Vec<Mark>,
+    // the mark in each cell that has collapsed, by index into `marks`
+    classical: [Option<usize>; Board::SIZE],
+    to_move: Player,
+    // the mark that closed a cycle and is waiting for `to_move` to collapse it
+    pending: Option<usize>,
+    winner: Option<Player>,
+}
+
+impl QuantumGame {
+    pub fn new() -> QuantumGame {
+        QuantumGame {
+            marks: Vec::new(),
+            classical: [None; Board::SIZE],
+            to_move: Player::Cross,
+            pending: None,
+            winner: None,
+        }
+    }
+
+    // the player who has to move, or to choose a collapse if one is pending
+    pub fn to_move(&self) -> Player {
+        self.to_move
+    }
+
+    // the collapsed mark in `pos`, with its subscript
+    pub fn classical(&self, pos: Pos) -> Option<(Player, usize)> {
+        let mark = self.classical[pos.get() - 1]?;
+        Some((self.marks[mark].player, mark + 1))
+    }
+
+    // the uncollapsed marks in `pos`, with their subscripts
+    pub fn spooky(&self, pos: Pos) -> impl Iterator<Item = (Player, usize)> + '_ {
+        self.marks
+            .iter()
+            .enumerate()
+            .filter_map(move |(index, mark)| {
+                (mark.collapsed.is_none() && mark.cells.contains(&pos))
+                    .then_some((mark.player, index + 1))
+            })
+    }
+
+    // the two cells the last move could collapse into, if it closed a cycle;
+    // `to_move` has to pick one with `collapse` before anyone moves again
+    pub fn pending_collapse(&self) -> Option<[Pos; 2]> {
+        self.pending.map(|mark| self.marks[mark].cells)
+    }
+
+    pub fn play(&mut self, mv: QuantumMove) -> Result<(), QuantumError> {
+        if self.is_over() {
+            return Err(QuantumError::GameOver);
+        }
+        if self.pending.is_some() {
+            return Err(QuantumError::CollapsePending);
+        }
+        for pos in [mv.first, mv.second] {
+            if self.classical[pos.get() - 1].is_some() {
+                return Err(QuantumError::Classical { pos });
+            }
+        }
+
+        let free = self.free_cells();
+        if mv.first == mv.second {
+            if free.len() != 1 {
+                return Err(QuantumError::SameCell);
+            }
+            let mark = self.push(mv);
+            self.settle(mark, mv.first);
+        } else {
+            // the new mark closes a cycle if its cells are already entangled
+            let cycle = self.entangled(mv.first, mv.second);
+            let mark = self.push(mv);
+            if cycle {
+                self.pending = Some(mark);
+            }
+        }
+        self.to_move = self.to_move.toggle();
+        Ok(())
+    }
+
+    // collapses the pending mark into `pos`, one of its two cells
+    pub fn collapse(&mut self, pos: Pos) -> Result<(), QuantumError> {
+        let mark = self.pending.ok_or(QuantumError::NoCollapsePending)?;
+        if !self.marks[mark].cells.contains(&pos) {
+            return Err(QuantumError::NotACollapse { pos });
+        }
+        self.pending = None;
+        self.settle(mark, pos);
+        Ok(())
+    }
+
+    // set once a collapse makes a line; if it makes lines for both players,
+    // the one whose line was finished by the earlier move wins
+    pub fn winner(&self) -> Option<Player> {
+        self.winner
+    }
+
+    pub fn is_draw(&self) -> bool {
+        self.winner.is_none() && self.classical.iter().all(Option::is_some)
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.winner.is_some() || self.is_draw()
+    }
+
+    fn push(&mut self, mv: QuantumMove) -> usize {
+        self.marks.push(Mark {
+            player: self.to_move,
+            cells: [mv.first, mv.second],
+            collapsed: None,
+        });
+        self.marks.len() - 1
+    }
+
+    fn free_cells(&self) -> Vec<Pos> {
+        Pos::all()
+            .filter(|pos| self.classical[pos.get() - 1].is_none())
+            .collect()
+    }
+
+    // whether uncollapsed marks connect `from` to `to`
+    fn entangled(&self, from: Pos, to: Pos) -> bool {
+        let mut seen = [false; Board::SIZE];
+        let mut stack = vec![from];
+        while let Some(pos) = stack.pop() {
+            if pos == to {
+                return true;
+            }
+            if seen[pos.get() - 1] {
+                continue;
+            }
+            seen[pos.get() - 1] = true;
+            for mark in self.marks.iter().filter(|mark| mark.collapsed.is_none()) {
+                let [a, b] = mark.cells;
+                if a == pos {
+                    stack.push(b);
+                } else if b == pos {
+                    stack.push(a);
+                }
+            }
+        }
+        false
+    }
+
+    // puts `mark` in `pos` for good, pushes every other mark there into its
+    // other cell, and so on, then looks for lines
+    fn settle(&mut self, mark: usize, pos: Pos) {
+        let mut stack = vec![(mark, pos)];
+        while let Some((mark, pos)) = stack.pop() {
+            if self.marks[mark].collapsed.is_some() {
+                continue;
+            }
+            self.marks[mark].collapsed = Some(pos);
+            self.classical[pos.get() - 1] = Some(mark);
+            for (other, displaced) in self.marks.iter().enumerate() {
+                if displaced.collapsed.is_none() && displaced.cells.contains(&pos) {
+                    let [a, b] = displaced.cells;
+                    stack.push((other, if a == pos { b } else { a }));
+                }
+            }
+        }
+
+        // each player's line finished earliest, by its highest subscript
+        let earliest = |player| {
+            Board::lines()
+                .filter_map(|(_, positions)| {
+                    positions
+                        .iter()
+                        .map(|pos| {
+                            let mark = self.classical[pos.get() - 1]?;
+                            (self.marks[mark].player == player).then_some(mark)
+                        })
+                        .collect::<Option<Vec<usize>>>()
+                        .and_then(|marks| marks.into_iter().max())
+                })
+                .min()
+        };
+        self.winner = match (earliest(Player::Cross), earliest(Player::Nought)) {
+            (Some(cross), Some(nought)) if nought < cross => Some(Player::Nought),
+            (Some(_), _) => Some(Player::Cross),
+            (None, Some(_)) => Some(Player::Nought),
+            (None, None) => None,
+        };
+    }
+}
+
+impl Default for QuantumGame {
+    fn default() -> QuantumGame {
+        QuantumGame::new()
+    }
+}
+
+// classical marks in capitals and spooky ones in lower case, each with its
+// subscript, e.g. `X1` or `x3 o4`
+impl fmt::Display for QuantumGame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let cells: Vec<String> = Pos::all()
+            .map(|pos| {
+                if let Some((player, subscript)) = self.classical(pos) {
+                    return format!("{}{}", player, subscript);
+                }
+                let spooky: Vec<String> = self
+                    .spooky(pos)
+                    .map(|(player, subscript)| {
+                        format!("{}{}", player.to_string().to_lowercase(), subscript)
+                    })
+                    .collect();
+                if spooky.is_empty() {
+                    ".".into()
+                } else {
+                    spooky.join(" ")
+                }
+            })
+            .collect();
+        let width = cells.iter().map(String::len).max().unwrap_or(1);
+
+        for (row, cells) in cells.chunks(Board::WIDTH).enumerate() {
+            if row > 0 {
+                writeln!(f, "{}", vec!["-".repeat(width + 2); Board::WIDTH].join("+"))?;
+            }
+            let line: Vec<String> = cells
+                .iter()
+                .map(|cell| format!(" {:width$} ", cell, width = width))
+                .collect();
+            writeln!(f, "{}", line.join("|").trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum QuantumError {
+    // a spooky mark needs two different cells
+    SameCell,
+    // `pos` already has a collapsed mark
+    Classical { pos: Pos },
+    // the last move closed a cycle, which has to collapse first
+    CollapsePending,
+    NoCollapsePending,
+    // `pos` isn't one of the pending mark's cells
+    NotACollapse { pos: Pos },
+    GameOver,
+}
+
+impl fmt::Display for QuantumError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            QuantumError::SameCell => write!(f, "a move needs two different cells"),
+            QuantumError::Classical { pos } => write!(f, "position {} is already decided", pos),
+            QuantumError::CollapsePending => write!(f, "the cycle has to collapse first"),
+            QuantumError::NoCollapsePending => write!(f, "there is nothing to collapse"),
+            QuantumError::NotACollapse { pos } => {
+                write!(f, "the last move can't collapse into {}", pos)
+            }
+            QuantumError::GameOver => write!(f, "the game is already over"),
+        }
+    }
+}
+
+impl error::Error for QuantumError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::pos;
+
+    // moves like `1+2`, and collapses like `!2`
+    fn game(moves: &str) -> QuantumGame {
+        let mut game = QuantumGame::new();
+        for s in moves.split_whitespace() {
+            match s.strip_prefix('!') {
+                Some(cell) => game.collapse(cell.parse().unwrap()).unwrap(),
+                None => game.play(s.parse().unwrap()).unwrap(),
+            }
+        }
+        game
+    }
+
+    #[test]
+    fn parse_move() {
+        let mv: QuantumMove = "1+c1".parse().unwrap();
+        assert_eq!((pos(1), pos(9)), (mv.first, mv.second));
+        assert_eq!("1+9", mv.to_string());
+        assert!("19".parse::<QuantumMove>().is_err());
+    }
+
+    #[test]
+    fn cycle_collapses() {
+        let mut game = game("1+2 2+3");
+        assert_eq!(None, game.pending_collapse());
+        assert_eq!(
+            vec![(Player::Cross, 1), (Player::Nought, 2)],
+            game.spooky(pos(2)).collect::<Vec<_>>()
+        );
+
+        // closing the triangle leaves noughts to choose where x3 goes
+        game.play("3+1".parse().unwrap()).unwrap();
+        assert_eq!(Some([pos(3), pos(1)]), game.pending_collapse());
+        assert_eq!(Player::Nought, game.to_move());
+        assert_eq!(
+            Err(QuantumError::CollapsePending),
+            game.play("4+5".parse().unwrap())
+        );
+        assert_eq!(
+            Err(QuantumError::NotACollapse { pos: pos(2) }),
+            game.collapse(pos(2))
+        );
+
+        // x3 in 1 pushes x1 into 2, which pushes o2 into 3
+        game.collapse(pos(1)).unwrap();
+        assert_eq!(Some((Player::Cross, 3)), game.classical(pos(1)));
+        assert_eq!(Some((Player::Cross, 1)), game.classical(pos(2)));
+        assert_eq!(Some((Player::Nought, 2)), game.classical(pos(3)));
+        assert_eq!(0, game.spooky(pos(1)).count());
+        assert_eq!(None, game.winner());
+        assert_eq!(Err(QuantumError::NoCollapsePending), game.collapse(pos(1)));
+        assert_eq!(
+            Err(QuantumError::Classical { pos: pos(2) }),
+            game.play("2+5".parse().unwrap())
+        );
+        assert_eq!(
+            Err(QuantumError::SameCell),
+            game.play("5+5".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn wins() {
+        let game = game("1+2 1+2 !2 5+6 5+6 !6 9+8 9+8 !8");
+        assert_eq!(Some(Player::Cross), game.winner());
+        assert!(game.is_over());
+        let mut game = game;
+        assert_eq!(
+            Err(QuantumError::GameOver),
+            game.play("3+4".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn earlier_line_wins() {
+        // the last collapse completes both rows; crosses' was finished by x5
+        // and noughts' by o6
+        let game = game("1+4 1+4 !4 2+5 2+5 !5 3+6 3+6 !6");
+        assert_eq!(Some((Player::Nought, 6)), game.classical(pos(6)));
+        assert_eq!(Some(Player::Cross), game.winner());
+    }
+
+    #[test]
+    fn last_cell_is_classical() {
+        let mut game = game("1+2 1+2 !2 4+5 4+5 !5 3+6 3+6 !6 8+7 8+7 !7");
+        assert_eq!(
+            Err(QuantumError::Classical { pos: pos(1) }),
+            game.play("1+9".parse().unwrap())
+        );
+        game.play("9+9".parse().unwrap()).unwrap();
+        assert_eq!(Some((Player::Cross, 9)), game.classical(pos(9)));
+        assert!(game.is_draw());
+        assert_eq!(None, game.winner());
+    }
+
+    #[test]
+    fn display() {
+        let expected = concat!(
+            " x1    | x1 o2 | o2\n",
+            "-------+-------+-------\n",
+            " .     | .     | .\n",
+            "-------+-------+-------\n",
+            " .     | .     | .\n",
+        );
+        assert_eq!(expected, game("1+2 2+3").to_string());
+        assert!(game("1+2 1+2 !2").to_string().starts_with(" X1 | O2 | ."));
+    }
+}
@@ -0,0 +1,419 @@
+// Qubic: tic-tac-toe on a 4x4x4 cube, where four in a row along any of the
+// 76 straight lines through the cube wins. Crosses always move first
+
+use alloc::vec::Vec;
+use core::array;
+use core::error;
+use core::fmt;
+use core::ops::RangeInclusive;
+use core::str;
+
+use crate::ai::Position;
+use crate::{Cell, ParsePosError, Player};
+
+// a cell of the cube; layers are stacked bottom to top and each layer is
+// laid out like a board, rows top to bottom and columns left to right
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Pos3d {
+    index: usize,
+}
+
+impl Pos3d {
+    // all zero-based
+    pub fn new(layer: usize, row: usize, column: usize) -> Option<Pos3d> {
+        let width = Board3d::WIDTH;
+        if layer >= width || row >= width || column >= width {
+            return None;
+        }
+        Some(Pos3d {
+            index: (layer * width + row) * width + column,
+        })
+    }
+
+    pub fn all() -> impl Iterator<Item = Pos3d> {
+        (0..Board3d::SIZE).map(|index| Pos3d { index })
+    }
+
+    pub fn layer(self) -> usize {
+        self.index / (Board3d::WIDTH * Board3d::WIDTH)
+    }
+
+    pub fn row(self) -> usize {
+        self.index / Board3d::WIDTH % Board3d::WIDTH
+    }
+
+    pub fn column(self) -> usize {
+        self.index % Board3d::WIDTH
+    }
+}
+
+// one-based layer, row and column, so `111` is the top left of the bottom
+// layer and `444` the bottom right of the top one
+impl fmt::Display for Pos3d {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}{}{}",
+            self.layer() + 1,
+            self.row() + 1,
+            self.column() + 1
+        )
+    }
+}
+
+impl str::FromStr for Pos3d {
+    type Err = ParsePosError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits: Vec<usize> = s
+            .chars()
+            .map(|c| c.to_digit(10).map(|d| d as usize))
+            .collect::<Option<_>>()
+            .ok_or(ParsePosError {})?;
+        match digits[..] {
+            [layer, row, column] if layer > 0 && row > 0 && column > 0 => {
+                Pos3d::new(layer - 1, row - 1, column - 1).ok_or(ParsePosError {})
+            }
+            _ => Err(ParsePosError {}),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Board3d {
+    cells: [Cell; Board3d::SIZE],
+}
+
+impl Board3d {
+    pub const WIDTH: usize = 4;
+    pub const SIZE: usize = Board3d::WIDTH * Board3d::WIDTH * Board3d::WIDTH;
+
+    pub const fn new() -> Board3d {
+        Board3d {
+            cells: [Cell::Vacant; Board3d::SIZE],
+        }
+    }
+
+    pub fn get(&self, pos: Pos3d) -> Cell {
+        self.cells[pos.index]
+    }
+
+    pub fn place(&mut self, pos: Pos3d, player: Player) -> Result<(), Place3dError> {
+        if self.is_over() {
+            return Err(Place3dError::GameOver);
+        }
+        match self.cells[pos.index] {
+            Cell::Occupied(occupied_by) => Err(Place3dError::Occupied { pos, occupied_by }),
+            Cell::Vacant => {
+                self.cells[pos.index] = Cell::Occupied(player);
+                Ok(())
+            }
+        }
+    }
+
+    // every winning line: the rows, columns and diagonals of each layer, the
+    // verticals and diagonals through the layers, and the four diagonals
+    // from corner to corner of the cube
+    pub fn lines() -> impl Iterator<Item = [Pos3d; Board3d::WIDTH]> {
+        // a line fills its whole length, so along each axis it either starts
+        // at the near edge and steps forward, starts at the far edge and
+        // steps back, or stays put anywhere
+        fn starts(step: isize) -> RangeInclusive<isize> {
+            let last = Board3d::WIDTH as isize - 1;
+            match step {
+                1 => 0..=0,
+                -1 => last..=last,
+                _ => 0..=last,
+            }
+        }
+
+        let mut lines = Vec::new();
+        // one direction of each opposite pair: the first non-zero step is +1
+        let directions = (0..27)
+            .map(|n| [n / 9 - 1, n / 3 % 3 - 1, n % 3 - 1])
+            .filter(|step| step.iter().find(|&&d| d != 0) == Some(&1));
+        for step in directions {
+            for layer in starts(step[0]) {
+                for row in starts(step[1]) {
+                    for column in starts(step[2]) {
+                        let start = [layer, row, column];
+                        lines.push(array::from_fn(|i| {
+                            let at = |axis: usize| (start[axis] + step[axis] * i as isize) as usize;
+                            Pos3d::new(at(0), at(1), at(2)).unwrap()
+                        }));
+                    }
+                }
+            }
+        }
+        lines.into_iter()
+    }
+
+    pub fn count(&self, player: Player) -> usize {
+        self.cells
+            .iter()
+            .filter(|&&cell| cell == Cell::Occupied(player))
+            .count()
+    }
+
+    // crosses move first, so it's noughts' turn whenever crosses are ahead
+    pub fn to_move(&self) -> Player {
+        if self.count(Player::Cross) > self.count(Player::Nought) {
+            Player::Nought
+        } else {
+            Player::Cross
+        }
+    }
+
+    pub fn winning_line(&self) -> Option<[Pos3d; Board3d::WIDTH]> {
+        Board3d::lines().find(|line| {
+            let first = self.get(line[0]);
+            first != Cell::Vacant && line.iter().all(|&pos| self.get(pos) == first)
+        })
+    }
+
+    pub fn winner(&self) -> Option<Player> {
+        match self.get(self.winning_line()?[0]) {
+            Cell::Occupied(player) => Some(player),
+            Cell::Vacant => None,
+        }
+    }
+
+    pub fn is_draw(&self) -> bool {
+        self.is_full() && self.winner().is_none()
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.is_full() || self.winner().is_some()
+    }
+
+    // the vacant cells, or none once the game is over
+    pub fn legal_moves(&self) -> impl Iterator<Item = Pos3d> + '_ {
+        let over = self.is_over();
+        Pos3d::all().filter(move |&pos| !over && self.get(pos) == Cell::Vacant)
+    }
+
+    fn is_full(&self) -> bool {
+        self.cells.iter().all(|&cell| cell != Cell::Vacant)
+    }
+}
+
+impl Default for Board3d {
+    fn default() -> Board3d {
+        Board3d::new()
+    }
+}
+
+// one layer after another from the bottom up, each headed with its number
+impl fmt::Display for Board3d {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for layer in 0..Board3d::WIDTH {
+            if layer > 0 {
+                writeln!(f)?;
+            }
+            writeln!(f, "layer {}", layer + 1)?;
+            for row in 0..Board3d::WIDTH {
+                for column in 0..Board3d::WIDTH {
+                    if column > 0 {
+                        write!(f, " ")?;
+                    }
+                    let pos = Pos3d::new(layer, row, column).unwrap();
+                    write!(f, "{}", self.get(pos).symbol())?;
+                }
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Position for Board3d {
+    type Move = Pos3d;
+
+    fn to_move(&self) -> Player {
+        Board3d::to_move(self)
+    }
+
+    fn moves(&self) -> Vec<Pos3d> {
+        self.legal_moves().collect()
+    }
+
+    fn play(&mut self, pos: Pos3d) {
+        let player = Board3d::to_move(self);
+        self.place(pos, player).unwrap();
+    }
+
+    fn winner(&self) -> Option<Player> {
+        Board3d::winner(self)
+    }
+
+    // lines only one side has pieces on, worth more the fuller they are
+    fn heuristic(&self, player: Player) -> i32 {
+        let mut score = 0;
+        for line in Board3d::lines() {
+            let mine = line
+                .iter()
+                .filter(|&&pos| self.get(pos) == Cell::Occupied(player))
+                .count() as i32;
+            let theirs = line
+                .iter()
+                .filter(|&&pos| self.get(pos) == Cell::Occupied(player.toggle()))
+                .count() as i32;
+            match (mine, theirs) {
+                (0, 0) => {}
+                (mine, 0) => score += mine * mine,
+                (0, theirs) => score -= theirs * theirs,
+                _ => {}
+            }
+        }
+        score.clamp(-50, 50)
+    }
+}
+
+// like `PlaceError`, with a position in the cube
+#[derive(Debug, Eq, PartialEq)]
+pub enum Place3dError {
+    Occupied { pos: Pos3d, occupied_by: Player },
+    // someone has already won or the cube is full
+    GameOver,
+}
+
+impl fmt::Display for Place3dError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Place3dError::Occupied { pos, occupied_by } => {
+                write!(f, "position {} is already occupied by {}", pos, occupied_by)
+            }
+            Place3dError::GameOver => write!(f, "the game is already over"),
+        }
+    }
+}
+
+impl error::Error for Place3dError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::{alpha_beta, Agent, RandomAi, Searcher, WIN};
+
+    fn pos(s: &str) -> Pos3d {
+        s.parse().unwrap()
+    }
+
+    fn board(moves: &str) -> Board3d {
+        let mut board = Board3d::new();
+        for s in moves.split_whitespace() {
+            board.place(pos(s), board.to_move()).unwrap();
+        }
+        board
+    }
+
+    #[test]
+    fn parse_pos() {
+        let parsed = pos("234");
+        assert_eq!((1, 2, 3), (parsed.layer(), parsed.row(), parsed.column()));
+        assert_eq!("234", parsed.to_string());
+        for invalid in ["", "23", "2345", "034", "235", "2a4"] {
+            assert!(invalid.parse::<Pos3d>().is_err(), "{invalid}");
+        }
+        assert_eq!(64, Pos3d::all().count());
+    }
+
+    #[test]
+    fn lines() {
+        let lines: Vec<_> = Board3d::lines().collect();
+        assert_eq!(76, lines.len());
+        for (i, line) in lines.iter().enumerate() {
+            assert!(!lines[..i].contains(line));
+        }
+        // the corners and the middle of the cube are on seven lines each,
+        // every other cell on four
+        let on = |pos: Pos3d| lines.iter().filter(|line| line.contains(&pos)).count();
+        assert_eq!(7, on(pos("111")));
+        assert_eq!(7, on(pos("444")));
+        assert_eq!(7, on(pos("223")));
+        assert_eq!(4, on(pos("112")));
+        assert_eq!(4, on(pos("122")));
+        assert!(lines.contains(&[pos("114"), pos("223"), pos("332"), pos("441")]));
+    }
+
+    #[test]
+    fn wins() {
+        // crosses fill the diagonal through the cube
+        let board = board("111 112 222 113 333 114 444");
+        assert_eq!(Some(Player::Cross), board.winner());
+        assert!(board.is_over());
+        assert_eq!(0, board.legal_moves().count());
+        let mut board = board;
+        assert_eq!(
+            Err(Place3dError::GameOver),
+            board.place(pos("441"), Player::Nought)
+        );
+
+        let mut board = Board3d::new();
+        board.place(pos("111"), Player::Cross).unwrap();
+        assert_eq!(Player::Nought, board.to_move());
+        assert_eq!(
+            Err(Place3dError::Occupied {
+                pos: pos("111"),
+                occupied_by: Player::Cross
+            }),
+            board.place(pos("111"), Player::Nought)
+        );
+        assert_eq!(None, board.winner());
+    }
+
+    #[test]
+    fn display() {
+        let expected = "\
+layer 1
+X . . .
+. . . .
+. . . .
+. . . .
+
+layer 2
+. . . .
+. O . .
+. . . .
+. . . .
+
+layer 3
+. . . .
+. . . .
+. . . .
+. . . .
+
+layer 4
+. . . .
+. . . .
+. . . .
+. . . X
+";
+        assert_eq!(expected, board("111 222 444").to_string());
+    }
+
+    #[test]
+    fn search() {
+        // crosses win along the bottom row rather than block
+        let (best, score) = alpha_beta(&board("111 211 112 212 113 213"), 2).unwrap();
+        assert_eq!(pos("114"), best);
+        assert_eq!(WIN - 1, score);
+
+        // noughts block it
+        let (best, _) = alpha_beta(&board("111 211 112 212 113"), 2).unwrap();
+        assert_eq!(pos("114"), best);
+
+        let mut board = Board3d::new();
+        let mut searcher = Searcher::new(1);
+        let mut random = RandomAi::new(3);
+        while !board.is_over() {
+            let pos = if board.to_move() == Player::Cross {
+                searcher.choose(&board)
+            } else {
+                random.choose(&board)
+            };
+            Position::play(&mut board, pos);
+        }
+        assert_ne!(Some(Player::Nought), board.winner());
+    }
+}
@@ -0,0 +1,417 @@
+// boards as SVG images, for web pages and documents. The image is square,
+// drawn on a 300x300 grid scaled to `SvgOptions::size`, so it stays sharp at
+// any size. `to_html` puts a whole game on one page, a diagram per move
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use crate::analysis::{annotate, Annotation};
+use crate::{ApplyError, Board, Cell, Game, LineId, Player, Pos, Transcript};
+
+const CELL: usize = 100;
+const SIDE: usize = CELL * Board::WIDTH;
+// how far a mark stays from the edges of its cell
+const MARGIN: usize = 22;
+
+const BACKGROUND: &str = "#fff";
+const INK: &str = "#222";
+const LAST_MOVE: &str = "#ffd966";
+const WINNING: &str = "#b6d7a8";
+const STRIKE: &str = "#c00";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SvgOptions {
+    // width and height, in pixels
+    pub size: u32,
+    // shaded behind the mark
+    pub last_move: Option<Pos>,
+    // its cells shaded and, unless it wraps around, struck through
+    pub line: Option<LineId>,
+}
+
+// 300 pixels across, nothing highlighted
+impl Default for SvgOptions {
+    fn default() -> SvgOptions {
+        SvgOptions {
+            size: SIDE as u32,
+            last_move: None,
+            line: None,
+        }
+    }
+}
+
+impl SvgOptions {
+    // highlights `game`'s last move and, once someone has won, the line they
+    // won with under the game's rules
+    pub fn for_game(game: &Game) -> SvgOptions {
+        let rules = game.config().rules;
+        SvgOptions {
+            last_move: game.transcript().moves().last().map(|&(_, pos)| pos),
+            line: rules.winning_line(game.board()).map(|(line, _)| line),
+            ..SvgOptions::default()
+        }
+    }
+}
+
+// the top-left corner of `pos`'s cell
+fn corner(pos: Pos) -> (usize, usize) {
+    (pos.column() * CELL, pos.row() * CELL)
+}
+
+fn centre(pos: Pos) -> (usize, usize) {
+    let (x, y) = corner(pos);
+    (x + CELL / 2, y + CELL / 2)
+}
+
+pub fn to_svg(board: &Board, options: SvgOptions) -> String {
+    let mut svg = String::new();
+    // writing to a String can't fail
+    write_svg(&mut svg, board, options).unwrap();
+    svg
+}
+
+fn write_svg(svg: &mut String, board: &Board, options: SvgOptions) -> core::fmt::Result {
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{size}" height="{size}" viewBox="0 0 {side} {side}">"#,
+        size = options.size,
+        side = SIDE
+    )?;
+    writeln!(
+        svg,
+        r#"<rect width="{0}" height="{0}" fill="{1}"/>"#,
+        SIDE, BACKGROUND
+    )?;
+
+    let shade = |svg: &mut String, pos: Pos, colour: &str| {
+        let (x, y) = corner(pos);
+        writeln!(
+            svg,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+            x, y, CELL, CELL, colour
+        )
+    };
+    if let Some(line) = options.line {
+        for pos in line.positions() {
+            shade(svg, pos, WINNING)?;
+        }
+    }
+    if let Some(pos) = options.last_move {
+        shade(svg, pos, LAST_MOVE)?;
+    }
+
+    for i in 1..Board::WIDTH {
+        let at = i * CELL;
+        writeln!(
+            svg,
+            r#"<line x1="{0}" y1="0" x2="{0}" y2="{1}" stroke="{2}" stroke-width="4"/>"#,
+            at, SIDE, INK
+        )?;
+        writeln!(
+            svg,
+            r#"<line x1="0" y1="{0}" x2="{1}" y2="{0}" stroke="{2}" stroke-width="4"/>"#,
+            at, SIDE, INK
+        )?;
+    }
+
+    for pos in Pos::all() {
+        let Cell::Occupied(player) = board.cells[pos.get() - 1] else {
+            continue;
+        };
+        let (x, y) = corner(pos);
+        let (near, far) = (MARGIN, CELL - MARGIN);
+        match player {
+            Player::Cross => writeln!(
+                svg,
+                r#"<path d="M{} {}L{} {}M{} {}L{} {}" stroke="{}" stroke-width="10" stroke-linecap="round"/>"#,
+                x + near,
+                y + near,
+                x + far,
+                y + far,
+                x + far,
+                y + near,
+                x + near,
+                y + far,
+                INK
+            )?,
+            Player::Nought => {
+                let (cx, cy) = centre(pos);
+                writeln!(
+                    svg,
+                    r#"<circle cx="{}" cy="{}" r="{}" fill="none" stroke="{}" stroke-width="10"/>"#,
+                    cx,
+                    cy,
+                    CELL / 2 - MARGIN,
+                    INK
+                )?
+            }
+            // the extra players of bigger games are drawn as their marks
+            other => {
+                let (cx, cy) = centre(pos);
+                writeln!(
+                    svg,
+                    r#"<text x="{}" y="{}" font-family="sans-serif" font-size="{}" text-anchor="middle" dominant-baseline="central" fill="{}">{}</text>"#,
+                    cx,
+                    cy,
+                    CELL - 2 * MARGIN,
+                    INK,
+                    other
+                )?
+            }
+        }
+    }
+
+    // a wrapped line's cells aren't in a straight row, so shading has to do
+    let straight = matches!(
+        options.line,
+        Some(LineId::Row(_) | LineId::Column(_) | LineId::Diagonal | LineId::AntiDiagonal)
+    );
+    if let (true, Some(line)) = (straight, options.line) {
+        let positions = line.positions();
+        let (x1, y1) = centre(positions[0]);
+        let (x2, y2) = centre(positions[Board::WIDTH - 1]);
+        writeln!(
+            svg,
+            r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-width="8" stroke-linecap="round"/>"#,
+            x1, y1, x2, y2, STRIKE
+        )?;
+    }
+    writeln!(svg, "</svg>")
+}
+
+// the diagrams on the HTML page
+const DIAGRAM: u32 = 160;
+const BLUNDER: &str = "#c00";
+
+// names and headers can hold anything
+fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// a standalone page going through `transcript` move by move, each one
+// drawn and judged against perfect play as `analysis::annotate` does, with
+// blunders marked and the better move named; games for more than two are
+// only drawn. It needs no stylesheet or script, so it can be mailed or
+// dropped on any web server
+pub fn to_html(transcript: &Transcript) -> Result<String, ApplyError> {
+    let game = transcript.replay()?;
+    let annotations = if transcript.config().players() > 2 {
+        Vec::new()
+    } else {
+        annotate(transcript)?
+    };
+    // the board after each move, from the handicap stones or random start
+    // the game began with
+    let boards = (1..=transcript.len())
+        .map(|n| {
+            let mut played = transcript.clone();
+            played.moves.truncate(n);
+            played.replay().map(|game| *game.board())
+        })
+        .collect::<Result<Vec<Board>, ApplyError>>()?;
+    let mut html = String::new();
+    // writing to a String can't fail
+    write_html(&mut html, transcript, &game, &boards, &annotations).unwrap();
+    Ok(html)
+}
+
+fn write_html(
+    html: &mut String,
+    transcript: &Transcript,
+    game: &Game,
+    boards: &[Board],
+    annotations: &[Annotation],
+) -> core::fmt::Result {
+    let config = transcript.config();
+    let names: Vec<String> = Player::all(config.players())
+        .map(|player| format!("{} ({})", escape(&config.seat(player).name), player))
+        .collect();
+    let title = names.join(" vs ");
+    writeln!(html, "<!DOCTYPE html>")?;
+    writeln!(html, r#"<html lang="en">"#)?;
+    writeln!(html, "<head>")?;
+    writeln!(html, r#"<meta charset="utf-8">"#)?;
+    writeln!(html, "<title>{}</title>", title)?;
+    writeln!(html, "<style>")?;
+    writeln!(html, "body {{ font-family: sans-serif; }}")?;
+    writeln!(
+        html,
+        "figure {{ display: inline-block; margin: 0 1em 1em 0; text-align: center; }}"
+    )?;
+    writeln!(html, ".blunder {{ color: {}; }}", BLUNDER)?;
+    writeln!(html, "</style>")?;
+    writeln!(html, "</head>")?;
+    writeln!(html, "<body>")?;
+    writeln!(html, "<h1>{}</h1>", title)?;
+    match game.result() {
+        Some(result) => writeln!(html, "<p>{}</p>", result)?,
+        None => writeln!(html, "<p>unfinished</p>")?,
+    }
+    writeln!(html, "<p>{}</p>", transcript)?;
+
+    let rules = config.rules;
+    for (number, (&(player, pos), board)) in transcript.moves().iter().zip(boards).enumerate() {
+        let options = SvgOptions {
+            size: DIAGRAM,
+            last_move: Some(pos),
+            line: rules.winning_line(board).map(|(line, _)| line),
+        };
+        let annotation = annotations.get(number);
+        let class = if annotation.is_some_and(Annotation::is_blunder) {
+            r#" class="blunder""#
+        } else {
+            ""
+        };
+        let caption = match annotation {
+            Some(annotation) => annotation.to_string(),
+            None => format!("{}{}", player, pos),
+        };
+        writeln!(html, "<figure>")?;
+        html.push_str(&to_svg(board, options));
+        writeln!(
+            html,
+            "<figcaption{}>{}. {}</figcaption>",
+            class,
+            number + 1,
+            caption
+        )?;
+        writeln!(html, "</figure>")?;
+    }
+    writeln!(html, "</body>")?;
+    writeln!(html, "</html>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::pos;
+    use crate::{GameConfig, Handicap, Rules};
+
+    #[test]
+    fn empty() {
+        let svg = to_svg(&Board::new(), SvgOptions::default());
+        assert!(svg.starts_with(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="300" height="300" viewBox="0 0 300 300">"#
+        ));
+        assert!(svg.ends_with("</svg>\n"));
+        // the background and the four grid lines
+        assert_eq!(1, svg.matches("<rect").count());
+        assert_eq!(4, svg.matches("<line").count());
+    }
+
+    #[test]
+    fn game() {
+        let mut game = Game::new();
+        game.apply(&[pos(1), pos(4), pos(5), pos(6), pos(9)])
+            .unwrap();
+        let options = SvgOptions::for_game(&game);
+        assert_eq!(Some(pos(9)), options.last_move);
+        assert_eq!(Some(LineId::Diagonal), options.line);
+
+        let svg = to_svg(
+            game.board(),
+            SvgOptions {
+                size: 120,
+                ..options
+            },
+        );
+        assert!(svg.contains(r#"width="120" height="120" viewBox="0 0 300 300""#));
+        assert_eq!(3, svg.matches(r#"<path d="#).count());
+        assert_eq!(2, svg.matches("<circle").count());
+        // a circle in the centre of 4
+        assert!(svg.contains(r#"<circle cx="50" cy="150" r="28""#));
+        assert_eq!(3, svg.matches(WINNING).count());
+        assert!(svg.contains(&format!(
+            r#"<rect x="200" y="200" width="100" height="100" fill="{}"/>"#,
+            LAST_MOVE
+        )));
+        assert!(svg.contains(&format!(
+            r#"<line x1="50" y1="50" x2="250" y2="250" stroke="{}""#,
+            STRIKE
+        )));
+    }
+
+    #[test]
+    fn wrapped() {
+        let config = GameConfig {
+            rules: Rules {
+                toroidal: true,
+                ..Rules::default()
+            },
+            ..GameConfig::default()
+        };
+        let mut game = Game::with_config(config);
+        game.apply(&[pos(2), pos(1), pos(6), pos(5), pos(7)])
+            .unwrap();
+        let options = SvgOptions::for_game(&game);
+        assert_eq!(Some(LineId::WrappedDiagonal(1)), options.line);
+        let svg = to_svg(game.board(), options);
+        assert_eq!(3, svg.matches(WINNING).count());
+        assert!(!svg.contains(STRIKE));
+    }
+
+    #[test]
+    fn html() {
+        let mut config = GameConfig::default();
+        config.cross.name = "<script>".into();
+        let mut game = Game::with_config(config);
+        // O's 2 throws away the draw
+        game.apply(&[pos(5), pos(2), pos(1), pos(9), pos(4), pos(6), pos(7)])
+            .unwrap();
+        let html = to_html(game.transcript()).unwrap();
+        assert!(html.starts_with("<!DOCTYPE html>\n"));
+        assert!(html.ends_with("</html>\n"));
+        assert!(html.contains("<h1>&lt;script&gt; (X) vs Nought (O)</h1>"));
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("<p>X wins by three in a row</p>"));
+        assert_eq!(7, html.matches("<svg").count());
+        assert!(html.contains(r#"<figcaption class="blunder">2. O2?? draw -> loss"#));
+        assert!(html.contains("<figcaption>7. X7</figcaption>"));
+        // the last diagram strikes through the winning line
+        assert_eq!(1, html.matches(&format!(r#"stroke="{}""#, STRIKE)).count());
+    }
+
+    #[test]
+    fn three_players() {
+        let mut game = Game::with_config(GameConfig::with_players(3));
+        game.apply(&[pos(1), pos(2), pos(3)]).unwrap();
+        let svg = to_svg(game.board(), SvgOptions::for_game(&game));
+        assert_eq!(1, svg.matches("<text").count());
+        assert!(svg.contains(r#"<text x="250" y="50""#));
+        assert!(svg.contains(">+</text>"));
+
+        let html = to_html(game.transcript()).unwrap();
+        assert!(html.contains("<h1>Cross (X) vs Nought (O) vs Player 3 (+)</h1>"));
+        assert!(html.contains("<figcaption>3. +3</figcaption>"));
+    }
+
+    #[test]
+    fn handicap() {
+        let config = GameConfig {
+            handicap: Some(Handicap {
+                player: Player::Nought,
+                stones: vec![pos(1)],
+                double_move: false,
+            }),
+            ..GameConfig::default()
+        };
+        let mut game = Game::with_config(config);
+        game.apply(&[pos(5)]).unwrap();
+        let html = to_html(game.transcript()).unwrap();
+        // the stone is drawn along with the move
+        assert_eq!(1, html.matches("<circle").count());
+        assert_eq!(1, html.matches("<path").count());
+    }
+}
@@ -0,0 +1,510 @@
+// saving a game to a file and picking it up again later. A save is a few
+// lines of text, the first giving the format's version:
+//
+//     tic-tac-toe save 1
+//     cross human Alice
+//     nought ai Computer
+//     other human Carol          anyone after noughts, in turn order
+//     first X
+//     rules gravity pie          only the rules that are on
+//     time 60000 1000            initial and increment, in milliseconds
+//     handicap O double 1 5      the player, `double` if so, then the stones
+//     random 2 12345             pieces and seed
+//     clocks 58000 60000         what's left for X and O
+//     result O resignation       only for results the moves don't decide
+//     moves X5 O1 X9
+//
+// loading replays the moves, so a save that's been edited into an illegal
+// game is refused rather than trusted
+
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+use std::string::{String, ToString};
+use std::time::Duration;
+use std::vec::Vec;
+
+use crate::{
+    ApplyError, ConfigError, Game, GameConfig, Handicap, ParseTranscriptError, Player, PlayerKind,
+    Pos, RandomStart, Rules, Seat, TimeControl, Transcript, WinReason,
+};
+
+// the version this code writes, and the newest it reads
+pub const VERSION: u32 = 1;
+
+const MAGIC: &str = "tic-tac-toe save";
+
+impl Game {
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut file = io::BufWriter::new(fs::File::create(path)?);
+        write_save(&mut file, self)?;
+        file.flush()
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Game, SaveError> {
+        read_save(BufReader::new(fs::File::open(path)?))
+    }
+}
+
+fn millis(duration: Duration) -> u128 {
+    duration.as_millis()
+}
+
+fn kind(kind: PlayerKind) -> &'static str {
+    match kind {
+        PlayerKind::Human => "human",
+        PlayerKind::Ai => "ai",
+    }
+}
+
+// how a result the moves don't decide is written; shared with `archive`
+pub(crate) fn reason_name(reason: WinReason) -> Option<&'static str> {
+    match reason {
+        WinReason::Resignation => Some("resignation"),
+        WinReason::Timeout => Some("timeout"),
+        WinReason::Forfeit => Some("forfeit"),
+        // replaying the moves gets these back
+        WinReason::Line | WinReason::BoardFull | WinReason::MisereLine | WinReason::FiveInARow => {
+            None
+        }
+    }
+}
+
+pub(crate) fn parse_reason(name: &str) -> Option<WinReason> {
+    match name {
+        "resignation" => Some(WinReason::Resignation),
+        "timeout" => Some(WinReason::Timeout),
+        "forfeit" => Some(WinReason::Forfeit),
+        _ => None,
+    }
+}
+
+// the names of the rules that are on, e.g. ["gravity", "pie"]
+pub(crate) fn rule_names(rules: Rules) -> Vec<&'static str> {
+    let flags = [
+        (rules.misere, "misere"),
+        (rules.wild, "wild"),
+        (rules.gravity, "gravity"),
+        (rules.toroidal, "toroidal"),
+        (rules.pie, "pie"),
+    ];
+    flags
+        .iter()
+        .filter(|(on, _)| *on)
+        .map(|&(_, name)| name)
+        .collect()
+}
+
+// the rules with just the named ones on, or None if a name is unknown
+pub(crate) fn parse_rules<'a>(names: impl Iterator<Item = &'a str>) -> Option<Rules> {
+    let mut rules = Rules::default();
+    for name in names {
+        let flag = match name {
+            "misere" => &mut rules.misere,
+            "wild" => &mut rules.wild,
+            "gravity" => &mut rules.gravity,
+            "toroidal" => &mut rules.toroidal,
+            "pie" => &mut rules.pie,
+            _ => return None,
+        };
+        *flag = true;
+    }
+    Some(rules)
+}
+
+// `<initial ms> <increment ms>`; this and the handicap and random start
+// formats below are shared with `archive`
+pub(crate) fn time_control_text(time_control: TimeControl) -> String {
+    format!(
+        "{} {}",
+        millis(time_control.initial),
+        millis(time_control.increment)
+    )
+}
+
+pub(crate) fn parse_time_control<'a>(
+    mut words: impl Iterator<Item = &'a str>,
+) -> Option<TimeControl> {
+    let [initial, increment] = durations(&mut words)?;
+    Some(TimeControl { initial, increment })
+}
+
+// the handicapped player, `double` if they also move twice, then the
+// stones, e.g. `O double 1 5`
+pub(crate) fn handicap_text(handicap: &Handicap) -> String {
+    let mut text = handicap.player.to_string();
+    if handicap.double_move {
+        text.push_str(" double");
+    }
+    for stone in &handicap.stones {
+        text.push_str(&format!(" {}", stone));
+    }
+    text
+}
+
+pub(crate) fn parse_handicap<'a>(mut words: impl Iterator<Item = &'a str>) -> Option<Handicap> {
+    let player = words.next()?.parse().ok()?;
+    let mut words = words.peekable();
+    let double_move = words.next_if_eq(&"double").is_some();
+    let stones = words
+        .map(|word| word.parse::<Pos>().ok())
+        .collect::<Option<Vec<Pos>>>()?;
+    Some(Handicap {
+        player,
+        stones,
+        double_move,
+    })
+}
+
+// `<pieces> <seed>`
+pub(crate) fn random_start_text(random_start: RandomStart) -> String {
+    format!("{} {}", random_start.pieces, random_start.seed)
+}
+
+pub(crate) fn parse_random_start<'a>(
+    mut words: impl Iterator<Item = &'a str>,
+) -> Option<RandomStart> {
+    let mut number = || words.next()?.parse::<u64>().ok();
+    let (pieces, seed) = (number()?, number()?);
+    Some(RandomStart {
+        pieces: pieces as usize,
+        seed,
+    })
+}
+
+pub fn write_save(mut writer: impl Write, game: &Game) -> io::Result<()> {
+    let transcript = game.transcript();
+    // the config the game started with, before any swap
+    let config = transcript.config();
+    writeln!(writer, "{} {}", MAGIC, VERSION)?;
+    writeln!(
+        writer,
+        "cross {} {}",
+        kind(config.cross.kind),
+        config.cross.name
+    )?;
+    writeln!(
+        writer,
+        "nought {} {}",
+        kind(config.nought.kind),
+        config.nought.name
+    )?;
+    for seat in &config.others {
+        writeln!(writer, "other {} {}", kind(seat.kind), seat.name)?;
+    }
+    writeln!(writer, "first {}", config.first)?;
+
+    let on = rule_names(config.rules);
+    if !on.is_empty() {
+        writeln!(writer, "rules {}", on.join(" "))?;
+    }
+    if let Some(time_control) = config.time_control {
+        writeln!(writer, "time {}", time_control_text(time_control))?;
+    }
+    if let Some(handicap) = &config.handicap {
+        writeln!(writer, "handicap {}", handicap_text(handicap))?;
+    }
+    if let Some(random_start) = config.random_start {
+        writeln!(writer, "random {}", random_start_text(random_start))?;
+    }
+    if config.time_control.is_some() {
+        writeln!(
+            writer,
+            "clocks {} {}",
+            millis(game.remaining[Player::Cross.index()]),
+            millis(game.remaining[Player::Nought.index()])
+        )?;
+    }
+    if let Some(result) = game.result() {
+        if let (Some(winner), Some(reason)) = (result.winner, reason_name(result.reason)) {
+            writeln!(writer, "result {} {}", winner, reason)?;
+        }
+    }
+    writeln!(writer, "moves {}", transcript)
+}
+
+pub fn read_save(reader: impl BufRead) -> Result<Game, SaveError> {
+    let mut lines = reader.lines();
+    let first = lines.next().transpose()?.unwrap_or_default();
+    let version = first
+        .strip_prefix(MAGIC)
+        .and_then(|version| version.trim().parse::<u32>().ok());
+    match version {
+        Some(version) if version <= VERSION => {}
+        _ => return Err(SaveError::Version { found: first }),
+    }
+
+    let mut config = GameConfig::default();
+    let mut seats = [false; 2];
+    let mut clocks = None;
+    let mut result = None;
+    let mut moves = None;
+    for (index, line) in lines.enumerate() {
+        let line = line?;
+        // the version line was line 1
+        let number = index + 2;
+        let bad = || SaveError::Line {
+            number,
+            text: line.clone(),
+        };
+        let (key, rest) = line.split_once(' ').unwrap_or((&line, ""));
+        let mut words = rest.split_whitespace();
+        match key {
+            "" => {}
+            "cross" | "nought" | "other" => {
+                let (kind, name) = rest.split_once(' ').ok_or_else(bad)?;
+                let kind = match kind {
+                    "human" => PlayerKind::Human,
+                    "ai" => PlayerKind::Ai,
+                    _ => return Err(bad()),
+                };
+                let seat = Seat {
+                    name: name.to_string(),
+                    kind,
+                };
+                match key {
+                    "cross" => {
+                        config.cross = seat;
+                        seats[Player::Cross.index()] = true;
+                    }
+                    "nought" => {
+                        config.nought = seat;
+                        seats[Player::Nought.index()] = true;
+                    }
+                    _ => config.others.push(seat),
+                }
+            }
+            "first" => config.first = rest.parse().map_err(|_| bad())?,
+            "rules" => config.rules = parse_rules(words).ok_or_else(bad)?,
+            "time" => config.time_control = Some(parse_time_control(words).ok_or_else(bad)?),
+            "handicap" => config.handicap = Some(parse_handicap(words).ok_or_else(bad)?),
+            "random" => config.random_start = Some(parse_random_start(words).ok_or_else(bad)?),
+            "clocks" => clocks = Some(durations(&mut words).ok_or_else(bad)?),
+            // the results the moves don't decide all need an opponent, so
+            // the winner is crosses or noughts
+            "result" => {
+                let winner = words
+                    .next()
+                    .and_then(|word| word.parse::<Player>().ok())
+                    .filter(|winner| winner.number() < 2)
+                    .ok_or_else(bad)?;
+                let reason = words.next().and_then(parse_reason).ok_or_else(bad)?;
+                result = Some((winner, reason));
+            }
+            "moves" => moves = Some(rest.parse::<Transcript>().map_err(SaveError::Transcript)?),
+            _ => return Err(bad()),
+        }
+    }
+
+    if !seats[Player::Cross.index()] {
+        return Err(SaveError::Missing { key: "cross" });
+    }
+    if !seats[Player::Nought.index()] {
+        return Err(SaveError::Missing { key: "nought" });
+    }
+    let mut transcript = moves.ok_or(SaveError::Missing { key: "moves" })?;
+    // `Game::with_config` panics on a bad config, so check it first
+    Game::try_with_config(config.clone()).map_err(SaveError::Config)?;
+    transcript.config = config;
+    let mut game = transcript.replay().map_err(SaveError::Replay)?;
+    if let Some([cross, nought]) = clocks {
+        game.remaining[Player::Cross.index()] = cross;
+        game.remaining[Player::Nought.index()] = nought;
+    }
+    if let Some((winner, reason)) = result {
+        game.concede(winner.toggle(), reason).map_err(|error| {
+            SaveError::Replay(ApplyError {
+                index: transcript.len(),
+                error,
+            })
+        })?;
+    }
+    Ok(game)
+}
+
+fn durations<'a>(words: &mut impl Iterator<Item = &'a str>) -> Option<[Duration; 2]> {
+    let mut duration = || words.next()?.parse().ok().map(Duration::from_millis);
+    Some([duration()?, duration()?])
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    // the first line isn't a save header this version understands
+    Version { found: String },
+    // `number` counts from 1
+    Line { number: usize, text: String },
+    Missing { key: &'static str },
+    Config(ConfigError),
+    Transcript(ParseTranscriptError),
+    // the moves, or the result after them, aren't legal
+    Replay(ApplyError),
+}
+
+impl From<io::Error> for SaveError {
+    fn from(err: io::Error) -> SaveError {
+        SaveError::Io(err)
+    }
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::Io(err) => write!(f, "{}", err),
+            SaveError::Version { found } => {
+                write!(f, "not a version {} save: {:?}", VERSION, found)
+            }
+            SaveError::Line { number, text } => {
+                write!(f, "line {} isn't understood: {:?}", number, text)
+            }
+            SaveError::Missing { key } => write!(f, "no {} line", key),
+            SaveError::Config(error) => write!(f, "{}", error),
+            SaveError::Transcript(error) => write!(f, "{}", error),
+            SaveError::Replay(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SaveError::Io(err) => Some(err),
+            SaveError::Config(error) => Some(error),
+            SaveError::Transcript(error) => Some(error),
+            SaveError::Replay(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::pos;
+
+    fn round_trip(game: &Game) -> (String, Game) {
+        let mut bytes = Vec::new();
+        write_save(&mut bytes, game).unwrap();
+        let text = String::from_utf8(bytes).unwrap();
+        let loaded = read_save(text.as_bytes()).unwrap();
+        (text, loaded)
+    }
+
+    #[test]
+    fn plain() {
+        let mut game = Game::new();
+        game.apply(&[pos(5), pos(1)]).unwrap();
+        let (text, loaded) = round_trip(&game);
+        assert_eq!(
+            "tic-tac-toe save 1\ncross human Cross\nnought human Nought\nfirst X\nmoves X5 O1\n",
+            text
+        );
+        assert_eq!(game.board(), loaded.board());
+        assert_eq!(game.transcript(), loaded.transcript());
+        assert_eq!(Player::Cross, loaded.to_move());
+    }
+
+    #[test]
+    fn everything() {
+        let config = GameConfig {
+            cross: Seat {
+                name: "Deep Thought".to_string(),
+                kind: PlayerKind::Ai,
+            },
+            first: Player::Nought,
+            time_control: Some(TimeControl {
+                initial: Duration::from_secs(60),
+                increment: Duration::from_millis(500),
+            }),
+            rules: Rules {
+                gravity: true,
+                ..Rules::default()
+            },
+            handicap: Some(Handicap {
+                player: Player::Nought,
+                stones: vec![pos(8)],
+                double_move: false,
+            }),
+            ..GameConfig::default()
+        };
+        let mut game = Game::with_config(config);
+        game.play_timed(pos(7), Duration::from_secs(3)).unwrap();
+        game.play_timed(pos(9), Duration::from_secs(5)).unwrap();
+        game.resign(Player::Nought).unwrap();
+
+        let (text, loaded) = round_trip(&game);
+        assert!(text.contains("\ncross ai Deep Thought\n"));
+        assert!(text.contains("\nrules gravity\ntime 60000 500\nhandicap O 8\n"));
+        assert!(text.contains("\nclocks 55500 57500\nresult X resignation\nmoves O7 X9\n"));
+        assert_eq!(game.config(), loaded.config());
+        assert_eq!(game.board(), loaded.board());
+        assert_eq!(game.result(), loaded.result());
+        for player in [Player::Cross, Player::Nought] {
+            assert_eq!(game.remaining(player), loaded.remaining(player));
+        }
+    }
+
+    #[test]
+    fn three_players() {
+        let mut config = GameConfig::with_players(3);
+        config.others[0].name = "Carol".to_string();
+        let mut game = Game::with_config(config);
+        game.apply(&[pos(5), pos(1), pos(9)]).unwrap();
+        let (text, loaded) = round_trip(&game);
+        assert!(text.contains("\nnought human Nought\nother human Carol\n"));
+        assert!(text.ends_with("\nmoves X5 O1 +9\n"));
+        assert_eq!(game.config(), loaded.config());
+        assert_eq!(game.board(), loaded.board());
+        assert_eq!(Player::Cross, loaded.to_move());
+    }
+
+    #[test]
+    fn files() {
+        let path = std::env::temp_dir().join(format!("tic-tac-toe-{}.save", std::process::id()));
+        let mut game = Game::new();
+        game.apply(&[pos(1), pos(2), pos(5), pos(3), pos(9)])
+            .unwrap();
+        game.save(&path).unwrap();
+        let loaded = Game::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(game.result(), loaded.result());
+        assert!(matches!(Game::load(&path), Err(SaveError::Io(_))));
+    }
+
+    #[test]
+    fn errors() {
+        let load = |text: &str| read_save(text.as_bytes()).unwrap_err().to_string();
+        let header = "tic-tac-toe save 1\ncross human A\nnought human B\n";
+        assert_eq!(
+            r#"not a version 1 save: "tic-tac-toe save 2""#,
+            load("tic-tac-toe save 2\n")
+        );
+        assert_eq!(r#"not a version 1 save: """#, load(""));
+        assert_eq!("no moves line", load(header));
+        assert_eq!(
+            "invalid move at index 1",
+            load(&format!("{}moves X5 X1\n", header))
+        );
+        assert_eq!(
+            r#"line 4 isn't understood: "rules chess""#,
+            load(&format!("{}rules chess\nmoves\n", header))
+        );
+        assert_eq!(
+            "the handicap stones decide the game",
+            load(&format!("{}handicap X 1 2 3\nmoves\n", header))
+        );
+        assert_eq!(
+            r#"line 4 isn't understood: "result + resignation""#,
+            load(&format!("{}result + resignation\nmoves\n", header))
+        );
+        assert_eq!(
+            r#"line 4 isn't understood: "result resignation""#,
+            load(&format!("{}result resignation\nmoves\n", header))
+        );
+        assert_eq!(
+            "no nought line",
+            load("tic-tac-toe save 1\ncross human A\nmoves\n")
+        );
+    }
+}
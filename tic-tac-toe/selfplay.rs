@@ -0,0 +1,141 @@
+// training data from games between two AIs: every position that came up,
+// the move that was played in it, and how the game ended for the mover
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::ai::{AiPlayer, Outcome};
+use crate::{Board, Game, Player, Pos};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Sample {
+    pub board: Board,
+    pub to_move: Player,
+    pub chosen: Pos,
+    // the final result from `to_move`'s side
+    pub outcome: Outcome,
+}
+
+// plays `games` games of `cross` against `nought`, crosses moving first, and
+// returns a sample for every move of every game in order
+pub fn play(cross: &mut dyn AiPlayer, nought: &mut dyn AiPlayer, games: usize) -> Vec<Sample> {
+    let mut samples = Vec::new();
+    for _ in 0..games {
+        let mut game = Game::new();
+        let mut positions = Vec::new();
+        while game.result().is_none() {
+            let board = *game.board();
+            let to_move = game.to_move();
+            let ai: &mut dyn AiPlayer = if to_move == Player::Cross {
+                &mut *cross
+            } else {
+                &mut *nought
+            };
+            let chosen = game.play_ai(ai).expect("AI played an illegal move");
+            positions.push((board, to_move, chosen));
+        }
+
+        let winner = game.result().unwrap().winner;
+        samples.extend(positions.into_iter().map(|(board, to_move, chosen)| {
+            let outcome = match winner {
+                Some(winner) if winner == to_move => Outcome::Win,
+                Some(_) => Outcome::Loss,
+                None => Outcome::Draw,
+            };
+            Sample {
+                board,
+                to_move,
+                chosen,
+                outcome,
+            }
+        }));
+    }
+    samples
+}
+
+// one JSON object per line, e.g.
+//
+//     {"board":"X...O....","to_move":"X","move":9,"outcome":0}
+//
+// where outcome is 1 for a win, 0 for a draw and -1 for a loss
+pub fn write_jsonl(samples: &[Sample], out: &mut impl fmt::Write) -> fmt::Result {
+    for sample in samples {
+        writeln!(
+            out,
+            r#"{{"board":"{}","to_move":"{}","move":{},"outcome":{}}}"#,
+            sample.board.to_compact(),
+            sample.to_move,
+            sample.chosen,
+            score(sample.outcome)
+        )?;
+    }
+    Ok(())
+}
+
+// the same fields as `write_jsonl`, with a header row
+pub fn write_csv(samples: &[Sample], out: &mut impl fmt::Write) -> fmt::Result {
+    writeln!(out, "board,to_move,move,outcome")?;
+    for sample in samples {
+        writeln!(
+            out,
+            "{},{},{},{}",
+            sample.board.to_compact(),
+            sample.to_move,
+            sample.chosen,
+            score(sample.outcome)
+        )?;
+    }
+    Ok(())
+}
+
+fn score(outcome: Outcome) -> i8 {
+    match outcome {
+        Outcome::Loss => -1,
+        Outcome::Draw => 0,
+        Outcome::Win => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::{HeuristicAi, PerfectAi, RandomAi};
+    use alloc::string::String;
+
+    #[test]
+    fn play() {
+        let samples = super::play(&mut RandomAi::new(1), &mut PerfectAi, 5);
+        assert!(samples.len() >= 5 * 3);
+        assert_eq!(Board::new(), samples[0].board);
+        assert_eq!(Player::Cross, samples[0].to_move);
+        // perfect play never loses
+        assert!(samples
+            .iter()
+            .filter(|sample| sample.to_move == Player::Nought)
+            .all(|sample| sample.outcome != Outcome::Loss));
+        for sample in &samples {
+            assert!(sample.board.legal_moves().any(|pos| pos == sample.chosen));
+        }
+    }
+
+    #[test]
+    fn formats() {
+        let samples = super::play(&mut HeuristicAi::default(), &mut HeuristicAi::default(), 1);
+
+        let mut jsonl = String::new();
+        write_jsonl(&samples, &mut jsonl).unwrap();
+        let first = jsonl.lines().next().unwrap();
+        assert_eq!(
+            r#"{"board":".........","to_move":"X","move":5,"outcome":0}"#,
+            first
+        );
+        assert_eq!(samples.len(), jsonl.lines().count());
+
+        let mut csv = String::new();
+        write_csv(&samples, &mut csv).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(Some("board,to_move,move,outcome"), lines.next());
+        assert_eq!(Some("....X....,O,1,0"), lines.nth(1));
+        assert_eq!(samples.len() + 1, csv.lines().count());
+    }
+}
@@ -0,0 +1,231 @@
+// a game driven from async code, e.g. a tokio server, without a thread per
+// game. Each side is a `Seat`: moves arriving on a channel, from a human at
+// a UI or a player across the network, or an `AiPlayer`, which searches on
+// tokio's blocking pool so the server's worker threads stay free:
+//
+//     let (alice, seat) = session::remote();
+//     let mut session = Session::new(Game::new(), seat, Seat::ai(PerfectAi));
+//     // elsewhere: alice.send(pos).await
+//     while let Some((player, pos)) = session.next_move().await? {
+//         ...
+//     }
+
+use std::boxed::Box;
+use std::fmt;
+
+use tokio::sync::mpsc;
+
+use crate::ai::AiPlayer;
+use crate::{Game, PlaceError, Player, Pos};
+
+// how many moves a remote seat can send ahead of the game
+const BUFFER: usize = 8;
+
+pub enum Seat {
+    Remote(mpsc::Receiver<Pos>),
+    Ai(Box<dyn AiPlayer + Send>),
+}
+
+impl Seat {
+    pub fn ai(ai: impl AiPlayer + Send + 'static) -> Seat {
+        Seat::Ai(Box::new(ai))
+    }
+}
+
+// the sending end of a remote seat; dropping it disconnects the seat
+#[derive(Clone, Debug)]
+pub struct MoveSender(mpsc::Sender<Pos>);
+
+impl MoveSender {
+    // fails once the session is gone
+    pub async fn send(&self, pos: Pos) -> Result<(), SessionError> {
+        self.0.send(pos).await.map_err(|_| SessionError::Closed)
+    }
+}
+
+pub fn remote() -> (MoveSender, Seat) {
+    let (sender, receiver) = mpsc::channel(BUFFER);
+    (MoveSender(sender), Seat::Remote(receiver))
+}
+
+pub struct Session {
+    game: Game,
+    // indexed by `Player::index`; None while an AI is off searching
+    seats: [Option<Seat>; 2],
+}
+
+impl Session {
+    pub fn new(game: Game, cross: Seat, nought: Seat) -> Session {
+        let mut seats = [None, None];
+        seats[Player::Cross.index()] = Some(cross);
+        seats[Player::Nought.index()] = Some(nought);
+        Session { game, seats }
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    pub fn into_game(self) -> Game {
+        self.game
+    }
+
+    // waits for whoever is to move and plays their move, or returns None
+    // once the game is over. A refused move leaves the game as it was, so
+    // calling again waits for that seat to try something else
+    pub async fn next_move(&mut self) -> Result<Option<(Player, Pos)>, SessionError> {
+        if self.game.result().is_some() {
+            return Ok(None);
+        }
+        let player = self.game.to_move();
+        let seat = &mut self.seats[player.index()];
+        let (pos, mark) = match seat {
+            // borrowed rather than taken, so a `next_move` cancelled while
+            // waiting leaves the seat as it was
+            Some(Seat::Remote(receiver)) => {
+                let pos = receiver.recv().await;
+                (pos.ok_or(SessionError::Disconnected { player })?, player)
+            }
+            Some(Seat::Ai(_)) => {
+                let Some(Seat::Ai(mut ai)) = seat.take() else {
+                    unreachable!()
+                };
+                let board = *self.game.board();
+                let rules = self.game.config().rules;
+                let (ai, chosen) = tokio::task::spawn_blocking(move || {
+                    let chosen = ai.choose_mark(&board, player, rules);
+                    (ai, chosen)
+                })
+                .await
+                // the AI panicked; so does the session
+                .unwrap_or_else(|err| std::panic::resume_unwind(err.into_panic()));
+                *seat = Some(Seat::Ai(ai));
+                chosen
+            }
+            // only a `next_move` cancelled mid-search leaves the seat empty
+            None => return Err(SessionError::Closed),
+        };
+        self.game
+            .play_mark(pos, mark)
+            .map_err(|error| SessionError::Rejected { player, pos, error })?;
+        Ok(Some((player, pos)))
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum SessionError {
+    // a remote seat's sender was dropped while it was to move
+    Disconnected {
+        player: Player,
+    },
+    Rejected {
+        player: Player,
+        pos: Pos,
+        error: PlaceError,
+    },
+    // the session is gone, or a cancelled `next_move` took an AI seat
+    // with it
+    Closed,
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SessionError::Disconnected { player } => write!(f, "{} disconnected", player),
+            SessionError::Rejected { player, pos, error } => {
+                write!(f, "{}'s move {} was refused: {}", player, pos, error)
+            }
+            SessionError::Closed => write!(f, "the session is closed"),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SessionError::Rejected { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::pos;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::ai::PerfectAi;
+
+    #[tokio::test]
+    async fn remote_against_ai() {
+        let (human, seat) = remote();
+        let mut session = Session::new(Game::new(), seat, Seat::ai(PerfectAi));
+        human.send(pos(5)).await.unwrap();
+        assert_eq!(
+            Some((Player::Cross, pos(5))),
+            session.next_move().await.unwrap()
+        );
+        let (player, reply) = session.next_move().await.unwrap().unwrap();
+        assert_eq!(Player::Nought, player);
+
+        // a move onto the engine's mark is refused, and the next one taken
+        human.send(reply).await.unwrap();
+        let free = session.game().board().legal_moves().next().unwrap();
+        human.send(free).await.unwrap();
+        assert!(matches!(
+            session.next_move().await,
+            Err(SessionError::Rejected {
+                player: Player::Cross,
+                error: PlaceError::Occupied { .. },
+                ..
+            })
+        ));
+        assert_eq!(
+            Some((Player::Cross, free)),
+            session.next_move().await.unwrap()
+        );
+        assert_eq!(3, session.game().transcript().len());
+    }
+
+    #[tokio::test]
+    async fn disconnected() {
+        let (sender, seat) = remote();
+        let mut session = Session::new(Game::new(), Seat::ai(PerfectAi), seat);
+        assert_eq!(Player::Cross, session.next_move().await.unwrap().unwrap().0);
+        drop(sender);
+        assert_eq!(
+            Err(SessionError::Disconnected {
+                player: Player::Nought
+            }),
+            session.next_move().await
+        );
+        assert_eq!(1, session.into_game().transcript().len());
+    }
+
+    #[tokio::test]
+    async fn cancelled_while_waiting() {
+        let (human, seat) = remote();
+        let mut session = Session::new(Game::new(), seat, Seat::ai(PerfectAi));
+        let waiting = tokio::time::timeout(Duration::from_millis(10), session.next_move());
+        assert!(waiting.await.is_err());
+
+        // the seat is still there to take the move when it comes
+        human.send(pos(5)).await.unwrap();
+        assert_eq!(
+            Some((Player::Cross, pos(5))),
+            session.next_move().await.unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn ai_against_ai() {
+        let mut session = Session::new(Game::new(), Seat::ai(PerfectAi), Seat::ai(PerfectAi));
+        let mut count = 0;
+        while session.next_move().await.unwrap().is_some() {
+            count += 1;
+        }
+        assert_eq!(9, count);
+        assert_eq!(None, session.game().result().unwrap().winner);
+    }
+}
@@ -0,0 +1,237 @@
+// one game held by several threads, e.g. a server thread taking moves and a
+// broadcaster showing them to spectators. Moves go through the handle and
+// are checked under its lock, so two players racing for the same cell can't
+// both get it; anyone else `watch`es:
+//
+//     let game = SharedGame::new(Game::new());
+//     let mut watcher = game.watch();
+//     thread::spawn(move || {
+//         while let Some(state) = watcher.changed() {
+//             println!("{}", state.board);
+//         }
+//     });
+//     game.try_play(pos)?;
+
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+
+use crate::{Board, Game, GameResult, PlaceError, Player, Pos};
+
+// what a watcher sees of the game after each change
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct GameState {
+    pub board: Board,
+    pub to_move: Player,
+    pub last_move: Option<(Player, Pos)>,
+    pub result: Option<GameResult>,
+}
+
+impl GameState {
+    pub fn of(game: &Game) -> GameState {
+        GameState {
+            board: *game.board(),
+            to_move: game.to_move(),
+            last_move: game.transcript().moves().last().copied(),
+            result: game.result(),
+        }
+    }
+}
+
+struct Inner {
+    game: Game,
+    // counts the changes, so watchers can tell what they've missed
+    version: u64,
+    // `SharedGame`s still alive; watchers give up once there are none
+    handles: usize,
+}
+
+struct Shared {
+    inner: Mutex<Inner>,
+    changed: Condvar,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap()
+    }
+}
+
+pub struct SharedGame {
+    shared: Arc<Shared>,
+}
+
+impl SharedGame {
+    pub fn new(game: Game) -> SharedGame {
+        let inner = Inner {
+            game,
+            version: 0,
+            handles: 1,
+        };
+        SharedGame {
+            shared: Arc::new(Shared {
+                inner: Mutex::new(inner),
+                changed: Condvar::new(),
+            }),
+        }
+    }
+
+    pub fn state(&self) -> GameState {
+        GameState::of(&self.shared.lock().game)
+    }
+
+    // a look at the whole game, e.g. its transcript, under the lock
+    pub fn read<T>(&self, f: impl FnOnce(&Game) -> T) -> T {
+        f(&self.shared.lock().game)
+    }
+
+    // runs `f` on the game under the lock and tells the watchers if it
+    // succeeded
+    fn update<T>(
+        &self,
+        f: impl FnOnce(&mut Game) -> Result<T, PlaceError>,
+    ) -> Result<GameState, PlaceError> {
+        let mut inner = self.shared.lock();
+        f(&mut inner.game)?;
+        inner.version += 1;
+        self.shared.changed.notify_all();
+        Ok(GameState::of(&inner.game))
+    }
+
+    // plays `pos` for whoever is to move; the state is the one right after
+    // the move, whatever other threads do next
+    pub fn try_play(&self, pos: Pos) -> Result<GameState, PlaceError> {
+        self.update(|game| game.play(pos))
+    }
+
+    // like `try_play`, but refused unless it's `player`'s turn, for a
+    // server where each thread speaks for one side
+    pub fn try_play_as(&self, player: Player, pos: Pos) -> Result<GameState, PlaceError> {
+        self.update(|game| game.play_as(player, pos))
+    }
+
+    pub fn resign(&self, player: Player) -> Result<GameState, PlaceError> {
+        self.update(|game| game.resign(player))
+    }
+
+    // a receiver for the states from here on; it starts out having seen the
+    // current one
+    pub fn watch(&self) -> StateReceiver {
+        let version = self.shared.lock().version;
+        StateReceiver {
+            shared: Arc::clone(&self.shared),
+            seen: version,
+        }
+    }
+}
+
+impl Clone for SharedGame {
+    fn clone(&self) -> SharedGame {
+        self.shared.lock().handles += 1;
+        SharedGame {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+impl Drop for SharedGame {
+    fn drop(&mut self) {
+        self.shared.lock().handles -= 1;
+        // wakes watchers so they can see there's nobody left to move
+        self.shared.changed.notify_all();
+    }
+}
+
+// only ever sees the latest state: a watcher that falls behind skips to it
+// rather than queueing every move
+pub struct StateReceiver {
+    shared: Arc<Shared>,
+    seen: u64,
+}
+
+impl StateReceiver {
+    pub fn borrow(&self) -> GameState {
+        GameState::of(&self.shared.lock().game)
+    }
+
+    pub fn has_changed(&self) -> bool {
+        self.shared.lock().version != self.seen
+    }
+
+    // blocks until the game changes and returns its new state, or None once
+    // every `SharedGame` is gone and nothing more can change
+    pub fn changed(&mut self) -> Option<GameState> {
+        let mut inner = self.shared.lock();
+        while inner.version == self.seen {
+            if inner.handles == 0 {
+                return None;
+            }
+            inner = self.shared.changed.wait(inner).unwrap();
+        }
+        self.seen = inner.version;
+        Some(GameState::of(&inner.game))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::pos;
+    use std::thread;
+    use std::vec::Vec;
+
+    #[test]
+    fn watching() {
+        let game = SharedGame::new(Game::new());
+        let mut watcher = game.watch();
+        assert!(!watcher.has_changed());
+        let spectator = thread::spawn(move || {
+            let mut seen = Vec::new();
+            while let Some(state) = watcher.changed() {
+                seen.push(state);
+            }
+            seen
+        });
+
+        let player = game.clone();
+        thread::spawn(move || {
+            for n in [1, 4, 2, 5, 3] {
+                player.try_play(pos(n)).unwrap();
+            }
+        })
+        .join()
+        .unwrap();
+        assert_eq!(Err(PlaceError::GameOver), game.try_play(pos(9)));
+        let last = game.state();
+        drop(game);
+
+        let seen = spectator.join().unwrap();
+        // it may have missed some moves, but never the last one
+        assert!(!seen.is_empty());
+        assert_eq!(Some(&last), seen.last());
+        assert_eq!(Some((Player::Cross, pos(3))), last.last_move);
+        assert_eq!(Some(Player::Cross), last.result.unwrap().winner);
+    }
+
+    #[test]
+    fn racing() {
+        let game = SharedGame::new(Game::new());
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let game = game.clone();
+                thread::spawn(move || game.try_play(pos(5)).is_ok())
+            })
+            .collect();
+        let won = threads
+            .into_iter()
+            .map(|thread| thread.join().unwrap())
+            .filter(|&ok| ok)
+            .count();
+        assert_eq!(1, won);
+        assert_eq!(1, game.read(|game| game.transcript().len()));
+        assert_eq!(
+            Err(PlaceError::NotYourTurn {
+                player: Player::Cross
+            }),
+            game.try_play_as(Player::Cross, pos(1))
+        );
+    }
+}
@@ -0,0 +1,142 @@
+// live games for an audience. `Spectators` fans a game's events out to any
+// number of watchers, each with a channel of its own, so a watcher that
+// reads slowly or goes away never holds up the players:
+//
+//     let spectators = Spectators::new();
+//     let events = spectators.subscribe();
+//     thread::spawn(move || {
+//         for event in events {
+//             println!("{:?}", event);
+//         }
+//     });
+//     arena.play_game_watched(Player::Cross, &spectators)?;
+//
+// watchers see every move, clock update and the end of the game, and can
+// join or leave at any time; each sees the events from when it subscribed
+
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::vec::Vec;
+
+use crate::{Game, GameEvent};
+
+#[derive(Clone, Default)]
+pub struct Spectators {
+    watchers: Arc<Mutex<Vec<Sender<GameEvent>>>>,
+}
+
+impl Spectators {
+    pub fn new() -> Spectators {
+        Spectators::default()
+    }
+
+    // a new watcher; its channel closes once every clone of the
+    // `Spectators` is gone
+    pub fn subscribe(&self) -> Receiver<GameEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.watchers.lock().unwrap().push(sender);
+        receiver
+    }
+
+    // sends `event` to every watcher, forgetting those who've left
+    pub fn send(&self, event: GameEvent) {
+        let mut watchers = self.watchers.lock().unwrap();
+        watchers.retain(|watcher| watcher.send(event).is_ok());
+    }
+
+    // sends `game`'s events from now on; several games can share one
+    // audience, e.g. the games of a match played one after another
+    pub fn watch(&self, game: &mut Game) {
+        let spectators = self.clone();
+        game.subscribe(move |event| spectators.send(event));
+    }
+
+    // watchers still subscribed, as of the last event
+    pub fn len(&self) -> usize {
+        self.watchers.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::pos;
+    use crate::{Player, TimeControl};
+    use std::time::Duration;
+
+    #[test]
+    fn broadcast() {
+        let spectators = Spectators::new();
+        let early = spectators.subscribe();
+        let mut game = Game::new();
+        spectators.watch(&mut game);
+        game.play(pos(1)).unwrap();
+
+        let late = spectators.subscribe();
+        let gone = spectators.subscribe();
+        drop(gone);
+        for n in [4, 2, 5, 3] {
+            game.play(pos(n)).unwrap();
+        }
+        assert_eq!(2, spectators.len());
+        drop(game);
+        drop(spectators);
+
+        let early: Vec<GameEvent> = early.into_iter().collect();
+        let late: Vec<GameEvent> = late.into_iter().collect();
+        assert_eq!(6, early.len());
+        assert_eq!(early[1..], late[..]);
+        assert_eq!(
+            GameEvent::MovePlayed {
+                player: Player::Cross,
+                pos: pos(1)
+            },
+            early[0]
+        );
+        assert_eq!(
+            GameEvent::GameWon {
+                winner: Player::Cross
+            },
+            early[5]
+        );
+    }
+
+    #[test]
+    fn clocks() {
+        let spectators = Spectators::new();
+        let events = spectators.subscribe();
+        let mut game = Game::with_time_control(TimeControl {
+            initial: Duration::from_secs(10),
+            increment: Duration::ZERO,
+        });
+        spectators.watch(&mut game);
+        game.play_timed(pos(5), Duration::from_secs(3)).unwrap();
+        assert!(game.play_timed(pos(1), Duration::from_secs(12)).is_err());
+        drop((game, spectators));
+
+        assert_eq!(
+            vec![
+                GameEvent::MovePlayed {
+                    player: Player::Cross,
+                    pos: pos(5)
+                },
+                GameEvent::ClockUpdated {
+                    player: Player::Cross,
+                    remaining: Duration::from_secs(7)
+                },
+                GameEvent::ClockUpdated {
+                    player: Player::Nought,
+                    remaining: Duration::ZERO
+                },
+                GameEvent::GameWon {
+                    winner: Player::Cross
+                },
+            ],
+            events.into_iter().collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,296 @@
+// a leaderboard kept in an SQLite database, for results to pile up across
+// runs of the CLI, the arena or a server. Players are only names, so a
+// person and a bot with the same name share a record:
+//
+//     let stats = Stats::open("stats.db")?;
+//     stats.record(&game)?;
+//     for player in stats.leaderboard(10)? {
+//         println!("{}", player);
+//     }
+//
+// every game is kept, in the order it was recorded, and the tallies and
+// streaks are worked out from them when asked for
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::string::{String, ToString};
+use std::vec::Vec;
+
+use rusqlite::{params, Connection};
+
+use crate::ai::Outcome;
+use crate::{Game, Player};
+
+const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS games (
+    id INTEGER PRIMARY KEY,
+    cross TEXT NOT NULL,
+    nought TEXT NOT NULL,
+    winner TEXT
+)";
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PlayerStats {
+    pub name: String,
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+    // the results in a row that the player's last game belongs to
+    pub streak: Option<(Outcome, usize)>,
+    pub longest_winning_streak: usize,
+}
+
+impl PlayerStats {
+    fn new(name: &str) -> PlayerStats {
+        PlayerStats {
+            name: name.to_string(),
+            wins: 0,
+            draws: 0,
+            losses: 0,
+            streak: None,
+            longest_winning_streak: 0,
+        }
+    }
+
+    fn add(&mut self, outcome: Outcome) {
+        match outcome {
+            Outcome::Win => self.wins += 1,
+            Outcome::Draw => self.draws += 1,
+            Outcome::Loss => self.losses += 1,
+        }
+        self.streak = match self.streak {
+            Some((last, length)) if last == outcome => Some((outcome, length + 1)),
+            _ => Some((outcome, 1)),
+        };
+        if let Some((Outcome::Win, length)) = self.streak {
+            self.longest_winning_streak = self.longest_winning_streak.max(length);
+        }
+    }
+
+    pub fn games(&self) -> usize {
+        self.wins + self.draws + self.losses
+    }
+
+    // the share of games won, from 0 to 1; draws don't count as half
+    pub fn win_rate(&self) -> f64 {
+        match self.games() {
+            0 => 0.0,
+            games => self.wins as f64 / games as f64,
+        }
+    }
+}
+
+// e.g. "Alice: 7 won, 2 drawn, 1 lost (70%), won the last 3"
+impl fmt::Display for PlayerStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} won, {} drawn, {} lost ({:.0}%)",
+            self.name,
+            self.wins,
+            self.draws,
+            self.losses,
+            self.win_rate() * 100.0
+        )?;
+        match self.streak {
+            Some((Outcome::Win, length)) => write!(f, ", won the last {}", length),
+            Some((Outcome::Draw, length)) => write!(f, ", drew the last {}", length),
+            Some((Outcome::Loss, length)) => write!(f, ", lost the last {}", length),
+            None => Ok(()),
+        }
+    }
+}
+
+pub struct Stats {
+    connection: Connection,
+}
+
+impl Stats {
+    // creates the database if it isn't there yet
+    pub fn open(path: impl AsRef<Path>) -> Result<Stats, StatsError> {
+        Stats::with_connection(Connection::open(path)?)
+    }
+
+    // a database that's gone when the `Stats` is, e.g. for tests
+    pub fn in_memory() -> Result<Stats, StatsError> {
+        Stats::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(connection: Connection) -> Result<Stats, StatsError> {
+        connection.execute(SCHEMA, [])?;
+        Ok(Stats { connection })
+    }
+
+    // adds a finished two-player game under its seats' names
+    pub fn record(&self, game: &Game) -> Result<(), StatsError> {
+        let result = game.result().ok_or(StatsError::Unfinished)?;
+        let config = game.config();
+        if config.players() > 2 {
+            return Err(StatsError::TwoPlayersOnly);
+        }
+        self.record_result(&config.cross.name, &config.nought.name, result.winner)
+    }
+
+    // adds a game between `cross` and `nought`, with None for a draw
+    pub fn record_result(
+        &self,
+        cross: &str,
+        nought: &str,
+        winner: Option<Player>,
+    ) -> Result<(), StatsError> {
+        self.connection.execute(
+            "INSERT INTO games (cross, nought, winner) VALUES (?1, ?2, ?3)",
+            params![cross, nought, winner.map(|winner| winner.to_string())],
+        )?;
+        Ok(())
+    }
+
+    // everyone's record, played through in the order the games were
+    // recorded
+    fn tally(&self) -> Result<BTreeMap<String, PlayerStats>, StatsError> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT cross, nought, winner FROM games ORDER BY id")?;
+        let rows = statement.query_map([], |row| {
+            let winner: Option<String> = row.get(2)?;
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, winner))
+        })?;
+
+        let mut players = BTreeMap::new();
+        for row in rows {
+            let (cross, nought, winner) = row?;
+            let winner = match winner {
+                Some(winner) => Some(
+                    winner
+                        .parse::<Player>()
+                        .ok()
+                        .filter(|player| player.number() < 2)
+                        .ok_or(StatsError::Corrupt { winner })?,
+                ),
+                None => None,
+            };
+            for (name, player) in [(cross, Player::Cross), (nought, Player::Nought)] {
+                let outcome = match winner {
+                    None => Outcome::Draw,
+                    Some(winner) if winner == player => Outcome::Win,
+                    Some(_) => Outcome::Loss,
+                };
+                players
+                    .entry(name)
+                    .or_insert_with_key(|name| PlayerStats::new(name))
+                    .add(outcome);
+            }
+        }
+        Ok(players)
+    }
+
+    // None if `name` has never played
+    pub fn player(&self, name: &str) -> Result<Option<PlayerStats>, StatsError> {
+        Ok(self.tally()?.remove(name))
+    }
+
+    // the best `limit` players by win rate, then by games won, then by name
+    pub fn leaderboard(&self, limit: usize) -> Result<Vec<PlayerStats>, StatsError> {
+        let mut players: Vec<PlayerStats> = self.tally()?.into_values().collect();
+        players.sort_by(|a, b| {
+            b.win_rate()
+                .total_cmp(&a.win_rate())
+                .then(b.wins.cmp(&a.wins))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        players.truncate(limit);
+        Ok(players)
+    }
+}
+
+#[derive(Debug)]
+pub enum StatsError {
+    Database(rusqlite::Error),
+    // only finished games have a result to record
+    Unfinished,
+    // the leaderboard is for games between two
+    TwoPlayersOnly,
+    // a winner that isn't X or O, written by something else
+    Corrupt { winner: String },
+}
+
+impl From<rusqlite::Error> for StatsError {
+    fn from(err: rusqlite::Error) -> StatsError {
+        StatsError::Database(err)
+    }
+}
+
+impl fmt::Display for StatsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StatsError::Database(err) => write!(f, "{}", err),
+            StatsError::Unfinished => write!(f, "the game isn't over"),
+            StatsError::TwoPlayersOnly => write!(f, "only two-player games are recorded"),
+            StatsError::Corrupt { winner } => write!(f, "unknown winner {:?}", winner),
+        }
+    }
+}
+
+impl std::error::Error for StatsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StatsError::Database(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GameConfig, Pos};
+
+    #[test]
+    fn leaderboard() {
+        let stats = Stats::in_memory().unwrap();
+        let x = Some(Player::Cross);
+        let o = Some(Player::Nought);
+        stats.record_result("alice", "bob", x).unwrap();
+        stats.record_result("bob", "alice", o).unwrap();
+        stats.record_result("alice", "carol", None).unwrap();
+        stats.record_result("carol", "bob", x).unwrap();
+        stats.record_result("bob", "alice", x).unwrap();
+
+        let board = stats.leaderboard(10).unwrap();
+        let names: Vec<&str> = board.iter().map(|player| player.name.as_str()).collect();
+        assert_eq!(vec!["alice", "carol", "bob"], names);
+        assert_eq!(
+            "alice: 2 won, 1 drawn, 1 lost (50%), lost the last 1",
+            board[0].to_string()
+        );
+        assert_eq!(2, board[0].longest_winning_streak);
+        assert_eq!(Some((Outcome::Win, 1)), board[2].streak);
+        assert_eq!(1, stats.leaderboard(1).unwrap().len());
+        assert_eq!(None, stats.player("dave").unwrap());
+    }
+
+    #[test]
+    fn games() {
+        let path = std::env::temp_dir().join(format!("stats-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut config = GameConfig::default();
+        config.cross.name = "alice".to_string();
+        let mut game = Game::with_config(config);
+        let stats = Stats::open(&path).unwrap();
+        assert!(matches!(stats.record(&game), Err(StatsError::Unfinished)));
+
+        for n in [1, 4, 2, 5, 3] {
+            game.play(Pos::new(n).unwrap()).unwrap();
+        }
+        stats.record(&game).unwrap();
+        drop(stats);
+
+        // still there after reopening
+        let stats = Stats::open(&path).unwrap();
+        let alice = stats.player("alice").unwrap().unwrap();
+        assert_eq!((1, 1.0), (alice.wins, alice.win_rate()));
+        assert_eq!(1, stats.player("Nought").unwrap().unwrap().losses);
+        drop(stats);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,56 @@
+#ifndef TIC_TAC_TOE_H
+#define TIC_TAC_TOE_H
+
+#include <stdint.h>
+
+// opaque; only ever handled through a pointer
+typedef struct TttGame TttGame;
+
+// difficulties are passed as plain integers and checked, so anything but a
+// TTT_DIFFICULTY_* value gets TTT_STATUS_INVALID_DIFFICULTY
+
+typedef enum TttStatus {
+  TTT_STATUS_OK = 0,
+  TTT_STATUS_NULL_GAME = 1,
+  TTT_STATUS_INVALID_POS = 2,
+  TTT_STATUS_OCCUPIED = 3,
+  TTT_STATUS_GAME_OVER = 4,
+  TTT_STATUS_INVALID_DIFFICULTY = 5,
+} TttStatus;
+
+typedef enum TttDifficulty {
+  TTT_DIFFICULTY_EASY = 0,
+  TTT_DIFFICULTY_MEDIUM = 1,
+  TTT_DIFFICULTY_HARD = 2,
+} TttDifficulty;
+
+typedef enum TttCell {
+  TTT_CELL_EMPTY = 0,
+  TTT_CELL_CROSS = 1,
+  TTT_CELL_NOUGHT = 2,
+  TTT_CELL_INVALID = -1,
+} TttCell;
+
+typedef enum TttState {
+  TTT_STATE_IN_PROGRESS = 0,
+  TTT_STATE_CROSS_WON = 1,
+  TTT_STATE_NOUGHT_WON = 2,
+  TTT_STATE_DRAW = 3,
+  TTT_STATE_INVALID = -1,
+} TttState;
+
+TttGame *ttt_game_new(void);
+
+void ttt_game_free(TttGame *game);
+
+enum TttStatus ttt_game_play(TttGame *game, uint32_t pos);
+
+enum TttStatus ttt_game_ai_move(TttGame *game, uint32_t difficulty, uint64_t seed, uint32_t *pos);
+
+enum TttCell ttt_game_cell(const TttGame *game, uint32_t pos);
+
+enum TttCell ttt_game_to_move(const TttGame *game);
+
+enum TttState ttt_game_state(const TttGame *game);
+
+#endif  /* TIC_TAC_TOE_H */
@@ -0,0 +1,303 @@
+// matches between AIs, for comparing how strong they are
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::ai::AiPlayer;
+use crate::{Game, Player};
+
+// results from the first player's side
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct MatchStats {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl MatchStats {
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    // points per game, counting a draw as half a win
+    pub fn score(&self) -> f64 {
+        if self.games() == 0 {
+            return 0.5;
+        }
+        (f64::from(self.wins) + f64::from(self.draws) / 2.0) / f64::from(self.games())
+    }
+
+    // the same results from the other side
+    pub fn flip(self) -> MatchStats {
+        MatchStats {
+            wins: self.losses,
+            draws: self.draws,
+            losses: self.wins,
+        }
+    }
+
+    // how many Elo points stronger the first player looks; None if one side
+    // won every game, which no finite difference explains
+    #[cfg(feature = "std")]
+    pub fn elo_difference(&self) -> Option<f64> {
+        elo(self.score())
+    }
+
+    // a 95% confidence interval around `elo_difference`, from the spread of
+    // the individual results; an open end is None
+    #[cfg(feature = "std")]
+    pub fn elo_interval(&self) -> (Option<f64>, Option<f64>) {
+        let games = f64::from(self.games());
+        if games == 0.0 {
+            return (None, None);
+        }
+        let score = self.score();
+        let variance = (f64::from(self.wins) * (1.0 - score).powi(2)
+            + f64::from(self.draws) * (0.5 - score).powi(2)
+            + f64::from(self.losses) * score.powi(2))
+            / games;
+        let margin = 1.96 * (variance / games).sqrt();
+        (elo(score - margin), elo(score + margin))
+    }
+}
+
+impl fmt::Display for MatchStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "+{} ={} -{}", self.wins, self.draws, self.losses)
+    }
+}
+
+#[cfg(feature = "std")]
+fn elo(score: f64) -> Option<f64> {
+    if score <= 0.0 || score >= 1.0 {
+        None
+    } else {
+        Some(-400.0 * (1.0 / score - 1.0).log10())
+    }
+}
+
+// plays one game to the end and returns the winner
+pub fn play_game(cross: &mut dyn AiPlayer, nought: &mut dyn AiPlayer) -> Option<Player> {
+    let mut game = Game::new();
+    while game.result().is_none() {
+        let ai: &mut dyn AiPlayer = if game.to_move() == Player::Cross {
+            &mut *cross
+        } else {
+            &mut *nought
+        };
+        game.play_ai(ai).expect("AI played an illegal move");
+    }
+    game.result().unwrap().winner
+}
+
+// plays `games` games between `first` and `second`, swapping colours every
+// game with `first` starting as crosses
+pub fn play_match(first: &mut dyn AiPlayer, second: &mut dyn AiPlayer, games: u32) -> MatchStats {
+    let mut stats = MatchStats::default();
+    for game in 0..games {
+        let first_plays = if game % 2 == 0 {
+            Player::Cross
+        } else {
+            Player::Nought
+        };
+        let winner = if first_plays == Player::Cross {
+            play_game(first, second)
+        } else {
+            play_game(second, first)
+        };
+        match winner {
+            Some(winner) if winner == first_plays => stats.wins += 1,
+            Some(_) => stats.losses += 1,
+            None => stats.draws += 1,
+        }
+    }
+    stats
+}
+
+// every player's results against every other, players numbered in the order
+// they were entered
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Crosstable {
+    // results[i][j] is player i's record against player j
+    results: Vec<Vec<MatchStats>>,
+}
+
+impl Crosstable {
+    pub fn players(&self) -> usize {
+        self.results.len()
+    }
+
+    // `player`'s record against `opponent`; empty against themselves
+    pub fn result(&self, player: usize, opponent: usize) -> MatchStats {
+        self.results[player][opponent]
+    }
+
+    // a point per win and half a point per draw, over all opponents
+    pub fn points(&self, player: usize) -> f64 {
+        self.results[player]
+            .iter()
+            .map(|stats| f64::from(stats.wins) + f64::from(stats.draws) / 2.0)
+            .sum()
+    }
+
+    // players from most points to fewest; ties keep entry order
+    pub fn standings(&self) -> Vec<usize> {
+        let mut players: Vec<usize> = (0..self.players()).collect();
+        players.sort_by(|&a, &b| self.points(b).total_cmp(&self.points(a)));
+        players
+    }
+}
+
+// one row per player, e.g.
+//
+//        1         2         3         points
+//     1  -         +2 =0 -0  +0 =2 -0  3
+impl fmt::Display for Crosstable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "   ")?;
+        for opponent in 0..self.players() {
+            write!(f, " {:<10}", opponent + 1)?;
+        }
+        writeln!(f, " points")?;
+        for player in 0..self.players() {
+            write!(f, "{:<3}", player + 1)?;
+            for opponent in 0..self.players() {
+                if player == opponent {
+                    write!(f, " {:<10}", "-")?;
+                } else {
+                    let stats = format!("{}", self.result(player, opponent));
+                    write!(f, " {:<10}", stats)?;
+                }
+            }
+            writeln!(f, " {}", self.points(player))?;
+        }
+        Ok(())
+    }
+}
+
+// plays a `play_match` of `games_per_pair` games between every pair of
+// `players`
+pub fn round_robin(mut players: Vec<Box<dyn AiPlayer>>, games_per_pair: u32) -> Crosstable {
+    let count = players.len();
+    let mut matches = Vec::new();
+    for second in 1..count {
+        let (earlier, rest) = players.split_at_mut(second);
+        for (first, player) in earlier.iter_mut().enumerate() {
+            let stats = play_match(&mut **player, &mut *rest[0], games_per_pair);
+            matches.push((first, second, stats));
+        }
+    }
+
+    let mut results = vec![vec![MatchStats::default(); count]; count];
+    for (first, second, stats) in matches {
+        results[first][second] = stats;
+        results[second][first] = stats.flip();
+    }
+    Crosstable { results }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::{HeuristicAi, PerfectAi, RandomAi};
+
+    #[test]
+    fn perfect_never_loses() {
+        let stats = play_match(&mut PerfectAi, &mut RandomAi::new(1), 10);
+        assert_eq!(10, stats.games());
+        assert_eq!(0, stats.losses);
+        assert!(stats.wins > 0);
+    }
+
+    #[test]
+    fn colours_alternate() {
+        // heuristic against itself always draws, whoever starts
+        let stats = play_match(&mut HeuristicAi::default(), &mut HeuristicAi::default(), 4);
+        assert_eq!(
+            MatchStats {
+                wins: 0,
+                draws: 4,
+                losses: 0
+            },
+            stats
+        );
+        assert_eq!("+0 =4 -0", stats.to_string());
+    }
+
+    #[test]
+    fn round_robin() {
+        let players: Vec<Box<dyn AiPlayer>> = vec![
+            Box::new(RandomAi::new(1)),
+            Box::new(PerfectAi),
+            Box::new(HeuristicAi::default()),
+        ];
+        let table = super::round_robin(players, 4);
+        assert_eq!(3, table.players());
+        assert_eq!(MatchStats::default(), table.result(1, 1));
+        for player in 0..3 {
+            for opponent in 0..3 {
+                assert_eq!(
+                    table.result(player, opponent).flip(),
+                    table.result(opponent, player)
+                );
+            }
+        }
+        assert_eq!(0, table.result(1, 0).losses);
+        assert_eq!(0, table.result(1, 2).losses);
+        assert_eq!(0, table.standings()[2]);
+        assert_eq!(
+            table.points(0) + table.points(1) + table.points(2),
+            3.0 * 4.0
+        );
+
+        let rendered = table.to_string();
+        assert_eq!(4, rendered.lines().count());
+        assert!(rendered.lines().next().unwrap().ends_with(" points"));
+    }
+
+    #[test]
+    fn score() {
+        let stats = MatchStats {
+            wins: 3,
+            draws: 2,
+            losses: 5,
+        };
+        assert_eq!(0.4, stats.score());
+        assert_eq!(0.6, stats.flip().score());
+        assert_eq!(0.5, MatchStats::default().score());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn elo() {
+        let even = MatchStats {
+            wins: 10,
+            draws: 0,
+            losses: 10,
+        };
+        assert_eq!(Some(0.0), even.elo_difference());
+        let (low, high) = even.elo_interval();
+        assert!(low.unwrap() < 0.0 && high.unwrap() > 0.0);
+        assert_eq!(low.map(|low| -low), high);
+
+        // a 3-1 score is about 191 points
+        let better = MatchStats {
+            wins: 3,
+            draws: 0,
+            losses: 1,
+        };
+        let elo = better.elo_difference().unwrap();
+        assert!((elo - 190.85).abs() < 0.01, "{elo}");
+
+        let sweep = MatchStats {
+            wins: 5,
+            draws: 0,
+            losses: 0,
+        };
+        assert_eq!(None, sweep.elo_difference());
+        assert_eq!((None, None), sweep.elo_interval());
+    }
+}
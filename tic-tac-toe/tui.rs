@@ -0,0 +1,485 @@
+// a full-screen terminal frontend: arrow keys move a cursor over the board
+// and Enter places a piece, or a click places one straight away, and the
+// sidebar shows the moves so far and how the position stands under perfect
+// play. Once a game ends a rematch screen offers another, with the other
+// side moving first
+
+use std::io;
+
+use crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
+    MouseButton, MouseEvent, MouseEventKind,
+};
+use crossterm::execute;
+use ratatui::layout::{Constraint, Layout, Margin, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Clear, List, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::ai::{Difficulty, DifficultyAi, Engine};
+use crate::{Board, Cell, Game, GameConfig, Player, Pos};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Screen {
+    Playing,
+    Rematch,
+}
+
+pub struct App {
+    game: Game,
+    // the side the engine plays, if any
+    ai: Option<Player>,
+    opponent: DifficultyAi,
+    cursor: Pos,
+    // the cell under the mouse pointer
+    hover: Option<Pos>,
+    screen: Screen,
+    // a hint or a rejected move, cleared by the next key
+    message: Option<String>,
+    // the position under perfect play, for the sidebar
+    evaluation: String,
+    quit: bool,
+}
+
+impl App {
+    pub fn new(config: GameConfig, ai: Option<Player>, difficulty: Difficulty, seed: u64) -> App {
+        let mut app = App {
+            game: Game::with_config(config),
+            ai,
+            opponent: DifficultyAi::new(difficulty, seed),
+            cursor: Pos::new(5).unwrap(),
+            hover: None,
+            screen: Screen::Playing,
+            message: None,
+            evaluation: String::new(),
+            quit: false,
+        };
+        app.update();
+        app
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    pub fn cursor(&self) -> Pos {
+        self.cursor
+    }
+
+    pub fn screen(&self) -> Screen {
+        self.screen
+    }
+
+    pub fn should_quit(&self) -> bool {
+        self.quit
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        if key.kind != KeyEventKind::Press {
+            return;
+        }
+        self.message = None;
+        match (self.screen, key.code) {
+            (_, KeyCode::Char('q') | KeyCode::Esc) => self.quit = true,
+            (Screen::Playing, KeyCode::Up) => self.move_cursor(-1, 0),
+            (Screen::Playing, KeyCode::Down) => self.move_cursor(1, 0),
+            (Screen::Playing, KeyCode::Left) => self.move_cursor(0, -1),
+            (Screen::Playing, KeyCode::Right) => self.move_cursor(0, 1),
+            (Screen::Playing, KeyCode::Enter | KeyCode::Char(' ')) => self.place(),
+            (Screen::Playing, KeyCode::Char('h')) => {
+                self.message = self.game.hint().map(|hint| format!("hint: {}", hint));
+            }
+            (Screen::Rematch, KeyCode::Char('r') | KeyCode::Enter) => self.rematch(),
+            _ => {}
+        }
+    }
+
+    // `area` is the whole terminal, as drawn by `draw`
+    pub fn handle_mouse(&mut self, mouse: MouseEvent, area: Rect) {
+        let pos = self.cell_at(area, mouse.column, mouse.row);
+        match mouse.kind {
+            MouseEventKind::Moved => self.hover = pos,
+            MouseEventKind::Down(MouseButton::Left) if self.screen == Screen::Playing => {
+                if let Some(pos) = pos {
+                    self.message = None;
+                    self.cursor = pos;
+                    self.place();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // lets the engine move if it's its turn
+    pub fn tick(&mut self) {
+        if self.screen == Screen::Playing && self.ai == Some(self.game.to_move()) {
+            self.game.play_ai(&mut self.opponent).unwrap();
+            self.update();
+        }
+    }
+
+    pub fn draw(&self, frame: &mut Frame) {
+        let [main, sidebar] = layout(frame.area());
+        let [history, evaluation] =
+            Layout::vertical([Constraint::Min(3), Constraint::Length(3)]).areas(sidebar);
+
+        let mut lines = self.board_lines();
+        lines.push(Line::default());
+        lines.push(Line::from(self.status()));
+        if let Some(message) = &self.message {
+            lines.push(Line::from(message.as_str()));
+        }
+        let block = Block::bordered().title(" tic-tac-toe ");
+        let board = Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false });
+        frame.render_widget(board, main);
+
+        let moves = self
+            .game
+            .transcript()
+            .moves()
+            .iter()
+            .enumerate()
+            .map(|(i, (player, pos))| format!("{:>2}. {}{}", i + 1, player, pos));
+        frame.render_widget(
+            List::new(moves).block(Block::bordered().title(" moves ")),
+            history,
+        );
+        let block = Block::bordered().title(" evaluation ");
+        frame.render_widget(
+            Paragraph::new(self.evaluation.as_str()).block(block),
+            evaluation,
+        );
+
+        if self.screen == Screen::Rematch {
+            self.draw_rematch(frame);
+        }
+    }
+
+    fn draw_rematch(&self, frame: &mut Frame) {
+        let area = centred(frame.area(), 36, 5);
+        let lines = [
+            Line::from(self.status()),
+            Line::default(),
+            Line::from("r: rematch   q: quit"),
+        ];
+        let block = Block::bordered().title(" game over ");
+        frame.render_widget(Clear, area);
+        frame.render_widget(Paragraph::new(lines.to_vec()).block(block).centered(), area);
+    }
+
+    // the board with the cursor's cell highlighted, and the empty cells
+    // numbered so keyboard-only players can see what they're on
+    fn board_lines(&self) -> Vec<Line<'static>> {
+        let board = self.game.board();
+        let mut lines = Vec::new();
+        for row in 0..Board::WIDTH {
+            if row > 0 {
+                lines.push(Line::from(" ---+---+---"));
+            }
+            let mut spans = Vec::new();
+            for column in 0..Board::WIDTH {
+                if column > 0 {
+                    spans.push(Span::raw("|"));
+                }
+                let pos = Pos::from_row_column(row, column).unwrap();
+                let (text, mut style) = match board.cells[pos.get() - 1] {
+                    Cell::Occupied(player) => (format!(" {} ", player), Style::new()),
+                    Cell::Vacant => (format!(" {} ", pos), Style::new().fg(Color::DarkGray)),
+                };
+                if self.screen == Screen::Playing {
+                    if pos == self.cursor {
+                        style = style.add_modifier(Modifier::REVERSED);
+                    } else if Some(pos) == self.hover {
+                        style = style.add_modifier(Modifier::UNDERLINED);
+                    }
+                }
+                spans.push(Span::styled(text, style));
+            }
+            lines.push(Line::from(spans));
+        }
+        lines
+    }
+
+    // the cell drawn at (`column`, `row`) on screen, if any; each takes three
+    // columns, with a `|` between them and a separator line between rows
+    fn cell_at(&self, area: Rect, column: u16, row: u16) -> Option<Pos> {
+        let [main, _] = layout(area);
+        let board = main.inner(Margin::new(1, 1));
+        let x = usize::from(column.checked_sub(board.x)?);
+        let y = usize::from(row.checked_sub(board.y)?);
+        if x % 4 == 3 || y % 2 == 1 {
+            return None;
+        }
+        Pos::from_row_column(y / 2, x / 4)
+    }
+
+    fn status(&self) -> String {
+        let config = self.game.config();
+        match self.game.result() {
+            Some(result) => config.describe(result),
+            None => {
+                let player = self.game.to_move();
+                format!("{} ({}) to move", config.seat(player).name, player)
+            }
+        }
+    }
+
+    // the cursor stops at the edges rather than wrapping
+    fn move_cursor(&mut self, rows: isize, columns: isize) {
+        let row = self.cursor.row().saturating_add_signed(rows);
+        let column = self.cursor.column().saturating_add_signed(columns);
+        if let Some(pos) = Pos::from_row_column(row, column) {
+            self.cursor = pos;
+        }
+    }
+
+    fn place(&mut self) {
+        if self.ai == Some(self.game.to_move()) {
+            return;
+        }
+        match self.game.play(self.cursor) {
+            Ok(()) => self.update(),
+            Err(err) => self.message = Some(err.to_string()),
+        }
+    }
+
+    // the same players again, with the other side moving first
+    fn rematch(&mut self) {
+        let mut config = self.game.config().clone();
+        config.first = config.first.toggle();
+        self.game = Game::with_config(config);
+        self.screen = Screen::Playing;
+        self.update();
+    }
+
+    // called after every change to the game
+    fn update(&mut self) {
+        if self.game.result().is_some() {
+            self.screen = Screen::Rematch;
+            self.evaluation = String::from("game over");
+            return;
+        }
+        let player = self.game.to_move();
+        let mut engine = Engine::with_rules(self.game.config().rules);
+        let score = engine.evaluate(self.game.board(), player);
+        self.evaluation = match score {
+            0 => String::from("a draw with best play"),
+            score if score > 0 => format!("{} can force a win", player),
+            _ => format!("{} can force a win", player.toggle()),
+        };
+    }
+}
+
+// the board and the sidebar
+fn layout(area: Rect) -> [Rect; 2] {
+    Layout::horizontal([Constraint::Length(25), Constraint::Min(20)]).areas(area)
+}
+
+// a `width` by `height` rectangle in the middle of `area`
+fn centred(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+// takes over the terminal until the player quits, and restores it afterwards
+// even if drawing fails
+pub fn run(app: &mut App) -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    let result =
+        execute!(io::stdout(), EnableMouseCapture).and_then(|_| event_loop(&mut terminal, app));
+    let _ = execute!(io::stdout(), DisableMouseCapture);
+    ratatui::restore();
+    result
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, app: &mut App) -> io::Result<()> {
+    loop {
+        app.tick();
+        terminal.draw(|frame| app.draw(frame))?;
+        if app.should_quit() {
+            return Ok(());
+        }
+        match event::read()? {
+            Event::Key(key) => app.handle_key(key),
+            Event::Mouse(mouse) => {
+                let size = terminal.size()?;
+                app.handle_mouse(mouse, Rect::new(0, 0, size.width, size.height));
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    fn press(app: &mut App, codes: &[KeyCode]) {
+        for &code in codes {
+            app.handle_key(KeyEvent::from(code));
+        }
+    }
+
+    fn render(app: &App) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(60, 14)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer
+            .content()
+            .chunks(buffer.area.width as usize)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn cursor_and_moves() {
+        let mut app = App::new(GameConfig::default(), None, Difficulty::Hard, 0);
+        assert_eq!(Pos::new(5).unwrap(), app.cursor());
+        press(&mut app, &[KeyCode::Up, KeyCode::Left, KeyCode::Left]);
+        assert_eq!(Pos::new(1).unwrap(), app.cursor());
+        press(&mut app, &[KeyCode::Enter, KeyCode::Enter]);
+        // the second press is on a taken cell
+        assert_eq!(1, app.game().move_number());
+        assert!(render(&app).contains("position 1 is already"));
+        press(&mut app, &[KeyCode::Down, KeyCode::Char(' ')]);
+        assert_eq!(2, app.game().move_number());
+
+        let screen = render(&app);
+        assert!(screen.contains(" 1. X1"));
+        assert!(screen.contains(" 2. O4"));
+        assert!(screen.contains("X can force a win"));
+        assert!(screen.contains("Cross (X) to move"));
+        press(&mut app, &[KeyCode::Char('q')]);
+        assert!(app.should_quit());
+    }
+
+    fn mouse(kind: MouseEventKind, column: u16, row: u16) -> MouseEvent {
+        MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    #[test]
+    fn mouse_clicks() {
+        let area = Rect::new(0, 0, 60, 14);
+        let mut app = App::new(GameConfig::default(), None, Difficulty::Hard, 0);
+        let click = mouse(MouseEventKind::Down(MouseButton::Left), 0, 0);
+        // the bottom-right cell is drawn in columns 9-11 of the fifth row
+        for (column, row) in [(9, 5), (11, 5)] {
+            assert_eq!(Pos::new(9), app.cell_at(area, column, row));
+        }
+        assert_eq!(Pos::new(1), app.cell_at(area, 1, 1));
+        // the border, the `|` between cells and the separator lines
+        for (column, row) in [(0, 0), (4, 1), (1, 2), (13, 1), (1, 7)] {
+            assert_eq!(None, app.cell_at(area, column, row));
+        }
+
+        // hovering over 6 underlines it, away from the cursor on 5
+        app.handle_mouse(mouse(MouseEventKind::Moved, 10, 3), area);
+        let mut terminal = Terminal::new(TestBackend::new(60, 14)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let modifier = |x, y| terminal.backend().buffer()[(x, y)].modifier;
+        assert!(modifier(10, 3).contains(Modifier::UNDERLINED));
+        assert!(modifier(6, 3).contains(Modifier::REVERSED));
+        assert!(!modifier(2, 1).contains(Modifier::UNDERLINED));
+        app.handle_mouse(
+            MouseEvent {
+                column: 6,
+                row: 3,
+                ..click
+            },
+            area,
+        );
+        assert_eq!(
+            Some((Player::Cross, Pos::new(5).unwrap())),
+            app.game().last_move()
+        );
+        assert_eq!(Pos::new(5).unwrap(), app.cursor());
+        // clicking outside the board or on a taken cell changes nothing
+        app.handle_mouse(
+            MouseEvent {
+                column: 40,
+                row: 3,
+                ..click
+            },
+            area,
+        );
+        app.handle_mouse(
+            MouseEvent {
+                column: 6,
+                row: 3,
+                ..click
+            },
+            area,
+        );
+        assert_eq!(1, app.game().move_number());
+        assert!(render(&app).contains("position 5 is"));
+    }
+
+    // moves the cursor to `pos` and plays there
+    fn play(app: &mut App, pos: Pos) {
+        while app.cursor() != pos {
+            let cursor = app.cursor();
+            let code = if cursor.row() < pos.row() {
+                KeyCode::Down
+            } else if cursor.row() > pos.row() {
+                KeyCode::Up
+            } else if cursor.column() < pos.column() {
+                KeyCode::Right
+            } else {
+                KeyCode::Left
+            };
+            press(app, &[code]);
+        }
+        press(app, &[KeyCode::Enter]);
+    }
+
+    #[test]
+    fn rematch() {
+        let mut app = App::new(
+            GameConfig::default(),
+            Some(Player::Nought),
+            Difficulty::Hard,
+            0,
+        );
+        while app.screen() == Screen::Playing {
+            let pos = app.game().board().legal_moves().next().unwrap();
+            play(&mut app, pos);
+            app.tick();
+        }
+        // the engine never loses
+        assert_ne!(Some(Player::Cross), app.game().result().unwrap().winner);
+        let screen = render(&app);
+        assert!(screen.contains("r: rematch"));
+        assert!(screen.contains("game over"));
+        // keys for the board do nothing until the rematch starts
+        let cursor = app.cursor();
+        press(&mut app, &[KeyCode::Left, KeyCode::Enter]);
+        assert_eq!(cursor, app.cursor());
+        press(&mut app, &[KeyCode::Char('r')]);
+        assert_eq!(Screen::Playing, app.screen());
+        assert_eq!(0, app.game().move_number());
+        assert_eq!(Player::Nought, app.game().config().first);
+        // now the engine moves first, and a click on its turn is ignored
+        press(&mut app, &[KeyCode::Enter]);
+        assert_eq!(0, app.game().move_number());
+        app.tick();
+        assert_eq!(1, app.game().move_number());
+    }
+}
@@ -0,0 +1,362 @@
+// ultimate tic-tac-toe: nine boards in a 3x3 grid. The cell a move is played
+// in sends the opponent to the board in the same place in the grid, and
+// winning three boards in a line wins the game. A player sent to a board that
+// is already won or full may play in any board that isn't
+
+use alloc::vec::Vec;
+use core::error;
+use core::fmt;
+use core::str;
+
+use crate::ai::Position;
+use crate::{Board, Cell, ParsePosError, PlaceError, Player, Pos};
+
+// a cell in one of the nine boards; `board` and `cell` are both numbered
+// like a single board's positions
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Move {
+    pub board: Pos,
+    pub cell: Pos,
+}
+
+// `5.3` is cell 3 of the centre board
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.board, self.cell)
+    }
+}
+
+impl str::FromStr for Move {
+    type Err = ParsePosError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (board, cell) = s.split_once('.').ok_or(ParsePosError {})?;
+        Ok(Move {
+            board: board.parse()?,
+            cell: cell.parse()?,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MetaBoard {
+    boards: [Board; Board::SIZE],
+    to_move: Player,
+    // where the last move sent the next one, None if it can go anywhere
+    active: Option<Pos>,
+}
+
+impl MetaBoard {
+    pub fn new() -> MetaBoard {
+        MetaBoard {
+            boards: [Board::new(); Board::SIZE],
+            to_move: Player::Cross,
+            active: None,
+        }
+    }
+
+    pub fn to_move(&self) -> Player {
+        self.to_move
+    }
+
+    pub fn board(&self, board: Pos) -> &Board {
+        &self.boards[board.get() - 1]
+    }
+
+    // the board the next move has to be played in, or None if any board
+    // that is still open will do
+    pub fn active(&self) -> Option<Pos> {
+        self.active
+    }
+
+    // a board is decided once it is won or full, and takes no more moves
+    pub fn is_decided(&self, board: Pos) -> bool {
+        self.board(board).is_over()
+    }
+
+    // the grid of boards as a single board, each won board marked with its
+    // winner; the game is won on this board
+    pub fn overview(&self) -> Board {
+        Board::with_cells(self.boards.map(|board| match board.winner() {
+            Some(player) => Cell::Occupied(player),
+            None => Cell::Vacant,
+        }))
+    }
+
+    pub fn legal_moves(&self) -> impl Iterator<Item = Move> + '_ {
+        let over = self.is_over();
+        Pos::all()
+            .filter(move |&board| !over && self.allows(board))
+            .flat_map(move |board| {
+                self.board(board)
+                    .legal_moves()
+                    .map(move |cell| Move { board, cell })
+            })
+    }
+
+    pub fn play(&mut self, mv: Move) -> Result<(), MetaPlaceError> {
+        if self.is_over() {
+            return Err(MetaPlaceError::Place(PlaceError::GameOver));
+        }
+        if self.is_decided(mv.board) {
+            return Err(MetaPlaceError::BoardDecided { board: mv.board });
+        }
+        if let Some(required) = self.active.filter(|&required| required != mv.board) {
+            return Err(MetaPlaceError::WrongBoard { required });
+        }
+        self.boards[mv.board.get() - 1]
+            .place(mv.cell, self.to_move)
+            .map_err(MetaPlaceError::Place)?;
+
+        self.to_move = self.to_move.toggle();
+        self.active = Some(mv.cell).filter(|&board| !self.is_decided(board));
+        Ok(())
+    }
+
+    pub fn winner(&self) -> Option<Player> {
+        self.overview().winner()
+    }
+
+    // nobody has won and every board is decided
+    pub fn is_draw(&self) -> bool {
+        self.winner().is_none() && Pos::all().all(|board| self.is_decided(board))
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.winner().is_some() || self.is_draw()
+    }
+
+    fn allows(&self, board: Pos) -> bool {
+        !self.is_decided(board) && self.active.is_none_or(|active| active == board)
+    }
+}
+
+impl Default for MetaBoard {
+    fn default() -> MetaBoard {
+        MetaBoard::new()
+    }
+}
+
+// the nine boards laid out as they sit in the grid
+impl fmt::Display for MetaBoard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in 0..Board::SIZE {
+            if row > 0 && row % Board::WIDTH == 0 {
+                writeln!(f, "------+-------+------")?;
+            }
+            for column in 0..Board::SIZE {
+                if column > 0 {
+                    let separator = if column % Board::WIDTH == 0 {
+                        " | "
+                    } else {
+                        " "
+                    };
+                    write!(f, "{}", separator)?;
+                }
+                let board = Pos::from_row_column(row / Board::WIDTH, column / Board::WIDTH);
+                let cell = Pos::from_row_column(row % Board::WIDTH, column % Board::WIDTH);
+                let board = self.board(board.unwrap());
+                write!(f, "{}", board.cells[cell.unwrap().get() - 1].symbol())?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Position for MetaBoard {
+    type Move = Move;
+
+    fn to_move(&self) -> Player {
+        self.to_move
+    }
+
+    fn moves(&self) -> Vec<Move> {
+        self.legal_moves().collect()
+    }
+
+    fn play(&mut self, mv: Move) {
+        MetaBoard::play(self, mv).unwrap();
+    }
+
+    fn winner(&self) -> Option<Player> {
+        MetaBoard::winner(self)
+    }
+
+    // boards won, with the centre board counting double
+    fn heuristic(&self, player: Player) -> i32 {
+        Pos::all()
+            .filter_map(|board| {
+                let weight = if board.get() == 5 { 10 } else { 5 };
+                let winner = self.board(board).winner()?;
+                Some(if winner == player { weight } else { -weight })
+            })
+            .sum()
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum MetaPlaceError {
+    // the previous move sent play to `required`
+    WrongBoard { required: Pos },
+    // `board` is already won or full
+    BoardDecided { board: Pos },
+    Place(PlaceError),
+}
+
+impl fmt::Display for MetaPlaceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MetaPlaceError::WrongBoard { required } => {
+                write!(f, "the move has to be played on board {}", required)
+            }
+            MetaPlaceError::BoardDecided { board } => {
+                write!(f, "board {} is already decided", board)
+            }
+            MetaPlaceError::Place(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl error::Error for MetaPlaceError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            MetaPlaceError::Place(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::{alpha_beta, Agent, RandomAi, Searcher};
+    use crate::test_util::pos;
+
+    fn mv(s: &str) -> Move {
+        s.parse().unwrap()
+    }
+
+    fn meta(moves: &str) -> MetaBoard {
+        let mut meta = MetaBoard::new();
+        for s in moves.split_whitespace() {
+            meta.play(mv(s)).unwrap();
+        }
+        meta
+    }
+
+    // crosses win the top three boards along their bottom rows; every
+    // nought sends them straight back to the next one, taking boards 7 and 8
+    // on the way
+    const CROSS_WINS: &str = "1.7 7.2 2.7 7.3 3.7 7.1 1.8 8.2 2.8 8.3 3.8 8.1 1.9 9.2 2.9 9.3 3.9";
+
+    #[test]
+    fn parse_move() {
+        let parsed = mv("5.3");
+        assert_eq!(pos(5), parsed.board);
+        assert_eq!(pos(3), parsed.cell);
+        assert_eq!("5.3", parsed.to_string());
+        assert_eq!(parsed, mv("b2.c3"));
+        assert!("53".parse::<Move>().is_err());
+        assert!("5.0".parse::<Move>().is_err());
+    }
+
+    #[test]
+    fn sends_to_board() {
+        let mut meta = MetaBoard::new();
+        assert_eq!(81, meta.legal_moves().count());
+        meta.play(mv("5.3")).unwrap();
+        assert_eq!(Some(pos(3)), meta.active());
+        assert_eq!(Player::Nought, meta.to_move());
+        assert!(meta.legal_moves().all(|mv| mv.board == pos(3)));
+        assert_eq!(
+            Err(MetaPlaceError::WrongBoard { required: pos(3) }),
+            meta.play(mv("5.1"))
+        );
+        meta.play(mv("3.5")).unwrap();
+        assert_eq!(
+            Err(MetaPlaceError::Place(PlaceError::Occupied {
+                pos: pos(3),
+                occupied_by: Player::Cross
+            })),
+            meta.play(mv("5.3"))
+        );
+    }
+
+    #[test]
+    fn local_win() {
+        let mut meta = meta("1.7 7.2 2.7 7.3 3.7 7.1");
+        assert_eq!(Some(Player::Nought), meta.board(pos(7)).winner());
+        assert!(meta.is_decided(pos(7)));
+        assert_eq!(1, meta.overview().count(Player::Nought));
+        assert_eq!(None, meta.winner());
+
+        // being sent to a decided board frees the next move
+        meta.play(mv("1.4")).unwrap();
+        meta.play(mv("4.7")).unwrap();
+        assert_eq!(None, meta.active());
+        assert!(meta.legal_moves().all(|mv| mv.board != pos(7)));
+        assert_eq!(8 * 9 - 5, meta.legal_moves().count());
+        assert_eq!(
+            Err(MetaPlaceError::BoardDecided { board: pos(7) }),
+            meta.play(mv("7.9"))
+        );
+    }
+
+    #[test]
+    fn global_win() {
+        let mut meta = meta(CROSS_WINS);
+        assert_eq!(Some(Player::Cross), meta.winner());
+        assert_eq!(2, meta.overview().count(Player::Nought));
+        assert!(meta.is_over());
+        assert!(!meta.is_draw());
+        assert_eq!(0, meta.legal_moves().count());
+        assert_eq!(
+            Err(MetaPlaceError::Place(PlaceError::GameOver)),
+            meta.play(mv("4.4"))
+        );
+    }
+
+    #[test]
+    fn display() {
+        let meta = meta("5.3 3.5");
+        let expected = "\
+. . . | . . . | . . .
+. . . | . . . | . O .
+. . . | . . . | . . .
+------+-------+------
+. . . | . . X | . . .
+. . . | . . . | . . .
+. . . | . . . | . . .
+------+-------+------
+. . . | . . . | . . .
+. . . | . . . | . . .
+. . . | . . . | . . .
+";
+        assert_eq!(expected, meta.to_string());
+    }
+
+    #[test]
+    fn search_takes_the_win() {
+        let moves = CROSS_WINS.rsplit_once(' ').unwrap().0;
+        let (best, score) = alpha_beta(&meta(moves), 2).unwrap();
+        assert_eq!(mv("3.9"), best);
+        assert_eq!(crate::ai::WIN - 1, score);
+    }
+
+    #[test]
+    fn agents_finish_a_game() {
+        let mut meta = MetaBoard::new();
+        let mut searcher = Searcher::new(2);
+        let mut random = RandomAi::new(7);
+        while !meta.is_over() {
+            let mv = if meta.to_move() == Player::Cross {
+                searcher.choose(&meta)
+            } else {
+                random.choose(&meta)
+            };
+            meta.play(mv).unwrap();
+        }
+        assert_ne!(Some(Player::Nought), meta.winner());
+    }
+}
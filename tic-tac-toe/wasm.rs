@@ -0,0 +1,130 @@
+// bindings for running the engine in a browser, built with e.g.
+//
+//     wasm-pack build --target web --features wasm
+//
+// `wasm.html` next to this file is a page that plays against them. Positions
+// go in and out as cell numbers 1-9, and the state as a JSON string the page
+// can `JSON.parse`
+
+use std::string::{String, ToString};
+use std::vec::Vec;
+
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::ai::{Difficulty, DifficultyAi};
+use crate::{Game, Pos};
+
+#[wasm_bindgen]
+pub struct WasmGame {
+    game: Game,
+    // seeds each engine `ai_move` makes, so they don't all play alike
+    seed: u64,
+}
+
+#[wasm_bindgen]
+impl WasmGame {
+    #[wasm_bindgen(constructor)]
+    pub fn new(seed: u32) -> WasmGame {
+        WasmGame {
+            game: Game::new(),
+            seed: seed.into(),
+        }
+    }
+
+    // `pos` is 1-9
+    pub fn play(&mut self, pos: usize) -> Result<(), String> {
+        let pos = Pos::new(pos).ok_or(format!("there is no cell {}", pos))?;
+        self.game.play(pos).map_err(|err| err.to_string())
+    }
+
+    // plays for whoever is to move at "easy", "medium" or "hard", and
+    // returns the cell it chose
+    pub fn ai_move(&mut self, difficulty: &str) -> Result<usize, String> {
+        let difficulty: Difficulty = difficulty
+            .parse()
+            .map_err(|err| format!("{}: {}", difficulty, err))?;
+        self.seed = self.seed.wrapping_add(1);
+        let mut ai = DifficultyAi::new(difficulty, self.seed);
+        let pos = self.game.play_ai(&mut ai).map_err(|err| err.to_string())?;
+        Ok(pos.get())
+    }
+
+    pub fn reset(&mut self) {
+        self.game = Game::new();
+    }
+
+    // the cells in order, e.g. "X...O...."
+    pub fn board_string(&self) -> String {
+        self.game.board().to_compact()
+    }
+
+    // e.g. {"board":"X...O....","to_move":"X","moves":[1,5],"line":null,
+    // "winner":null,"result":null}, where `line` is the cells of the
+    // winning line once there is one
+    pub fn state(&self) -> String {
+        let numbers = |positions: &mut dyn Iterator<Item = Pos>| {
+            let numbers: Vec<String> = positions.map(|pos| pos.to_string()).collect();
+            format!("[{}]", numbers.join(","))
+        };
+        let moves = numbers(&mut self.game.transcript().moves().iter().map(|&(_, pos)| pos));
+        let rules = self.game.config().rules;
+        let line = match rules.winning_line(self.game.board()) {
+            Some((_, positions)) => numbers(&mut positions.into_iter()),
+            None => "null".to_string(),
+        };
+        let (winner, result) = match self.game.result() {
+            Some(result) => (
+                result
+                    .winner
+                    .map_or("null".to_string(), |winner| format!(r#""{}""#, winner)),
+                format!(r#""{}""#, result),
+            ),
+            None => ("null".to_string(), "null".to_string()),
+        };
+        format!(
+            r#"{{"board":"{}","to_move":"{}","moves":{},"line":{},"winner":{},"result":{}}}"#,
+            self.board_string(),
+            self.game.to_move(),
+            moves,
+            line,
+            winner,
+            result
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn game() {
+        let mut game = WasmGame::new(0);
+        for pos in [1, 4, 2, 5] {
+            game.play(pos).unwrap();
+        }
+        assert_eq!(
+            Err("position 1 is already occupied by X".to_string()),
+            game.play(1)
+        );
+        assert!(game.play(10).is_err());
+        assert_eq!("XX.OO....", game.board_string());
+        // the engine takes the win
+        assert_eq!(Ok(3), game.ai_move("hard"));
+        assert_eq!(
+            r#"{"board":"XXXOO....","to_move":"O","moves":[1,4,2,5,3],"line":[1,2,3],"winner":"X","result":"X wins by three in a row"}"#,
+            game.state()
+        );
+        assert!(game.ai_move("hard").is_err());
+        assert_eq!(
+            Err("impossible: unknown difficulty, expected easy, medium or hard".to_string()),
+            game.ai_move("impossible")
+        );
+
+        game.reset();
+        assert_eq!(".........", game.board_string());
+        assert!(game
+            .state()
+            .ends_with(r#""line":null,"winner":null,"result":null}"#));
+    }
+}
@@ -0,0 +1,418 @@
+// a WebSocket server hosting games between pairs of clients. Clients send
+// text commands:
+//
+//     create           opens a room and plays X in it
+//     join <code>      plays O in the room with that code
+//     move <pos>       1-9 or algebraic
+//     resign
+//
+// and the server answers in JSON: `{"type":"room",...}` to whoever created
+// a room, `{"type":"state",...}` to both players after anything changes the
+// game, `{"type":"left"}` when the opponent disconnects and
+// `{"type":"error","message":...}` to a client whose command was refused.
+// Every move is checked against the server's own `Game`, so a client can't
+// move out of turn or play an illegal move
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io;
+use std::net::SocketAddr;
+use std::string::{String, ToString};
+use std::sync::{Arc, Mutex};
+use std::vec::Vec;
+
+use futures_util::{SinkExt, StreamExt};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::{Game, Player, Pos, Rng};
+
+// how many letters a room code has
+pub const CODE_LENGTH: usize = 4;
+
+// a connected client, numbered in order of connection
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct ClientId(pub u64);
+
+struct Room {
+    game: Game,
+    // indexed by `Player::index`; noughts are None until someone joins
+    players: [Option<ClientId>; 2],
+}
+
+// every room, and which room each client is in; knows nothing about
+// sockets, and just says what to send to whom
+pub struct Lobby {
+    rooms: HashMap<String, Room>,
+    seats: HashMap<ClientId, (String, Player)>,
+    rng: Rng,
+}
+
+impl Lobby {
+    // `seed` decides the room codes
+    pub fn new(seed: u64) -> Lobby {
+        Lobby {
+            rooms: HashMap::new(),
+            seats: HashMap::new(),
+            rng: Rng::new(seed),
+        }
+    }
+
+    pub fn rooms(&self) -> usize {
+        self.rooms.len()
+    }
+
+    // runs one command from `client`, and returns the replies
+    pub fn handle(&mut self, client: ClientId, command: &str) -> Vec<(ClientId, String)> {
+        let mut words = command.split_whitespace();
+        let result = match (words.next(), words.next(), words.next()) {
+            (Some("create"), None, None) => self.create(client),
+            (Some("join"), Some(code), None) => self.join(client, code),
+            (Some("move"), Some(pos), None) => match pos.parse::<Pos>() {
+                Ok(pos) => self.play(client, pos),
+                Err(err) => Err(err.to_string()),
+            },
+            (Some("resign"), None, None) => self.resign(client),
+            _ => Err(format!("unknown command {:?}", command)),
+        };
+        result.unwrap_or_else(|message| vec![(client, error_json(&message))])
+    }
+
+    // `client` has disconnected; their room closes and the opponent hears
+    // about it
+    pub fn leave(&mut self, client: ClientId) -> Vec<(ClientId, String)> {
+        let Some((code, _)) = self.seats.remove(&client) else {
+            return Vec::new();
+        };
+        let Some(room) = self.rooms.remove(&code) else {
+            return Vec::new();
+        };
+        room.players
+            .into_iter()
+            .flatten()
+            .filter(|&player| player != client)
+            .map(|player| {
+                self.seats.remove(&player);
+                (player, r#"{"type":"left"}"#.to_string())
+            })
+            .collect()
+    }
+
+    fn create(&mut self, client: ClientId) -> Result<Vec<(ClientId, String)>, String> {
+        if self.seats.contains_key(&client) {
+            return Err("already in a room".to_string());
+        }
+        let code = loop {
+            let code: String = (0..CODE_LENGTH)
+                .map(|_| char::from(b'A' + self.rng.below(26) as u8))
+                .collect();
+            if !self.rooms.contains_key(&code) {
+                break code;
+            }
+        };
+        let reply = format!(r#"{{"type":"room","code":"{}","you":"X"}}"#, code);
+        self.rooms.insert(
+            code.clone(),
+            Room {
+                game: Game::new(),
+                players: [None, Some(client)],
+            },
+        );
+        self.seats.insert(client, (code, Player::Cross));
+        Ok(vec![(client, reply)])
+    }
+
+    fn join(&mut self, client: ClientId, code: &str) -> Result<Vec<(ClientId, String)>, String> {
+        if self.seats.contains_key(&client) {
+            return Err("already in a room".to_string());
+        }
+        let code = code.to_uppercase();
+        let room = self
+            .rooms
+            .get_mut(&code)
+            .ok_or(format!("no room {}", code))?;
+        let seat = &mut room.players[Player::Nought.index()];
+        if seat.is_some() {
+            return Err(format!("room {} is full", code));
+        }
+        *seat = Some(client);
+        self.seats.insert(client, (code.clone(), Player::Nought));
+        Ok(self.broadcast(&code))
+    }
+
+    fn play(&mut self, client: ClientId, pos: Pos) -> Result<Vec<(ClientId, String)>, String> {
+        let (code, player) = self.seat(client)?;
+        let room = &mut self.rooms.get_mut(&code).unwrap();
+        if room.players.contains(&None) {
+            return Err("waiting for an opponent".to_string());
+        }
+        if room.game.to_move() != player {
+            return Err(format!("it is not {}'s turn", player));
+        }
+        room.game.play(pos).map_err(|err| err.to_string())?;
+        Ok(self.broadcast(&code))
+    }
+
+    fn resign(&mut self, client: ClientId) -> Result<Vec<(ClientId, String)>, String> {
+        let (code, player) = self.seat(client)?;
+        let room = self.rooms.get_mut(&code).unwrap();
+        room.game.resign(player).map_err(|err| err.to_string())?;
+        Ok(self.broadcast(&code))
+    }
+
+    fn seat(&self, client: ClientId) -> Result<(String, Player), String> {
+        self.seats
+            .get(&client)
+            .cloned()
+            .ok_or("not in a room".to_string())
+    }
+
+    // the room's state, for everyone in it
+    fn broadcast(&self, code: &str) -> Vec<(ClientId, String)> {
+        let room = &self.rooms[code];
+        let state = state_json(code, &room.game);
+        room.players
+            .iter()
+            .flatten()
+            .map(|&player| (player, state.clone()))
+            .collect()
+    }
+}
+
+// e.g. {"type":"state","room":"ABCD","board":"X...O....","to_move":"X",
+// "moves":[1,5],"winner":null,"result":null}, with the result described as
+// by `GameResult`'s Display once the game is over
+pub fn state_json(code: &str, game: &Game) -> String {
+    let board = game.board().to_compact();
+    let moves: Vec<String> = game
+        .transcript()
+        .moves()
+        .iter()
+        .map(|(_, pos)| pos.to_string())
+        .collect();
+    let mut json = format!(
+        r#"{{"type":"state","room":"{}","board":"{}","to_move":"{}","moves":[{}]"#,
+        code,
+        board,
+        game.to_move(),
+        moves.join(",")
+    );
+    match game.result() {
+        Some(result) => {
+            let winner = match result.winner {
+                Some(winner) => format!(r#""{}""#, winner),
+                None => "null".to_string(),
+            };
+            write!(json, r#","winner":{},"result":"{}"}}"#, winner, result).unwrap();
+        }
+        None => json.push_str(r#","winner":null,"result":null}"#),
+    }
+    json
+}
+
+fn error_json(message: &str) -> String {
+    let escaped = message.replace('\\', "\\\\").replace('"', "\\\"");
+    format!(r#"{{"type":"error","message":"{}"}}"#, escaped)
+}
+
+// the lobby plus a way to reach every connected client
+struct Server {
+    lobby: Lobby,
+    clients: HashMap<ClientId, mpsc::UnboundedSender<String>>,
+    next_id: u64,
+}
+
+impl Server {
+    fn send(&self, replies: Vec<(ClientId, String)>) {
+        for (client, reply) in replies {
+            if let Some(sender) = self.clients.get(&client) {
+                // a client that's gone is cleaned up by its own task
+                let _ = sender.send(reply);
+            }
+        }
+    }
+}
+
+// listens on `addr` until the listener fails
+pub async fn run(addr: impl ToSocketAddrs, seed: u64) -> io::Result<()> {
+    serve(TcpListener::bind(addr).await?, seed).await
+}
+
+// like `run`, on a listener that's already bound, e.g. to port 0
+pub async fn serve(listener: TcpListener, seed: u64) -> io::Result<()> {
+    let server = Arc::new(Mutex::new(Server {
+        lobby: Lobby::new(seed),
+        clients: HashMap::new(),
+        next_id: 0,
+    }));
+    loop {
+        let (stream, addr) = listener.accept().await?;
+        tokio::spawn(connection(Arc::clone(&server), stream, addr));
+    }
+}
+
+// one client from handshake to disconnect; a failed handshake just drops
+// the connection
+async fn connection(server: Arc<Mutex<Server>>, stream: TcpStream, _addr: SocketAddr) {
+    let Ok(socket) = tokio_tungstenite::accept_async(stream).await else {
+        return;
+    };
+    let (mut sink, mut source) = socket.split();
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    let client = {
+        let mut server = server.lock().unwrap();
+        let client = ClientId(server.next_id);
+        server.next_id += 1;
+        server.clients.insert(client, sender);
+        client
+    };
+
+    let writer = tokio::spawn(async move {
+        while let Some(text) = receiver.recv().await {
+            if sink.send(Message::text(text)).await.is_err() {
+                break;
+            }
+        }
+    });
+    while let Some(Ok(message)) = source.next().await {
+        match message {
+            Message::Text(command) => {
+                let mut server = server.lock().unwrap();
+                let replies = server.lobby.handle(client, &command);
+                server.send(replies);
+            }
+            Message::Close(_) => break,
+            _ => {}
+        }
+    }
+
+    let mut server = server.lock().unwrap();
+    server.clients.remove(&client);
+    let replies = server.lobby.leave(client);
+    server.send(replies);
+    writer.abort();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALICE: ClientId = ClientId(0);
+    const BOB: ClientId = ClientId(1);
+    const CAROL: ClientId = ClientId(2);
+
+    // the room code from a `create` reply
+    fn code(replies: &[(ClientId, String)]) -> String {
+        let start = replies[0].1.find(r#""code":""#).unwrap() + 8;
+        replies[0].1[start..start + CODE_LENGTH].to_string()
+    }
+
+    #[test]
+    fn lobby() {
+        let mut lobby = Lobby::new(0);
+        let replies = lobby.handle(ALICE, "create");
+        let code = code(&replies);
+        assert_eq!(
+            vec![(
+                ALICE,
+                format!(r#"{{"type":"room","code":"{}","you":"X"}}"#, code)
+            )],
+            replies
+        );
+        assert_eq!(
+            vec![(ALICE, error_json("waiting for an opponent"))],
+            lobby.handle(ALICE, "move 5")
+        );
+
+        let replies = lobby.handle(BOB, &format!("join {}", code.to_lowercase()));
+        let state = format!(
+            r#"{{"type":"state","room":"{}","board":".........","to_move":"X","moves":[],"winner":null,"result":null}}"#,
+            code
+        );
+        assert_eq!(vec![(BOB, state.clone()), (ALICE, state)], replies);
+        assert_eq!(
+            vec![(
+                CAROL,
+                format!(r#"{{"type":"error","message":"room {} is full"}}"#, code)
+            )],
+            lobby.handle(CAROL, &format!("join {}", code))
+        );
+
+        assert_eq!(
+            vec![(BOB, error_json("it is not O's turn"))],
+            lobby.handle(BOB, "move 1")
+        );
+        for (client, pos) in [(ALICE, "1"), (BOB, "4"), (ALICE, "b3"), (BOB, "5")] {
+            let replies = lobby.handle(client, &format!("move {}", pos));
+            assert_eq!(2, replies.len());
+        }
+        let replies = lobby.handle(ALICE, "move 3");
+        assert!(replies[0].1.contains(r#""board":"XXXOO....""#));
+        assert!(replies[0]
+            .1
+            .ends_with(r#""moves":[1,4,2,5,3],"winner":"X","result":"X wins by three in a row"}"#));
+        assert_eq!(
+            vec![(BOB, error_json("the game is already over"))],
+            lobby.handle(BOB, "move 6")
+        );
+    }
+
+    #[test]
+    fn leaving() {
+        let mut lobby = Lobby::new(0);
+        let code = code(&lobby.handle(ALICE, "create"));
+        lobby.handle(BOB, &format!("join {}", code));
+        assert_eq!(
+            vec![(BOB, r#"{"type":"left"}"#.to_string())],
+            lobby.leave(ALICE)
+        );
+        assert_eq!(0, lobby.rooms());
+        // Bob is free to start again
+        assert_eq!(1, lobby.handle(BOB, "create").len());
+        assert_eq!(Vec::<(ClientId, String)>::new(), lobby.leave(CAROL));
+        assert_eq!(
+            vec![(CAROL, error_json("unknown command \"dance\""))],
+            lobby.handle(CAROL, "dance")
+        );
+        assert_eq!(
+            vec![(CAROL, error_json("not in a room"))],
+            lobby.handle(CAROL, "resign")
+        );
+    }
+
+    #[tokio::test]
+    async fn over_websockets() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        tokio::spawn(serve(listener, 0));
+
+        let (mut alice, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+        let (mut bob, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+        let send = |text: &str| Message::text(text);
+        alice.send(send("create")).await.unwrap();
+        let Some(Ok(Message::Text(reply))) = alice.next().await else {
+            panic!("no reply to create");
+        };
+        let code = code(&[(ALICE, reply.to_string())]);
+        bob.send(send(&format!("join {}", code))).await.unwrap();
+        let Some(Ok(Message::Text(state))) = alice.next().await else {
+            panic!("no state after join");
+        };
+        assert!(state.contains(r#""to_move":"X""#));
+        bob.next().await.unwrap().unwrap();
+
+        bob.send(send("resign")).await.unwrap();
+        for socket in [&mut alice, &mut bob] {
+            let Some(Ok(Message::Text(state))) = socket.next().await else {
+                panic!("no state after resigning");
+            };
+            assert!(state.contains(r#""winner":"X""#));
+        }
+        bob.close(None).await.unwrap();
+        let Some(Ok(Message::Text(left))) = alice.next().await else {
+            panic!("not told the opponent left");
+        };
+        assert_eq!(r#"{"type":"left"}"#, left.as_str());
+    }
+}